@@ -365,7 +365,7 @@ async function handleGetTables({ connectionId, cacheName }) {
   }
 }
 
-async function handleQuery({ connectionId, query, cacheName }) {
+async function handleQuery({ connectionId, query, cacheName, params }) {
   const client = connections.get(connectionId);
   if (!client) {
     return { success: false, message: "Not connected" };
@@ -519,6 +519,11 @@ async function handleQuery({ connectionId, query, cacheName }) {
   const sqlQuery = new SqlFieldsQuery(modifiedQuery);
   sqlQuery.setIncludeFieldNames(true);
 
+  // Bind positional arguments for "?" placeholders
+  if (Array.isArray(params) && params.length > 0) {
+    sqlQuery.setArgs(...params);
+  }
+
   // Retry logic for transient Ignite errors (schema/thread errors)
   const maxRetries = 3;
   let cursor = null;
@@ -1,11 +1,20 @@
+use crate::db::transaction::TransactionCommand;
 use crate::models::connection::*;
+use crate::models::schema::TableSchema;
+use crate::models::transaction::TransactionAction;
 use crate::utils::{connection_pool::ConnectionPool, storage};
-use std::sync::Mutex;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use tauri::State;
 
+/// Table a grid loads pages of: connection, database and table name
+type TableKey = (String, String, String);
+
 pub struct ConnectionStore {
     pub connections: Mutex<Vec<ConnectionConfig>>,
     pub pool: ConnectionPool,
+    /// Schemas of the tables grids are paging through, so later pages skip the lookup
+    table_schemas: Mutex<HashMap<TableKey, Arc<TableSchema>>>,
 }
 
 impl ConnectionStore {
@@ -19,15 +28,53 @@ impl ConnectionStore {
         ConnectionStore {
             connections: Mutex::new(connections),
             pool: ConnectionPool::new(),
+            table_schemas: Mutex::new(HashMap::new()),
         }
     }
 
+    /// Schema a grid last loaded a table with
+    pub fn cached_table_schema(&self, key: &TableKey) -> Option<Arc<TableSchema>> {
+        self.table_schemas.lock().unwrap().get(key).cloned()
+    }
+
+    pub fn cache_table_schema(&self, key: TableKey, schema: Arc<TableSchema>) {
+        self.table_schemas.lock().unwrap().insert(key, schema);
+    }
+
+    /// Drop the cached table schemas of a connection
+    pub fn forget_table_schemas(&self, connection_id: &str) {
+        self.table_schemas
+            .lock()
+            .unwrap()
+            .retain(|(id, _, _), _| id != connection_id);
+    }
+
     /// Save current connections to file
     fn save_to_file(&self) -> Result<(), String> {
         let connections = self.connections.lock().unwrap();
         storage::save_connections(&connections)
             .map_err(|e| format!("Failed to save connections: {}", e))
     }
+
+    /// Look up a stored connection config by ID
+    pub fn get_config(&self, connection_id: &str) -> Result<ConnectionConfig, String> {
+        let connections = self.connections.lock().unwrap();
+        connections
+            .iter()
+            .find(|c| c.id == connection_id)
+            .cloned()
+            .ok_or_else(|| format!("Connection '{}' not found", connection_id))
+    }
+
+    /// Connect to the database if it is not already in the pool
+    pub async fn ensure_connected(&self, connection_id: &str) -> Result<(), String> {
+        if self.pool.is_connected(connection_id).await {
+            return Ok(());
+        }
+
+        let config = self.get_config(connection_id)?; // MutexGuard dropped here before await
+        self.pool.connect(config).await
+    }
 }
 
 impl Default for ConnectionStore {
@@ -127,6 +174,7 @@ pub async fn delete_connection(
 
     // Disconnect from pool if connected
     let _ = state.pool.disconnect(&id).await;
+    state.forget_table_schemas(&id);
 
     let mut connections = state.connections.lock().unwrap();
     connections.retain(|c| c.id != id);
@@ -183,13 +231,34 @@ pub async fn connect_to_database(
 #[tauri::command]
 pub async fn disconnect_from_database(
     connection_id: String,
+    transaction_action: Option<TransactionAction>,
     state: State<'_, ConnectionStore>,
 ) -> Result<(), String> {
     tracing::info!(
         "🔌 [COMMAND] Disconnecting from database: {}",
         connection_id
     );
+
+    // Never silently discard or keep an open transaction; the frontend asks the user
+    for transaction in state.pool.open_transactions(&connection_id).await {
+        let command = match transaction_action {
+            Some(TransactionAction::Commit) => TransactionCommand::Commit,
+            Some(TransactionAction::Rollback) => TransactionCommand::Rollback,
+            None => {
+                return Err(format!(
+                "Connection '{}' has an open transaction. Commit or roll back before disconnecting",
+                connection_id
+            ))
+            }
+        };
+        state
+            .pool
+            .transaction_control(&connection_id, transaction.tab_id.as_deref(), command)
+            .await?;
+    }
+
     let result = state.pool.disconnect(&connection_id).await;
+    state.forget_table_schemas(&connection_id);
 
     if result.is_ok() {
         tracing::info!(
@@ -206,6 +275,15 @@ pub async fn disconnect_from_database(
     result
 }
 
+/// Close the dedicated database sessions of an editor tab that is being closed
+#[tauri::command]
+pub async fn close_tab_session(
+    tab_id: String,
+    state: State<'_, ConnectionStore>,
+) -> Result<(), String> {
+    state.pool.close_tab_sessions(&tab_id).await
+}
+
 #[tauri::command]
 pub async fn is_database_connected(
     connection_id: String,
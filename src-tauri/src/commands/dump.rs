@@ -0,0 +1,776 @@
+use crate::commands::connection::ConnectionStore;
+use crate::commands::export::{stream_table_rows, table_ddl};
+use crate::commands::table::get_db_type_from_connection;
+use crate::db::ddl;
+use crate::db::sql_splitter::{split_statements, SqlDialect};
+use crate::db::traits::{get_crud_query_builder, DatabaseConnection};
+use crate::formats::dump::{order_by_dependencies, DumpWriter};
+use crate::models::connection::DatabaseType;
+use crate::models::dump::*;
+use crate::models::schema::{Procedure, Table, Trigger, View};
+use crate::models::table_request::QueryRequest;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::time::Instant;
+use tauri::ipc::Channel;
+use tauri::State;
+
+/// Statements run between two restore progress events
+const RESTORE_PROGRESS_INTERVAL: usize = 50;
+
+/// Failed statements kept in a restore summary; later ones are only counted
+const MAX_REPORTED_ERRORS: usize = 500;
+
+/// Write the schema and data of every table of a database to a SQL file, followed by
+/// its routines, views and triggers. Tables are ordered so the tables a foreign key
+/// references are created and loaded first.
+#[tauri::command]
+pub async fn dump_database(
+    connection_id: String,
+    database: String,
+    path: String,
+    options: Option<DumpOptions>,
+    on_event: Channel<DumpEvent>,
+    state: State<'_, ConnectionStore>,
+) -> Result<DumpSummary, String> {
+    let options = options.unwrap_or_default();
+    state.ensure_connected(&connection_id).await?;
+    let db_type = get_db_type_from_connection(&state, &connection_id)?;
+    if !matches!(
+        db_type,
+        DatabaseType::MySQL | DatabaseType::PostgreSQL | DatabaseType::MSSQL
+    ) {
+        return Err("Database dumps are not supported for this database".to_string());
+    }
+    tracing::info!("📦 [DUMP] Dumping database {} to {}", database, path);
+    let start = Instant::now();
+
+    // Everything is read on one session, inside a snapshot where the server offers
+    // one, so rows of tables that reference each other match
+    let session = format!("dump-{}", uuid::Uuid::new_v4());
+    let source = DumpSource {
+        state: &state,
+        connection_id: &connection_id,
+        session: &session,
+        database: &database,
+        db_type,
+    };
+    let result = match begin_snapshot(&source).await {
+        Ok(consistent) => write_dump(&source, consistent, &path, &options, &on_event).await,
+        Err(e) => Err(e),
+    };
+    // Closing the session ends its read-only transaction
+    if let Err(e) = state.pool.close_tab_sessions(&session).await {
+        tracing::warn!("⚠️ [DUMP] Failed to close dump session: {}", e);
+    }
+
+    let mut summary = result?;
+    summary.execution_time = start.elapsed().as_millis();
+    Ok(summary)
+}
+
+/// The session a dump reads from
+struct DumpSource<'a> {
+    state: &'a ConnectionStore,
+    connection_id: &'a str,
+    session: &'a str,
+    database: &'a str,
+    db_type: DatabaseType,
+}
+
+impl DumpSource<'_> {
+    async fn read<F, T>(&self, operation: F) -> Result<T, String>
+    where
+        F: FnOnce(&mut Box<dyn DatabaseConnection>) -> BoxFuture<'_, Result<T, anyhow::Error>>,
+    {
+        self.state
+            .pool
+            .with_session(self.connection_id, Some(self.session), operation)
+            .await
+    }
+}
+
+/// Open the dump session in a read-only snapshot transaction. Returns false when the
+/// server offers no snapshot, so each statement sees the data as it is then.
+async fn begin_snapshot(source: &DumpSource<'_>) -> Result<bool, String> {
+    let statements = match source.db_type {
+        DatabaseType::PostgreSQL => {
+            // Sessions cannot switch databases, so this one opens on the dumped one
+            source
+                .state
+                .pool
+                .open_session_on_database(source.connection_id, source.session, source.database)
+                .await?;
+            vec!["BEGIN ISOLATION LEVEL REPEATABLE READ READ ONLY"]
+        }
+        DatabaseType::MySQL => vec![
+            "SET SESSION TRANSACTION ISOLATION LEVEL REPEATABLE READ",
+            "START TRANSACTION WITH CONSISTENT SNAPSHOT, READ ONLY",
+        ],
+        DatabaseType::MSSQL => {
+            let sql = format!(
+                "SELECT snapshot_isolation_state AS state FROM sys.databases WHERE name = N'{}'",
+                source.database.replace('\'', "''")
+            );
+            let result = source
+                .read(|conn| async move { conn.execute_query(&sql).await }.boxed())
+                .await?;
+            let allowed = result
+                .rows
+                .first()
+                .and_then(|row| row.get("state"))
+                .and_then(|state| state.as_i64())
+                == Some(1);
+            if !allowed {
+                tracing::warn!(
+                    "⚠️ [DUMP] Snapshot isolation is off for {}, tables are read one after another",
+                    source.database
+                );
+                return Ok(false);
+            }
+            vec![
+                "SET TRANSACTION ISOLATION LEVEL SNAPSHOT",
+                "BEGIN TRANSACTION",
+            ]
+        }
+        _ => return Ok(false),
+    };
+
+    for sql in statements {
+        source
+            .read(|conn| async move { conn.execute_raw(sql).await }.boxed())
+            .await
+            .map_err(|e| format!("Failed to start the dump snapshot: {}", e))?;
+    }
+    Ok(true)
+}
+
+/// Write the dump file from the snapshot of `source`
+async fn write_dump(
+    source: &DumpSource<'_>,
+    consistent: bool,
+    path: &str,
+    options: &DumpOptions,
+    on_event: &Channel<DumpEvent>,
+) -> Result<DumpSummary, String> {
+    let (database, db_type) = (source.database, &source.db_type);
+
+    // MySQL has no schemas within a database
+    let schema_filter = options
+        .schema
+        .clone()
+        .filter(|_| *db_type != DatabaseType::MySQL);
+    let in_schema = |schema: &Option<String>| {
+        schema_filter.is_none() || schema.as_deref() == schema_filter.as_deref()
+    };
+    let qualify = |schema: Option<&str>, name: &str| match schema {
+        Some(schema) if *db_type != DatabaseType::MySQL => format!("{}.{}", schema, name),
+        _ => name.to_string(),
+    };
+
+    let db = database.to_string();
+    let tables: Vec<Table> = source
+        .read(|conn| async move { conn.get_tables(&db).await }.boxed())
+        .await?
+        .into_iter()
+        .filter(|table| in_schema(&table.schema))
+        .collect();
+    let (views, routines, triggers) = list_definitions(source, options).await?;
+    let views: Vec<View> = views.into_iter().filter(|v| in_schema(&v.schema)).collect();
+    let routines: Vec<Procedure> = routines
+        .into_iter()
+        .filter(|r| in_schema(&r.schema))
+        .collect();
+    // Triggers only name their table, so keep those on a dumped table
+    let table_schema_of = |name: &str| {
+        tables
+            .iter()
+            .find(|table| table.name == name)
+            .map(|table| table.schema.clone())
+    };
+    let triggers: Vec<(Trigger, Option<String>)> = triggers
+        .into_iter()
+        .filter_map(|trigger| {
+            let schema = table_schema_of(&trigger.table_name)?;
+            Some((trigger, schema))
+        })
+        .collect();
+
+    // Foreign keys between the dumped tables decide their order
+    let mut references = Vec::new();
+    for table in &tables {
+        let key = qualify(table.schema.as_deref(), &table.name);
+        let db = database.to_string();
+        let tbl = key.clone();
+        let relationships = source
+            .read(|conn| async move { conn.get_table_relationships(&db, &tbl).await }.boxed())
+            .await?;
+        references.extend(
+            relationships
+                .into_iter()
+                .filter(|rel| {
+                    rel.relationship_type == "FOREIGN_KEY" && rel.table_name == table.name
+                })
+                .map(|rel| {
+                    // The referenced table may live in another schema
+                    let schema = rel
+                        .referenced_table_schema
+                        .as_deref()
+                        .or(table.schema.as_deref());
+                    (key.clone(), qualify(schema, &rel.referenced_table_name))
+                }),
+        );
+    }
+    let keys: Vec<String> = tables
+        .iter()
+        .map(|table| qualify(table.schema.as_deref(), &table.name))
+        .collect();
+    let (mut ordered, cyclic_tables) = order_by_dependencies(&keys, &references);
+    ordered.extend(cyclic_tables.iter().cloned());
+    let ordered: Vec<&Table> = ordered
+        .iter()
+        .filter_map(|key| keys.iter().position(|k| k == key).map(|i| &tables[i]))
+        .collect();
+
+    let file = File::create(path).map_err(|e| format!("Failed to create {}: {}", path, e))?;
+    let mut dump = DumpWriter::new(BufWriter::new(file), db_type.clone());
+    let write_error = |e: std::io::Error| format!("Failed to write dump: {}", e);
+    dump.header(database, &chrono::Local::now().to_rfc3339())
+        .map_err(write_error)?;
+    if !consistent {
+        dump.section(
+            "Snapshot isolation is off for this database, so tables were read one after another",
+        )
+        .map_err(write_error)?;
+    }
+    if !cyclic_tables.is_empty() {
+        let handling = match db_type {
+            DatabaseType::PostgreSQL => "their foreign keys are added after the data",
+            DatabaseType::MySQL => "foreign key checks are off while they load",
+            _ => "these tables are created last",
+        };
+        dump.section(&format!(
+            "Foreign keys of {} form a cycle; {}",
+            cyclic_tables.join(", "),
+            handling
+        ))
+        .map_err(write_error)?;
+    }
+
+    let crud_builder = get_crud_query_builder(db_type);
+    let total = tables.len() + routines.len() + views.len() + triggers.len();
+    let mut index = 0;
+    let mut progress = |kind: DumpObjectKind, name: &str| {
+        index += 1;
+        let event = DumpEvent::Object {
+            kind,
+            name: name.to_string(),
+            index,
+            total,
+        };
+        if let Err(e) = on_event.send(event) {
+            tracing::warn!("⚠️ [DUMP] Frontend channel closed: {}", e);
+        }
+    };
+
+    if options.drop_existing {
+        dump.section("Drop existing tables").map_err(write_error)?;
+        for table in ordered.iter().rev() {
+            let name = crud_builder.crud_table_name(&table.name, table.schema.as_deref());
+            dump.statement(&ddl::drop_table_statement(db_type, &name))
+                .map_err(write_error)?;
+        }
+        dump.end_batch().map_err(write_error)?;
+    }
+
+    let mut rows_total = 0u64;
+    // Foreign keys of PostgreSQL tables in a cycle, added once every row is loaded
+    let mut deferred_keys = Vec::new();
+    for table in &ordered {
+        let key = qualify(table.schema.as_deref(), &table.name);
+        progress(DumpObjectKind::Table, &key);
+        let schema = table
+            .schema
+            .as_deref()
+            .filter(|_| *db_type != DatabaseType::MySQL);
+
+        let split = if *db_type == DatabaseType::PostgreSQL && cyclic_tables.contains(&key) {
+            let db = database.to_string();
+            let tbl = key.clone();
+            source
+                .read(|conn| async move { conn.get_table_ddl_split(&db, &tbl).await }.boxed())
+                .await?
+        } else {
+            None
+        };
+        let ddl = match split {
+            Some((create, foreign_keys)) => {
+                deferred_keys.extend(foreign_keys);
+                create
+            }
+            None => {
+                table_ddl(
+                    source.state,
+                    source.connection_id,
+                    Some(source.session),
+                    database,
+                    &table.name,
+                    schema,
+                )
+                .await?
+            }
+        };
+        dump.section(&format!("Structure of table {}", key))
+            .map_err(write_error)?;
+        dump.script(&ddl).map_err(write_error)?;
+
+        if options.include_data {
+            rows_total += dump_rows(source, (table, schema), &mut dump, on_event).await?;
+        }
+    }
+    if !deferred_keys.is_empty() {
+        dump.section("Foreign keys of tables in a cycle")
+            .map_err(write_error)?;
+        for statement in &deferred_keys {
+            dump.statement(statement).map_err(write_error)?;
+        }
+    }
+
+    let mut skipped = Vec::new();
+    let mut routines_written = 0;
+    for routine in &routines {
+        let name = qualify(routine.schema.as_deref(), &routine.name);
+        progress(DumpObjectKind::Routine, &name);
+        let routine_source = routine_source(source, routine).await?;
+        // Sources that are only a body, or a placeholder comment, cannot be replayed
+        if !routine_source
+            .trim_start()
+            .to_uppercase()
+            .starts_with("CREATE")
+        {
+            skipped.push(name);
+            continue;
+        }
+        dump.section(&format!("Routine {}", name))
+            .map_err(write_error)?;
+        dump.definition(&routine_source).map_err(write_error)?;
+        routines_written += 1;
+    }
+
+    let mut views_written = 0;
+    for view in &views {
+        let name = qualify(view.schema.as_deref(), &view.name);
+        progress(DumpObjectKind::View, &name);
+        let db = database.to_string();
+        let view_name = view.name.clone();
+        let schema = view.schema.clone();
+        let view_source = source
+            .read(|conn| {
+                async move {
+                    conn.get_view_source(&db, &view_name, schema.as_deref())
+                        .await
+                }
+                .boxed()
+            })
+            .await?;
+        match view_source {
+            Some(view_source) => {
+                dump.section(&format!("View {}", name))
+                    .map_err(write_error)?;
+                dump.definition(&view_source).map_err(write_error)?;
+                views_written += 1;
+            }
+            None => skipped.push(name),
+        }
+    }
+
+    let mut triggers_written = 0;
+    for (trigger, schema) in &triggers {
+        let name = qualify(schema.as_deref(), &trigger.name);
+        progress(DumpObjectKind::Trigger, &name);
+        let db = database.to_string();
+        let trigger_name = trigger.name.clone();
+        let table_name = trigger.table_name.clone();
+        let schema = schema.clone();
+        let trigger_source = source
+            .read(|conn| {
+                async move {
+                    conn.get_trigger_source(&db, &trigger_name, &table_name, schema.as_deref())
+                        .await
+                }
+                .boxed()
+            })
+            .await?;
+        match trigger_source {
+            Some(trigger_source) => {
+                dump.section(&format!("Trigger {} on {}", name, trigger.table_name))
+                    .map_err(write_error)?;
+                dump.definition(&trigger_source).map_err(write_error)?;
+                triggers_written += 1;
+            }
+            None => skipped.push(name),
+        }
+    }
+
+    dump.footer().map_err(write_error)?;
+
+    tracing::info!(
+        "✅ [DUMP] Wrote {} tables and {} rows to {}",
+        ordered.len(),
+        rows_total,
+        path
+    );
+    Ok(DumpSummary {
+        path: path.to_string(),
+        tables: ordered.len(),
+        rows: rows_total,
+        views: views_written,
+        routines: routines_written,
+        triggers: triggers_written,
+        skipped,
+        cyclic_tables,
+        execution_time: 0,
+    })
+}
+
+/// Views, routines and triggers of a database, each left empty when not requested
+async fn list_definitions(
+    source: &DumpSource<'_>,
+    options: &DumpOptions,
+) -> Result<(Vec<View>, Vec<Procedure>, Vec<Trigger>), String> {
+    let db = source.database.to_string();
+    let schema = options.schema.clone();
+    let (include_views, include_routines, include_triggers) = (
+        options.include_views,
+        options.include_routines,
+        options.include_triggers,
+    );
+    source
+        .read(|conn| {
+            async move {
+                let schema = schema.as_deref();
+                let views = if include_views {
+                    conn.get_views(&db, schema).await?
+                } else {
+                    Vec::new()
+                };
+                let routines = if include_routines {
+                    conn.get_procedures(&db, schema).await?
+                } else {
+                    Vec::new()
+                };
+                let triggers = if include_triggers {
+                    conn.get_triggers(&db, schema).await?
+                } else {
+                    Vec::new()
+                };
+                Ok((views, routines, triggers))
+            }
+            .boxed()
+        })
+        .await
+}
+
+/// Source of a routine. PostgreSQL overloads share a name, so its routines are read by
+/// oid; aggregates and window functions have no definition to read.
+async fn routine_source(source: &DumpSource<'_>, routine: &Procedure) -> Result<String, String> {
+    let oid = routine
+        .oid
+        .as_deref()
+        .and_then(|oid| oid.parse::<u32>().ok())
+        .filter(|_| source.db_type == DatabaseType::PostgreSQL);
+    if let Some(oid) = oid {
+        let sql = format!(
+            "SELECT CASE WHEN prokind IN ('f', 'p') THEN pg_get_functiondef(oid) END AS source \
+             FROM pg_proc WHERE oid = {}",
+            oid
+        );
+        let result = source
+            .read(|conn| async move { conn.execute_query(&sql).await }.boxed())
+            .await?;
+        return Ok(result
+            .rows
+            .first()
+            .and_then(|row| row.get("source"))
+            .and_then(|source| source.as_str())
+            .unwrap_or_default()
+            .to_string());
+    }
+
+    let db = source.database.to_string();
+    let routine_name = routine.name.clone();
+    let routine_type = routine.procedure_type.clone();
+    let schema = routine.schema.clone();
+    source
+        .read(|conn| {
+            async move {
+                conn.get_procedure_source(&db, &routine_name, routine_type, schema)
+                    .await
+            }
+            .boxed()
+        })
+        .await
+}
+
+/// Write the rows of a table as INSERT statements, one SQL Server batch per page of
+/// rows, and bring PostgreSQL sequences past the loaded keys. Generated and row
+/// version columns are left out; the server computes them again.
+async fn dump_rows<W: Write + Send>(
+    source: &DumpSource<'_>,
+    (table, schema): (&Table, Option<&str>),
+    dump: &mut DumpWriter<W>,
+    on_event: &Channel<DumpEvent>,
+) -> Result<u64, String> {
+    let db_type = &source.db_type;
+    let crud_builder = get_crud_query_builder(db_type);
+    let table_name = crud_builder.crud_table_name(&table.name, schema);
+    let qualified = match schema {
+        Some(schema) => format!("{}.{}", schema, table.name),
+        None => table.name.clone(),
+    };
+
+    let db = source.database.to_string();
+    let tbl = qualified.clone();
+    let table_schema = source
+        .read(|conn| async move { conn.get_table_schema(&db, &tbl).await }.boxed())
+        .await
+        .map_err(|e| format!("Failed to get table schema: {}", e))?;
+    let auto_increment: Vec<&str> = table_schema
+        .columns
+        .iter()
+        .filter(|col| col.is_auto_increment)
+        .map(|col| col.name.as_str())
+        .collect();
+    let generated: Vec<&str> = table_schema
+        .columns
+        .iter()
+        .filter(|col| col.is_generated)
+        .map(|col| col.name.as_str())
+        .collect();
+    // Inserting explicit identity values needs this on, within each batch
+    let identity_insert = *db_type == DatabaseType::MSSQL && !auto_increment.is_empty();
+    // PostgreSQL refuses explicit values for GENERATED ALWAYS identity columns otherwise
+    let overriding = if *db_type == DatabaseType::PostgreSQL && !auto_increment.is_empty() {
+        " OVERRIDING SYSTEM VALUE"
+    } else {
+        ""
+    };
+
+    let request = QueryRequest {
+        db_type: db_type.clone(),
+        database: Some(source.database.to_string()),
+        schema: schema.map(str::to_string),
+        table: table.name.clone(),
+        limit: 0,
+        offset: 0,
+        filters: Vec::new(),
+        order_by: Vec::new(),
+    };
+
+    // The INSERT prefix, and which of the streamed columns it writes
+    let mut prefix: Option<(String, Vec<bool>)> = None;
+    let mut rows_written = 0u64;
+    dump.section(&format!("Data of table {}", qualified))
+        .map_err(|e| format!("Failed to write dump: {}", e))?;
+    let rows = stream_table_rows(
+        source.state,
+        source.connection_id,
+        source.session,
+        &request,
+        |columns, rows| {
+            if rows.is_empty() {
+                return Ok(());
+            }
+            let (prefix, written) = prefix.get_or_insert_with(|| {
+                let written: Vec<bool> = columns
+                    .iter()
+                    .map(|col| !generated.contains(&col.name.as_str()))
+                    .collect();
+                let names = columns
+                    .iter()
+                    .zip(&written)
+                    .filter(|(_, written)| **written)
+                    .map(|(col, _)| crud_builder.quote_identifier(&col.name))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let prefix = format!(
+                    "INSERT INTO {} ({}){} VALUES ",
+                    table_name, names, overriding
+                );
+                (prefix, written)
+            });
+            if identity_insert {
+                dump.statement(&ddl::identity_insert_statement(&table_name, true))?;
+            }
+            for row in rows {
+                let values = row
+                    .iter()
+                    .zip(written.iter())
+                    .filter(|(_, written)| **written)
+                    .map(|(value, _)| crud_builder.format_value(value))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                dump.statement(&format!("{}({})", prefix, values))?;
+            }
+            if identity_insert {
+                dump.statement(&ddl::identity_insert_statement(&table_name, false))?;
+            }
+            dump.end_batch()?;
+
+            rows_written += rows.len() as u64;
+            if let Err(e) = on_event.send(DumpEvent::Rows {
+                table: qualified.clone(),
+                rows: rows_written,
+            }) {
+                tracing::warn!("⚠️ [DUMP] Frontend channel closed: {}", e);
+            }
+            Ok(())
+        },
+    )
+    .await?;
+
+    if *db_type == DatabaseType::PostgreSQL {
+        for column in auto_increment {
+            dump.statement(&ddl::reset_sequence_statement(&table_name, column))
+                .map_err(|e| format!("Failed to write dump: {}", e))?;
+        }
+    }
+
+    Ok(rows)
+}
+
+/// Run the statements of a SQL file, such as a dump, one at a time on a dedicated
+/// session. With `continue_on_error` failing statements are reported and skipped,
+/// otherwise the restore stops at the first one.
+#[tauri::command]
+pub async fn restore_sql_file(
+    connection_id: String,
+    database: Option<String>,
+    path: String,
+    continue_on_error: bool,
+    on_event: Channel<RestoreEvent>,
+    state: State<'_, ConnectionStore>,
+) -> Result<RestoreSummary, String> {
+    state.ensure_connected(&connection_id).await?;
+    let db_type = get_db_type_from_connection(&state, &connection_id)?;
+    let dialect = SqlDialect::from_db_type(&db_type)
+        .ok_or_else(|| "SQL files cannot be restored to this database".to_string())?;
+
+    let script =
+        std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let script = script.strip_prefix('\u{feff}').unwrap_or(&script);
+    let statements = split_statements(script, dialect);
+    let total = statements.len();
+    tracing::info!(
+        "📥 [RESTORE] Restoring {} statement(s) from {}",
+        total,
+        path
+    );
+
+    // Session settings of the file, such as FOREIGN_KEY_CHECKS, must hold for all of it
+    let session_id = format!("restore-{}", uuid::Uuid::new_v4());
+    let start = Instant::now();
+    let mut summary = RestoreSummary {
+        path: path.clone(),
+        total,
+        executed: 0,
+        failed: 0,
+        errors: Vec::new(),
+        aborted: false,
+        execution_time: 0,
+    };
+    let send = |event: RestoreEvent| {
+        if let Err(e) = on_event.send(event) {
+            tracing::warn!("⚠️ [RESTORE] Frontend channel closed: {}", e);
+        }
+    };
+
+    let use_database = match (&db_type, &database) {
+        (DatabaseType::MySQL, Some(db)) => Some(format!("USE `{}`", db.replace('`', "``"))),
+        (DatabaseType::MSSQL, Some(db)) => Some(format!("USE [{}]", db.replace(']', "]]"))),
+        _ => None,
+    };
+    let selected = match (&db_type, database, use_database) {
+        // Sessions cannot switch databases, so this one opens on the target instead
+        (DatabaseType::PostgreSQL, Some(db), _) => {
+            state
+                .pool
+                .open_session_on_database(&connection_id, &session_id, &db)
+                .await
+        }
+        (_, _, Some(sql)) => state
+            .pool
+            .with_session(&connection_id, Some(&session_id), |conn| {
+                async move { conn.execute_raw(&sql).await }.boxed()
+            })
+            .await
+            .map(|_| ()),
+        _ => Ok(()),
+    };
+
+    if let Err(e) = selected {
+        summary.aborted = true;
+        summary.errors.push(RestoreError {
+            index: 0,
+            statement: String::new(),
+            error: format!("Failed to select the database: {}", e),
+        });
+    } else {
+        for (index, statement) in statements.iter().enumerate() {
+            let sql = statement.text.clone();
+            let result = state
+                .pool
+                .with_session(&connection_id, Some(&session_id), |conn| {
+                    async move { conn.execute_raw(&sql).await }.boxed()
+                })
+                .await;
+            summary.executed += 1;
+
+            if let Err(error) = result {
+                summary.failed += 1;
+                let failure = RestoreError {
+                    index,
+                    statement: statement.text.chars().take(200).collect(),
+                    error,
+                };
+                send(RestoreEvent::Failed(failure.clone()));
+                if summary.errors.len() < MAX_REPORTED_ERRORS {
+                    summary.errors.push(failure);
+                }
+                if !continue_on_error {
+                    summary.aborted = true;
+                    break;
+                }
+            }
+
+            if summary.executed.is_multiple_of(RESTORE_PROGRESS_INTERVAL) {
+                send(RestoreEvent::Progress {
+                    executed: summary.executed,
+                    failed: summary.failed,
+                    total,
+                });
+            }
+        }
+    }
+
+    if let Err(e) = state.pool.close_tab_sessions(&session_id).await {
+        tracing::warn!("⚠️ [RESTORE] Failed to close restore session: {}", e);
+    }
+    send(RestoreEvent::Progress {
+        executed: summary.executed,
+        failed: summary.failed,
+        total,
+    });
+
+    summary.execution_time = start.elapsed().as_millis();
+    tracing::info!(
+        "✅ [RESTORE] Ran {} of {} statement(s) from {}, {} failed",
+        summary.executed,
+        total,
+        path,
+        summary.failed
+    );
+    Ok(summary)
+}
@@ -1,39 +1,442 @@
+use crate::commands::connection::ConnectionStore;
+use crate::commands::table::get_db_type_from_connection;
+use crate::db::ddl;
+use crate::db::traits::get_crud_query_builder;
+use crate::formats::xlsx::XlsxWriter;
+use crate::formats::{self, ExportColumn, RowWriter, TextSink};
+use crate::models::connection::DatabaseType;
+use crate::models::export::{
+    ClipboardSelection, ExportFormat, ExportOptions, ExportSummary, TextEncoding,
+};
+use crate::models::query_result::{QueryResult, QueryStreamEvent};
 use crate::models::schema::*;
+use crate::models::table_request::QueryRequest;
+use crate::utils::clipboard;
+use crate::utils::table_reader::{
+    driver_table_name, fetch_table_schema, result_columns, schema_columns, TableReader,
+};
+use futures::FutureExt;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Instant;
+use tauri::State;
 
+/// `CREATE TABLE` DDL of a table, with its indexes and foreign keys
 #[tauri::command]
-pub async fn export_schema(schema: TableSchema) -> Result<String, String> {
+pub async fn export_schema(
+    connection_id: String,
+    database: String,
+    table: String,
+    schema: Option<String>,
+    state: State<'_, ConnectionStore>,
+) -> Result<String, String> {
+    tracing::info!("📤 [EXPORT] Exporting schema for table: {}", table);
+    table_ddl(
+        &state,
+        &connection_id,
+        None,
+        &database,
+        &table,
+        schema.as_deref(),
+    )
+    .await
+}
+
+/// DDL of a table as the server reports it (`SHOW CREATE TABLE`, pg_catalog), or built
+/// from its schema for engines that cannot report it. Read on `session` when given,
+/// otherwise on the shared connection.
+pub(crate) async fn table_ddl(
+    state: &ConnectionStore,
+    connection_id: &str,
+    session: Option<&str>,
+    database: &str,
+    table: &str,
+    schema: Option<&str>,
+) -> Result<String, String> {
+    let db_type = get_db_type_from_connection(state, connection_id)?;
+    let qualified = driver_table_name(&db_type, table, schema);
+
+    let db = database.to_string();
+    let tbl = qualified.clone();
+    let native = state
+        .pool
+        .with_session(connection_id, session, |conn| {
+            async move { conn.get_table_ddl(&db, &tbl).await }.boxed()
+        })
+        .await;
+    match native {
+        Ok(Some(ddl)) => return Ok(ddl),
+        Ok(None) => {}
+        Err(e) => tracing::warn!(
+            "⚠️ [EXPORT] Server DDL unavailable for {}, building it from the schema: {}",
+            qualified,
+            e
+        ),
+    }
+
+    let table_schema = fetch_table_schema(
+        state,
+        connection_id,
+        session,
+        &db_type,
+        database,
+        table,
+        schema,
+    )
+    .await?;
+    let schema = schema.filter(|_| db_type != DatabaseType::MySQL);
+    ddl::create_table_ddl(&db_type, schema, &table_schema).map_err(|e| e.to_string())
+}
+
+/// Rows fetched per round trip while exporting
+const EXPORT_BATCH_SIZE: usize = 1000;
+
+/// Reject a custom date format the writers could not apply
+fn check_date_format(options: &ExportOptions) -> Result<(), String> {
+    match &options.date_format {
+        Some(date_format) if !formats::valid_date_format(date_format) => {
+            Err(format!("Invalid date format '{}'", date_format))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Export every row matching the request's filters and sort to a file, fetching them
+/// from the server in batches rather than from the grid
+#[tauri::command]
+pub async fn export_data(
+    connection_id: String,
+    query: QueryRequest,
+    path: String,
+    format: ExportFormat,
+    options: Option<ExportOptions>,
+    state: State<'_, ConnectionStore>,
+) -> Result<ExportSummary, String> {
+    let options = options.unwrap_or_default();
+    check_date_format(&options)?;
     tracing::info!(
-        "📤 [EXPORT] Exporting schema for table: {}",
-        schema.table_name
+        "📤 [EXPORT] Exporting {} as {:?} to {}",
+        query.table,
+        format,
+        path
     );
-    // TODO: Generate DDL statements
-    Ok(format!(
-        "-- Schema for table {}\n-- Not yet implemented",
-        schema.table_name
-    ))
+
+    let start = Instant::now();
+    let mut writer = formats::create_file_writer(
+        format,
+        &options,
+        &path,
+        &query.db_type,
+        (&query.table, query.schema.as_deref()),
+    )
+    .map_err(|e| format!("Failed to create {}: {}", path, e))?;
+
+    // The rows stream on a session of their own, so the grid stays usable meanwhile
+    let session = format!("export-{}", uuid::Uuid::new_v4());
+    let mut started = false;
+    let streamed = stream_table_rows(&state, &connection_id, &session, &query, |columns, rows| {
+        if !started {
+            writer.begin(columns)?;
+            started = true;
+        }
+        rows.iter().try_for_each(|row| writer.write_row(row))?;
+        Ok(())
+    })
+    .await;
+    if let Err(e) = state.pool.close_tab_sessions(&session).await {
+        tracing::warn!("⚠️ [EXPORT] Failed to close export session: {}", e);
+    }
+    let rows_written = streamed?;
+    writer.finish().map_err(|e| e.to_string())?;
+
+    tracing::info!("✅ [EXPORT] Wrote {} rows to {}", rows_written, path);
+    Ok(ExportSummary {
+        path,
+        format,
+        rows_written,
+        execution_time: start.elapsed().as_millis(),
+    })
 }
 
+/// Run a query and export every result set it produces to an Excel workbook, one
+/// sheet per result set
 #[tauri::command]
-pub async fn export_data(
-    table_name: String,
-    _data: Vec<serde_json::Value>,
+pub async fn export_query_xlsx(
+    connection_id: String,
+    tab_id: Option<String>,
+    query: String,
+    path: String,
+    options: Option<ExportOptions>,
+    state: State<'_, ConnectionStore>,
+) -> Result<ExportSummary, String> {
+    tracing::info!("📤 [EXPORT] Exporting query results to {}", path);
+    state.ensure_connected(&connection_id).await?;
+
+    let start = Instant::now();
+    let result_sets = state
+        .pool
+        .with_session(&connection_id, tab_id.as_deref(), |conn| {
+            async move { conn.execute_query_multi(&query).await }.boxed()
+        })
+        .await?;
+
+    let sheet_names = (1..=result_sets.len())
+        .map(|n| format!("Result {}", n))
+        .collect();
+    let mut writer = XlsxWriter::new(
+        path.clone().into(),
+        options.unwrap_or_default(),
+        sheet_names,
+    );
+    let mut rows_written = 0u64;
+    for result in &result_sets {
+        let columns = export_columns(result, None);
+        write_result_rows(&mut writer, &columns, &result.rows)
+            .map_err(|e| format!("Failed to write rows: {}", e))?;
+        rows_written += result.rows.len() as u64;
+    }
+    writer
+        .finish()
+        .map_err(|e| format!("Failed to save {}: {}", path, e))?;
+
+    tracing::info!(
+        "✅ [EXPORT] Wrote {} result set(s), {} rows to {}",
+        result_sets.len(),
+        rows_written,
+        path
+    );
+    Ok(ExportSummary {
+        path,
+        format: ExportFormat::Xlsx,
+        rows_written,
+        execution_time: start.elapsed().as_millis(),
+    })
+}
+
+/// Run a query and export its result set to a file as the rows stream from the server,
+/// so the result is never held in memory as a whole. With a `tab_id` the query runs on
+/// that editor tab's session.
+#[tauri::command]
+pub async fn export_query(
+    connection_id: String,
+    tab_id: Option<String>,
+    query: String,
+    path: String,
+    format: ExportFormat,
+    options: Option<ExportOptions>,
+    state: State<'_, ConnectionStore>,
+) -> Result<ExportSummary, String> {
+    let options = options.unwrap_or_default();
+    check_date_format(&options)?;
+    tracing::info!(
+        "📤 [EXPORT] Exporting query result as {:?} to {}",
+        format,
+        path
+    );
+    state.ensure_connected(&connection_id).await?;
+    let db_type = get_db_type_from_connection(&state, &connection_id)?;
+
+    let start = Instant::now();
+    // SQL INSERT statements and the XLSX sheet are named after the result
+    let mut writer =
+        formats::create_file_writer(format, &options, &path, &db_type, ("result", None))
+            .map_err(|e| format!("Failed to create {}: {}", path, e))?;
+
+    // Bounded so a slow disk applies backpressure to the database cursor
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<QueryStreamEvent>(4);
+    let write = async {
+        let mut columns: Option<Vec<ExportColumn>> = None;
+        let mut rows_written = 0u64;
+        while let Some(event) = rx.recv().await {
+            match event {
+                // Schemaless sources may resend extended columns; the file keeps the first
+                QueryStreamEvent::Columns {
+                    columns: names,
+                    column_types,
+                    ..
+                } if columns.is_none() => {
+                    let export_columns: Vec<ExportColumn> = names
+                        .iter()
+                        .map(|name| ExportColumn {
+                            name: name.clone(),
+                            data_type: column_types
+                                .as_ref()
+                                .and_then(|types| types.get(name).cloned())
+                                .unwrap_or_default(),
+                            nullable: true,
+                        })
+                        .collect();
+                    writer.begin(&export_columns)?;
+                    columns = Some(export_columns);
+                }
+                QueryStreamEvent::Rows { rows } => {
+                    let columns = columns.as_deref().unwrap_or_default();
+                    for row in &rows {
+                        let values: Vec<Value> = columns
+                            .iter()
+                            .map(|col| row.get(&col.name).cloned().unwrap_or(Value::Null))
+                            .collect();
+                        writer.write_row(&values)?;
+                    }
+                    rows_written += rows.len() as u64;
+                }
+                _ => {}
+            }
+        }
+        writer.finish()?;
+        Ok::<u64, std::io::Error>(rows_written)
+    };
+    let stream = state
+        .pool
+        .with_session(&connection_id, tab_id.as_deref(), |conn| {
+            async move {
+                conn.execute_query_streaming(&query, EXPORT_BATCH_SIZE, &tx)
+                    .await
+            }
+            .boxed()
+        });
+    let (streamed, written) = tokio::join!(stream, write);
+    // A failed write closes the stream, so its error explains a failed query too
+    let rows_written = written.map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    streamed?;
+
+    tracing::info!("✅ [EXPORT] Wrote {} rows to {}", rows_written, path);
+    Ok(ExportSummary {
+        path,
+        format,
+        rows_written,
+        execution_time: start.elapsed().as_millis(),
+    })
+}
+
+/// Stream every row matching the request's filters and sort on `session`, handing each
+/// batch to `write_batch` with the result columns. The request's limit and offset
+/// describe a grid page and are ignored.
+pub(crate) async fn stream_table_rows<F>(
+    state: &ConnectionStore,
+    connection_id: &str,
+    session: &str,
+    request: &QueryRequest,
+    mut write_batch: F,
+) -> Result<u64, String>
+where
+    F: FnMut(&[ExportColumn], &[Vec<Value>]) -> std::io::Result<()>,
+{
+    let mut reader =
+        TableReader::open(state, connection_id, session, request, EXPORT_BATCH_SIZE).await?;
+    let mut total = 0u64;
+    while let Some((columns, rows)) = reader.next_batch().await? {
+        write_batch(columns, &rows).map_err(|e| format!("Failed to write rows: {}", e))?;
+        total += rows.len() as u64;
+    }
+    Ok(total)
+}
+
+/// Columns of a result with their types, falling back to the table schema when the
+/// result is empty and reports no columns
+fn export_columns(result: &QueryResult, table_schema: Option<&TableSchema>) -> Vec<ExportColumn> {
+    match (result.columns.is_empty(), table_schema) {
+        (true, Some(schema)) => schema_columns(schema),
+        _ => result_columns(&result.columns, result.column_types.as_ref(), table_schema),
+    }
+}
+
+/// Copy the DDL of a table to the clipboard, from the same generator as
+/// `export_schema`. Returns the copied DDL.
+#[tauri::command]
+pub async fn copy_schema(
+    connection_id: String,
+    database: String,
+    table: String,
+    schema: Option<String>,
+    state: State<'_, ConnectionStore>,
 ) -> Result<String, String> {
-    tracing::info!("📤 [EXPORT] Exporting data for table: {}", table_name);
-    // TODO: Generate INSERT statements
-    Ok(format!(
-        "-- Data export for table {}\n-- Not yet implemented",
-        table_name
-    ))
+    let ddl = table_ddl(
+        &state,
+        &connection_id,
+        None,
+        &database,
+        &table,
+        schema.as_deref(),
+    )
+    .await?;
+    clipboard::write_text(&ddl).map_err(|e| e.to_string())?;
+    tracing::info!("📋 [CLIPBOARD] Copied DDL of {}", table);
+    Ok(ddl)
 }
 
+/// Copy rows picked in the grid to the clipboard in one of the export formats, e.g.
+/// TSV to paste into a spreadsheet. SQL INSERT statements target `table` in `schema`.
 #[tauri::command]
-pub async fn copy_schema(_schema: TableSchema) -> Result<(), String> {
-    // TODO: Copy schema to clipboard
+pub async fn copy_data(
+    db_type: DatabaseType,
+    table: String,
+    schema: Option<String>,
+    selection: ClipboardSelection,
+    format: ExportFormat,
+    options: Option<ExportOptions>,
+) -> Result<(), String> {
+    let options = ExportOptions {
+        // Text on the clipboard is always UTF-8
+        encoding: TextEncoding::Utf8,
+        ..options.unwrap_or_default()
+    };
+    check_date_format(&options)?;
+
+    let columns: Vec<ExportColumn> = selection
+        .columns
+        .iter()
+        .map(|name| ExportColumn {
+            name: name.clone(),
+            data_type: selection
+                .column_types
+                .as_ref()
+                .and_then(|types| types.get(name).cloned())
+                .unwrap_or_default(),
+            nullable: true,
+        })
+        .collect();
+
+    let mut buf = Vec::new();
+    {
+        let sink = TextSink::new(&mut buf, options.encoding).map_err(|e| e.to_string())?;
+        let mut writer = formats::create_writer(
+            format,
+            &options,
+            sink,
+            get_crud_query_builder(&db_type),
+            (&table, schema.as_deref()),
+        )
+        .map_err(|e| e.to_string())?;
+        write_result_rows(writer.as_mut(), &columns, &selection.rows)
+            .and_then(|_| writer.finish())
+            .map_err(|e| format!("Failed to write rows: {}", e))?;
+    }
+    let text = String::from_utf8(buf).map_err(|e| e.to_string())?;
+
+    clipboard::write_text(&text).map_err(|e| e.to_string())?;
+    tracing::info!(
+        "📋 [CLIPBOARD] Copied {} rows of {} as {:?}",
+        selection.rows.len(),
+        table,
+        format
+    );
     Ok(())
 }
 
-#[tauri::command]
-pub async fn copy_data(_table_name: String, _data: Vec<serde_json::Value>) -> Result<(), String> {
-    // TODO: Copy data to clipboard
+/// Write a header and rows keyed by column name, leaving the writer open for more
+fn write_result_rows(
+    writer: &mut dyn RowWriter,
+    columns: &[ExportColumn],
+    rows: &[HashMap<String, Value>],
+) -> std::io::Result<()> {
+    writer.begin(columns)?;
+    for row in rows {
+        let values: Vec<Value> = columns
+            .iter()
+            .map(|col| row.get(&col.name).cloned().unwrap_or(Value::Null))
+            .collect();
+        writer.write_row(&values)?;
+    }
     Ok(())
 }
@@ -0,0 +1,775 @@
+use crate::commands::connection::ConnectionStore;
+use crate::commands::table::get_db_type_from_connection;
+use crate::db::bound_query::{self, BoundQuery};
+use crate::db::traits::{get_crud_query_builder, CRUDQueryBuilder, DatabaseConnection};
+use crate::db::transaction::UnitOfWork;
+use crate::formats::records::{self, Record, RecordReader};
+use crate::formats::xlsx;
+use crate::models::connection::DatabaseType;
+use crate::models::import::*;
+use crate::models::schema::{Column, TableSchema};
+use crate::utils::table_reader::fetch_table_schema;
+use anyhow::anyhow;
+use futures::FutureExt;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::time::Instant;
+use tauri::State;
+
+/// Savepoint an import uses when a manual transaction is already open
+const IMPORT_SAVEPOINT: &str = "rustdbgrid_import";
+
+/// Rows shown when previewing a worksheet
+const PREVIEW_ROWS: usize = 20;
+
+/// List the worksheets of a workbook and the first rows below the header row of one,
+/// to choose the sheet, header row and column mapping of an import
+#[tauri::command]
+pub async fn preview_xlsx(
+    path: String,
+    sheet: Option<String>,
+    header_row: Option<u32>,
+    limit: Option<usize>,
+) -> Result<SheetPreview, String> {
+    tokio::task::spawn_blocking(move || {
+        xlsx::read_sheet(
+            &path,
+            sheet.as_deref(),
+            header_row.unwrap_or(1),
+            Some(limit.unwrap_or(PREVIEW_ROWS)),
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
+
+/// Insert the rows of a worksheet into a table in batches, mapping its columns by name
+/// to the table's columns. All rows are inserted in one transaction, so a failing row
+/// leaves the table as it was.
+#[tauri::command]
+pub async fn import_xlsx(
+    connection_id: String,
+    database: String,
+    table: String,
+    schema: Option<String>,
+    path: String,
+    options: Option<XlsxImportOptions>,
+    state: State<'_, ConnectionStore>,
+) -> Result<ImportSummary, String> {
+    let options = options.unwrap_or_default();
+    tracing::info!("📥 [IMPORT] Importing {} into {}", path, table);
+    state.ensure_connected(&connection_id).await?;
+    let db_type = get_db_type_from_connection(&state, &connection_id)?;
+
+    let start = Instant::now();
+    let (sheet_name, header_row) = (options.sheet.clone(), options.header_row);
+    let sheet = tokio::task::spawn_blocking(move || {
+        xlsx::read_sheet(&path, sheet_name.as_deref(), header_row, None)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())?;
+
+    let table_schema = fetch_table_schema(
+        &state,
+        &connection_id,
+        None,
+        &db_type,
+        &database,
+        &table,
+        schema.as_deref(),
+    )
+    .await?;
+
+    // Rows below the header, numbered as Excel shows them
+    let rows = sheet
+        .rows
+        .into_iter()
+        .enumerate()
+        .map(|(index, row)| ((options.header_row as usize + index + 1) as u64, row))
+        .collect();
+    let reader = RecordReader::from_rows(sheet.headers, rows);
+    let file_options = FileImportOptions {
+        mapping: options.mapping,
+        ..FileImportOptions::default()
+    };
+    let source = format!("sheet '{}'", sheet.sheet);
+    let import = FileImport::new(
+        reader,
+        &source,
+        table,
+        schema,
+        table_schema,
+        &db_type,
+        file_options,
+    )?;
+    run_import(&state, &connection_id, import, start).await
+}
+
+/// Read the column names and first rows of a CSV or NDJSON file, to choose the options
+/// and column mapping of an import
+#[tauri::command]
+pub async fn preview_file(
+    path: String,
+    options: Option<FileImportOptions>,
+    limit: Option<usize>,
+) -> Result<FilePreview, String> {
+    let options = options.unwrap_or_default();
+    tokio::task::spawn_blocking(move || {
+        records::read_preview(&path, &options, limit.unwrap_or(PREVIEW_ROWS))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
+
+/// Insert the rows of a CSV or NDJSON file into a table in batches, converting each
+/// value to the type of its column. Plain inserts into PostgreSQL are loaded with
+/// `COPY FROM STDIN`.
+#[tauri::command]
+pub async fn import_file(
+    connection_id: String,
+    database: String,
+    table: String,
+    schema: Option<String>,
+    path: String,
+    options: Option<FileImportOptions>,
+    state: State<'_, ConnectionStore>,
+) -> Result<ImportSummary, String> {
+    let options = options.unwrap_or_default();
+    tracing::info!(
+        "📥 [IMPORT] Importing {} as {:?} into {}",
+        path,
+        options.format,
+        table
+    );
+    state.ensure_connected(&connection_id).await?;
+    let db_type = get_db_type_from_connection(&state, &connection_id)?;
+
+    let start = Instant::now();
+    let table_schema = fetch_table_schema(
+        &state,
+        &connection_id,
+        None,
+        &db_type,
+        &database,
+        &table,
+        schema.as_deref(),
+    )
+    .await?;
+    let reader = RecordReader::open(&path, &options)
+        .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let import = FileImport::new(
+        reader,
+        &path,
+        table,
+        schema,
+        table_schema,
+        &db_type,
+        options,
+    )?;
+    run_import(&state, &connection_id, import, start).await
+}
+
+/// Insert the rows of an import on the connection and summarize it
+async fn run_import(
+    state: &ConnectionStore,
+    connection_id: &str,
+    mut import: FileImport,
+    start: Instant,
+) -> Result<ImportSummary, String> {
+    let table = import.table.clone();
+    let mapped_columns = std::mem::take(&mut import.mapped_columns);
+    let unmapped_columns = std::mem::take(&mut import.unmapped_columns);
+    let counts = state
+        .pool
+        .with_connection(connection_id, move |conn| import.run(conn).boxed())
+        .await?;
+
+    tracing::info!(
+        "✅ [IMPORT] Imported {} of {} rows into {}, {} failed",
+        counts.imported,
+        counts.read,
+        table,
+        counts.failed
+    );
+    Ok(ImportSummary {
+        rows_read: counts.read,
+        rows_imported: counts.imported,
+        rows_failed: counts.failed,
+        mapped_columns,
+        unmapped_columns,
+        execution_time: start.elapsed().as_millis(),
+    })
+}
+
+/// An import of rows read from a file whose columns are mapped to the table's
+struct FileImport {
+    reader: RecordReader,
+    /// Position in the file of the value of each of `columns`
+    sources: Vec<usize>,
+    columns: Vec<Column>,
+    column_names: Vec<String>,
+    mapped_columns: Vec<ColumnMapping>,
+    unmapped_columns: Vec<String>,
+    table: String,
+    schema: Option<String>,
+    table_schema: TableSchema,
+    crud_builder: Box<dyn CRUDQueryBuilder>,
+    options: FileImportOptions,
+    batch_size: usize,
+    error_log: Option<BufWriter<File>>,
+}
+
+/// Rows an import read, inserted and skipped
+#[derive(Default)]
+struct ImportCounts {
+    read: usize,
+    imported: u64,
+    failed: usize,
+}
+
+impl FileImport {
+    /// Map the columns `reader` reads to the table's; `source` names where the rows
+    /// come from in errors
+    fn new(
+        reader: RecordReader,
+        source: &str,
+        table: String,
+        schema: Option<String>,
+        table_schema: TableSchema,
+        db_type: &DatabaseType,
+        options: FileImportOptions,
+    ) -> Result<Self, String> {
+        let (targets, mapped_columns, unmapped_columns) =
+            map_columns(reader.headers(), &table_schema, &options.mapping);
+        if mapped_columns.is_empty() {
+            return Err(format!(
+                "No column of {} matches a column of {}",
+                source, table
+            ));
+        }
+        let columns: Vec<Column> = targets
+            .iter()
+            .filter_map(|(_, target)| table_schema.columns.iter().find(|col| col.name == *target))
+            .cloned()
+            .collect();
+        let column_names: Vec<String> = columns.iter().map(|col| col.name.clone()).collect();
+        if options.upsert {
+            bound_query::upsert_columns(&column_names, &table_schema).map_err(|e| e.to_string())?;
+        }
+        if options.truncate && options.error_log.is_some() {
+            return Err(
+                "Deleting the rows of the table first cannot be combined with logging failing rows, as each batch is then committed on its own"
+                    .to_string(),
+            );
+        }
+        let error_log = options
+            .error_log
+            .as_deref()
+            .map(|log| {
+                File::create(log)
+                    .map(BufWriter::new)
+                    .map_err(|e| format!("Failed to create {}: {}", log, e))
+            })
+            .transpose()?;
+
+        let crud_builder = get_crud_query_builder(db_type);
+        let batch_size = options
+            .batch_size
+            .clamp(1, crud_builder.max_batch_rows(columns.len()));
+        Ok(Self {
+            reader,
+            sources: targets.into_iter().map(|(index, _)| index).collect(),
+            columns,
+            column_names,
+            mapped_columns,
+            unmapped_columns,
+            table,
+            schema,
+            table_schema,
+            crud_builder,
+            options,
+            batch_size,
+            error_log,
+        })
+    }
+
+    /// Without an error log the whole import is one unit of work, undone by the first
+    /// failing row, and deleting the table's rows first is part of it. With one, each
+    /// batch is its own unit and failing rows are logged.
+    async fn run(mut self, conn: &mut Box<dyn DatabaseConnection>) -> anyhow::Result<ImportCounts> {
+        let use_copy = !self.options.upsert && conn.supports_copy_in();
+        let unit = if self.error_log.is_none() && conn.supports_transactions() {
+            Some(UnitOfWork::begin(conn, IMPORT_SAVEPOINT).await?)
+        } else {
+            None
+        };
+        if self.options.truncate && unit.is_none() {
+            return Err(anyhow!(
+                "Deleting the rows of the table first needs a transaction, which this connection does not support"
+            ));
+        }
+        let result = match self.truncate(conn).await {
+            Ok(()) => self.load(conn, use_copy).await,
+            Err(e) => Err(e),
+        };
+        match (unit, result) {
+            (Some(unit), Ok(counts)) => {
+                unit.commit(conn).await?;
+                Ok(counts)
+            }
+            (Some(unit), Err(e)) => {
+                unit.rollback(conn).await?;
+                Err(e)
+            }
+            (None, result) => result,
+        }
+    }
+
+    /// Delete the rows of the table when asked to, in the import's unit of work
+    async fn truncate(&self, conn: &mut Box<dyn DatabaseConnection>) -> anyhow::Result<()> {
+        if !self.options.truncate {
+            return Ok(());
+        }
+        // DELETE rather than TRUNCATE, which MySQL commits on its own and PostgreSQL and
+        // SQL Server refuse on tables other tables reference
+        let table_name = self
+            .crud_builder
+            .crud_table_name(&self.table, self.schema.as_deref());
+        conn.execute_update_bound(&BoundQuery::new(format!("DELETE FROM {}", table_name)))
+            .await?;
+        Ok(())
+    }
+
+    async fn load(
+        &mut self,
+        conn: &mut Box<dyn DatabaseConnection>,
+        use_copy: bool,
+    ) -> anyhow::Result<ImportCounts> {
+        let mut counts = ImportCounts::default();
+
+        let mut batch: Vec<(u64, Vec<Value>)> = Vec::with_capacity(self.batch_size);
+        loop {
+            let record = self.reader.next_record()?;
+            let done = record.is_none();
+            if let Some(Record { line, values }) = record {
+                match values.and_then(|values| self.convert(&values)) {
+                    Ok(Some(row)) => batch.push((line, row)),
+                    // Blank rows are skipped
+                    Ok(None) => continue,
+                    Err(e) => self.fail(line, &e, None, &mut counts)?,
+                }
+                counts.read += 1;
+            }
+            if batch.len() >= self.batch_size || (done && !batch.is_empty()) {
+                counts.imported += self.insert(conn, &batch, use_copy, &mut counts).await?;
+                batch.clear();
+            }
+            if done {
+                break;
+            }
+        }
+
+        if let Some(log) = &mut self.error_log {
+            log.flush()?;
+        }
+        Ok(counts)
+    }
+
+    /// Values of a row for the table's columns, `None` when the row is blank
+    fn convert(&self, values: &[Value]) -> Result<Option<Vec<Value>>, String> {
+        let source = |index: &usize| values.get(*index).unwrap_or(&Value::Null);
+        if self
+            .sources
+            .iter()
+            .all(|index| matches!(source(index), Value::Null) || source(index) == "")
+        {
+            return Ok(None);
+        }
+        self.sources
+            .iter()
+            .zip(&self.columns)
+            .map(|(index, column)| {
+                coerce_value(source(index).clone(), column, &self.options)
+                    .map_err(|e| format!("Column '{}': {}", column.name, e))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(Some)
+    }
+
+    /// Insert a batch, returning how many of its rows were inserted. Without an error
+    /// log a failing batch fails the import; with one, the rows of a failing batch are
+    /// retried one at a time and those that fail again are logged.
+    async fn insert(
+        &mut self,
+        conn: &mut Box<dyn DatabaseConnection>,
+        batch: &[(u64, Vec<Value>)],
+        use_copy: bool,
+        counts: &mut ImportCounts,
+    ) -> anyhow::Result<u64> {
+        let rows: Vec<Vec<Value>> = batch.iter().map(|(_, row)| row.clone()).collect();
+        if self.error_log.is_none() {
+            return match self.write_rows(conn, &rows, use_copy).await {
+                Ok(()) => Ok(rows.len() as u64),
+                Err(e) => match (batch.first(), batch.last()) {
+                    (Some((first, _)), Some((last, _))) if first != last => {
+                        Err(anyhow!("Lines {}-{}: {}", first, last, e))
+                    }
+                    _ => Err(anyhow!("Line {}: {}", batch[0].0, e)),
+                },
+            };
+        }
+
+        if self.write_unit(conn, &rows, use_copy).await.is_ok() {
+            return Ok(rows.len() as u64);
+        }
+        let mut inserted = 0;
+        for (line, row) in batch {
+            match self
+                .write_unit(conn, std::slice::from_ref(row), false)
+                .await
+            {
+                Ok(()) => inserted += 1,
+                Err(e) => self.fail(*line, &e.to_string(), Some(row), counts)?,
+            }
+        }
+        Ok(inserted)
+    }
+
+    /// Write rows in a unit of work of their own, undone when they fail
+    async fn write_unit(
+        &self,
+        conn: &mut Box<dyn DatabaseConnection>,
+        rows: &[Vec<Value>],
+        use_copy: bool,
+    ) -> anyhow::Result<()> {
+        let unit = if conn.supports_transactions() {
+            Some(UnitOfWork::begin(conn, IMPORT_SAVEPOINT).await?)
+        } else {
+            None
+        };
+        let result = self.write_rows(conn, rows, use_copy).await;
+        match (unit, result) {
+            (Some(unit), Ok(())) => unit.commit(conn).await,
+            (Some(unit), Err(e)) => {
+                unit.rollback(conn).await?;
+                Err(e)
+            }
+            (None, result) => result,
+        }
+    }
+
+    async fn write_rows(
+        &self,
+        conn: &mut Box<dyn DatabaseConnection>,
+        rows: &[Vec<Value>],
+        use_copy: bool,
+    ) -> anyhow::Result<()> {
+        if use_copy {
+            let table_name = self
+                .crud_builder
+                .crud_table_name(&self.table, self.schema.as_deref());
+            let columns: Vec<String> = self
+                .column_names
+                .iter()
+                .map(|col| self.crud_builder.quote_identifier(col))
+                .collect();
+            conn.copy_in(&table_name, &columns, rows).await?;
+        } else {
+            let query = self.crud_builder.build_insert_batch_bound(
+                &self.table,
+                self.schema.as_deref(),
+                &self.column_names,
+                rows,
+                &self.table_schema,
+                self.options.upsert,
+            )?;
+            conn.execute_update_bound(&query).await?;
+        }
+        Ok(())
+    }
+
+    /// Log a row that failed and count it, or fail the import when there is no log
+    fn fail(
+        &mut self,
+        line: u64,
+        error: &str,
+        row: Option<&Vec<Value>>,
+        counts: &mut ImportCounts,
+    ) -> anyhow::Result<()> {
+        let Some(log) = &mut self.error_log else {
+            return Err(anyhow!("Line {}: {}", line, error));
+        };
+        match row {
+            Some(row) => writeln!(
+                log,
+                "Line {}: {}: {}",
+                line,
+                error,
+                Value::from(row.clone())
+            )?,
+            None => writeln!(log, "Line {}: {}", line, error)?,
+        }
+        counts.failed += 1;
+        Ok(())
+    }
+}
+
+/// How an imported value is converted for a column type
+#[derive(Debug, PartialEq)]
+enum ColumnKind {
+    Integer,
+    Float,
+    /// Exact numbers, kept as text so no digit is lost
+    Decimal,
+    Boolean,
+    Json,
+    Text,
+    /// Dates, times, UUIDs and the like, passed on as text for the database to parse
+    Other,
+}
+
+fn column_kind(data_type: &str) -> ColumnKind {
+    let lower = data_type.to_lowercase();
+    // The type name without its modifiers, e.g. `int` of `int(11) unsigned` and
+    // `character` of `character varying(20)`
+    let base = lower.split('(').next().unwrap_or_default();
+    let name = base.split_whitespace().next().unwrap_or_default();
+    match name {
+        "bool" | "boolean" => ColumnKind::Boolean,
+        "json" | "jsonb" => ColumnKind::Json,
+        "real" | "float" | "float4" | "float8" | "double" => ColumnKind::Float,
+        "numeric" | "decimal" | "dec" | "money" | "smallmoney" | "number" => ColumnKind::Decimal,
+        "tinyint" | "smallint" | "mediumint" | "int" | "integer" | "bigint" | "int2" | "int4"
+        | "int8" | "smallserial" | "serial" | "bigserial" | "serial2" | "serial4" | "serial8" => {
+            ColumnKind::Integer
+        }
+        "char" | "character" | "nchar" | "varchar" | "nvarchar" | "bpchar" | "text"
+        | "tinytext" | "mediumtext" | "longtext" | "ntext" | "citext" | "string" | "xml" => {
+            ColumnKind::Text
+        }
+        _ => ColumnKind::Other,
+    }
+}
+
+/// Value a column is given for an imported value: text is parsed as the column's type,
+/// the NULL marker and empty text become NULL (empty text stays in text columns unless
+/// `empty_as_null`), and nested JSON becomes its text outside JSON columns
+fn coerce_value(
+    value: Value,
+    column: &Column,
+    options: &FileImportOptions,
+) -> Result<Value, String> {
+    let kind = column_kind(&column.data_type);
+    let text = match value {
+        Value::Null => return Ok(Value::Null),
+        Value::String(text) => text,
+        Value::Bool(b) => match kind {
+            ColumnKind::Integer | ColumnKind::Float | ColumnKind::Decimal => {
+                return Ok(Value::from(i64::from(b)))
+            }
+            _ => return Ok(Value::Bool(b)),
+        },
+        Value::Number(n) => {
+            return match kind {
+                ColumnKind::Integer if !n.is_i64() && !n.is_u64() => n
+                    .as_f64()
+                    .filter(|f| f.fract() == 0.0 && f.abs() < i64::MAX as f64)
+                    .map(|f| Value::from(f as i64))
+                    .ok_or_else(|| format!("{} is not an integer", n)),
+                ColumnKind::Boolean => Ok(Value::Bool(n.as_f64() != Some(0.0))),
+                _ => Ok(Value::Number(n)),
+            }
+        }
+        nested @ (Value::Array(_) | Value::Object(_)) => {
+            return Ok(match kind {
+                ColumnKind::Json => nested,
+                _ => Value::String(nested.to_string()),
+            })
+        }
+    };
+
+    if !options.null_value.is_empty() && text == options.null_value {
+        return Ok(Value::Null);
+    }
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return Ok(if kind == ColumnKind::Text && !options.empty_as_null {
+            Value::String(text)
+        } else {
+            Value::Null
+        });
+    }
+
+    match kind {
+        ColumnKind::Integer => trimmed
+            .parse::<i64>()
+            .map(Value::from)
+            .or_else(|_| {
+                parse_bool(trimmed)
+                    .map(|b| Value::from(i64::from(b)))
+                    .ok_or(())
+            })
+            .map_err(|_| format!("'{}' is not an integer", text)),
+        ColumnKind::Float => trimmed
+            .parse::<f64>()
+            .map(|f| {
+                serde_json::Number::from_f64(f)
+                    .map(Value::Number)
+                    // NaN and infinity have no JSON number; the database parses the text
+                    .unwrap_or_else(|| Value::String(trimmed.to_string()))
+            })
+            .map_err(|_| format!("'{}' is not a number", text)),
+        ColumnKind::Decimal => trimmed
+            .parse::<f64>()
+            .map(|_| Value::String(trimmed.to_string()))
+            .map_err(|_| format!("'{}' is not a number", text)),
+        ColumnKind::Boolean => parse_bool(trimmed)
+            .map(Value::Bool)
+            .ok_or_else(|| format!("'{}' is not a boolean", text)),
+        ColumnKind::Json | ColumnKind::Text => Ok(Value::String(text)),
+        ColumnKind::Other => Ok(Value::String(trimmed.to_string())),
+    }
+}
+
+fn parse_bool(text: &str) -> Option<bool> {
+    match text.to_lowercase().as_str() {
+        "true" | "t" | "yes" | "y" | "on" | "1" => Some(true),
+        "false" | "f" | "no" | "n" | "off" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// Table column each source column goes to: the one `mapping` names, none when it
+/// names an empty one, otherwise the one of the same name ignoring case. Returns the
+/// source positions with their target columns, the mapping for display and the source
+/// columns left out.
+fn map_columns(
+    headers: &[String],
+    table_schema: &TableSchema,
+    mapping: &HashMap<String, String>,
+) -> (Vec<(usize, String)>, Vec<ColumnMapping>, Vec<String>) {
+    let mut targets: Vec<(usize, String)> = Vec::new();
+    let mut unmapped = Vec::new();
+    for (index, header) in headers.iter().enumerate() {
+        let wanted = mapping.get(header).unwrap_or(header);
+        let target = Some(wanted)
+            .filter(|wanted| !wanted.is_empty())
+            .and_then(|wanted| {
+                table_schema
+                    .columns
+                    .iter()
+                    .find(|col| col.name == *wanted)
+                    .or_else(|| {
+                        table_schema
+                            .columns
+                            .iter()
+                            .find(|col| col.name.eq_ignore_ascii_case(wanted))
+                    })
+            });
+        match target {
+            // A column fed by two source columns would be inserted twice
+            Some(col) if !targets.iter().any(|(_, name)| *name == col.name) => {
+                targets.push((index, col.name.clone()))
+            }
+            _ => unmapped.push(header.clone()),
+        }
+    }
+
+    let mapped = targets
+        .iter()
+        .map(|(index, target)| ColumnMapping {
+            source: headers[*index].clone(),
+            target: target.clone(),
+        })
+        .collect();
+    (targets, mapped, unmapped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::schema::test_column;
+    use serde_json::json;
+
+    #[test]
+    fn test_columns_map_by_explicit_mapping_then_by_name() {
+        let column = |name: &str| test_column(name, "text", true, None);
+        let table_schema = TableSchema {
+            table_name: "people".to_string(),
+            columns: vec![column("id"), column("full_name"), column("email")],
+            indexes: vec![],
+            foreign_keys: vec![],
+            has_triggers: false,
+        };
+        let headers: Vec<String> = ["ID", "Name", "Notes", "id", "Email"]
+            .iter()
+            .map(|h| h.to_string())
+            .collect();
+        let mapping = HashMap::from([
+            ("Name".to_string(), "full_name".to_string()),
+            ("Email".to_string(), String::new()),
+        ]);
+
+        let (targets, _, unmapped) = map_columns(&headers, &table_schema, &mapping);
+        assert_eq!(
+            targets,
+            vec![(0, "id".to_string()), (1, "full_name".to_string())]
+        );
+        assert_eq!(
+            unmapped,
+            vec!["Notes".to_string(), "id".to_string(), "Email".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_values_are_coerced_to_the_column_type() {
+        let column = |data_type: &str| test_column("c", data_type, true, None);
+        let options = FileImportOptions {
+            null_value: "\\N".to_string(),
+            ..Default::default()
+        };
+        let coerce =
+            |value: Value, data_type: &str| coerce_value(value, &column(data_type), &options);
+
+        assert_eq!(coerce(json!(" 42 "), "bigint"), Ok(json!(42)));
+        assert_eq!(coerce(json!("yes"), "tinyint(1)"), Ok(json!(1)));
+        assert_eq!(coerce(json!(3.0), "int4"), Ok(json!(3)));
+        assert!(coerce(json!("4.5"), "integer").is_err());
+        assert_eq!(coerce(json!("1.5"), "double precision"), Ok(json!(1.5)));
+        assert_eq!(
+            coerce(json!("12345678901234567890.12"), "numeric(30,2)"),
+            Ok(json!("12345678901234567890.12"))
+        );
+        assert_eq!(coerce(json!("Off"), "boolean"), Ok(json!(false)));
+        assert!(coerce(json!("maybe"), "bool").is_err());
+        assert_eq!(coerce(json!("2024-01-02"), "date"), Ok(json!("2024-01-02")));
+        assert_eq!(coerce(json!("7"), "bigint unsigned"), Ok(json!(7)));
+        assert_eq!(
+            coerce(json!(" ab "), "character varying(5)"),
+            Ok(json!(" ab "))
+        );
+        assert_eq!(column_kind("multipoint"), ColumnKind::Other);
+        assert_eq!(column_kind("point"), ColumnKind::Other);
+        assert_eq!(column_kind("interval day to second"), ColumnKind::Other);
+        assert_eq!(column_kind("tsvector"), ColumnKind::Other);
+        assert_eq!(coerce(json!({"a": 1}), "jsonb"), Ok(json!({"a": 1})));
+        assert_eq!(coerce(json!(["x"]), "text"), Ok(json!("[\"x\"]")));
+
+        assert_eq!(coerce(json!("\\N"), "varchar(10)"), Ok(Value::Null));
+        assert_eq!(coerce(json!(""), "varchar(10)"), Ok(json!("")));
+        assert_eq!(coerce(json!(""), "int"), Ok(Value::Null));
+        let options = FileImportOptions {
+            empty_as_null: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            coerce_value(json!(""), &column("text"), &options),
+            Ok(Value::Null)
+        );
+    }
+}
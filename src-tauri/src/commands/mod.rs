@@ -1,9 +1,13 @@
 pub mod app;
 pub mod connection;
 pub mod connection_info;
+pub mod dump;
 pub mod export;
+pub mod import;
 pub mod logging;
 pub mod query;
 pub mod schema;
 pub mod settings;
 pub mod table;
+pub mod transaction;
+pub mod transfer;
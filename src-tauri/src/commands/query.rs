@@ -1,5 +1,7 @@
 use crate::commands::connection::ConnectionStore;
+use crate::models::explain::ExecutionPlan;
 use crate::models::query_result::*;
+use crate::models::script_request::*;
 use futures::FutureExt;
 use tauri::State;
 
@@ -769,3 +771,274 @@ pub async fn get_distinct_values(
         },
     )
 }
+
+/// Run a raw SQL script from the editor, one statement at a time
+#[tauri::command]
+pub async fn execute_script(
+    request: ScriptRequest,
+    state: State<'_, ConnectionStore>,
+) -> Result<ScriptResponse, String> {
+    use crate::db::sql_splitter::{split_statements, SqlDialect, SqlStatement};
+    use crate::db::transaction::TransactionCommand;
+    use crate::utils::server_messages::capture_messages;
+    use std::time::Instant;
+
+    let connection_id = request.connection_id.clone();
+    let tab_id = request.tab_id.as_deref();
+    state.ensure_connected(&connection_id).await?;
+    let dialect = SqlDialect::from_db_type(&state.get_config(&connection_id)?.db_type);
+
+    // MongoDB and Redis scripts are single JSON/command documents, not SQL
+    let statements = match dialect {
+        Some(dialect) => split_statements(&request.script, dialect),
+        None => vec![SqlStatement::from_range(
+            &request.script,
+            0,
+            request.script.len(),
+        )],
+    };
+
+    tracing::info!(
+        "📜 [SCRIPT] Executing {} statement(s) on connection: {}",
+        statements.len(),
+        connection_id
+    );
+
+    let script_start = Instant::now();
+    let mut results = Vec::with_capacity(statements.len());
+    let mut failed_count = 0;
+
+    for (index, statement) in statements.iter().enumerate() {
+        if failed_count > 0 && request.stop_on_error {
+            break;
+        }
+
+        let start = Instant::now();
+        let query_clone = statement.text.clone();
+        // Transaction control must run on the pinned session, not any pooled connection
+        let transaction_name = match dialect {
+            Some(SqlDialect::MSSQL) => state
+                .pool
+                .transaction_state(&connection_id, tab_id)
+                .await
+                .ok()
+                .and_then(|t| t.name),
+            _ => None,
+        };
+        let transaction_command = dialect.and_then(|dialect| {
+            TransactionCommand::parse(&statement.text, dialect, transaction_name.as_deref())
+        });
+
+        let (result, messages) = capture_messages(async {
+            if let Some(command) = transaction_command {
+                state
+                    .pool
+                    .transaction_control(&connection_id, tab_id, command)
+                    .await
+                    .map(|_| StatementResult::from_update(index, statement.text.clone(), 0, 0))
+            } else {
+                // Every statement reports its result sets, or its affected rows when it
+                // returns none
+                state
+                    .pool
+                    .with_query_timeout(&connection_id, tab_id, |conn| {
+                        async move { conn.execute_query_multi(&query_clone).await }.boxed()
+                    })
+                    .await
+                    .map(|r| StatementResult::from_results(index, statement.text.clone(), r, 0))
+            }
+        })
+        .await;
+        let execution_time = start.elapsed().as_millis();
+
+        match result {
+            Ok(mut statement_result) => {
+                statement_result.execution_time = execution_time;
+                results.push(
+                    statement_result
+                        .with_messages(messages)
+                        .with_offsets(statement.start, statement.end),
+                );
+            }
+            Err(e) => {
+                tracing::warn!("⚠️ [SCRIPT] Statement {} failed: {}", index + 1, e);
+                failed_count += 1;
+                results.push(
+                    StatementResult::from_error(index, statement.text.clone(), e, execution_time)
+                        .with_messages(messages)
+                        .with_offsets(statement.start, statement.end),
+                );
+            }
+        }
+    }
+
+    let executed_count = results.len();
+    let in_transaction = state
+        .pool
+        .transaction_state(&connection_id, tab_id)
+        .await
+        .map(|t| t.active)
+        .unwrap_or(false);
+    let response = ScriptResponse {
+        results,
+        executed_count,
+        failed_count,
+        skipped_count: statements.len() - executed_count,
+        execution_time: script_start.elapsed().as_millis(),
+        in_transaction,
+    };
+
+    tracing::info!(
+        "✅ [SCRIPT] Finished. Executed: {}, Failed: {}, Skipped: {}, Time: {}ms",
+        response.executed_count,
+        response.failed_count,
+        response.skipped_count,
+        response.execution_time
+    );
+
+    Ok(response)
+}
+
+/// Run a query and return every result set it produced along with the server's
+/// informational messages (PRINT, RAISERROR, NOTICE)
+#[tauri::command]
+pub async fn execute_query_multi(
+    connection_id: String,
+    tab_id: Option<String>,
+    query: String,
+    state: State<'_, ConnectionStore>,
+) -> Result<MultiQueryResult, String> {
+    use crate::utils::server_messages::capture_messages;
+    use std::time::Instant;
+
+    tracing::info!(
+        "📚 [COMMAND] Executing multi-result query on connection: {}",
+        connection_id
+    );
+
+    state.ensure_connected(&connection_id).await?;
+    let start = Instant::now();
+
+    let (result, messages) = capture_messages(state.pool.with_query_timeout(
+        &connection_id,
+        tab_id.as_deref(),
+        |conn| async move { conn.execute_query_multi(&query).await }.boxed(),
+    ))
+    .await;
+    let result_sets = result?;
+
+    tracing::info!(
+        "✅ [COMMAND] Query returned {} result set(s) and {} message(s)",
+        result_sets.len(),
+        messages.len()
+    );
+
+    Ok(MultiQueryResult {
+        result_sets,
+        messages,
+        execution_time: start.elapsed().as_millis(),
+    })
+}
+
+/// Get the execution plan of a query as a tree. With `analyze` the query is actually
+/// run to collect timings and row counts, inside a transaction (or savepoint) that is
+/// rolled back afterwards.
+#[tauri::command]
+pub async fn explain_query(
+    connection_id: String,
+    tab_id: Option<String>,
+    query: String,
+    analyze: bool,
+    state: State<'_, ConnectionStore>,
+) -> Result<ExecutionPlan, String> {
+    tracing::info!(
+        "🔎 [COMMAND] Explaining query on connection: {} (analyze: {})",
+        connection_id,
+        analyze
+    );
+
+    state.ensure_connected(&connection_id).await?;
+    state
+        .pool
+        .with_query_timeout(&connection_id, tab_id.as_deref(), |conn| {
+            async move { conn.explain(&query, analyze).await }.boxed()
+        })
+        .await
+}
+
+/// Number of rows per batch when the frontend doesn't ask for a size
+const DEFAULT_STREAM_BATCH_SIZE: usize = 500;
+
+/// Run a query and stream its rows to the frontend in batches over `on_event`. With a
+/// `tab_id` the query runs on that editor tab's dedicated session.
+#[tauri::command]
+pub async fn execute_query_stream(
+    connection_id: String,
+    tab_id: Option<String>,
+    query: String,
+    batch_size: Option<usize>,
+    on_event: tauri::ipc::Channel<QueryStreamEvent>,
+    state: State<'_, ConnectionStore>,
+) -> Result<u64, String> {
+    use std::time::Instant;
+
+    tracing::info!(
+        "📡 [STREAM] Streaming query on connection: {}",
+        connection_id
+    );
+
+    state.ensure_connected(&connection_id).await?;
+    let batch_size = batch_size.unwrap_or(DEFAULT_STREAM_BATCH_SIZE).max(1);
+    let start = Instant::now();
+
+    // Bounded so a slow frontend applies backpressure to the database cursor
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<QueryStreamEvent>(4);
+    let forward_channel = on_event.clone();
+    let forwarder = tokio::spawn(async move {
+        while let Some(event) = rx.recv().await {
+            if let Err(e) = forward_channel.send(event) {
+                tracing::warn!("⚠️ [STREAM] Frontend channel closed: {}", e);
+                break;
+            }
+        }
+    });
+
+    let result = state
+        .pool
+        .with_query_timeout(&connection_id, tab_id.as_deref(), |conn| {
+            async move { conn.execute_query_streaming(&query, batch_size, &tx).await }.boxed()
+        })
+        .await;
+    let _ = forwarder.await;
+
+    let row_count = result?;
+    let execution_time = start.elapsed().as_millis();
+    on_event
+        .send(QueryStreamEvent::Finished {
+            row_count,
+            execution_time,
+        })
+        .map_err(|e| format!("Failed to send stream result: {}", e))?;
+
+    tracing::info!(
+        "✅ [STREAM] Streamed {} rows in {}ms",
+        row_count,
+        execution_time
+    );
+
+    Ok(row_count)
+}
+
+/// Cancel the query currently running on a connection, or on an editor tab's session
+#[tauri::command]
+pub async fn cancel_query(
+    connection_id: String,
+    tab_id: Option<String>,
+    state: State<'_, ConnectionStore>,
+) -> Result<(), String> {
+    tracing::info!(
+        "🛑 [COMMAND] Cancelling query on connection: {}",
+        connection_id
+    );
+    state.pool.cancel(&connection_id, tab_id.as_deref()).await
+}
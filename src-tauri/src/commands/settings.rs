@@ -1,5 +1,6 @@
+use crate::commands::connection::ConnectionStore;
 use crate::utils::settings::{self, AppSettings};
-use tauri::command;
+use tauri::{command, State};
 
 /// Get config directory path
 #[command]
@@ -20,9 +21,13 @@ pub async fn get_settings() -> Result<AppSettings, String> {
 
 /// Save all settings
 #[command]
-pub async fn save_settings(settings: AppSettings) -> Result<(), String> {
+pub async fn save_settings(
+    settings: AppSettings,
+    state: State<'_, ConnectionStore>,
+) -> Result<(), String> {
     tracing::info!("⚙️ [SETTINGS] Saving application settings");
     let result = settings::save_settings(&settings).map_err(|e| e.to_string());
+    state.pool.invalidate_settings();
 
     if result.is_ok() {
         tracing::info!("✅ [SETTINGS] Settings saved successfully");
@@ -33,8 +38,14 @@ pub async fn save_settings(settings: AppSettings) -> Result<(), String> {
 
 /// Update a single setting
 #[command]
-pub async fn update_setting(key: String, value: serde_json::Value) -> Result<AppSettings, String> {
-    settings::update_setting(&key, value).map_err(|e| e.to_string())
+pub async fn update_setting(
+    key: String,
+    value: serde_json::Value,
+    state: State<'_, ConnectionStore>,
+) -> Result<AppSettings, String> {
+    let result = settings::update_setting(&key, value).map_err(|e| e.to_string());
+    state.pool.invalidate_settings();
+    result
 }
 
 /// Get theme setting
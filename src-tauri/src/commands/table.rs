@@ -1,15 +1,30 @@
 use crate::commands::connection::ConnectionStore;
-use crate::db::traits::{get_crud_query_builder, get_query_builder};
+use crate::db::bound_query::{
+    self, BoundQuery, InsertReadBack, ReferenceLink, ROW_LOCATOR_COLUMN, ROW_VERSION_COLUMN,
+};
+use crate::db::traits::{
+    get_crud_query_builder, get_query_builder, CRUDQueryBuilder, DatabaseConnection, QueryBuilder,
+};
+use crate::db::transaction::UnitOfWork;
 use crate::models::connection::DatabaseType;
-use crate::models::save_request::{SaveRequest, SaveResponse};
+use crate::models::delete_impact::{DeleteImpact, DependentRows, OnDeleteAction};
+use crate::models::edit_history::EditHistoryEntry;
+use crate::models::save_request::{
+    EditedRow, MaterializedRow, SaveConflict, SaveFailure, SaveOperation, SavePreview, SaveRequest,
+    SaveResponse, StatementPreview,
+};
+use crate::models::schema::{TableRelationship, TableSchema};
 use crate::models::table_request::*;
+use crate::utils::edit_history;
 use futures::FutureExt;
+use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::Instant;
 use tauri::State;
 use tracing::{error, info, warn};
 
 /// Helper function to get database type from connection_id
-fn get_db_type_from_connection(
+pub(crate) fn get_db_type_from_connection(
     state: &ConnectionStore,
     connection_id: &str,
 ) -> Result<DatabaseType, String> {
@@ -53,46 +68,115 @@ pub async fn load_table_data(
         state.pool.connect(config).await?;
     }
 
-    // Build SQL query using appropriate query builder from the db_type in request
-    let sql_query = {
+    // Get table schema to include auto_increment information and bind typed filter values.
+    // The first page reads it fresh; later pages of the same table reuse it.
+    let schema_key = (
+        connection_id.clone(),
+        query_req.database.clone().unwrap_or_default(),
+        query_req.table.clone(),
+    );
+    let cached_schema = (query_req.offset > 0)
+        .then(|| state.cached_table_schema(&schema_key))
+        .flatten();
+    let table_schema = match cached_schema {
+        Some(schema) => Some(schema),
+        None => {
+            let (db, tbl) = (schema_key.1.clone(), schema_key.2.clone());
+            let fetched = state
+                .pool
+                .with_connection(&connection_id, |conn| {
+                    async move { conn.get_table_schema(&db, &tbl).await }.boxed()
+                })
+                .await
+                .ok()
+                .map(Arc::new);
+            if let Some(schema) = &fetched {
+                state.cache_table_schema(schema_key, schema.clone());
+            }
+            fetched
+        }
+    };
+
+    // Rows of a table without a primary key are identified by the engine's row locator
+    // when it has one, otherwise edits match them by all of their values
+    let is_table = matches!(
+        query_req.db_type,
+        DatabaseType::MySQL | DatabaseType::PostgreSQL | DatabaseType::MSSQL
+    ) && !query_req.table.trim().starts_with("RustDBGridQuery(");
+    let has_primary_key = table_schema
+        .as_ref()
+        .map(|schema| schema.columns.iter().any(|col| col.is_primary_key));
+    let keyless = is_table && has_primary_key == Some(false);
+
+    // Build SQL query using appropriate query builder from the db_type in request.
+    // Filter values are bound as parameters; the literal form is only for display.
+    let (bound_query, sql_query, row_locator, row_version) = {
         let query_builder = get_query_builder(&query_req.db_type);
-        query_builder
+        let mut bound_query = query_builder
+            .build_select_query_bound(query_req, table_schema.as_deref())
+            .map_err(|e| format!("Failed to build query: {}", e))?;
+        let sql_query = query_builder
             .build_select_query(query_req)
-            .map_err(|e| format!("Failed to build query: {}", e))?
+            .map_err(|e| format!("Failed to build query: {}", e))?;
+        // Keyed rows also load the dialect's row version so saves can tell they changed
+        // since; a row locator changes with the row already
+        let row_locator = query_builder.row_locator().filter(|_| keyless);
+        let row_version = query_builder
+            .row_version()
+            .filter(|_| is_table && has_primary_key == Some(true));
+        let hidden: Vec<(&str, &str)> = row_locator
+            .map(|locator| (locator, ROW_LOCATOR_COLUMN))
+            .into_iter()
+            .chain(row_version.map(|version| (version, ROW_VERSION_COLUMN)))
+            .collect();
+        let hidden_select = bound_query::select_with_hidden_columns(
+            query_builder.as_ref(),
+            &bound_query.sql,
+            &hidden,
+        );
+        let loaded = hidden_select.is_some();
+        if let Some(sql) = hidden_select {
+            bound_query.sql = sql;
+        }
+        (
+            bound_query,
+            sql_query,
+            row_locator
+                .filter(|_| loaded)
+                .map(|_| ROW_LOCATOR_COLUMN.to_string()),
+            row_version
+                .filter(|_| loaded)
+                .map(|_| ROW_VERSION_COLUMN.to_string()),
+        )
     }; // query_builder dropped here before await
 
-    tracing::debug!("🔍 [TABLE_DATA] Generated SQL: {}", sql_query);
-
-    // Get table schema to include auto_increment information
-    let table_schema = state
-        .pool
-        .with_connection(&connection_id, |conn| {
-            let db = query_req.database.clone().unwrap_or_default();
-            let tbl = query_req.table.clone();
-            async move { conn.get_table_schema(&db, &tbl).await }.boxed()
-        })
-        .await
-        .ok();
+    tracing::debug!(
+        "🔍 [TABLE_DATA] Generated SQL: {} (params: {})",
+        bound_query.sql,
+        bound_query.params.len()
+    );
 
     // Execute query and measure time
     let start = Instant::now();
     let query_result = state
         .pool
         .with_connection(&connection_id, |conn| {
-            let query_clone = sql_query.clone();
-            async move { conn.execute_query(&query_clone).await }.boxed()
+            let query_clone = bound_query.clone();
+            async move { conn.execute_query_bound(&query_clone).await }.boxed()
         })
         .await?;
     let execution_time = start.elapsed();
 
     // Convert QueryResult to TableDataResponse with Vec<Vec<Value>>
-    let columns = convert_columns(&query_result, table_schema.as_ref());
+    let columns = convert_columns(&query_result, table_schema.as_deref());
     let rows = convert_rows_to_vec(&query_result);
 
     // Check if there's more data by comparing returned rows with limit
     let has_more_data = query_result.rows.len() >= query_req.limit;
 
-    let response = TableDataResponse::new(columns, rows, sql_query, has_more_data, execution_time);
+    let ambiguous_edits = keyless && row_locator.is_none();
+    let response = TableDataResponse::new(columns, rows, sql_query, has_more_data, execution_time)
+        .with_row_identity(row_locator, row_version, ambiguous_edits);
 
     tracing::info!(
         "✅ [TABLE_DATA] Data loaded. Rows: {}, Columns: {}, Time: {:?}",
@@ -153,6 +237,8 @@ fn convert_rows_to_vec(
         .collect()
 }
 
+/// SQL a save request would run, one statement per row with the values it binds. The
+/// statements come from the same builders as the save.
 #[tauri::command]
 pub async fn generate_sql(
     connection_id: String,
@@ -165,115 +251,643 @@ pub async fn generate_sql(
     let db_type = get_db_type_from_connection(&state, &connection_id)?;
     info!("🔍 Generating SQL for table: {}.{}", database, &table);
 
+    let (statements, _) = build_row_statements(
+        &state,
+        &connection_id,
+        &db_type,
+        (&database, &table, schema.as_deref()),
+        &save_request,
+    )
+    .await?;
+
     let mut queries = Vec::new();
-    let crud_builder = get_crud_query_builder(&db_type);
+    for statement in &statements {
+        match &statement.query {
+            Ok(query) => queries.push(query.preview()),
+            Err(e) => {
+                let operation = statement.operation.as_str().to_uppercase();
+                error!(
+                    "Failed to generate {} for row {}: {}",
+                    operation, statement.row_index, e
+                );
+                return Err(format!(
+                    "Failed to generate {} for row {}: {}",
+                    operation, statement.row_index, e
+                ));
+            }
+        }
+    }
 
-    // Get table schema to find primary keys and column order
-    let database_clone = database.clone();
-    let table_clone = table.clone();
+    let combined_sql = queries.join("\n");
+    info!("✅ Generated {} SQL statements", queries.len());
+    Ok(combined_sql)
+}
+
+/// Savepoint used when a save joins a manual transaction that is already open
+const SAVE_SAVEPOINT: &str = "rustdbgrid_save";
+
+/// Savepoint used when a dry run joins a manual transaction that is already open
+const PREVIEW_SAVEPOINT: &str = "rustdbgrid_preview";
+
+/// Savepoint around each statement of a dry run, so a failing one can be undone alone
+const PREVIEW_STEP_SAVEPOINT: &str = "rustdbgrid_preview_step";
+
+/// Statement generated for one row of a save request
+#[derive(Clone)]
+struct RowStatement {
+    operation: SaveOperation,
+    row_index: usize,
+    query: Result<BoundQuery, String>,
+    /// Set for conflict-checked updates: a miss means the row changed on the server
+    conflict_check: Option<ConflictCheck>,
+    /// How an insert reads back the stored row
+    read_back: InsertReadBack,
+}
+
+/// What is needed to report a conflict-checked update that matched no row
+#[derive(Clone)]
+struct ConflictCheck {
+    current_row: BoundQuery,
+    edited_row: EditedRow,
+}
+
+/// Outcome of applying a save request in one transaction
+enum AtomicSave {
+    Committed(AppliedRows),
+    RolledBack(SaveFailure),
+    Conflicts(Vec<SaveConflict>),
+    Unsupported,
+}
+
+/// What applying the statements of a save produced
+#[derive(Default)]
+struct AppliedRows {
+    /// Rows affected by each statement, in order
+    affected: Vec<u64>,
+    /// Checked updates that matched no row because it changed on the server
+    conflicts: Vec<SaveConflict>,
+    /// Stored values of the inserted rows
+    materialized: Vec<MaterializedRow>,
+}
+
+/// Rows a save changed, by operation and index within the save request
+type AppliedRowIds = Vec<(SaveOperation, usize)>;
+
+#[tauri::command]
+pub async fn save_data(
+    connection_id: String,
+    database: String,
+    table: String,
+    schema: Option<String>,
+    save_request: SaveRequest,
+    state: State<'_, ConnectionStore>,
+) -> Result<SaveResponse, String> {
+    let db_type = get_db_type_from_connection(&state, &connection_id)?;
+
+    info!(
+        "💾 Saving data to {}.{} - New: {}, Edited: {}, Deleted: {}",
+        database,
+        table,
+        save_request.new_rows.len(),
+        save_request.edited_rows.len(),
+        save_request.deleted_rows.len()
+    );
+
+    apply_save(
+        &state,
+        &connection_id,
+        &db_type,
+        (database, table, schema),
+        &save_request,
+    )
+    .await
+    .map(|(response, _)| response)
+}
+
+/// Apply a save request and record what it changed in the edit history, so it can be
+/// reverted later. Also returns the rows that were applied.
+async fn apply_save(
+    state: &ConnectionStore,
+    connection_id: &str,
+    db_type: &DatabaseType,
+    (database, table, schema): (String, String, Option<String>),
+    save_request: &SaveRequest,
+) -> Result<(SaveResponse, AppliedRowIds), String> {
+    let (statements, table_schema) = build_row_statements(
+        state,
+        connection_id,
+        db_type,
+        (&database, &table, schema.as_deref()),
+        save_request,
+    )
+    .await?;
+
+    let (response, applied) = if save_request.best_effort {
+        save_rows_best_effort(state, connection_id, statements).await?
+    } else {
+        save_rows_atomically(state, connection_id, statements).await?
+    };
+
+    if !applied.is_empty() {
+        let entry = EditHistoryEntry::new(
+            (connection_id.to_string(), database, table, schema),
+            save_request,
+            &applied,
+            &response.materialized_rows,
+            &table_schema,
+        );
+        // The save itself succeeded, so a journal failure only costs the undo
+        if let Err(e) = edit_history::record(entry) {
+            warn!("📜 Failed to record edit history: {}", e);
+        }
+    }
+    Ok((response, applied))
+}
+
+/// List recorded grid edits, newest first, optionally only those of one connection
+/// or table
+#[tauri::command]
+pub async fn list_edit_history(
+    connection_id: Option<String>,
+    table: Option<String>,
+) -> Result<Vec<EditHistoryEntry>, String> {
+    let mut entries = edit_history::load_history().map_err(|e| e.to_string())?;
+    entries.retain(|entry| {
+        connection_id
+            .as_ref()
+            .is_none_or(|id| &entry.connection_id == id)
+            && table.as_ref().is_none_or(|t| &entry.table == t)
+    });
+    entries.reverse();
+    Ok(entries)
+}
+
+/// Undo a recorded edit by applying its inverse change set. Restored rows are
+/// conflict-checked, so rows changed again since the edit are reported instead of
+/// overwritten.
+#[tauri::command]
+pub async fn revert_edit(
+    id: String,
+    state: State<'_, ConnectionStore>,
+) -> Result<SaveResponse, String> {
+    // Marked reverted up front, so a second request for the same edit is refused
+    // while this one runs
+    let entry = edit_history::begin_revert(&id).map_err(|e| e.to_string())?;
+    match apply_inverse(&state, &entry).await {
+        Ok((response, _)) if response.status == "success" => Ok(response),
+        // Restoring the rest would need the rows restored so far left out, so the edit
+        // keeps its mark and the applied part is undone through its own journal entry
+        Ok((response, applied)) if !applied.is_empty() => {
+            let reason = format!(
+                "only {} of its rows were reverted: {}",
+                applied.len(),
+                response.message
+            );
+            edit_history::finish_partial_revert(&entry.id, reason).map_err(|e| e.to_string())?;
+            Ok(response)
+        }
+        result => {
+            edit_history::cancel_revert(&entry.id).map_err(|e| e.to_string())?;
+            result.map(|(response, _)| response)
+        }
+    }
+}
+
+/// Apply the inverse change set of a recorded edit
+async fn apply_inverse(
+    state: &ConnectionStore,
+    entry: &EditHistoryEntry,
+) -> Result<(SaveResponse, AppliedRowIds), String> {
+    let mut inverse = entry
+        .inverse
+        .clone()
+        .ok_or_else(|| "This edit cannot be reverted".to_string())?;
+    let db_type = get_db_type_from_connection(state, &entry.connection_id)?;
+    info!(
+        "⏪ Reverting edit {} on {}.{}",
+        entry.id, entry.database, entry.table
+    );
 
+    let database = entry.database.clone();
+    let table = entry.table.clone();
     let table_schema = state
         .pool
-        .with_connection(&connection_id, |conn| {
-            let db = database_clone.clone();
-            let tbl = table_clone.clone();
-            async move { conn.get_table_schema(&db, &tbl).await }.boxed()
+        .with_connection(&entry.connection_id, |conn| {
+            async move { conn.get_table_schema(&database, &table).await }.boxed()
         })
         .await
         .map_err(|e| format!("Failed to get table schema: {}", e))?;
 
-    // Generate INSERT queries for new rows
-    for (idx, row) in save_request.new_rows.iter().enumerate() {
-        match crud_builder.build_insert_query(&table, schema.as_deref(), row, &table_schema) {
-            Ok(query) => {
-                queries.push(query);
-            }
-            Err(e) => {
-                error!("Failed to generate INSERT for row {}: {}", idx, e);
-                return Err(format!("Failed to generate INSERT for row {}: {}", idx, e));
-            }
-        }
+    let builder = get_query_builder(&db_type);
+    strip_stale_columns(&mut inverse, &table_schema, builder.as_ref());
+    inverse.best_effort = !state
+        .pool
+        .with_connection(&entry.connection_id, |conn| {
+            async move { Ok(conn.supports_transactions()) }.boxed()
+        })
+        .await?;
+
+    apply_save(
+        state,
+        &entry.connection_id,
+        &db_type,
+        (
+            entry.database.clone(),
+            entry.table.clone(),
+            entry.schema.clone(),
+        ),
+        &inverse,
+    )
+    .await
+}
+
+/// Keep only the table's own columns in a recorded change set, and drop row versions,
+/// whose recorded values went stale when the edit was saved. Row locators and other
+/// loaded extras do not survive a save either.
+fn strip_stale_columns(
+    save_request: &mut SaveRequest,
+    table_schema: &TableSchema,
+    builder: &dyn QueryBuilder,
+) {
+    let keep = |row: &mut HashMap<String, serde_json::Value>| {
+        row.retain(|name, _| {
+            table_schema
+                .columns
+                .iter()
+                .any(|col| &col.name == name && !builder.is_row_version_column(col))
+        })
+    };
+
+    save_request.new_rows.iter_mut().for_each(keep);
+    save_request.deleted_rows.iter_mut().for_each(keep);
+    for edited in &mut save_request.edited_rows {
+        keep(&mut edited.original_data);
+        keep(&mut edited.updated_data);
     }
+}
 
-    let primary_keys: Vec<&String> = table_schema
-        .columns
-        .iter()
-        .filter(|col| col.is_primary_key)
-        .map(|col| &col.name)
-        .collect();
+/// Run the statements of a save request inside a transaction that is always rolled
+/// back, reporting what each one would change and which constraints it would violate
+#[tauri::command]
+pub async fn preview_save(
+    connection_id: String,
+    database: String,
+    table: String,
+    schema: Option<String>,
+    save_request: SaveRequest,
+    state: State<'_, ConnectionStore>,
+) -> Result<SavePreview, String> {
+    let db_type = get_db_type_from_connection(&state, &connection_id)?;
+    info!("🧪 Previewing save to {}.{}", database, table);
 
-    // Generate UPDATE queries for edited rows
-    for (idx, edited_row) in save_request.edited_rows.iter().enumerate() {
-        match crud_builder.build_update_query(
-            &table,
-            schema.as_deref(),
-            edited_row,
-            &primary_keys,
-            &table_schema,
-        ) {
-            Ok(query) => {
-                queries.push(query);
+    let (statements, _) = build_row_statements(
+        &state,
+        &connection_id,
+        &db_type,
+        (&database, &table, schema.as_deref()),
+        &save_request,
+    )
+    .await?;
+    let total = statements.len();
+
+    let previews = state
+        .pool
+        .with_connection(&connection_id, move |conn| {
+            async move {
+                if !conn.supports_transactions() {
+                    return Err(anyhow::anyhow!(
+                        "This database does not support transactions, so a save cannot be previewed"
+                    ));
+                }
+
+                let unit = UnitOfWork::begin(conn, PREVIEW_SAVEPOINT).await?;
+                let previews = preview_statements(conn, &statements).await;
+                unit.rollback(conn).await?;
+                Ok(previews)
             }
+            .boxed()
+        })
+        .await?;
+
+    let clean = previews.iter().filter(|p| p.error.is_none()).count();
+    info!(
+        "🧪 Save preview rolled back - {} of {} statements ran cleanly",
+        clean, total
+    );
+    Ok(SavePreview {
+        statements: previews,
+        would_succeed: clean == total,
+    })
+}
+
+/// Run each statement under its own savepoint, undoing only the ones that fail so later
+/// statements still see the effect of earlier ones. Stops early when the transaction
+/// itself can no longer be used.
+async fn preview_statements(
+    conn: &mut Box<dyn DatabaseConnection>,
+    statements: &[RowStatement],
+) -> Vec<StatementPreview> {
+    let mut previews = Vec::with_capacity(statements.len());
+
+    for statement in statements {
+        let (operation, row_index) = (statement.operation, statement.row_index);
+        let query = match &statement.query {
+            Ok(query) => query,
             Err(e) => {
-                error!("Failed to generate UPDATE for row {}: {}", idx, e);
-                return Err(format!("Failed to generate UPDATE for row {}: {}", idx, e));
+                previews.push(StatementPreview::failed(
+                    operation,
+                    row_index,
+                    String::new(),
+                    format!("Failed to generate statement: {}", e),
+                ));
+                continue;
             }
-        }
-    }
+        };
+        let failed = |error: String| {
+            StatementPreview::failed(operation, row_index, query.sql.clone(), error)
+        };
 
-    // Generate DELETE queries for deleted rows
-    for (idx, row) in save_request.deleted_rows.iter().enumerate() {
-        match crud_builder.build_delete_query(&table, schema.as_deref(), row, &primary_keys) {
-            Ok(query) => {
-                queries.push(query);
+        let step = match UnitOfWork::begin(conn, PREVIEW_STEP_SAVEPOINT).await {
+            Ok(step) => step,
+            Err(e) => {
+                previews.push(failed(e.to_string()));
+                break;
+            }
+        };
+        match execute_statement(conn, query, &statement.read_back).await {
+            Ok((rows, _)) => {
+                if let Err(e) = step.commit(conn).await {
+                    previews.push(failed(e.to_string()));
+                    break;
+                }
+                previews.push(if rows == 0 && statement.conflict_check.is_some() {
+                    failed("Row was changed on the server since it was loaded".to_string())
+                } else {
+                    StatementPreview::applied(operation, row_index, query.sql.clone(), rows)
+                });
             }
             Err(e) => {
-                error!("Failed to generate DELETE for row {}: {}", idx, e);
-                return Err(format!("Failed to generate DELETE for row {}: {}", idx, e));
+                previews.push(failed(e.to_string()));
+                if step.rollback(conn).await.is_err() {
+                    break;
+                }
             }
         }
     }
 
-    let combined_sql = queries.join("\n");
-    info!("✅ Generated {} SQL statements", queries.len());
-    Ok(combined_sql)
+    previews
 }
 
+/// Foreign keys are followed at most this many levels below the edited table, which also
+/// ends self-referencing cascades
+const MAX_CASCADE_DEPTH: usize = 8;
+
+/// Foreign key of another table referencing the table being deleted from
+struct ReferencingKey {
+    table: String,
+    schema: Option<String>,
+    constraint_name: String,
+    /// Each referencing column with the referenced column it points at
+    columns: Vec<(String, String)>,
+    on_delete: OnDeleteAction,
+}
+
+/// Count the rows in other tables that deleting the request's `deleted_rows` would
+/// reach through foreign keys, following ON DELETE CASCADE chains and flagging the
+/// RESTRICT and NO ACTION references that would make the delete fail
 #[tauri::command]
-pub async fn save_data(
+pub async fn preview_delete_impact(
     connection_id: String,
     database: String,
     table: String,
     schema: Option<String>,
     save_request: SaveRequest,
     state: State<'_, ConnectionStore>,
-) -> Result<SaveResponse, String> {
+) -> Result<DeleteImpact, String> {
     let db_type = get_db_type_from_connection(&state, &connection_id)?;
-
+    if !matches!(
+        db_type,
+        DatabaseType::MySQL | DatabaseType::PostgreSQL | DatabaseType::MSSQL
+    ) {
+        return Err("Foreign key relationships are not available for this database".to_string());
+    }
+    if save_request.deleted_rows.is_empty() {
+        return Ok(DeleteImpact {
+            can_delete: true,
+            ..Default::default()
+        });
+    }
     info!(
-        "💾 Saving data to {}.{} - New: {}, Edited: {}, Deleted: {}",
+        "🔗 Previewing delete of {} rows from {}.{}",
+        save_request.deleted_rows.len(),
         database,
-        table,
-        save_request.new_rows.len(),
-        save_request.edited_rows.len(),
-        save_request.deleted_rows.len()
+        table
     );
 
-    let mut executed_queries = Vec::new();
-    let mut errors = Vec::new();
-    let mut affected_rows: i64 = 0;
-    let mut inserted_rows: i64 = 0;
-    let mut updated_rows: i64 = 0;
-    let mut deleted_rows: i64 = 0;
     let crud_builder = get_crud_query_builder(&db_type);
-
-    // Get table schema to find primary keys
     let database_clone = database.clone();
     let table_clone = table.clone();
-
     let table_schema = state
         .pool
         .with_connection(&connection_id, |conn| {
+            async move { conn.get_table_schema(&database_clone, &table_clone).await }.boxed()
+        })
+        .await
+        .map_err(|e| format!("Failed to get table schema: {}", e))?;
+
+    let primary_keys: Vec<&String> = table_schema
+        .columns
+        .iter()
+        .filter(|col| col.is_primary_key)
+        .map(|col| &col.name)
+        .collect();
+    let root_condition = bound_query::build_rows_condition(
+        crud_builder.as_ref(),
+        &save_request.deleted_rows,
+        &primary_keys,
+        &table_schema,
+    )
+    .map_err(|e| e.to_string())?;
+
+    let table_clone = table.clone();
+    let impact = state
+        .pool
+        .with_connection(&connection_id, move |conn| {
+            async move {
+                collect_dependents(
+                    conn,
+                    crud_builder.as_ref(),
+                    (&database, &table_clone, schema.as_deref()),
+                    &root_condition,
+                )
+                .await
+            }
+            .boxed()
+        })
+        .await?;
+
+    info!(
+        "🔗 Delete from {} reaches {} referencing tables{}",
+        table,
+        impact.dependents.len(),
+        if impact.can_delete {
+            ""
+        } else {
+            " and is blocked"
+        }
+    );
+    Ok(impact)
+}
+
+/// Walk the foreign keys referencing the edited table, counting the referencing rows of
+/// each and descending into the rows a cascade would delete in turn
+async fn collect_dependents(
+    conn: &mut Box<dyn DatabaseConnection>,
+    builder: &dyn CRUDQueryBuilder,
+    (database, table, schema): (&str, &str, Option<&str>),
+    root_condition: &BoundQuery,
+) -> anyhow::Result<DeleteImpact> {
+    let root_table = builder.crud_table_name(table, schema);
+    let mut impact = DeleteImpact {
+        can_delete: true,
+        ..Default::default()
+    };
+
+    // Cascade chains still to expand, with the tables along each one and the schema
+    // of the last
+    let mut pending: Vec<(Vec<ReferenceLink>, Vec<String>, Option<String>)> = vec![(
+        Vec::new(),
+        vec![table.to_string()],
+        schema.map(str::to_string),
+    )];
+    while let Some((chain, path, parent_schema)) = pending.pop() {
+        let parent = &path[path.len() - 1];
+        let qualified_parent = match &parent_schema {
+            Some(s) => format!("{}.{}", s, parent),
+            None => parent.clone(),
+        };
+        let relationships = conn
+            .get_table_relationships(database, &qualified_parent)
+            .await?;
+
+        let mut cascades = Vec::new();
+        for key in referencing_keys(relationships, parent_schema.as_deref(), parent) {
+            let mut key_chain = chain.clone();
+            key_chain.push(ReferenceLink {
+                table: builder.crud_table_name(&key.table, key.schema.as_deref()),
+                columns: key.columns.clone(),
+            });
+            let count = bound_query::build_dependent_count(
+                builder,
+                &root_table,
+                root_condition,
+                &key_chain,
+            )?;
+            let result = conn.execute_query_bound(&count).await?;
+            let row_count = result
+                .rows
+                .first()
+                .and_then(|row| row.get("dependent_count"))
+                .and_then(|value| match value {
+                    serde_json::Value::String(s) => s.parse().ok(),
+                    other => other.as_u64(),
+                })
+                .unwrap_or(0);
+            if row_count == 0 {
+                continue;
+            }
+
+            let blocks_delete = key.on_delete.blocks();
+            impact.can_delete &= !blocks_delete;
+            if key.on_delete == OnDeleteAction::Cascade {
+                if key_chain.len() < MAX_CASCADE_DEPTH {
+                    let mut key_path = path.clone();
+                    key_path.push(key.table.clone());
+                    cascades.push((key_chain, key_path, key.schema.clone()));
+                } else {
+                    impact.truncated = true;
+                }
+            }
+            impact.dependents.push(DependentRows {
+                table: key.table,
+                schema: key.schema,
+                constraint_name: key.constraint_name,
+                columns: key.columns.into_iter().map(|(column, _)| column).collect(),
+                on_delete: key.on_delete,
+                row_count,
+                path: path.clone(),
+                blocks_delete,
+            });
+        }
+        // Expand the first cascade next so each table's chain stays together
+        pending.extend(cascades.into_iter().rev());
+    }
+
+    Ok(impact)
+}
+
+/// Group the relationship rows that reference `table` into one key per constraint.
+/// Self-references are reported as foreign keys of the table itself, so the direction
+/// is read from the referenced table rather than the relationship type. Referencing
+/// tables may live in other schemas; without a reported schema they share `schema`.
+fn referencing_keys(
+    relationships: Vec<TableRelationship>,
+    schema: Option<&str>,
+    table: &str,
+) -> Vec<ReferencingKey> {
+    let same_schema = |other: Option<&str>| match (other, schema) {
+        (Some(other), Some(schema)) => other.eq_ignore_ascii_case(schema),
+        _ => true,
+    };
+    let mut keys: Vec<ReferencingKey> = Vec::new();
+    for rel in relationships {
+        if !rel.referenced_table_name.eq_ignore_ascii_case(table)
+            || !same_schema(rel.referenced_table_schema.as_deref())
+        {
+            continue;
+        }
+        let key_schema = rel.table_schema.or_else(|| schema.map(str::to_string));
+        let pair = (rel.column_name, rel.referenced_column_name);
+        match keys.iter_mut().find(|k| {
+            k.table == rel.table_name
+                && k.schema == key_schema
+                && k.constraint_name == rel.constraint_name
+        }) {
+            Some(key) => {
+                if !key.columns.contains(&pair) {
+                    key.columns.push(pair);
+                }
+            }
+            None => keys.push(ReferencingKey {
+                table: rel.table_name,
+                schema: key_schema,
+                constraint_name: rel.constraint_name,
+                columns: vec![pair],
+                on_delete: OnDeleteAction::parse(rel.on_delete.as_deref()),
+            }),
+        }
+    }
+    keys
+}
+
+/// Generate the statements of a save request: inserts, then updates, then deletes.
+/// Returns them with the table schema they were built from.
+async fn build_row_statements(
+    state: &ConnectionStore,
+    connection_id: &str,
+    db_type: &DatabaseType,
+    (database, table, schema): (&str, &str, Option<&str>),
+    save_request: &SaveRequest,
+) -> Result<(Vec<RowStatement>, TableSchema), String> {
+    let crud_builder = get_crud_query_builder(db_type);
+
+    // Get table schema to find primary keys
+    let database_clone = database.to_string();
+    let table_clone = table.to_string();
+
+    let table_schema = state
+        .pool
+        .with_connection(connection_id, |conn| {
             let db = database_clone.clone();
             let tbl = table_clone.clone();
             async move { conn.get_table_schema(&db, &tbl).await }.boxed()
@@ -288,113 +902,366 @@ pub async fn save_data(
         .map(|col| &col.name)
         .collect();
 
-    // Execute INSERT queries
-    for (idx, row) in save_request.new_rows.iter().enumerate() {
-        match crud_builder.build_insert_query(&table, schema.as_deref(), row, &table_schema) {
-            Ok(query) => {
-                let connection_id_clone = connection_id.clone();
-                let query_clone = query.clone();
-                match state
-                    .pool
-                    .with_connection(&connection_id_clone, |conn| {
-                        async move { conn.execute_update(&query_clone).await }.boxed()
-                    })
+    // Generate every statement up front: inserts, then updates, then deletes
+    let mut statements = Vec::new();
+    for (row_index, row) in save_request.new_rows.iter().enumerate() {
+        let (query, read_back) =
+            match crud_builder.build_insert_returning_bound(table, schema, row, &table_schema) {
+                Ok((query, read_back)) => (Ok(query), read_back),
+                Err(e) => (Err(e.to_string()), InsertReadBack::Unavailable),
+            };
+        statements.push(RowStatement {
+            operation: SaveOperation::Insert,
+            row_index,
+            query,
+            conflict_check: None,
+            read_back,
+        });
+    }
+    for (row_index, edited_row) in save_request.edited_rows.iter().enumerate() {
+        let table_name = (table, schema);
+        let (query, conflict_check) = if save_request.detect_conflicts {
+            match build_checked_update(
+                crud_builder.as_ref(),
+                table_name,
+                edited_row,
+                &primary_keys,
+                &table_schema,
+            ) {
+                Ok((query, check)) => (Ok(query), Some(check)),
+                Err(e) => (Err(e.to_string()), None),
+            }
+        } else {
+            let query = crud_builder
+                .build_update_query_bound(
+                    table_name.0,
+                    table_name.1,
+                    edited_row,
+                    &primary_keys,
+                    &table_schema,
+                )
+                .map_err(|e| e.to_string());
+            (query, None)
+        };
+        statements.push(RowStatement {
+            operation: SaveOperation::Update,
+            row_index,
+            query,
+            conflict_check,
+            read_back: InsertReadBack::Unavailable,
+        });
+    }
+    for (row_index, row) in save_request.deleted_rows.iter().enumerate() {
+        statements.push(RowStatement {
+            operation: SaveOperation::Delete,
+            row_index,
+            query: crud_builder
+                .build_delete_query_bound(table, schema, row, &primary_keys, &table_schema)
+                .map_err(|e| e.to_string()),
+            conflict_check: None,
+            read_back: InsertReadBack::Unavailable,
+        });
+    }
+
+    Ok((statements, table_schema))
+}
+
+/// Build an update that only applies while the row is unchanged on the server, with the
+/// query that reads the row back when it does not
+fn build_checked_update(
+    crud_builder: &dyn CRUDQueryBuilder,
+    (table, schema): (&str, Option<&str>),
+    edited_row: &EditedRow,
+    primary_keys: &[&String],
+    table_schema: &TableSchema,
+) -> anyhow::Result<(BoundQuery, ConflictCheck)> {
+    let query = crud_builder.build_update_query_checked_bound(
+        table,
+        schema,
+        edited_row,
+        primary_keys,
+        table_schema,
+    )?;
+    let current_row = crud_builder.build_select_row_bound(
+        table,
+        schema,
+        &edited_row.original_data,
+        primary_keys,
+        table_schema,
+    )?;
+    Ok((
+        query,
+        ConflictCheck {
+            current_row,
+            edited_row: edited_row.clone(),
+        },
+    ))
+}
+
+/// Read the server row behind a conflict-checked update that matched nothing
+async fn read_conflict(
+    conn: &mut Box<dyn DatabaseConnection>,
+    row_index: usize,
+    check: &ConflictCheck,
+) -> anyhow::Result<SaveConflict> {
+    let current = conn.execute_query_bound(&check.current_row).await?;
+    Ok(SaveConflict::new(
+        row_index,
+        &check.edited_row,
+        current.rows.into_iter().next(),
+    ))
+}
+
+/// Run one save statement, returning the rows it affected and, for an insert that can
+/// read it back, the row as stored
+async fn execute_statement(
+    conn: &mut Box<dyn DatabaseConnection>,
+    query: &BoundQuery,
+    read_back: &InsertReadBack,
+) -> anyhow::Result<(u64, Option<HashMap<String, serde_json::Value>>)> {
+    match read_back {
+        InsertReadBack::Returning => {
+            let stored = conn.execute_query_bound(query).await?;
+            Ok((stored.rows.len() as u64, stored.rows.into_iter().next()))
+        }
+        InsertReadBack::Select(select) => {
+            let (affected, stored) = conn.execute_insert_read_back(query, select).await?;
+            Ok((affected, stored.rows.into_iter().next()))
+        }
+        InsertReadBack::Unavailable => Ok((conn.execute_update_bound(query).await?, None)),
+    }
+}
+
+/// Run the statements of a save in order, stopping at the first failure. Conflicting
+/// updates are collected and do not stop the run.
+async fn apply_statements(
+    conn: &mut Box<dyn DatabaseConnection>,
+    statements: &[RowStatement],
+) -> Result<AppliedRows, SaveFailure> {
+    let mut applied = AppliedRows {
+        affected: Vec::with_capacity(statements.len()),
+        ..AppliedRows::default()
+    };
+
+    for statement in statements {
+        let fail = |error: String| SaveFailure {
+            operation: statement.operation,
+            row_index: statement.row_index,
+            error,
+        };
+        let query = statement
+            .query
+            .as_ref()
+            .map_err(|e| fail(format!("Failed to generate statement: {}", e)))?;
+
+        match execute_statement(conn, query, &statement.read_back).await {
+            Ok((0, _)) if statement.operation == SaveOperation::Update => {
+                let Some(check) = &statement.conflict_check else {
+                    return Err(fail(
+                        "No rows updated - row may have been deleted or modified".to_string(),
+                    ));
+                };
+                let conflict = read_conflict(conn, statement.row_index, check)
                     .await
-                {
-                    Ok(rows_affected) => {
-                        executed_queries.push(query);
-                        affected_rows += rows_affected as i64;
-                        inserted_rows += rows_affected as i64;
-                    }
-                    Err(e) => {
-                        let error_msg = format!("Failed to insert row {}: {}", idx, e);
-                        warn!("{}", error_msg);
-                        errors.push(error_msg);
-                    }
-                }
+                    .map_err(|e| fail(format!("Failed to read the current row: {}", e)))?;
+                applied.conflicts.push(conflict);
+                applied.affected.push(0);
             }
-            Err(e) => {
-                let error_msg = format!("Failed to generate INSERT for row {}: {}", idx, e);
-                warn!("{}", error_msg);
-                errors.push(error_msg);
+            Ok((rows, stored)) => {
+                applied.affected.push(rows);
+                if let Some(values) = stored {
+                    applied.materialized.push(MaterializedRow {
+                        row_index: statement.row_index,
+                        values,
+                    });
+                }
             }
+            Err(e) => return Err(fail(e.to_string())),
         }
     }
 
-    // Execute UPDATE queries
-    for (idx, edited_row) in save_request.edited_rows.iter().enumerate() {
-        match crud_builder.build_update_query(
-            &table,
-            schema.as_deref(),
-            edited_row,
-            &primary_keys,
-            &table_schema,
-        ) {
-            Ok(query) => {
-                let connection_id_clone = connection_id.clone();
-                let query_clone = query.clone();
-                match state
-                    .pool
-                    .with_connection(&connection_id_clone, |conn| {
-                        async move { conn.execute_update(&query_clone).await }.boxed()
-                    })
-                    .await
-                {
-                    Ok(rows_affected) => {
-                        if rows_affected == 0 {
-                            let error_msg =
-                                "No rows updated - row may have been deleted or modified"
-                                    .to_string();
-                            warn!("{}", error_msg);
-                            errors.push(error_msg);
-                        } else {
-                            executed_queries.push(query);
-                            affected_rows += rows_affected as i64;
-                            updated_rows += rows_affected as i64;
-                        }
+    Ok(applied)
+}
+
+/// Apply all statements in one transaction, rolling everything back on the first
+/// failure or when any edited row conflicts
+async fn save_rows_atomically(
+    state: &ConnectionStore,
+    connection_id: &str,
+    statements: Vec<RowStatement>,
+) -> Result<(SaveResponse, AppliedRowIds), String> {
+    let plan = statements.clone();
+    let outcome = state
+        .pool
+        .with_connection(connection_id, move |conn| {
+            async move {
+                if !conn.supports_transactions() {
+                    return Ok(AtomicSave::Unsupported);
+                }
+
+                let unit = UnitOfWork::begin(conn, SAVE_SAVEPOINT).await?;
+                match apply_statements(conn, &plan).await {
+                    Ok(applied) if applied.conflicts.is_empty() => {
+                        unit.commit(conn).await?;
+                        Ok(AtomicSave::Committed(applied))
                     }
-                    Err(e) => {
-                        let error_msg = format!("Failed to update row {}: {}", idx, e);
-                        warn!("{}", error_msg);
-                        errors.push(error_msg);
+                    Ok(applied) => {
+                        unit.rollback(conn).await?;
+                        Ok(AtomicSave::Conflicts(applied.conflicts))
+                    }
+                    Err(failure) => {
+                        unit.rollback(conn).await?;
+                        Ok(AtomicSave::RolledBack(failure))
                     }
                 }
             }
+            .boxed()
+        })
+        .await?;
+
+    match outcome {
+        AtomicSave::Committed(applied) => {
+            let affected = &applied.affected;
+            let count = |op: SaveOperation| -> i64 {
+                statements
+                    .iter()
+                    .zip(affected)
+                    .filter(|(s, _)| s.operation == op)
+                    .map(|(_, rows)| *rows as i64)
+                    .sum()
+            };
+            let affected_rows = affected.iter().map(|rows| *rows as i64).sum();
+            info!(
+                "✅ Successfully saved all changes - {} rows affected",
+                affected_rows
+            );
+            let applied_rows = statements
+                .iter()
+                .zip(affected)
+                .filter(|(_, rows)| **rows > 0)
+                .map(|(s, _)| (s.operation, s.row_index))
+                .collect();
+            let response = SaveResponse {
+                materialized_rows: applied.materialized,
+                ..SaveResponse::success_with_counts(
+                    "All changes saved successfully".to_string(),
+                    affected_rows,
+                    statements
+                        .iter()
+                        .filter_map(|s| s.query.as_ref().ok().map(|q| q.sql.clone()))
+                        .collect(),
+                    count(SaveOperation::Insert),
+                    count(SaveOperation::Update),
+                    count(SaveOperation::Delete),
+                )
+            };
+            Ok((response, applied_rows))
+        }
+        AtomicSave::RolledBack(failure) => {
+            warn!(
+                "↩️ Save rolled back at {} of row {}: {}",
+                failure.operation.as_str(),
+                failure.row_index,
+                failure.error
+            );
+            Ok((SaveResponse::rolled_back(failure), Vec::new()))
+        }
+        AtomicSave::Conflicts(conflicts) => {
+            warn!(
+                "↩️ Save rolled back - {} edited row(s) changed on the server",
+                conflicts.len()
+            );
+            Ok((SaveResponse::conflicted(conflicts), Vec::new()))
+        }
+        AtomicSave::Unsupported => Ok((SaveResponse::transactions_unsupported(), Vec::new())),
+    }
+}
+
+/// Apply each statement on its own, keeping the ones that succeed
+async fn save_rows_best_effort(
+    state: &ConnectionStore,
+    connection_id: &str,
+    statements: Vec<RowStatement>,
+) -> Result<(SaveResponse, AppliedRowIds), String> {
+    let mut executed_queries = Vec::new();
+    let mut errors = Vec::new();
+    let mut affected_rows: i64 = 0;
+    let mut inserted_rows: i64 = 0;
+    let mut updated_rows: i64 = 0;
+    let mut deleted_rows: i64 = 0;
+    let mut conflicts = Vec::new();
+    let mut materialized_rows = Vec::new();
+    let mut applied_rows = Vec::new();
+
+    for statement in statements {
+        let (operation, idx) = (statement.operation.as_str(), statement.row_index);
+        let query = match statement.query {
+            Ok(query) => query,
             Err(e) => {
-                let error_msg = format!("Failed to generate UPDATE for row {}: {}", idx, e);
+                let error_msg = format!(
+                    "Failed to generate {} for row {}: {}",
+                    operation.to_uppercase(),
+                    idx,
+                    e
+                );
                 warn!("{}", error_msg);
                 errors.push(error_msg);
+                continue;
             }
-        }
-    }
+        };
 
-    // Execute DELETE queries
-    for (idx, row) in save_request.deleted_rows.iter().enumerate() {
-        match crud_builder.build_delete_query(&table, schema.as_deref(), row, &primary_keys) {
-            Ok(query) => {
-                let connection_id_clone = connection_id.clone();
-                let query_clone = query.clone();
-                match state
-                    .pool
-                    .with_connection(&connection_id_clone, |conn| {
-                        async move { conn.execute_update(&query_clone).await }.boxed()
-                    })
-                    .await
-                {
-                    Ok(rows_affected) => {
-                        executed_queries.push(query);
-                        affected_rows += rows_affected as i64;
-                        deleted_rows += rows_affected as i64;
-                    }
-                    Err(e) => {
-                        let error_msg = format!("Failed to delete row {}: {}", idx, e);
-                        warn!("{}", error_msg);
-                        errors.push(error_msg);
+        let query_clone = query.clone();
+        let read_back = statement.read_back.clone();
+        match state
+            .pool
+            .with_connection(connection_id, |conn| {
+                async move { execute_statement(conn, &query_clone, &read_back).await }.boxed()
+            })
+            .await
+        {
+            Ok((0, _)) if statement.operation == SaveOperation::Update => {
+                let error_msg = match &statement.conflict_check {
+                    Some(check) => {
+                        let check = check.clone();
+                        match state
+                            .pool
+                            .with_connection(connection_id, |conn| {
+                                async move { read_conflict(conn, idx, &check).await }.boxed()
+                            })
+                            .await
+                        {
+                            Ok(conflict) => {
+                                conflicts.push(conflict);
+                                format!("Row {} was changed on the server since it was loaded", idx)
+                            }
+                            Err(e) => format!("Failed to read the current row {}: {}", idx, e),
+                        }
                     }
+                    None => "No rows updated - row may have been deleted or modified".to_string(),
+                };
+                warn!("{}", error_msg);
+                errors.push(error_msg);
+            }
+            Ok((rows_affected, stored)) => {
+                if let Some(values) = stored {
+                    materialized_rows.push(MaterializedRow {
+                        row_index: idx,
+                        values,
+                    });
+                }
+                executed_queries.push(query.sql);
+                if rows_affected > 0 {
+                    applied_rows.push((statement.operation, idx));
+                }
+                affected_rows += rows_affected as i64;
+                match statement.operation {
+                    SaveOperation::Insert => inserted_rows += rows_affected as i64,
+                    SaveOperation::Update => updated_rows += rows_affected as i64,
+                    SaveOperation::Delete => deleted_rows += rows_affected as i64,
                 }
             }
             Err(e) => {
-                let error_msg = format!("Failed to generate DELETE for row {}: {}", idx, e);
+                let error_msg = format!("Failed to {} row {}: {}", operation, idx, e);
                 warn!("{}", error_msg);
                 errors.push(error_msg);
             }
@@ -406,14 +1273,18 @@ pub async fn save_data(
             "✅ Successfully saved all changes - {} rows affected",
             affected_rows
         );
-        Ok(SaveResponse::success_with_counts(
-            "All changes saved successfully".to_string(),
-            affected_rows,
-            executed_queries,
-            inserted_rows,
-            updated_rows,
-            deleted_rows,
-        ))
+        let response = SaveResponse {
+            materialized_rows,
+            ..SaveResponse::success_with_counts(
+                "All changes saved successfully".to_string(),
+                affected_rows,
+                executed_queries,
+                inserted_rows,
+                updated_rows,
+                deleted_rows,
+            )
+        };
+        Ok((response, applied_rows))
     } else if !executed_queries.is_empty() {
         let message = format!(
             "Partial save - {} queries executed, {} errors",
@@ -421,18 +1292,27 @@ pub async fn save_data(
             errors.len()
         );
         warn!("{}", message);
-        Ok(SaveResponse::partial_with_counts(
-            message,
-            affected_rows,
-            executed_queries,
-            inserted_rows,
-            updated_rows,
-            deleted_rows,
-            errors,
-        ))
+        let response = SaveResponse {
+            conflicts,
+            materialized_rows,
+            ..SaveResponse::partial_with_counts(
+                message,
+                affected_rows,
+                executed_queries,
+                inserted_rows,
+                updated_rows,
+                deleted_rows,
+                errors,
+            )
+        };
+        Ok((response, applied_rows))
     } else {
         let message = format!("All save operations failed - {} errors", errors.len());
         error!("{}", message);
-        Ok(SaveResponse::error(message))
+        let response = SaveResponse {
+            conflicts,
+            ..SaveResponse::error(message)
+        };
+        Ok((response, applied_rows))
     }
 }
@@ -0,0 +1,121 @@
+use crate::commands::connection::ConnectionStore;
+use crate::db::transaction::TransactionCommand;
+use crate::models::transaction::TransactionState;
+use tauri::State;
+
+/// Run a transaction control command on the connection's pinned session
+async fn run_transaction_command(
+    connection_id: &str,
+    tab_id: Option<&str>,
+    command: TransactionCommand,
+    state: &ConnectionStore,
+) -> Result<TransactionState, String> {
+    tracing::info!(
+        "🔁 [COMMAND] {:?} on connection: {}",
+        command,
+        connection_id
+    );
+    state.ensure_connected(connection_id).await?;
+    state
+        .pool
+        .transaction_control(connection_id, tab_id, command)
+        .await
+}
+
+/// Open a manual transaction; subsequent statements on the connection (or the tab's
+/// dedicated session) run on one server session
+#[tauri::command]
+pub async fn begin_transaction(
+    connection_id: String,
+    tab_id: Option<String>,
+    state: State<'_, ConnectionStore>,
+) -> Result<TransactionState, String> {
+    run_transaction_command(
+        &connection_id,
+        tab_id.as_deref(),
+        TransactionCommand::Begin(None),
+        &state,
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn commit_transaction(
+    connection_id: String,
+    tab_id: Option<String>,
+    state: State<'_, ConnectionStore>,
+) -> Result<TransactionState, String> {
+    run_transaction_command(
+        &connection_id,
+        tab_id.as_deref(),
+        TransactionCommand::Commit,
+        &state,
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn rollback_transaction(
+    connection_id: String,
+    tab_id: Option<String>,
+    state: State<'_, ConnectionStore>,
+) -> Result<TransactionState, String> {
+    run_transaction_command(
+        &connection_id,
+        tab_id.as_deref(),
+        TransactionCommand::Rollback,
+        &state,
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn create_savepoint(
+    connection_id: String,
+    tab_id: Option<String>,
+    name: String,
+    state: State<'_, ConnectionStore>,
+) -> Result<TransactionState, String> {
+    let command = TransactionCommand::savepoint(&name).map_err(|e| e.to_string())?;
+    run_transaction_command(&connection_id, tab_id.as_deref(), command, &state).await
+}
+
+#[tauri::command]
+pub async fn rollback_to_savepoint(
+    connection_id: String,
+    tab_id: Option<String>,
+    name: String,
+    state: State<'_, ConnectionStore>,
+) -> Result<TransactionState, String> {
+    let command = TransactionCommand::rollback_to_savepoint(&name).map_err(|e| e.to_string())?;
+    run_transaction_command(&connection_id, tab_id.as_deref(), command, &state).await
+}
+
+#[tauri::command]
+pub async fn release_savepoint(
+    connection_id: String,
+    tab_id: Option<String>,
+    name: String,
+    state: State<'_, ConnectionStore>,
+) -> Result<TransactionState, String> {
+    let command = TransactionCommand::release_savepoint(&name).map_err(|e| e.to_string())?;
+    run_transaction_command(&connection_id, tab_id.as_deref(), command, &state).await
+}
+
+/// Current manual transaction state; inactive when the connection is not open
+#[tauri::command]
+pub async fn get_transaction_state(
+    connection_id: String,
+    tab_id: Option<String>,
+    state: State<'_, ConnectionStore>,
+) -> Result<TransactionState, String> {
+    Ok(state
+        .pool
+        .transaction_state(&connection_id, tab_id.as_deref())
+        .await
+        .unwrap_or_else(|_| TransactionState {
+            connection_id,
+            tab_id,
+            ..Default::default()
+        }))
+}
@@ -0,0 +1,685 @@
+use crate::commands::connection::ConnectionStore;
+use crate::commands::table::get_db_type_from_connection;
+use crate::db::bound_query::BoundQuery;
+use crate::db::ddl;
+use crate::db::sql_splitter::{split_statements, SqlDialect};
+use crate::db::traits::{get_crud_query_builder, get_query_builder, CRUDQueryBuilder};
+use crate::db::type_mapping::ColumnType;
+use crate::models::connection::DatabaseType;
+use crate::models::schema::{Column, Index, TableSchema};
+use crate::models::table_request::QueryRequest;
+use crate::models::transfer::*;
+use crate::utils::table_reader::{fetch_table_schema, TableReader};
+use futures::FutureExt;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::time::Instant;
+use tauri::ipc::Channel;
+use tauri::State;
+
+/// Failed rows kept in a transfer summary; later ones are only counted
+const MAX_REPORTED_FAILURES: usize = 500;
+
+/// Columns, types and DDL a transfer would use, without copying anything
+#[tauri::command]
+pub async fn preview_transfer(
+    request: TransferRequest,
+    state: State<'_, ConnectionStore>,
+) -> Result<TransferPlan, String> {
+    Ok(Transfer::prepare(&state, &request).await?.plan)
+}
+
+/// Copy a table from one connection to another, which may be of another database
+/// type. The target table is created with each column's type mapped to the target's
+/// dialect, then rows stream from the source and are written in batches. Values that
+/// do not convert to their target type and rows the target refuses are skipped and
+/// reported. Foreign keys are left out, as the tables they reference may not exist
+/// on the target. Binary columns are refused up front, as drivers do not read their
+/// bytes.
+#[tauri::command]
+pub async fn transfer_table(
+    request: TransferRequest,
+    on_event: Channel<TransferEvent>,
+    state: State<'_, ConnectionStore>,
+) -> Result<TransferSummary, String> {
+    let transfer = Transfer::prepare(&state, &request).await?;
+    tracing::info!(
+        "🚚 [TRANSFER] Copying {} ({:?}) to {} ({:?})",
+        request.source.table,
+        transfer.source_type,
+        request.target.table,
+        transfer.target_type
+    );
+    let start = Instant::now();
+
+    // Statements on the target run on one session, so USE and IDENTITY_INSERT hold.
+    // The source is read on another, which stays busy while the rows stream.
+    let session_id = format!("transfer-{}", uuid::Uuid::new_v4());
+    let source_session = format!("{}-source", session_id);
+    let result = transfer
+        .run(&state, &request, (&source_session, &session_id), &on_event)
+        .await;
+    for session in [&source_session, &session_id] {
+        if let Err(e) = state.pool.close_tab_sessions(session).await {
+            tracing::warn!("⚠️ [TRANSFER] Failed to close transfer session: {}", e);
+        }
+    }
+
+    let mut summary = result?;
+    summary.execution_time = start.elapsed().as_millis();
+    tracing::info!(
+        "✅ [TRANSFER] Wrote {} of {} rows to {}, {} failed",
+        summary.rows_written,
+        summary.rows_read,
+        request.target.table,
+        summary.rows_failed
+    );
+    Ok(summary)
+}
+
+/// A column copied by a transfer
+struct CopiedColumn {
+    source: String,
+    target: Column,
+    target_type: ColumnType,
+}
+
+struct Transfer {
+    source_type: DatabaseType,
+    target_type: DatabaseType,
+    target_schema: TableSchema,
+    columns: Vec<CopiedColumn>,
+    /// Statement creating the target table, then those creating its indexes
+    create_table: Option<String>,
+    create_indexes: Vec<String>,
+    plan: TransferPlan,
+}
+
+impl Transfer {
+    async fn prepare(state: &ConnectionStore, request: &TransferRequest) -> Result<Self, String> {
+        let (source, target) = (&request.source, &request.target);
+        let source_type = transfer_db_type(state, source).await?;
+        let target_type = transfer_db_type(state, target).await?;
+
+        let source_schema = fetch_table_schema(
+            state,
+            &source.connection_id,
+            None,
+            &source_type,
+            &source.database,
+            &source.table,
+            source.schema.as_deref(),
+        )
+        .await?;
+        if source_schema.columns.is_empty() {
+            return Err(format!("Table '{}' has no columns", source.table));
+        }
+
+        let mut skipped_columns = Vec::new();
+        let (target_schema, create_table, create_indexes) = if request.options.create_table {
+            let target_schema =
+                map_table(&source_type, &target_type, &source_schema, source, target);
+            let schema_name = target
+                .schema
+                .as_deref()
+                .filter(|_| target_type != DatabaseType::MySQL);
+            let ddl = ddl::create_table_ddl(&target_type, schema_name, &target_schema)
+                .map_err(|e| e.to_string())?;
+            let dialect = SqlDialect::from_db_type(&target_type).ok_or_else(|| {
+                format!("Table transfers are not supported for {:?}", target_type)
+            })?;
+            let mut statements = split_statements(&ddl, dialect)
+                .into_iter()
+                .map(|statement| statement.text);
+            (target_schema, statements.next(), statements.collect())
+        } else {
+            let target_schema = fetch_table_schema(
+                state,
+                &target.connection_id,
+                None,
+                &target_type,
+                &target.database,
+                &target.table,
+                target.schema.as_deref(),
+            )
+            .await?;
+            if target_schema.columns.is_empty() {
+                return Err(format!(
+                    "Table '{}' does not exist on the target",
+                    target.table
+                ));
+            }
+            (target_schema, None, Vec::new())
+        };
+
+        let mut columns = Vec::new();
+        let mut binary_columns = Vec::new();
+        for col in &source_schema.columns {
+            let Some(target_col) = target_schema
+                .columns
+                .iter()
+                .find(|c| c.name.eq_ignore_ascii_case(&col.name))
+            else {
+                if !request.options.create_table {
+                    skipped_columns.push(format!("{} (not in the target table)", col.name));
+                }
+                continue;
+            };
+            let target_type_of = ColumnType::from_data_type(&target_type, &target_col.data_type);
+            // Drivers read binary values as a size placeholder, not their bytes
+            if target_type_of == ColumnType::Binary
+                || ColumnType::from_data_type(&source_type, &col.data_type) == ColumnType::Binary
+            {
+                binary_columns.push(col.name.clone());
+                continue;
+            }
+            columns.push(CopiedColumn {
+                source: col.name.clone(),
+                target: target_col.clone(),
+                target_type: target_type_of,
+            });
+        }
+        if !binary_columns.is_empty() {
+            return Err(format!(
+                "Binary columns cannot be transferred: {}. Copy into an existing table \
+                 without these columns to leave them out.",
+                binary_columns.join(", ")
+            ));
+        }
+        if columns.is_empty() {
+            return Err(format!(
+                "No column of {} can be copied to {}",
+                source.table, target.table
+            ));
+        }
+
+        let plan = TransferPlan {
+            columns: columns
+                .iter()
+                .map(|col| TransferColumn {
+                    name: col.target.name.clone(),
+                    source_type: source_schema
+                        .columns
+                        .iter()
+                        .find(|c| c.name == col.source)
+                        .map(|c| c.data_type.clone())
+                        .unwrap_or_default(),
+                    target_type: col.target.data_type.clone(),
+                })
+                .collect(),
+            skipped_columns,
+            ddl: create_table
+                .iter()
+                .chain(&create_indexes)
+                .map(|statement| format!("{};\n", statement.trim_end_matches(';')))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        };
+
+        Ok(Self {
+            source_type,
+            target_type,
+            target_schema,
+            columns,
+            create_table,
+            create_indexes,
+            plan,
+        })
+    }
+
+    async fn run(
+        self,
+        state: &ConnectionStore,
+        request: &TransferRequest,
+        (source_session, session_id): (&str, &str),
+        on_event: &Channel<TransferEvent>,
+    ) -> Result<TransferSummary, String> {
+        let (source, target) = (&request.source, &request.target);
+        let options = &request.options;
+        let crud_builder = get_crud_query_builder(&self.target_type);
+        let schema_name = target
+            .schema
+            .as_deref()
+            .filter(|_| self.target_type != DatabaseType::MySQL);
+        let table_name = crud_builder.crud_table_name(&target.table, schema_name);
+        let session = TargetSession {
+            state,
+            connection_id: &target.connection_id,
+            session_id,
+        };
+
+        match self.target_type {
+            DatabaseType::MySQL => {
+                session
+                    .execute(format!("USE `{}`", target.database.replace('`', "``")))
+                    .await?
+            }
+            DatabaseType::MSSQL => {
+                session
+                    .execute(format!("USE [{}]", target.database.replace(']', "]]")))
+                    .await?
+            }
+            _ => 0,
+        };
+        if let Some(create_table) = &self.create_table {
+            if options.drop_existing {
+                session
+                    .execute(ddl::drop_table_statement(&self.target_type, &table_name))
+                    .await?;
+            }
+            session
+                .execute(create_table.clone())
+                .await
+                .map_err(|e| format!("Failed to create {}: {}", target.table, e))?;
+        } else if options.truncate {
+            session
+                .execute(format!("DELETE FROM {}", table_name))
+                .await?;
+        }
+
+        let auto_increment: Vec<&str> = self
+            .columns
+            .iter()
+            .filter(|col| col.target.is_auto_increment)
+            .map(|col| col.target.name.as_str())
+            .collect();
+        // Inserting explicit identity values needs this on, for the whole session
+        let identity_insert = self.target_type == DatabaseType::MSSQL && !auto_increment.is_empty();
+        if identity_insert {
+            session
+                .execute(ddl::identity_insert_statement(&table_name, true))
+                .await?;
+        }
+
+        let source_request = QueryRequest {
+            db_type: self.source_type.clone(),
+            database: Some(source.database.clone()),
+            schema: source.schema.clone(),
+            table: source.table.clone(),
+            limit: 0,
+            offset: 0,
+            filters: Vec::new(),
+            order_by: Vec::new(),
+        };
+        let total = count_rows(state, &source.connection_id, &source_request).await;
+        let use_copy = state
+            .pool
+            .with_session(&target.connection_id, Some(session_id), |conn| {
+                let supported = conn.supports_copy_in();
+                async move { Ok(supported) }.boxed()
+            })
+            .await?;
+        let mut writer = BatchWriter {
+            session: &session,
+            crud_builder: crud_builder.as_ref(),
+            table: &target.table,
+            schema: schema_name,
+            table_name: &table_name,
+            column_names: self.columns.iter().map(|c| c.target.name.clone()).collect(),
+            table_schema: &self.target_schema,
+            use_copy,
+        };
+
+        let mut summary = TransferSummary {
+            plan: self.plan.clone(),
+            rows_read: 0,
+            rows_written: 0,
+            rows_failed: 0,
+            failures: Vec::new(),
+            warnings: Vec::new(),
+            execution_time: 0,
+        };
+        let fail = |summary: &mut TransferSummary, failure: TransferFailure| {
+            summary.rows_failed += 1;
+            if let Err(e) = on_event.send(TransferEvent::Failed(failure.clone())) {
+                tracing::warn!("⚠️ [TRANSFER] Frontend channel closed: {}", e);
+            }
+            if summary.failures.len() < MAX_REPORTED_FAILURES {
+                summary.failures.push(failure);
+            }
+        };
+
+        let batch_size = options
+            .batch_size
+            .clamp(1, crud_builder.max_batch_rows(self.columns.len()));
+        let mut reader = TableReader::open(
+            state,
+            &source.connection_id,
+            source_session,
+            &source_request,
+            batch_size,
+        )
+        .await?;
+        let mut positions: Option<Vec<Option<usize>>> = None;
+        while let Some((page_columns, rows)) = reader.next_batch().await? {
+            let positions = positions.get_or_insert_with(|| {
+                self.columns
+                    .iter()
+                    .map(|col| page_columns.iter().position(|c| c.name == col.source))
+                    .collect()
+            });
+
+            let mut batch: Vec<(u64, Vec<Value>)> = Vec::with_capacity(rows.len());
+            for row in rows {
+                summary.rows_read += 1;
+                let position = summary.rows_read;
+                match self.convert_row(&row, positions) {
+                    Ok(values) => batch.push((position, values)),
+                    Err((column, error)) => fail(
+                        &mut summary,
+                        TransferFailure {
+                            row: position,
+                            column: Some(column),
+                            error,
+                        },
+                    ),
+                }
+            }
+
+            if !batch.is_empty() {
+                let values: Vec<Vec<Value>> = batch.iter().map(|(_, row)| row.clone()).collect();
+                if writer.write(values).await.is_ok() {
+                    summary.rows_written += batch.len() as u64;
+                } else {
+                    // Find the rows the target refuses by writing them one at a time
+                    writer.use_copy = false;
+                    for (row, values) in batch {
+                        match writer.write(vec![values]).await {
+                            Ok(()) => summary.rows_written += 1,
+                            Err(error) => fail(
+                                &mut summary,
+                                TransferFailure {
+                                    row,
+                                    column: None,
+                                    error,
+                                },
+                            ),
+                        }
+                    }
+                    writer.use_copy = use_copy;
+                }
+            }
+
+            if let Err(e) = on_event.send(TransferEvent::Progress {
+                rows_read: summary.rows_read,
+                rows_written: summary.rows_written,
+                rows_failed: summary.rows_failed,
+                total,
+            }) {
+                tracing::warn!("⚠️ [TRANSFER] Frontend channel closed: {}", e);
+            }
+        }
+
+        if identity_insert {
+            session
+                .execute(ddl::identity_insert_statement(&table_name, false))
+                .await?;
+        }
+        if self.target_type == DatabaseType::PostgreSQL {
+            for column in auto_increment {
+                let sql = ddl::reset_sequence_statement(&table_name, column);
+                if let Err(e) = session.execute(sql).await {
+                    summary.warnings.push(format!(
+                        "Failed to advance the sequence of {}: {}",
+                        column, e
+                    ));
+                }
+            }
+        }
+        // Indexes are built once the rows are in, which is faster than keeping them up
+        for statement in &self.create_indexes {
+            if let Err(e) = session.execute(statement.clone()).await {
+                summary
+                    .warnings
+                    .push(format!("Failed to create index: {}: {}", statement, e));
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// Values of a source row converted to the target's column types, or the column
+    /// whose value does not convert
+    fn convert_row(
+        &self,
+        row: &[Value],
+        positions: &[Option<usize>],
+    ) -> Result<Vec<Value>, (String, String)> {
+        self.columns
+            .iter()
+            .zip(positions)
+            .map(|(col, position)| {
+                let value = position
+                    .and_then(|position| row.get(position))
+                    .unwrap_or(&Value::Null);
+                col.target_type
+                    .convert_value(value, &self.target_type)
+                    .map_err(|e| (col.source.clone(), e))
+            })
+            .collect()
+    }
+}
+
+/// Target table of a transfer that creates it: the source's columns with their types
+/// mapped to the target's dialect, its primary key and its indexes. Types are kept as
+/// they are between databases of the same type, and so are defaults, which are
+/// expressions of the source's dialect. Key columns of unbounded text get a length,
+/// which MySQL and SQL Server need to index them.
+fn map_table(
+    source_type: &DatabaseType,
+    target_type: &DatabaseType,
+    source_schema: &TableSchema,
+    source: &TableLocation,
+    target: &TableLocation,
+) -> TableSchema {
+    let same_dialect = source_type == target_type;
+    let key_columns: HashSet<&str> = source_schema
+        .indexes
+        .iter()
+        .flat_map(|index| index.columns.iter().map(String::as_str))
+        .chain(
+            source_schema
+                .columns
+                .iter()
+                .filter(|col| col.is_primary_key)
+                .map(|col| col.name.as_str()),
+        )
+        .collect();
+
+    let columns: Vec<Column> = source_schema
+        .columns
+        .iter()
+        .map(|col| {
+            let column_type = ColumnType::from_data_type(source_type, &col.data_type);
+            let data_type = if same_dialect {
+                col.data_type.clone()
+            } else if key_columns.contains(col.name.as_str()) {
+                column_type.for_key().to_data_type(target_type)
+            } else {
+                column_type.to_data_type(target_type)
+            };
+            Column {
+                name: col.name.clone(),
+                data_type,
+                nullable: col.nullable,
+                default_value: col.default_value.clone().filter(|_| same_dialect),
+                is_primary_key: col.is_primary_key,
+                is_auto_increment: col.is_auto_increment,
+                is_generated: false,
+                default_is_expression: col.default_is_expression,
+            }
+        })
+        .collect();
+
+    // PostgreSQL index names are unique per schema, so a copy next to its source needs
+    // its own
+    let same_schema = *target_type == DatabaseType::PostgreSQL
+        && source.connection_id == target.connection_id
+        && source.database == target.database
+        && source.schema.as_deref().unwrap_or("public")
+            == target.schema.as_deref().unwrap_or("public");
+    let indexes = source_schema
+        .indexes
+        .iter()
+        .map(|index| Index {
+            name: if same_schema {
+                target_index_name(&index.name, &source.table, &target.table)
+            } else {
+                index.name.clone()
+            },
+            ..index.clone()
+        })
+        .collect();
+
+    TableSchema {
+        table_name: target.table.clone(),
+        columns,
+        indexes,
+        foreign_keys: Vec::new(),
+        has_triggers: false,
+    }
+}
+
+/// Name of an index copied to another table: the source table's name at its start is
+/// replaced by the target's, otherwise the target's name is prepended
+fn target_index_name(index: &str, source_table: &str, target_table: &str) -> String {
+    match index.strip_prefix(source_table) {
+        Some(rest) if rest.starts_with('_') => format!("{}{}", target_table, rest),
+        _ => format!("{}_{}", target_table, index),
+    }
+}
+
+/// Database type of a transfer endpoint, connecting to it first
+async fn transfer_db_type(
+    state: &ConnectionStore,
+    location: &TableLocation,
+) -> Result<DatabaseType, String> {
+    state.ensure_connected(&location.connection_id).await?;
+    let db_type = get_db_type_from_connection(state, &location.connection_id)?;
+    if !matches!(
+        db_type,
+        DatabaseType::MySQL | DatabaseType::PostgreSQL | DatabaseType::MSSQL
+    ) {
+        return Err(format!(
+            "Table transfers are not supported for {:?}",
+            db_type
+        ));
+    }
+    Ok(db_type)
+}
+
+/// Rows of the source table, when the database counts them
+async fn count_rows(
+    state: &ConnectionStore,
+    connection_id: &str,
+    request: &QueryRequest,
+) -> Option<u64> {
+    let table = get_query_builder(&request.db_type).format_table_name(request);
+    let query = BoundQuery::new(format!("SELECT COUNT(*) AS total FROM {}", table));
+    let result = state
+        .pool
+        .with_connection(connection_id, |conn| {
+            async move { conn.execute_query_bound(&query).await }.boxed()
+        })
+        .await
+        .ok()?;
+    match result.rows.first()?.get("total")? {
+        Value::Number(n) => n.as_u64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+/// The session a transfer writes to the target on
+struct TargetSession<'a> {
+    state: &'a ConnectionStore,
+    connection_id: &'a str,
+    session_id: &'a str,
+}
+
+impl TargetSession<'_> {
+    async fn execute(&self, sql: String) -> Result<u64, String> {
+        self.state
+            .pool
+            .with_session(self.connection_id, Some(self.session_id), |conn| {
+                async move { conn.execute_raw(&sql).await }.boxed()
+            })
+            .await
+    }
+}
+
+/// Writes batches of converted rows to the target table
+struct BatchWriter<'a> {
+    session: &'a TargetSession<'a>,
+    crud_builder: &'a dyn CRUDQueryBuilder,
+    table: &'a str,
+    schema: Option<&'a str>,
+    table_name: &'a str,
+    column_names: Vec<String>,
+    table_schema: &'a TableSchema,
+    /// Load with `COPY FROM STDIN` rather than INSERT
+    use_copy: bool,
+}
+
+impl BatchWriter<'_> {
+    async fn write(&self, rows: Vec<Vec<Value>>) -> Result<(), String> {
+        let session = self.session;
+        if self.use_copy {
+            let table_name = self.table_name.to_string();
+            let columns: Vec<String> = self
+                .column_names
+                .iter()
+                .map(|col| self.crud_builder.quote_identifier(col))
+                .collect();
+            return session
+                .state
+                .pool
+                .with_session(session.connection_id, Some(session.session_id), |conn| {
+                    async move { conn.copy_in(&table_name, &columns, &rows).await }.boxed()
+                })
+                .await
+                .map(|_| ());
+        }
+
+        let query = self
+            .crud_builder
+            .build_insert_batch_bound(
+                self.table,
+                self.schema,
+                &self.column_names,
+                &rows,
+                self.table_schema,
+                false,
+            )
+            .map_err(|e| e.to_string())?;
+        session
+            .state
+            .pool
+            .with_session(session.connection_id, Some(session.session_id), |conn| {
+                async move { conn.execute_update_bound(&query).await }.boxed()
+            })
+            .await
+            .map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_renames_indexes_for_the_target_table() {
+        assert_eq!(
+            target_index_name("users_email_idx", "users", "users_copy"),
+            "users_copy_email_idx"
+        );
+        assert_eq!(
+            target_index_name("idx_email", "users", "users_copy"),
+            "users_copy_idx_email"
+        );
+        assert_eq!(
+            target_index_name("usersemail", "users", "archive"),
+            "archive_usersemail"
+        );
+    }
+}
@@ -0,0 +1,1205 @@
+use crate::db::traits::QueryBuilder;
+use crate::models::save_request::EditedRow;
+use crate::models::schema::TableSchema;
+use crate::models::table_request::*;
+use anyhow::Result;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Hidden column holding the row locator of tables without a primary key
+pub const ROW_LOCATOR_COLUMN: &str = "__rustdbgrid_rowid";
+
+/// Hidden column holding the row version of tables whose dialect selects it
+pub const ROW_VERSION_COLUMN: &str = "__rustdbgrid_row_version";
+
+/// SQL text with placeholders and the values bound to them, in placeholder order
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BoundQuery {
+    pub sql: String,
+    pub params: Vec<Value>,
+}
+
+impl BoundQuery {
+    /// Wrap a statement that takes no parameters
+    pub fn new(sql: impl Into<String>) -> Self {
+        Self {
+            sql: sql.into(),
+            params: Vec::new(),
+        }
+    }
+
+    /// The statement for display, with the values it binds in a comment after it
+    pub fn preview(&self) -> String {
+        let sql = format!("{};", self.sql.trim_end().trim_end_matches(';'));
+        if self.params.is_empty() {
+            return sql;
+        }
+        // JSON keeps each value on one line, so the comment cannot end early
+        let params = self
+            .params
+            .iter()
+            .map(Value::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("{}\n-- Parameters: {}", sql, params)
+    }
+}
+
+/// Declared data type of a column, if the table schema is known
+pub fn column_type<'a>(table_schema: Option<&'a TableSchema>, column: &str) -> Option<&'a str> {
+    table_schema
+        .and_then(|schema| schema.columns.iter().find(|col| col.name == column))
+        .map(|col| col.data_type.as_str())
+}
+
+/// Whether values of a column type compare reliably with `=` after a round trip through
+/// the grid. Floating point, binary, JSON and spatial values do not.
+pub fn comparable_type(data_type: &str) -> bool {
+    let base = data_type
+        .split('(')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase();
+    !matches!(
+        base.as_str(),
+        "json"
+            | "xml"
+            | "bytea"
+            | "blob"
+            | "tinyblob"
+            | "mediumblob"
+            | "longblob"
+            | "binary"
+            | "varbinary"
+            | "image"
+            | "geometry"
+            | "geography"
+            | "point"
+            | "real"
+            | "float"
+            | "float4"
+            | "float8"
+            | "double"
+            | "double precision"
+    )
+}
+
+/// Whether a declared column type is a plain type name that can be spliced into a
+/// cast: words, optionally schema-qualified or quoted, then numeric modifiers such as
+/// `(20,6)`, trailing words such as `with time zone` and array brackets. Metadata comes
+/// from the server, but a type name is still SQL text and must not end the expression.
+pub fn is_type_name(data_type: &str) -> bool {
+    let word = |c: char| c.is_ascii_alphanumeric() || matches!(c, '_' | ' ' | '.' | '"');
+    let data_type = data_type.trim();
+    let name_end = data_type.find(['(', '[']).unwrap_or(data_type.len());
+    let (name, mut rest) = data_type.split_at(name_end);
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_' || c == '"')
+        || !name.chars().all(word)
+        || name.matches('"').count() % 2 != 0
+    {
+        return false;
+    }
+    if let Some(modifiers) = rest.strip_prefix('(') {
+        let Some((inner, after)) = modifiers.split_once(')') else {
+            return false;
+        };
+        if inner.trim().is_empty()
+            || !inner
+                .chars()
+                .all(|c| c.is_ascii_digit() || c == ',' || c == ' ')
+        {
+            return false;
+        }
+        rest = after.trim_start_matches(|c: char| c.is_ascii_alphabetic() || c == ' ');
+    }
+    while let Some(brackets) = rest.strip_prefix('[') {
+        let Some((inner, after)) = brackets.split_once(']') else {
+            return false;
+        };
+        if !inner.chars().all(|c| c.is_ascii_digit()) {
+            return false;
+        }
+        rest = after;
+    }
+    rest.is_empty()
+}
+
+/// Row locator the grid loaded for a row, if any
+pub fn row_locator_value(row: &HashMap<String, Value>) -> Option<&Value> {
+    row.get(ROW_LOCATOR_COLUMN).filter(|value| !value.is_null())
+}
+
+/// Row version the grid loaded for a row, if any
+pub fn row_version_value(row: &HashMap<String, Value>) -> Option<&Value> {
+    row.get(ROW_VERSION_COLUMN).filter(|value| !value.is_null())
+}
+
+/// Add hidden columns to a generated `SELECT *`, each an expression and the column
+/// name it is loaded as. `None` when there are none.
+pub fn select_with_hidden_columns<B: QueryBuilder + ?Sized>(
+    builder: &B,
+    sql: &str,
+    hidden: &[(&str, &str)],
+) -> Option<String> {
+    if hidden.is_empty() {
+        return None;
+    }
+    let (head, tail) = sql.split_once("* FROM ")?;
+    let columns = hidden
+        .iter()
+        .map(|(expression, name)| format!("{} AS {}", expression, builder.quote_identifier(name)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    Some(format!("{}*, {} FROM {}", head, columns, tail))
+}
+
+/// Add a value to the parameter list and return the placeholder that refers to it
+pub fn bind<B: QueryBuilder + ?Sized>(
+    builder: &B,
+    params: &mut Vec<Value>,
+    value: &Value,
+    column_type: Option<&str>,
+) -> String {
+    params.push(value.clone());
+    builder.placeholder(params.len(), column_type, value)
+}
+
+/// Build a WHERE clause (without the keyword) from grid filters, binding every filter value
+pub fn build_where_clause<B: QueryBuilder + ?Sized>(
+    builder: &B,
+    filters: &[Filter],
+    table_schema: Option<&TableSchema>,
+    params: &mut Vec<Value>,
+) -> Result<String> {
+    let conditions: Vec<String> = filters
+        .iter()
+        .map(|f| {
+            let col_type = column_type(table_schema, &f.column);
+            build_filter_condition(builder, f, col_type, params)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(conditions.join(" AND "))
+}
+
+/// Build a single filter condition, binding its value(s)
+pub fn build_filter_condition<B: QueryBuilder + ?Sized>(
+    builder: &B,
+    filter: &Filter,
+    column_type: Option<&str>,
+    params: &mut Vec<Value>,
+) -> Result<String> {
+    let mut compare = |op: &str, value: &Value, column_type: Option<&str>| {
+        let column = builder.bound_column(&filter.column, column_type, value);
+        let placeholder = bind(builder, params, value, column_type);
+        format!("{} {} {}", column, op, placeholder)
+    };
+
+    let condition = match (&filter.operator, &filter.value) {
+        (FilterOperator::Equals, FilterValue::Single(v)) => compare("=", v, column_type),
+        (FilterOperator::NotEquals, FilterValue::Single(v)) => compare("!=", v, column_type),
+        (FilterOperator::GreaterThan, FilterValue::Single(v)) => compare(">", v, column_type),
+        (FilterOperator::GreaterThanOrEqual, FilterValue::Single(v)) => {
+            compare(">=", v, column_type)
+        }
+        (FilterOperator::LessThan, FilterValue::Single(v)) => compare("<", v, column_type),
+        (FilterOperator::LessThanOrEqual, FilterValue::Single(v)) => compare("<=", v, column_type),
+        // LIKE patterns are always text, whatever the column type
+        (FilterOperator::Like, FilterValue::Single(v)) => compare("LIKE", v, None),
+        (FilterOperator::NotLike, FilterValue::Single(v)) => compare("NOT LIKE", v, None),
+        (FilterOperator::In, FilterValue::Multiple(values))
+        // Handle case where array is parsed as Single (due to serde(untagged))
+        | (FilterOperator::In, FilterValue::Single(Value::Array(values))) => {
+            build_in_list(builder, filter, "IN", values, column_type, params)
+        }
+        (FilterOperator::NotIn, FilterValue::Multiple(values))
+        | (FilterOperator::NotIn, FilterValue::Single(Value::Array(values))) => {
+            build_in_list(builder, filter, "NOT IN", values, column_type, params)
+        }
+        (FilterOperator::Between, FilterValue::Range { from, to }) => {
+            let column = builder.bound_column(&filter.column, column_type, from);
+            let from = bind(builder, params, from, column_type);
+            let to = bind(builder, params, to, column_type);
+            format!("{} BETWEEN {} AND {}", column, from, to)
+        }
+        (FilterOperator::IsNull, _) => {
+            format!("{} IS NULL", builder.quote_identifier(&filter.column))
+        }
+        (FilterOperator::IsNotNull, _) => {
+            format!("{} IS NOT NULL", builder.quote_identifier(&filter.column))
+        }
+        (operator, _) => {
+            let expected = match operator {
+                FilterOperator::In | FilterOperator::NotIn => "multiple values",
+                FilterOperator::Between => "range value",
+                _ => "single value",
+            };
+            anyhow::bail!("{:?} operator requires {}", operator, expected)
+        }
+    };
+
+    Ok(condition)
+}
+
+fn build_in_list<B: QueryBuilder + ?Sized>(
+    builder: &B,
+    filter: &Filter,
+    op: &str,
+    values: &[Value],
+    column_type: Option<&str>,
+    params: &mut Vec<Value>,
+) -> String {
+    let column = builder.bound_column(
+        &filter.column,
+        column_type,
+        values.first().unwrap_or(&Value::Null),
+    );
+    let placeholders = values
+        .iter()
+        .map(|v| bind(builder, params, v, column_type))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("{} {} ({})", column, op, placeholders)
+}
+
+/// Build a `column = ?` condition, or `column IS NULL` for NULL values
+fn build_match_condition<B: QueryBuilder + ?Sized>(
+    builder: &B,
+    column: &str,
+    value: &Value,
+    table_schema: &TableSchema,
+    params: &mut Vec<Value>,
+) -> String {
+    if value.is_null() {
+        return format!("{} IS NULL", builder.quote_identifier(column));
+    }
+
+    let col_type = column_type(Some(table_schema), column);
+    let column_expr = builder.bound_column(column, col_type, value);
+    format!(
+        "{} = {}",
+        column_expr,
+        bind(builder, params, value, col_type)
+    )
+}
+
+/// WHERE clause identifying a row, and the limit that keeps it to one row when the
+/// clause could match duplicates
+struct RowCondition {
+    sql: String,
+    limit: &'static str,
+}
+
+/// Build the WHERE clause identifying a row: by primary keys, by the row locator of a
+/// keyless table, or by every comparable column
+fn build_row_condition<B: QueryBuilder + ?Sized>(
+    builder: &B,
+    row: &HashMap<String, Value>,
+    primary_keys: &[&String],
+    table_schema: &TableSchema,
+    params: &mut Vec<Value>,
+    source: &str,
+) -> Result<RowCondition> {
+    if !primary_keys.is_empty() {
+        let sql = primary_keys
+            .iter()
+            .map(|pk| {
+                let val = row
+                    .get(pk.as_str())
+                    .ok_or_else(|| anyhow::anyhow!("Primary key {} not found in {}", pk, source))?;
+                Ok(build_match_condition(
+                    builder,
+                    pk,
+                    val,
+                    table_schema,
+                    params,
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?
+            .join(" AND ");
+        return Ok(RowCondition { sql, limit: "" });
+    }
+
+    if let Some(locator) = row_locator_value(row) {
+        let placeholder = builder.placeholder(params.len() + 1, None, locator);
+        if let Some(sql) = builder.row_locator_condition(&placeholder) {
+            params.push(locator.clone());
+            return Ok(RowCondition { sql, limit: "" });
+        }
+    }
+
+    // Fallback: match every column whose values compare reliably
+    let sql = table_schema
+        .columns
+        .iter()
+        .filter(|col| builder.supports_equality(&col.data_type))
+        .filter_map(|col| {
+            row.get(&col.name)
+                .map(|val| build_match_condition(builder, &col.name, val, table_schema, params))
+        })
+        .collect::<Vec<_>>()
+        .join(" AND ");
+    Ok(RowCondition {
+        sql,
+        limit: builder.single_row_limit().unwrap_or_default(),
+    })
+}
+
+/// How the row stored by an INSERT is read back, with server-generated values such as
+/// identities, defaults and trigger changes filled in
+#[derive(Debug, Clone, PartialEq)]
+pub enum InsertReadBack {
+    /// The INSERT itself returns the stored row
+    Returning,
+    /// A query run right after the INSERT, on the same session, selects the stored row
+    Select(BoundQuery),
+    /// The stored row cannot be identified
+    Unavailable,
+}
+
+/// Build an INSERT with one bound value per column, in table schema order
+pub fn build_insert<B: QueryBuilder + ?Sized>(
+    builder: &B,
+    table_name: &str,
+    row: &HashMap<String, Value>,
+    table_schema: &TableSchema,
+) -> Result<BoundQuery> {
+    build_insert_returning(builder, table_name, row, table_schema, "", "")
+}
+
+/// Build an INSERT that returns the stored row: `output` goes before `VALUES` (SQL
+/// Server's `OUTPUT INSERTED.*`) and `returning` after it (`RETURNING *`)
+pub fn build_insert_returning<B: QueryBuilder + ?Sized>(
+    builder: &B,
+    table_name: &str,
+    row: &HashMap<String, Value>,
+    table_schema: &TableSchema,
+    output: &str,
+    returning: &str,
+) -> Result<BoundQuery> {
+    if row.is_empty() {
+        anyhow::bail!("Cannot insert empty row");
+    }
+
+    let mut params = Vec::new();
+    let mut columns = Vec::new();
+    let mut placeholders = Vec::new();
+    for col in table_schema
+        .columns
+        .iter()
+        .filter(|col| row.contains_key(&col.name))
+    {
+        columns.push(builder.quote_identifier(&col.name));
+        placeholders.push(bind(
+            builder,
+            &mut params,
+            &row[&col.name],
+            Some(&col.data_type),
+        ));
+    }
+
+    if columns.is_empty() {
+        anyhow::bail!("No known columns to insert");
+    }
+
+    Ok(BoundQuery {
+        sql: format!(
+            "INSERT INTO {} ({}){} VALUES ({}){}",
+            table_name,
+            columns.join(", "),
+            output,
+            placeholders.join(", "),
+            returning
+        ),
+        params,
+    })
+}
+
+/// Bind the values of several rows, each holding the values of `columns` in order, and
+/// return the parenthesized placeholder list of each row
+pub fn bind_row_values<B: QueryBuilder + ?Sized>(
+    builder: &B,
+    columns: &[String],
+    rows: &[Vec<Value>],
+    table_schema: &TableSchema,
+    params: &mut Vec<Value>,
+) -> Vec<String> {
+    let types: Vec<Option<&str>> = columns
+        .iter()
+        .map(|col| column_type(Some(table_schema), col))
+        .collect();
+    rows.iter()
+        .map(|row| {
+            let placeholders: Vec<String> = row
+                .iter()
+                .zip(&types)
+                .map(|(value, col_type)| bind(builder, params, value, *col_type))
+                .collect();
+            format!("({})", placeholders.join(", "))
+        })
+        .collect()
+}
+
+/// Build one INSERT of several rows, binding every value; `suffix` follows the VALUES
+/// list, e.g. a dialect's upsert clause
+pub fn build_insert_batch<B: QueryBuilder + ?Sized>(
+    builder: &B,
+    table_name: &str,
+    columns: &[String],
+    rows: &[Vec<Value>],
+    table_schema: &TableSchema,
+    suffix: &str,
+) -> Result<BoundQuery> {
+    if columns.is_empty() || rows.is_empty() {
+        anyhow::bail!("Cannot insert empty rows");
+    }
+
+    let mut params = Vec::new();
+    let values = bind_row_values(builder, columns, rows, table_schema, &mut params);
+    let quoted: Vec<String> = columns
+        .iter()
+        .map(|col| builder.quote_identifier(col))
+        .collect();
+    Ok(BoundQuery {
+        sql: format!(
+            "INSERT INTO {} ({}) VALUES {}{}",
+            table_name,
+            quoted.join(", "),
+            values.join(", "),
+            suffix
+        ),
+        params,
+    })
+}
+
+/// Split inserted columns into the primary key an upsert matches rows on and the
+/// columns it updates. Fails unless the table has a primary key and every key column
+/// is inserted.
+pub fn upsert_columns<'a>(
+    columns: &'a [String],
+    table_schema: &TableSchema,
+) -> Result<(Vec<&'a String>, Vec<&'a String>)> {
+    let primary_keys: Vec<&str> = table_schema
+        .columns
+        .iter()
+        .filter(|col| col.is_primary_key)
+        .map(|col| col.name.as_str())
+        .collect();
+    if primary_keys.is_empty() {
+        anyhow::bail!("Upserting needs a table with a primary key");
+    }
+    if let Some(missing) = primary_keys
+        .iter()
+        .find(|key| !columns.iter().any(|col| col == *key))
+    {
+        anyhow::bail!(
+            "Upserting needs a value for primary key column '{}'",
+            missing
+        );
+    }
+    Ok(columns
+        .iter()
+        .partition(|col| primary_keys.contains(&col.as_str())))
+}
+
+/// Build an UPDATE that binds new values and the original key values of the row
+pub fn build_update<B: QueryBuilder + ?Sized>(
+    builder: &B,
+    table_name: &str,
+    edited_row: &EditedRow,
+    primary_keys: &[&String],
+    table_schema: &TableSchema,
+) -> Result<BoundQuery> {
+    update_statement(
+        builder,
+        table_name,
+        edited_row,
+        primary_keys,
+        table_schema,
+        false,
+    )
+}
+
+/// Build an UPDATE that only matches the row while it is unchanged since it was loaded:
+/// by its row version when the dialect has one and the grid loaded it, otherwise by
+/// the original values of the columns being changed
+pub fn build_update_checked<B: QueryBuilder + ?Sized>(
+    builder: &B,
+    table_name: &str,
+    edited_row: &EditedRow,
+    primary_keys: &[&String],
+    table_schema: &TableSchema,
+) -> Result<BoundQuery> {
+    update_statement(
+        builder,
+        table_name,
+        edited_row,
+        primary_keys,
+        table_schema,
+        true,
+    )
+}
+
+fn update_statement<B: QueryBuilder + ?Sized>(
+    builder: &B,
+    table_name: &str,
+    edited_row: &EditedRow,
+    primary_keys: &[&String],
+    table_schema: &TableSchema,
+    checked: bool,
+) -> Result<BoundQuery> {
+    let updated_data = &edited_row.updated_data;
+    let original = &edited_row.original_data;
+
+    if updated_data.is_empty() {
+        anyhow::bail!("Cannot update with no columns");
+    }
+
+    // Build SET clause using table_schema column order (exclude primary keys)
+    let mut params = Vec::new();
+    let set_clause = table_schema
+        .columns
+        .iter()
+        .filter(|col| {
+            updated_data.contains_key(&col.name)
+                && !primary_keys
+                    .iter()
+                    .any(|pk| pk.as_str() == col.name.as_str())
+        })
+        .map(|col| {
+            format!(
+                "{} = {}",
+                builder.quote_identifier(&col.name),
+                bind(
+                    builder,
+                    &mut params,
+                    &updated_data[&col.name],
+                    Some(&col.data_type)
+                )
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    if set_clause.is_empty() {
+        anyhow::bail!("No columns to update (all are primary keys)");
+    }
+
+    let row_condition = build_row_condition(
+        builder,
+        original,
+        primary_keys,
+        table_schema,
+        &mut params,
+        "original data",
+    )?;
+
+    if row_condition.sql.is_empty() {
+        anyhow::bail!("Cannot generate WHERE clause");
+    }
+
+    let mut where_clause = row_condition.sql;
+    if checked {
+        let checks = match builder.row_version_condition(table_schema, original, &mut params) {
+            Some(condition) => vec![condition],
+            None => table_schema
+                .columns
+                .iter()
+                .filter(|col| {
+                    updated_data.contains_key(&col.name)
+                        && !primary_keys.iter().any(|pk| pk.as_str() == col.name)
+                        && builder.supports_equality(&col.data_type)
+                })
+                .filter_map(|col| {
+                    original.get(&col.name).map(|value| {
+                        build_match_condition(builder, &col.name, value, table_schema, &mut params)
+                    })
+                })
+                .collect(),
+        };
+        for check in checks {
+            where_clause.push_str(" AND ");
+            where_clause.push_str(&check);
+        }
+    }
+
+    Ok(BoundQuery {
+        sql: format!(
+            "UPDATE {} SET {} WHERE {}{}",
+            table_name, set_clause, where_clause, row_condition.limit
+        ),
+        params,
+    })
+}
+
+/// Build a SELECT of the current server values of one row, by its keys
+pub fn build_select_row<B: QueryBuilder + ?Sized>(
+    builder: &B,
+    table_name: &str,
+    row: &HashMap<String, Value>,
+    primary_keys: &[&String],
+    table_schema: &TableSchema,
+) -> Result<BoundQuery> {
+    let mut params = Vec::new();
+    let row_condition =
+        build_row_condition(builder, row, primary_keys, table_schema, &mut params, "row")?;
+
+    if row_condition.sql.is_empty() {
+        anyhow::bail!("Cannot generate WHERE clause for SELECT");
+    }
+
+    Ok(BoundQuery {
+        sql: format!("SELECT * FROM {} WHERE {}", table_name, row_condition.sql),
+        params,
+    })
+}
+
+/// Build a DELETE that binds the key values of the row
+pub fn build_delete<B: QueryBuilder + ?Sized>(
+    builder: &B,
+    table_name: &str,
+    row: &HashMap<String, Value>,
+    primary_keys: &[&String],
+    table_schema: &TableSchema,
+) -> Result<BoundQuery> {
+    let mut params = Vec::new();
+    let row_condition =
+        build_row_condition(builder, row, primary_keys, table_schema, &mut params, "row")?;
+
+    if row_condition.sql.is_empty() {
+        anyhow::bail!("Cannot generate WHERE clause for DELETE");
+    }
+
+    Ok(BoundQuery {
+        sql: format!(
+            "DELETE FROM {} WHERE {}{}",
+            table_name, row_condition.sql, row_condition.limit
+        ),
+        params,
+    })
+}
+
+/// Foreign key from a referencing table to the table one level up a delete cascade
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReferenceLink {
+    /// Quoted name of the referencing table
+    pub table: String,
+    /// Each referencing column with the referenced column it points at
+    pub columns: Vec<(String, String)>,
+}
+
+/// Build a WHERE clause (without the keyword) matching any of the given rows, each
+/// identified the way a DELETE identifies it
+pub fn build_rows_condition<B: QueryBuilder + ?Sized>(
+    builder: &B,
+    rows: &[HashMap<String, Value>],
+    primary_keys: &[&String],
+    table_schema: &TableSchema,
+) -> Result<BoundQuery> {
+    let mut params = Vec::new();
+    let conditions = rows
+        .iter()
+        .map(|row| {
+            let condition =
+                build_row_condition(builder, row, primary_keys, table_schema, &mut params, "row")?;
+            if condition.sql.is_empty() {
+                anyhow::bail!("Cannot generate WHERE clause for row");
+            }
+            Ok(format!("({})", condition.sql))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(BoundQuery {
+        sql: conditions.join(" OR "),
+        params,
+    })
+}
+
+/// Build a count of the rows reached from the rows of `root_table` matching
+/// `root_condition` by following `chain`, one foreign key per level. Each level is an
+/// `EXISTS` on the level above, so the root condition is evaluated against the root
+/// table only.
+pub fn build_dependent_count<B: QueryBuilder + ?Sized>(
+    builder: &B,
+    root_table: &str,
+    root_condition: &BoundQuery,
+    chain: &[ReferenceLink],
+) -> Result<BoundQuery> {
+    let join = |parent: usize, link: &ReferenceLink| {
+        link.columns
+            .iter()
+            .map(|(column, referenced)| {
+                format!(
+                    "t{}.{} = t{}.{}",
+                    parent,
+                    builder.quote_identifier(referenced),
+                    parent + 1,
+                    builder.quote_identifier(column)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" AND ")
+    };
+
+    let first = chain
+        .first()
+        .ok_or_else(|| anyhow::anyhow!("No foreign key to follow"))?;
+    let mut exists = format!(
+        "SELECT 1 FROM {} t0 WHERE {} AND ({})",
+        root_table,
+        join(0, first),
+        root_condition.sql
+    );
+    for (level, link) in chain.iter().enumerate().skip(1) {
+        exists = format!(
+            "SELECT 1 FROM {} t{} WHERE {} AND EXISTS ({})",
+            chain[level - 1].table,
+            level,
+            join(level, link),
+            exists
+        );
+    }
+
+    Ok(BoundQuery {
+        sql: format!(
+            "SELECT COUNT(*) AS dependent_count FROM {} t{} WHERE EXISTS ({})",
+            chain[chain.len() - 1].table,
+            chain.len(),
+            exists
+        ),
+        params: root_condition.params.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::mssql::MSSQLQueryBuilder;
+    use crate::db::mysql::MySQLQueryBuilder;
+    use crate::db::postgres::PostgreSQLQueryBuilder;
+    use crate::models::schema::{test_column, Column};
+    use serde_json::json;
+
+    fn users_schema() -> TableSchema {
+        TableSchema {
+            table_name: "users".to_string(),
+            columns: vec![
+                Column {
+                    is_primary_key: true,
+                    ..test_column("id", "int4", false, None)
+                },
+                test_column("name", "varchar(50)", true, None),
+                test_column("balance", "numeric(20,6)", true, None),
+            ],
+            indexes: vec![],
+            foreign_keys: vec![],
+            has_triggers: false,
+        }
+    }
+
+    fn filter(column: &str, operator: FilterOperator, value: FilterValue) -> Filter {
+        Filter {
+            column: column.to_string(),
+            operator,
+            value,
+        }
+    }
+
+    #[test]
+    fn test_filter_values_are_bound_not_inlined() {
+        let filters = vec![
+            filter(
+                "name",
+                FilterOperator::Equals,
+                FilterValue::Single(json!("O'Brien'; DROP TABLE users; --")),
+            ),
+            filter(
+                "id",
+                FilterOperator::In,
+                FilterValue::Multiple(vec![json!(1), json!(2)]),
+            ),
+        ];
+        let mut params = Vec::new();
+        let sql = build_where_clause(&MySQLQueryBuilder, &filters, None, &mut params).unwrap();
+
+        assert_eq!(sql, "`name` = ? AND `id` IN (?, ?)");
+        assert_eq!(
+            params,
+            vec![json!("O'Brien'; DROP TABLE users; --"), json!(1), json!(2)]
+        );
+    }
+
+    #[test]
+    fn test_postgres_placeholders_cast_to_column_type() {
+        let schema = users_schema();
+        let filters = vec![
+            filter(
+                "balance",
+                FilterOperator::Between,
+                FilterValue::Range {
+                    from: json!("12345678901234.123456"),
+                    to: json!(99),
+                },
+            ),
+            filter(
+                "name",
+                FilterOperator::Like,
+                FilterValue::Single(json!("%a%")),
+            ),
+            filter(
+                "missing",
+                FilterOperator::Equals,
+                FilterValue::Single(json!(5)),
+            ),
+        ];
+        let mut params = Vec::new();
+        let sql = build_where_clause(
+            &PostgreSQLQueryBuilder,
+            &filters,
+            Some(&schema),
+            &mut params,
+        )
+        .unwrap();
+
+        assert_eq!(
+            sql,
+            "\"balance\" BETWEEN CAST($1 AS numeric(20,6)) AND CAST($2 AS numeric(20,6)) \
+             AND CAST(\"name\" AS TEXT) LIKE $3 AND \"missing\" = CAST($4 AS NUMERIC)"
+        );
+        assert_eq!(params.len(), 4);
+    }
+
+    #[test]
+    fn test_postgres_casts_only_to_plain_type_names() {
+        for data_type in [
+            "int4",
+            "numeric(20,6)",
+            "timestamp(3) with time zone",
+            "double precision",
+            "text[]",
+            "public.\"Mood\"",
+        ] {
+            assert!(is_type_name(data_type), "{}", data_type);
+        }
+        for data_type in [
+            "int4) = 1 OR (1",
+            "text; DROP TABLE users",
+            "int4 -- comment",
+            "numeric(x)",
+            "\"unterminated",
+            "",
+        ] {
+            assert!(!is_type_name(data_type), "{}", data_type);
+        }
+
+        let placeholder = PostgreSQLQueryBuilder.placeholder(1, Some("int4); --"), &json!(5));
+        assert_eq!(placeholder, "CAST($1 AS NUMERIC)");
+    }
+
+    #[test]
+    fn test_update_binds_set_values_then_keys() {
+        let schema = users_schema();
+        let edited_row = EditedRow {
+            original_data: HashMap::from([
+                ("id".to_string(), json!(7)),
+                ("name".to_string(), json!("old")),
+            ]),
+            updated_data: HashMap::from([
+                ("name".to_string(), json!("new")),
+                ("balance".to_string(), Value::Null),
+            ]),
+        };
+        let id = "id".to_string();
+        let query = build_update(
+            &MSSQLQueryBuilder,
+            "[dbo].[users]",
+            &edited_row,
+            &[&id],
+            &schema,
+        )
+        .unwrap();
+
+        assert_eq!(
+            query.sql,
+            "UPDATE [dbo].[users] SET [name] = @P1, [balance] = @P2 WHERE [id] = @P3"
+        );
+        assert_eq!(query.params, vec![json!("new"), Value::Null, json!(7)]);
+    }
+
+    #[test]
+    fn test_checked_update_compares_original_values_of_changed_columns() {
+        let mut schema = users_schema();
+        schema
+            .columns
+            .push(test_column("score", "double precision", true, None));
+        let edited_row = EditedRow {
+            original_data: HashMap::from([
+                ("id".to_string(), json!(7)),
+                ("name".to_string(), json!("old")),
+                ("balance".to_string(), Value::Null),
+                ("score".to_string(), json!(1.5)),
+            ]),
+            updated_data: HashMap::from([
+                ("name".to_string(), json!("new")),
+                ("balance".to_string(), json!("10")),
+                ("score".to_string(), json!(2.5)),
+            ]),
+        };
+        let id = "id".to_string();
+        let query =
+            build_update_checked(&MySQLQueryBuilder, "`users`", &edited_row, &[&id], &schema)
+                .unwrap();
+
+        assert_eq!(
+            query.sql,
+            "UPDATE `users` SET `name` = ?, `balance` = ?, `score` = ? \
+             WHERE `id` = ? AND `name` = ? AND `balance` IS NULL"
+        );
+        assert_eq!(
+            query.params,
+            vec![
+                json!("new"),
+                json!("10"),
+                json!(2.5),
+                json!(7),
+                json!("old")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_checked_update_prefers_the_loaded_row_version() {
+        let edited_row = EditedRow {
+            original_data: HashMap::from([
+                ("id".to_string(), json!(7)),
+                ("name".to_string(), json!("old")),
+                (ROW_VERSION_COLUMN.to_string(), json!("1234")),
+            ]),
+            updated_data: HashMap::from([("name".to_string(), json!("new"))]),
+        };
+        let id = "id".to_string();
+        let query = build_update_checked(
+            &PostgreSQLQueryBuilder,
+            "\"users\"",
+            &edited_row,
+            &[&id],
+            &users_schema(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            query.sql,
+            "UPDATE \"users\" SET \"name\" = CAST($1 AS varchar(50)) \
+             WHERE \"id\" = CAST($2 AS int4) AND CAST(xmin AS TEXT) = $3"
+        );
+        assert_eq!(query.params, vec![json!("new"), json!(7), json!("1234")]);
+
+        let select = select_with_hidden_columns(
+            &PostgreSQLQueryBuilder,
+            "SELECT * FROM \"users\" LIMIT 100",
+            &[(
+                PostgreSQLQueryBuilder.row_version().unwrap(),
+                ROW_VERSION_COLUMN,
+            )],
+        );
+        assert_eq!(
+            select.as_deref(),
+            Some(
+                "SELECT *, CAST(xmin AS TEXT) AS \"__rustdbgrid_row_version\" \
+                 FROM \"users\" LIMIT 100"
+            )
+        );
+    }
+
+    #[test]
+    fn test_null_keys_match_with_is_null() {
+        let schema = users_schema();
+        let row = HashMap::from([("id".to_string(), Value::Null)]);
+        let id = "id".to_string();
+        let query = build_delete(&MySQLQueryBuilder, "`users`", &row, &[&id], &schema).unwrap();
+
+        assert_eq!(query.sql, "DELETE FROM `users` WHERE `id` IS NULL");
+        assert!(query.params.is_empty());
+    }
+
+    #[test]
+    fn test_keyless_rows_match_by_loaded_row_locator() {
+        let row = HashMap::from([
+            ("name".to_string(), json!("a")),
+            (ROW_LOCATOR_COLUMN.to_string(), json!("(0,3)")),
+        ]);
+        let query = build_delete(
+            &PostgreSQLQueryBuilder,
+            "\"logs\"",
+            &row,
+            &[],
+            &users_schema(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            query.sql,
+            "DELETE FROM \"logs\" WHERE ctid = CAST($1 AS tid)"
+        );
+        assert_eq!(query.params, vec![json!("(0,3)")]);
+
+        let select = select_with_hidden_columns(
+            &MSSQLQueryBuilder,
+            "SELECT TOP 10 * FROM [logs]",
+            &[(MSSQLQueryBuilder.row_locator().unwrap(), ROW_LOCATOR_COLUMN)],
+        );
+        assert_eq!(
+            select.as_deref(),
+            Some(
+                "SELECT TOP 10 *, CONVERT(VARCHAR(20), %%physloc%%, 1) AS [__rustdbgrid_rowid] \
+                 FROM [logs]"
+            )
+        );
+    }
+
+    #[test]
+    fn test_keyless_rows_without_locator_match_all_values_once() {
+        let edited_row = EditedRow {
+            original_data: HashMap::from([
+                ("id".to_string(), json!(7)),
+                ("name".to_string(), json!("old")),
+                ("balance".to_string(), Value::Null),
+            ]),
+            updated_data: HashMap::from([("name".to_string(), json!("new"))]),
+        };
+        let query = build_update(
+            &MySQLQueryBuilder,
+            "`logs`",
+            &edited_row,
+            &[],
+            &users_schema(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            query.sql,
+            "UPDATE `logs` SET `name` = ? \
+             WHERE `id` = ? AND `name` = ? AND `balance` IS NULL LIMIT 1"
+        );
+        assert_eq!(query.params, vec![json!("new"), json!(7), json!("old")]);
+    }
+
+    #[test]
+    fn test_inserts_read_back_generated_values() {
+        use crate::db::traits::CRUDQueryBuilder;
+
+        let mut schema = users_schema();
+        schema.columns[0].is_auto_increment = true;
+        let row = HashMap::from([("name".to_string(), json!("a"))]);
+
+        let (insert, read_back) = MySQLQueryBuilder
+            .build_insert_returning_bound("users", None, &row, &schema)
+            .unwrap();
+        assert_eq!(insert.sql, "INSERT INTO `users` (`name`) VALUES (?)");
+        assert_eq!(
+            read_back,
+            InsertReadBack::Select(BoundQuery::new(
+                "SELECT * FROM `users` WHERE `id` = LAST_INSERT_ID()"
+            ))
+        );
+
+        let (insert, read_back) = MSSQLQueryBuilder
+            .build_insert_returning_bound("users", Some("dbo"), &row, &schema)
+            .unwrap();
+        assert_eq!(
+            insert.sql,
+            "INSERT INTO [dbo].[users] ([name]) OUTPUT INSERTED.* VALUES (@P1)"
+        );
+        assert_eq!(read_back, InsertReadBack::Returning);
+    }
+
+    #[test]
+    fn test_dependent_counts_follow_the_cascade_chain() {
+        let rows = vec![
+            HashMap::from([("id".to_string(), json!(1))]),
+            HashMap::from([("id".to_string(), json!(2))]),
+        ];
+        let id = "id".to_string();
+        let condition =
+            build_rows_condition(&MySQLQueryBuilder, &rows, &[&id], &users_schema()).unwrap();
+        assert_eq!(condition.sql, "(`id` = ?) OR (`id` = ?)");
+
+        let orders = ReferenceLink {
+            table: "`orders`".to_string(),
+            columns: vec![("user_id".to_string(), "id".to_string())],
+        };
+        let items = ReferenceLink {
+            table: "`order_items`".to_string(),
+            columns: vec![
+                ("order_id".to_string(), "id".to_string()),
+                ("order_region".to_string(), "region".to_string()),
+            ],
+        };
+        let query =
+            build_dependent_count(&MySQLQueryBuilder, "`users`", &condition, &[orders, items])
+                .unwrap();
+
+        assert_eq!(
+            query.sql,
+            "SELECT COUNT(*) AS dependent_count FROM `order_items` t2 WHERE EXISTS (\
+             SELECT 1 FROM `orders` t1 WHERE t1.`id` = t2.`order_id` \
+             AND t1.`region` = t2.`order_region` AND EXISTS (\
+             SELECT 1 FROM `users` t0 WHERE t0.`id` = t1.`user_id` \
+             AND ((`id` = ?) OR (`id` = ?))))"
+        );
+        assert_eq!(query.params, vec![json!(1), json!(2)]);
+    }
+
+    #[test]
+    fn test_batch_inserts_upsert_on_the_primary_key() {
+        use crate::db::traits::CRUDQueryBuilder;
+
+        let columns = vec!["id".to_string(), "name".to_string()];
+        let rows = vec![vec![json!(1), json!("a")], vec![json!(2), json!("b")]];
+        let schema = users_schema();
+
+        let insert = MySQLQueryBuilder
+            .build_insert_batch_bound("users", None, &columns, &rows, &schema, false)
+            .unwrap();
+        assert_eq!(
+            insert.sql,
+            "INSERT INTO `users` (`id`, `name`) VALUES (?, ?), (?, ?)"
+        );
+        assert_eq!(
+            insert.params,
+            vec![json!(1), json!("a"), json!(2), json!("b")]
+        );
+
+        let upsert = MySQLQueryBuilder
+            .build_insert_batch_bound("users", None, &columns, &rows, &schema, true)
+            .unwrap();
+        assert!(upsert
+            .sql
+            .ends_with(" ON DUPLICATE KEY UPDATE `name` = VALUES(`name`)"));
+
+        let upsert = PostgreSQLQueryBuilder
+            .build_insert_batch_bound("users", Some("public"), &columns, &rows, &schema, true)
+            .unwrap();
+        assert!(upsert
+            .sql
+            .ends_with(" ON CONFLICT (\"id\") DO UPDATE SET \"name\" = EXCLUDED.\"name\""));
+
+        let upsert = MSSQLQueryBuilder
+            .build_insert_batch_bound("users", Some("dbo"), &columns, &rows, &schema, true)
+            .unwrap();
+        assert_eq!(
+            upsert.sql,
+            "MERGE INTO [dbo].[users] AS target \
+             USING (VALUES (@P1, @P2), (@P3, @P4)) AS source ([id], [name]) \
+             ON target.[id] = source.[id] \
+             WHEN MATCHED THEN UPDATE SET target.[name] = source.[name] \
+             WHEN NOT MATCHED THEN INSERT ([id], [name]) VALUES (source.[id], source.[name]);"
+        );
+
+        let keyless = vec!["name".to_string()];
+        assert!(PostgreSQLQueryBuilder
+            .build_insert_batch_bound("users", None, &keyless, &[vec![json!("a")]], &schema, true)
+            .is_err());
+    }
+}
@@ -0,0 +1,335 @@
+use crate::db::traits::{get_crud_query_builder, CRUDQueryBuilder};
+use crate::models::connection::DatabaseType;
+use crate::models::schema::{Column, ForeignKey, TableSchema};
+use anyhow::{anyhow, Result};
+
+/// Build the `CREATE TABLE` statement of a table, followed by `CREATE INDEX` statements
+/// for its secondary indexes, from the schema the driver reported. Used for engines
+/// without a native source of DDL.
+pub fn create_table_ddl(
+    db_type: &DatabaseType,
+    schema_name: Option<&str>,
+    table: &TableSchema,
+) -> Result<String> {
+    if !matches!(
+        db_type,
+        DatabaseType::MySQL | DatabaseType::PostgreSQL | DatabaseType::MSSQL
+    ) {
+        return Err(anyhow!("DDL generation is not supported for {:?}", db_type));
+    }
+    if table.columns.is_empty() {
+        return Err(anyhow!("Table '{}' has no columns", table.table_name));
+    }
+
+    let builder = get_crud_query_builder(db_type);
+    let quote_list = |names: &[&str]| {
+        names
+            .iter()
+            .map(|name| builder.quote_identifier(name))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let table_name = builder.crud_table_name(&table.table_name, schema_name);
+
+    let mut definitions: Vec<String> = table
+        .columns
+        .iter()
+        .map(|col| column_definition(db_type, builder.as_ref(), col))
+        .collect();
+
+    let primary_key: Vec<&str> = table
+        .columns
+        .iter()
+        .filter(|col| col.is_primary_key)
+        .map(|col| col.name.as_str())
+        .collect();
+    if !primary_key.is_empty() {
+        definitions.push(format!("PRIMARY KEY ({})", quote_list(&primary_key)));
+    }
+
+    for fk in group_foreign_keys(&table.foreign_keys) {
+        let columns: Vec<&str> = fk.iter().map(|part| part.column.as_str()).collect();
+        let referenced: Vec<&str> = fk
+            .iter()
+            .map(|part| part.referenced_column.as_str())
+            .collect();
+        let mut definition = format!(
+            "CONSTRAINT {} FOREIGN KEY ({}) REFERENCES {} ({})",
+            builder.quote_identifier(&fk[0].name),
+            quote_list(&columns),
+            builder.crud_table_name(&fk[0].referenced_table, schema_name),
+            quote_list(&referenced)
+        );
+        for (clause, rule) in [
+            ("ON DELETE", &fk[0].on_delete),
+            ("ON UPDATE", &fk[0].on_update),
+        ] {
+            if let Some(action) = referential_action(db_type, rule.as_deref()) {
+                definition.push_str(&format!(" {} {}", clause, action));
+            }
+        }
+        definitions.push(definition);
+    }
+
+    let mut ddl = format!(
+        "CREATE TABLE {} (\n    {}\n);\n",
+        table_name,
+        definitions.join(",\n    ")
+    );
+
+    // The primary key and its backing index were already declared with the table
+    let mut indexes: Vec<_> = table
+        .indexes
+        .iter()
+        .filter(|index| {
+            let backs_primary_key = index.is_unique
+                && index.columns.len() == primary_key.len()
+                && index
+                    .columns
+                    .iter()
+                    .all(|col| primary_key.contains(&col.as_str()));
+            index.name != "PRIMARY" && !index.columns.is_empty() && !backs_primary_key
+        })
+        .collect();
+    // Drivers collect indexes through hash maps, so their order is not stable
+    indexes.sort_by(|a, b| a.name.cmp(&b.name));
+    for index in indexes {
+        let columns: Vec<&str> = index.columns.iter().map(String::as_str).collect();
+        ddl.push_str(&format!(
+            "\nCREATE {}INDEX {} ON {} ({});\n",
+            if index.is_unique { "UNIQUE " } else { "" },
+            builder.quote_identifier(&index.name),
+            table_name,
+            quote_list(&columns)
+        ));
+    }
+
+    Ok(ddl)
+}
+
+/// `DROP TABLE IF EXISTS` for a table about to be recreated. PostgreSQL refuses to drop
+/// a table that other tables still reference, so the drop cascades there.
+pub fn drop_table_statement(db_type: &DatabaseType, table_name: &str) -> String {
+    let cascade = if *db_type == DatabaseType::PostgreSQL {
+        " CASCADE"
+    } else {
+        ""
+    };
+    format!("DROP TABLE IF EXISTS {}{}", table_name, cascade)
+}
+
+/// SQL Server statement allowing or refusing explicit values for a table's identity
+/// column. Only one table of a session can allow them at a time.
+pub fn identity_insert_statement(table_name: &str, enabled: bool) -> String {
+    format!(
+        "SET IDENTITY_INSERT {} {}",
+        table_name,
+        if enabled { "ON" } else { "OFF" }
+    )
+}
+
+/// PostgreSQL statement moving the sequence of a serial or identity column past the
+/// largest value loaded into it, so later inserts do not collide with loaded rows
+pub fn reset_sequence_statement(table_name: &str, column: &str) -> String {
+    let literal = |text: &str| format!("'{}'", text.replace('\'', "''"));
+    let builder = get_crud_query_builder(&DatabaseType::PostgreSQL);
+    format!(
+        "SELECT setval(pg_get_serial_sequence({}, {}), COALESCE(MAX({}), 0) + 1, false) FROM {}",
+        literal(table_name),
+        literal(column),
+        builder.quote_identifier(column),
+        table_name
+    )
+}
+
+fn column_definition(
+    db_type: &DatabaseType,
+    builder: &dyn CRUDQueryBuilder,
+    col: &Column,
+) -> String {
+    let name = builder.quote_identifier(&col.name);
+
+    // PostgreSQL auto-increment columns draw from a sequence the serial types create
+    if *db_type == DatabaseType::PostgreSQL && col.is_auto_increment {
+        let serial = match col.data_type.to_lowercase().as_str() {
+            "int2" | "smallint" => Some("smallserial"),
+            "int4" | "integer" | "int" => Some("serial"),
+            "int8" | "bigint" => Some("bigserial"),
+            _ => None,
+        };
+        if let Some(serial) = serial {
+            return format!("{} {}", name, serial);
+        }
+    }
+
+    let mut definition = format!("{} {}", name, col.data_type);
+    if *db_type == DatabaseType::MSSQL && col.is_auto_increment {
+        definition.push_str(" IDENTITY(1,1)");
+    }
+    if !col.nullable {
+        definition.push_str(" NOT NULL");
+    }
+    if let Some(default) = col.default_value.as_deref() {
+        definition.push_str(" DEFAULT ");
+        definition.push_str(&default_expression(db_type, col, default));
+    }
+    if *db_type == DatabaseType::MySQL && col.is_auto_increment {
+        definition.push_str(" AUTO_INCREMENT");
+    }
+    definition
+}
+
+/// MySQL reports defaults as bare values, literal or not. Expression defaults carry the
+/// DEFAULT_GENERATED flag and go back in parentheses, except the current time functions
+/// that temporal columns take bare; servers before 8.0 do not flag those. Numbers stay
+/// bare outside text columns, other values are quoted. PostgreSQL and SQL Server
+/// already report defaults as expressions.
+fn default_expression(db_type: &DatabaseType, col: &Column, default: &str) -> String {
+    if *db_type != DatabaseType::MySQL {
+        return default.to_string();
+    }
+    let upper = default.to_uppercase();
+    let base_type = col.data_type.to_lowercase();
+    let temporal = ["timestamp", "datetime", "date", "time"]
+        .iter()
+        .any(|prefix| base_type.starts_with(prefix));
+    let current_time = ["CURRENT_TIMESTAMP", "CURRENT_DATE", "CURRENT_TIME", "NOW("]
+        .iter()
+        .any(|prefix| upper.starts_with(prefix));
+    let bare = (temporal && current_time)
+        || (base_type.starts_with("bit") && default.starts_with("b'"))
+        || (default.parse::<f64>().is_ok() && !base_type.contains("char"));
+    if col.default_is_expression && !(temporal && current_time) {
+        format!("({})", default)
+    } else if bare {
+        default.to_string()
+    } else {
+        format!("'{}'", default.replace('\'', "''"))
+    }
+}
+
+/// Foreign keys arrive one row per column; put the columns of each constraint together,
+/// keeping the order constraints first appear in
+fn group_foreign_keys(foreign_keys: &[ForeignKey]) -> Vec<Vec<&ForeignKey>> {
+    let mut groups: Vec<Vec<&ForeignKey>> = Vec::new();
+    for fk in foreign_keys {
+        match groups.iter_mut().find(|group| group[0].name == fk.name) {
+            Some(group) => group.push(fk),
+            None => groups.push(vec![fk]),
+        }
+    }
+    groups
+}
+
+/// SQL for a referential action as reported by the driver (`SET_NULL`, `SET NULL`, ...).
+/// `None` for the default action, which needs no clause.
+fn referential_action(db_type: &DatabaseType, rule: Option<&str>) -> Option<String> {
+    let action = rule?.trim().replace('_', " ").to_uppercase();
+    match action.as_str() {
+        "" | "NO ACTION" => None,
+        // SQL Server has no RESTRICT; its NO ACTION behaves the same way
+        "RESTRICT" if *db_type == DatabaseType::MSSQL => None,
+        _ => Some(action),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::mysql::MySQLQueryBuilder;
+    use crate::models::schema::{test_column as column, Index};
+
+    fn foreign_key(column: &str, referenced_column: &str) -> ForeignKey {
+        ForeignKey {
+            name: "fk_line_order".to_string(),
+            column: column.to_string(),
+            referenced_table: "orders".to_string(),
+            referenced_column: referenced_column.to_string(),
+            owner: None,
+            ref_object_type: Some("TABLE".to_string()),
+            on_delete: Some("CASCADE".to_string()),
+            on_update: Some("NO ACTION".to_string()),
+        }
+    }
+
+    fn order_lines() -> TableSchema {
+        let mut id = column("id", "int", false, None);
+        id.is_primary_key = true;
+        id.is_auto_increment = true;
+        TableSchema {
+            table_name: "order_lines".to_string(),
+            columns: vec![
+                id,
+                column("order_id", "int", false, None),
+                column("region", "varchar(8)", false, None),
+                column("status", "varchar(16)", true, Some("new")),
+            ],
+            indexes: vec![
+                Index {
+                    name: "PRIMARY".to_string(),
+                    columns: vec!["id".to_string()],
+                    is_unique: true,
+                    index_type: None,
+                    ascending: Some(true),
+                    nullable: None,
+                    extra: None,
+                },
+                Index {
+                    name: "idx_status".to_string(),
+                    columns: vec!["status".to_string()],
+                    is_unique: false,
+                    index_type: None,
+                    ascending: Some(true),
+                    nullable: None,
+                    extra: None,
+                },
+            ],
+            foreign_keys: vec![
+                foreign_key("order_id", "id"),
+                foreign_key("region", "region"),
+            ],
+            has_triggers: false,
+        }
+    }
+
+    #[test]
+    fn test_mysql_ddl_declares_keys_and_quotes_text_defaults() {
+        let ddl = create_table_ddl(&DatabaseType::MySQL, None, &order_lines()).unwrap();
+        assert_eq!(
+            ddl,
+            "CREATE TABLE `order_lines` (\n    \
+             `id` int NOT NULL AUTO_INCREMENT,\n    \
+             `order_id` int NOT NULL,\n    \
+             `region` varchar(8) NOT NULL,\n    \
+             `status` varchar(16) DEFAULT 'new',\n    \
+             PRIMARY KEY (`id`),\n    \
+             CONSTRAINT `fk_line_order` FOREIGN KEY (`order_id`, `region`) REFERENCES `orders` (`id`, `region`) ON DELETE CASCADE\n\
+             );\n\n\
+             CREATE INDEX `idx_status` ON `order_lines` (`status`);\n"
+        );
+    }
+
+    #[test]
+    fn test_mysql_defaults_follow_the_expression_flag_and_column_type() {
+        let mut uuid = column("token", "varchar(36)", false, Some("uuid()"));
+        uuid.default_is_expression = true;
+        let created = column("created", "datetime", false, Some("CURRENT_TIMESTAMP"));
+        let label = column("label", "varchar(16)", true, Some("f(x)"));
+        let code = column("code", "char(4)", true, Some("0042"));
+        let amount = column("amount", "decimal(10,2)", true, Some("1.50"));
+
+        let definition =
+            |col: &Column| column_definition(&DatabaseType::MySQL, &MySQLQueryBuilder, col);
+        assert_eq!(
+            definition(&uuid),
+            "`token` varchar(36) NOT NULL DEFAULT (uuid())"
+        );
+        assert_eq!(
+            definition(&created),
+            "`created` datetime NOT NULL DEFAULT CURRENT_TIMESTAMP"
+        );
+        assert_eq!(definition(&label), "`label` varchar(16) DEFAULT 'f(x)'");
+        assert_eq!(definition(&code), "`code` char(4) DEFAULT '0042'");
+        assert_eq!(definition(&amount), "`amount` decimal(10,2) DEFAULT 1.50");
+    }
+}
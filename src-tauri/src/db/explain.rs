@@ -0,0 +1,561 @@
+use crate::models::explain::{ExecutionPlan, PlanNode};
+use anyhow::{anyhow, Result};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Parse the output of PostgreSQL `EXPLAIN (FORMAT JSON ...)`
+pub fn parse_postgres_plan(raw: &str, analyzed: bool) -> Result<ExecutionPlan> {
+    let value: Value = serde_json::from_str(raw)?;
+    let top = value
+        .get(0)
+        .ok_or_else(|| anyhow!("EXPLAIN returned an empty plan"))?;
+    let plan = top
+        .get("Plan")
+        .ok_or_else(|| anyhow!("EXPLAIN output has no \"Plan\""))?;
+
+    Ok(ExecutionPlan {
+        root: postgres_node(plan),
+        analyzed,
+        planning_time_ms: top.get("Planning Time").and_then(number),
+        execution_time_ms: top.get("Execution Time").and_then(number),
+        format: "json".to_string(),
+        raw: raw.to_string(),
+    })
+}
+
+fn postgres_node(plan: &Value) -> PlanNode {
+    const COMMON_KEYS: &[&str] = &[
+        "Node Type",
+        "Relation Name",
+        "Schema",
+        "Index Name",
+        "Plan Rows",
+        "Actual Rows",
+        "Total Cost",
+        "Actual Total Time",
+        "Actual Loops",
+        "Plans",
+    ];
+
+    let mut node = PlanNode::new(plan.get("Node Type").and_then(Value::as_str).unwrap_or("?"));
+    node.relation = plan
+        .get("Relation Name")
+        .and_then(Value::as_str)
+        .map(
+            |relation| match plan.get("Schema").and_then(Value::as_str) {
+                Some(schema) => format!("{}.{}", schema, relation),
+                None => relation.to_string(),
+            },
+        );
+    node.index = plan
+        .get("Index Name")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    node.estimated_rows = plan.get("Plan Rows").and_then(number);
+    node.actual_rows = plan.get("Actual Rows").and_then(number);
+    node.estimated_cost = plan.get("Total Cost").and_then(number);
+    node.actual_time_ms = plan.get("Actual Total Time").and_then(number);
+    node.loops = plan.get("Actual Loops").and_then(number);
+
+    if let Some(fields) = plan.as_object() {
+        node.details = fields
+            .iter()
+            .filter(|(key, _)| !COMMON_KEYS.contains(&key.as_str()))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+    }
+    node.children = plan
+        .get("Plans")
+        .and_then(Value::as_array)
+        .map(|plans| plans.iter().map(postgres_node).collect())
+        .unwrap_or_default();
+    node
+}
+
+/// Parse the output of MySQL `EXPLAIN FORMAT=JSON`
+pub fn parse_mysql_json_plan(raw: &str) -> Result<ExecutionPlan> {
+    let value: Value = serde_json::from_str(raw)?;
+    let mut roots = mysql_nodes(&value);
+    let root = match roots.len() {
+        0 => return Err(anyhow!("EXPLAIN output has no query block")),
+        1 => roots.remove(0),
+        _ => {
+            let mut root = PlanNode::new("Query");
+            root.children = roots;
+            root
+        }
+    };
+
+    Ok(ExecutionPlan {
+        root,
+        analyzed: false,
+        planning_time_ms: None,
+        execution_time_ms: None,
+        format: "json".to_string(),
+        raw: raw.to_string(),
+    })
+}
+
+/// Plan nodes for every operation found in a MySQL JSON plan fragment
+fn mysql_nodes(value: &Value) -> Vec<PlanNode> {
+    let Some(fields) = value.as_object() else {
+        return value
+            .as_array()
+            .map(|items| items.iter().flat_map(mysql_nodes).collect())
+            .unwrap_or_default();
+    };
+
+    let mut nodes = Vec::new();
+    for (key, field) in fields {
+        match key.as_str() {
+            "table" => nodes.push(mysql_table_node(field)),
+            "nested_loop" => {
+                let mut node = PlanNode::new("Nested Loop");
+                node.children = mysql_nodes(field);
+                nodes.push(node);
+            }
+            "query_block"
+            | "ordering_operation"
+            | "grouping_operation"
+            | "duplicates_removal"
+            | "windowing"
+            | "union_result"
+            | "materialized_from_subquery" => {
+                let mut node = PlanNode::new(title_case(key));
+                node.estimated_cost = mysql_cost(field, "query_cost");
+                node.details = mysql_scalars(field);
+                node.children = mysql_nodes(field);
+                nodes.push(node);
+            }
+            // Lists of nested query blocks
+            "query_specifications"
+            | "attached_subqueries"
+            | "optimized_away_subqueries"
+            | "order_by_subqueries"
+            | "group_by_subqueries"
+            | "having_subqueries"
+            | "select_list_subqueries"
+            | "update_value_subqueries" => nodes.extend(mysql_nodes(field)),
+            _ => {}
+        }
+    }
+    nodes
+}
+
+fn mysql_table_node(table: &Value) -> PlanNode {
+    let access_type = table
+        .get("access_type")
+        .and_then(Value::as_str)
+        .unwrap_or("?");
+    let mut node = PlanNode::new(format!("Table Access ({})", access_type));
+    node.relation = table
+        .get("table_name")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    node.index = table.get("key").and_then(Value::as_str).map(str::to_string);
+    node.estimated_rows = table.get("rows_examined_per_scan").and_then(number);
+    node.estimated_cost = mysql_cost(table, "prefix_cost");
+    node.details = mysql_scalars(table);
+    node.children = mysql_nodes(table);
+    node
+}
+
+fn mysql_cost(value: &Value, key: &str) -> Option<f64> {
+    value
+        .get("cost_info")
+        .and_then(|c| c.get(key))
+        .and_then(number)
+}
+
+/// Scalar and list-of-scalar attributes of a MySQL plan object, kept as node details
+fn mysql_scalars(value: &Value) -> std::collections::BTreeMap<String, Value> {
+    value
+        .as_object()
+        .map(|fields| {
+            fields
+                .iter()
+                .filter(|(_, v)| match v {
+                    Value::Object(_) => false,
+                    Value::Array(items) => items.iter().all(|i| !i.is_object()),
+                    _ => true,
+                })
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Parse the TREE output of MySQL `EXPLAIN ANALYZE`, one operator per line indented
+/// by four spaces per level:
+/// `-> Table scan on t  (cost=0.55 rows=3) (actual time=0.02..0.03 rows=3 loops=1)`
+pub fn parse_mysql_tree_plan(raw: &str) -> Result<ExecutionPlan> {
+    // Nodes whose children are still being read, with their indentation
+    let mut stack: Vec<(usize, PlanNode)> = Vec::new();
+    let mut roots: Vec<PlanNode> = Vec::new();
+
+    for line in raw.lines() {
+        let trimmed = line.trim_start();
+        let Some(text) = trimmed.strip_prefix("-> ") else {
+            continue;
+        };
+        let indent = line.len() - trimmed.len();
+
+        while stack.last().is_some_and(|(depth, _)| *depth >= indent) {
+            let (_, done) = stack.pop().unwrap();
+            attach(&mut stack, &mut roots, done);
+        }
+        stack.push((indent, mysql_tree_node(text)));
+    }
+    while let Some((_, done)) = stack.pop() {
+        attach(&mut stack, &mut roots, done);
+    }
+
+    if roots.is_empty() {
+        return Err(anyhow!("EXPLAIN ANALYZE returned no plan"));
+    }
+    let root = if roots.len() == 1 {
+        roots.remove(0)
+    } else {
+        let mut root = PlanNode::new("Query");
+        root.children = roots;
+        root
+    };
+
+    Ok(ExecutionPlan {
+        execution_time_ms: root.actual_time_ms,
+        root,
+        analyzed: true,
+        planning_time_ms: None,
+        format: "tree".to_string(),
+        raw: raw.to_string(),
+    })
+}
+
+fn attach(stack: &mut [(usize, PlanNode)], roots: &mut Vec<PlanNode>, node: PlanNode) {
+    match stack.last_mut() {
+        Some((_, parent)) => parent.children.push(node),
+        None => roots.push(node),
+    }
+}
+
+fn mysql_tree_node(text: &str) -> PlanNode {
+    let (label, stats) = match text.find("  (") {
+        Some(pos) => (&text[..pos], &text[pos..]),
+        None => (text, ""),
+    };
+
+    // "Index lookup on t using idx (a = 1)" or "Filter: (t.a > 1)"
+    let mut node = match label.split_once(": ") {
+        Some((kind, detail)) => {
+            let mut node = PlanNode::new(kind);
+            node.details
+                .insert("condition".to_string(), Value::String(detail.to_string()));
+            node
+        }
+        None => match label.split_once(" on ") {
+            Some((kind, target)) => {
+                let mut node = PlanNode::new(kind);
+                let mut words = target.split_whitespace();
+                node.relation = words.next().map(str::to_string);
+                if words.next() == Some("using") {
+                    node.index = words.next().map(str::to_string);
+                }
+                node
+            }
+            None => PlanNode::new(label),
+        },
+    };
+
+    let values = tree_stats(stats);
+    node.estimated_cost = values.get("cost").copied();
+    node.estimated_rows = values.get("rows").copied();
+    node.actual_rows = values.get("actual rows").copied();
+    node.actual_time_ms = values.get("actual time").copied();
+    node.loops = values.get("loops").copied();
+    node
+}
+
+/// Figures from "(cost=0.55 rows=3) (actual time=0.02..0.03 rows=3 loops=1)". Figures
+/// in the actual group are prefixed with "actual "; a time range keeps its end.
+fn tree_stats(stats: &str) -> HashMap<String, f64> {
+    let mut values = HashMap::new();
+    for group in stats.split('(').skip(1) {
+        let group = group.split(')').next().unwrap_or("");
+        let actual = group.starts_with("actual ");
+        for pair in group.trim_start_matches("actual ").split_whitespace() {
+            let Some((key, value)) = pair.split_once('=') else {
+                continue;
+            };
+            let value = value.rsplit("..").next().unwrap_or(value);
+            if let Ok(value) = value.parse::<f64>() {
+                let key = match (actual, key) {
+                    (true, "time") => "actual time".to_string(),
+                    (true, "rows") => "actual rows".to_string(),
+                    _ => key.to_string(),
+                };
+                values.insert(key, value);
+            }
+        }
+    }
+    values
+}
+
+/// Parse SQL Server showplan XML (`SET SHOWPLAN_XML` / `SET STATISTICS XML`). Each
+/// statement of the batch becomes a node; `RelOp` elements become its operators.
+pub fn parse_mssql_showplan(raw: &str, analyzed: bool) -> Result<ExecutionPlan> {
+    let mut reader = Reader::from_str(raw);
+    reader.config_mut().trim_text(true);
+
+    let mut statements: Vec<PlanNode> = Vec::new();
+    // Open StmtSimple / RelOp elements, innermost last
+    let mut stack: Vec<PlanNode> = Vec::new();
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(element) => match element.local_name().as_ref() {
+                b"StmtSimple" => stack.push(mssql_statement_node(&element)?),
+                b"RelOp" => stack.push(mssql_relop_node(&element)?),
+                _ => mssql_apply_element(&element, stack.last_mut())?,
+            },
+            Event::Empty(element) => match element.local_name().as_ref() {
+                b"StmtSimple" => statements.push(mssql_statement_node(&element)?),
+                _ => mssql_apply_element(&element, stack.last_mut())?,
+            },
+            Event::End(element) => {
+                if matches!(element.local_name().as_ref(), b"StmtSimple" | b"RelOp") {
+                    if let Some(done) = stack.pop() {
+                        match stack.last_mut() {
+                            Some(parent) => parent.children.push(done),
+                            None => statements.push(done),
+                        }
+                    }
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    let root = match statements.len() {
+        0 => return Err(anyhow!("Showplan XML contains no statements")),
+        1 => statements.remove(0),
+        _ => {
+            let mut root = PlanNode::new("Batch");
+            root.estimated_cost = statements
+                .iter()
+                .filter_map(|s| s.estimated_cost)
+                .reduce(|a, b| a + b);
+            root.children = statements;
+            root
+        }
+    };
+
+    Ok(ExecutionPlan {
+        root,
+        analyzed,
+        planning_time_ms: None,
+        execution_time_ms: None,
+        format: "xml".to_string(),
+        raw: raw.to_string(),
+    })
+}
+
+fn xml_attributes(element: &BytesStart) -> Result<HashMap<String, String>> {
+    let mut attributes = HashMap::new();
+    for attribute in element.attributes() {
+        let attribute = attribute?;
+        attributes.insert(
+            String::from_utf8_lossy(attribute.key.local_name().as_ref()).to_string(),
+            attribute.unescape_value()?.to_string(),
+        );
+    }
+    Ok(attributes)
+}
+
+fn mssql_statement_node(element: &BytesStart) -> Result<PlanNode> {
+    let attributes = xml_attributes(element)?;
+    let mut node = PlanNode::new(
+        attributes
+            .get("StatementType")
+            .cloned()
+            .unwrap_or_else(|| "STATEMENT".to_string()),
+    );
+    node.estimated_rows = attributes
+        .get("StatementEstRows")
+        .and_then(|v| v.parse().ok());
+    node.estimated_cost = attributes
+        .get("StatementSubTreeCost")
+        .and_then(|v| v.parse().ok());
+    if let Some(text) = attributes.get("StatementText") {
+        node.details.insert(
+            "statement".to_string(),
+            Value::String(text.trim().to_string()),
+        );
+    }
+    Ok(node)
+}
+
+fn mssql_relop_node(element: &BytesStart) -> Result<PlanNode> {
+    let attributes = xml_attributes(element)?;
+    let physical = attributes.get("PhysicalOp").cloned().unwrap_or_default();
+    let mut node = PlanNode::new(if physical.is_empty() {
+        "?".to_string()
+    } else {
+        physical
+    });
+    node.estimated_rows = attributes.get("EstimateRows").and_then(|v| v.parse().ok());
+    node.estimated_cost = attributes
+        .get("EstimatedTotalSubtreeCost")
+        .and_then(|v| v.parse().ok());
+    for key in ["LogicalOp", "Parallel", "EstimatedExecutionMode"] {
+        if let Some(value) = attributes.get(key) {
+            node.details
+                .insert(key.to_string(), Value::String(value.clone()));
+        }
+    }
+    Ok(node)
+}
+
+/// Fold details of child elements into the operator they belong to
+fn mssql_apply_element(element: &BytesStart, node: Option<&mut PlanNode>) -> Result<()> {
+    let Some(node) = node else {
+        return Ok(());
+    };
+
+    match element.local_name().as_ref() {
+        // The first Object is the operator's own table; later ones belong to predicates
+        b"Object" if node.relation.is_none() => {
+            let attributes = xml_attributes(element)?;
+            let unquote = |name: &String| name.trim_matches(['[', ']']).to_string();
+            node.relation = attributes
+                .get("Table")
+                .map(|table| match attributes.get("Schema") {
+                    Some(schema) => format!("{}.{}", unquote(schema), unquote(table)),
+                    None => unquote(table),
+                });
+            node.index = attributes.get("Index").map(unquote);
+        }
+        // One entry per thread: rows and executions add up, time is the slowest thread
+        b"RunTimeCountersPerThread" => {
+            let attributes = xml_attributes(element)?;
+            let get = |key: &str| attributes.get(key).and_then(|v| v.parse::<f64>().ok());
+            if let Some(rows) = get("ActualRows") {
+                node.actual_rows = Some(node.actual_rows.unwrap_or(0.0) + rows);
+            }
+            if let Some(executions) = get("ActualExecutions") {
+                node.loops = Some(node.loops.unwrap_or(0.0) + executions);
+            }
+            if let Some(elapsed) = get("ActualElapsedms") {
+                node.actual_time_ms = Some(node.actual_time_ms.unwrap_or(0.0).max(elapsed));
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Numbers in plans may be JSON numbers or numeric strings (MySQL)
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+/// "ordering_operation" -> "Ordering Operation"
+fn title_case(key: &str) -> String {
+    key.split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_postgres_json_plan() {
+        let raw = r#"[{"Plan": {"Node Type": "Nested Loop", "Join Type": "Inner",
+            "Total Cost": 12.5, "Plan Rows": 10, "Actual Rows": 8, "Actual Total Time": 0.42,
+            "Actual Loops": 1, "Plans": [
+                {"Node Type": "Index Scan", "Relation Name": "orders", "Schema": "public",
+                 "Index Name": "orders_pkey", "Total Cost": 4.2, "Plan Rows": 1}
+            ]}, "Planning Time": 0.1, "Execution Time": 0.5}]"#;
+
+        let plan = parse_postgres_plan(raw, true).unwrap();
+        assert_eq!(plan.root.node_type, "Nested Loop");
+        assert_eq!(plan.root.actual_rows, Some(8.0));
+        assert_eq!(
+            plan.root.details.get("Join Type"),
+            Some(&Value::from("Inner"))
+        );
+        assert_eq!(plan.execution_time_ms, Some(0.5));
+        let scan = &plan.root.children[0];
+        assert_eq!(scan.relation.as_deref(), Some("public.orders"));
+        assert_eq!(scan.index.as_deref(), Some("orders_pkey"));
+    }
+
+    #[test]
+    fn test_parses_mysql_json_and_tree_plans() {
+        let raw = r#"{"query_block": {"select_id": 1, "cost_info": {"query_cost": "2.45"},
+            "nested_loop": [
+                {"table": {"table_name": "o", "access_type": "ALL", "rows_examined_per_scan": 5,
+                           "cost_info": {"prefix_cost": "0.75"}}},
+                {"table": {"table_name": "c", "access_type": "eq_ref", "key": "PRIMARY",
+                           "rows_examined_per_scan": 1, "cost_info": {"prefix_cost": "2.45"}}}
+            ]}}"#;
+        let plan = parse_mysql_json_plan(raw).unwrap();
+        assert_eq!(plan.root.node_type, "Query Block");
+        assert_eq!(plan.root.estimated_cost, Some(2.45));
+        let join = &plan.root.children[0];
+        assert_eq!(join.node_type, "Nested Loop");
+        assert_eq!(join.children[1].index.as_deref(), Some("PRIMARY"));
+
+        let tree = "-> Filter: (o.total > 10)  (cost=0.75 rows=1.67) (actual time=0.05..0.07 rows=2 loops=1)\n    -> Index range scan on o using idx_total  (cost=0.75 rows=5) (actual time=0.04..0.06 rows=5 loops=1)\n";
+        let plan = parse_mysql_tree_plan(tree).unwrap();
+        assert_eq!(plan.root.node_type, "Filter");
+        assert_eq!(plan.root.actual_rows, Some(2.0));
+        assert_eq!(plan.root.actual_time_ms, Some(0.07));
+        let scan = &plan.root.children[0];
+        assert_eq!(scan.node_type, "Index range scan");
+        assert_eq!(scan.relation.as_deref(), Some("o"));
+        assert_eq!(scan.index.as_deref(), Some("idx_total"));
+        assert_eq!(scan.estimated_rows, Some(5.0));
+    }
+
+    #[test]
+    fn test_parses_mssql_showplan_xml() {
+        let raw = r#"<ShowPlanXML xmlns="http://schemas.microsoft.com/sqlserver/2004/07/showplan">
+            <BatchSequence><Batch><Statements>
+            <StmtSimple StatementText="SELECT * FROM dbo.Orders WHERE Id = 1" StatementType="SELECT" StatementSubTreeCost="0.0032" StatementEstRows="1">
+              <QueryPlan>
+                <RelOp PhysicalOp="Clustered Index Seek" LogicalOp="Clustered Index Seek" EstimateRows="1" EstimatedTotalSubtreeCost="0.0032">
+                  <RunTimeInformation>
+                    <RunTimeCountersPerThread Thread="0" ActualRows="1" ActualExecutions="1" ActualElapsedms="0" />
+                  </RunTimeInformation>
+                  <IndexScan><Object Database="[shop]" Schema="[dbo]" Table="[Orders]" Index="[PK_Orders]" /></IndexScan>
+                </RelOp>
+              </QueryPlan>
+            </StmtSimple>
+            </Statements></Batch></BatchSequence></ShowPlanXML>"#;
+
+        let plan = parse_mssql_showplan(raw, true).unwrap();
+        assert_eq!(plan.root.node_type, "SELECT");
+        let seek = &plan.root.children[0];
+        assert_eq!(seek.node_type, "Clustered Index Seek");
+        assert_eq!(seek.relation.as_deref(), Some("dbo.Orders"));
+        assert_eq!(seek.index.as_deref(), Some("PK_Orders"));
+        assert_eq!(seek.actual_rows, Some(1.0));
+        assert_eq!(seek.loops, Some(1.0));
+    }
+}
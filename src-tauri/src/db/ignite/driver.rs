@@ -1,3 +1,4 @@
+use crate::db::bound_query::BoundQuery;
 use crate::db::traits::DatabaseConnection;
 use crate::models::{connection::*, query_result::*, schema::*};
 use anyhow::{anyhow, Result};
@@ -31,6 +32,8 @@ pub struct IpcRequest {
     pub table_name: Option<String>,
     pub limit: Option<u32>,
     pub offset: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub params: Option<Vec<serde_json::Value>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -219,6 +222,50 @@ impl IgniteConnection {
         Ok(())
     }
 
+    /// Run a SQL fields query through the bridge, passing bound arguments if any
+    async fn run_sql(
+        &self,
+        connection_id: &str,
+        query: &str,
+        params: Option<Vec<serde_json::Value>>,
+        start: Instant,
+    ) -> Result<QueryResult> {
+        let request = IpcRequest {
+            action: "query".to_string(),
+            connection_id: Some(connection_id.to_string()),
+            host: None,
+            port: None,
+            username: None,
+            password: None,
+            query: Some(query.to_string()),
+            cache_name: None,
+            table_name: None,
+            limit: None,
+            offset: None,
+            params,
+        };
+
+        let result = self.send_request(&request).await?;
+
+        if !result.success {
+            return Err(anyhow!(result
+                .message
+                .unwrap_or("Query failed".to_string())));
+        }
+
+        let br = result.result.ok_or_else(|| anyhow!("No result data"))?;
+
+        Ok(QueryResult {
+            columns: br.columns,
+            column_display_names: None,
+            column_types: None,
+            rows: br.rows,
+            rows_affected: br.rows_affected.map(|v| v as u64),
+            execution_time: start.elapsed().as_millis(),
+            final_query: br.final_query.or_else(|| Some(query.to_string())),
+        })
+    }
+
     async fn send_request(&self, request: &IpcRequest) -> Result<IpcResponse> {
         let pipe_path = get_pipe_path();
 
@@ -267,6 +314,7 @@ impl IgniteConnection {
             table_name: None,
             limit: None,
             offset: None,
+            params: None,
         };
 
         match self.send_request(&request).await {
@@ -335,6 +383,7 @@ impl DatabaseConnection for IgniteConnection {
             table_name: None,
             limit: None,
             offset: None,
+            params: None,
         };
 
         let result = self.send_request(&request).await?;
@@ -375,6 +424,7 @@ impl DatabaseConnection for IgniteConnection {
                 table_name: None,
                 limit: None,
                 offset: None,
+                params: None,
             };
 
             match self.send_request(&request).await {
@@ -429,6 +479,7 @@ impl DatabaseConnection for IgniteConnection {
             table_name: None,
             limit: None,
             offset: None,
+            params: None,
         };
 
         let result = self.send_request(&request).await?;
@@ -510,6 +561,7 @@ impl DatabaseConnection for IgniteConnection {
                 table_name: None,
                 limit: Some(limit),
                 offset: Some(offset),
+                params: None,
             };
 
             let result = self.send_request(&request).await?;
@@ -531,39 +583,33 @@ impl DatabaseConnection for IgniteConnection {
             });
         }
 
-        let request = IpcRequest {
-            action: "query".to_string(),
-            connection_id: Some(connection_id.clone()),
-            host: None,
-            port: None,
-            username: None,
-            password: None,
-            query: Some(query.to_string()),
-            cache_name: None,
-            table_name: None,
-            limit: None,
-            offset: None,
-        };
-
-        let result = self.send_request(&request).await?;
+        self.run_sql(connection_id, query, None, start).await
+    }
 
-        if !result.success {
-            return Err(anyhow!(result
-                .message
-                .unwrap_or("Query failed".to_string())));
+    async fn execute_query_bound(&mut self, query: &BoundQuery) -> Result<QueryResult> {
+        if query.params.is_empty() {
+            return self.execute_query(&query.sql).await;
         }
 
-        let br = result.result.ok_or_else(|| anyhow!("No result data"))?;
+        self.ensure_bridge_running().await?;
 
-        Ok(QueryResult {
-            columns: br.columns,
-            column_display_names: None,
-            column_types: None,
-            rows: br.rows,
-            rows_affected: br.rows_affected.map(|v| v as u64),
-            execution_time: start.elapsed().as_millis(),
-            final_query: br.final_query.or_else(|| Some(query.to_string())),
-        })
+        let start = Instant::now();
+        let connection_id = self
+            .connection_id
+            .as_ref()
+            .ok_or_else(|| anyhow!("Not connected to Ignite"))?;
+
+        self.run_sql(connection_id, &query.sql, Some(query.params.clone()), start)
+            .await
+    }
+
+    async fn execute_update_bound(&mut self, query: &BoundQuery) -> Result<u64> {
+        if query.params.is_empty() {
+            return self.execute_update(&query.sql).await;
+        }
+
+        let result = self.execute_query_bound(query).await?;
+        Ok(result.rows_affected.unwrap_or(0))
     }
 
     async fn get_databases(&mut self) -> Result<Vec<Database>> {
@@ -586,6 +632,7 @@ impl DatabaseConnection for IgniteConnection {
             table_name: None,
             limit: None,
             offset: None,
+            params: None,
         };
 
         let result = self.send_request(&request).await?;
@@ -624,6 +671,7 @@ impl DatabaseConnection for IgniteConnection {
             table_name: None,
             limit: None,
             offset: None,
+            params: None,
         };
 
         let result = self.send_request(&request).await?;
@@ -666,6 +714,7 @@ impl DatabaseConnection for IgniteConnection {
             table_name: Some(table.to_string()),
             limit: None,
             offset: None,
+            params: None,
         };
 
         let result = self.send_request(&request).await?;
@@ -690,10 +739,13 @@ impl DatabaseConnection for IgniteConnection {
                     default_value: c.default_value.map(|v| v.to_string()),
                     is_primary_key: c.is_primary_key.unwrap_or(false),
                     is_auto_increment: false,
+                    is_generated: false,
+                    default_is_expression: false,
                 })
                 .collect(),
             indexes: vec![],
             foreign_keys: vec![],
+            has_triggers: false,
         })
     }
 
@@ -1,6 +1,8 @@
+use crate::db::bound_query::{self, BoundQuery};
 use crate::db::mysql::query_builder::MySQLQueryBuilder;
 use crate::db::traits::{CRUDQueryBuilder, QueryBuilder};
 use crate::models::save_request::EditedRow;
+use crate::models::schema::TableSchema;
 use crate::models::table_request::*;
 use anyhow::Result;
 use std::collections::HashMap;
@@ -9,23 +11,36 @@ pub struct IgniteQueryBuilder;
 
 impl QueryBuilder for IgniteQueryBuilder {
     fn build_select_query(&self, request: &QueryRequest) -> Result<String> {
-        let table = self.format_table_name(request);
-        let mut query = format!("SELECT * FROM {}", table);
-
-        if !request.filters.is_empty() {
-            let where_clause = self.build_where_clause(&request.filters)?;
-            query.push_str(&format!(" WHERE {}", where_clause));
-        }
+        let where_clause = if request.filters.is_empty() {
+            None
+        } else {
+            Some(self.build_where_clause(&request.filters)?)
+        };
 
-        if !request.order_by.is_empty() {
-            let order_clause = self.build_order_by_clause(&request.order_by);
-            query.push_str(&format!(" ORDER BY {}", order_clause));
-        }
+        Ok(self.assemble_select(request, where_clause))
+    }
 
-        let pagination = self.build_pagination_clause(request.limit, request.offset);
-        query.push_str(&format!(" {}", pagination));
+    fn build_select_query_bound(
+        &self,
+        request: &QueryRequest,
+        table_schema: Option<&TableSchema>,
+    ) -> Result<BoundQuery> {
+        let mut params = Vec::new();
+        let where_clause = if request.filters.is_empty() {
+            None
+        } else {
+            Some(bound_query::build_where_clause(
+                self,
+                &request.filters,
+                table_schema,
+                &mut params,
+            )?)
+        };
 
-        Ok(query)
+        Ok(BoundQuery {
+            sql: self.assemble_select(request, where_clause),
+            params,
+        })
     }
 
     fn quote_identifier(&self, identifier: &str) -> String {
@@ -76,6 +91,27 @@ impl QueryBuilder for IgniteQueryBuilder {
     }
 }
 
+impl IgniteQueryBuilder {
+    fn assemble_select(&self, request: &QueryRequest, where_clause: Option<String>) -> String {
+        let table = self.format_table_name(request);
+        let mut query = format!("SELECT * FROM {}", table);
+
+        if let Some(where_clause) = where_clause {
+            query.push_str(&format!(" WHERE {}", where_clause));
+        }
+
+        if !request.order_by.is_empty() {
+            let order_clause = self.build_order_by_clause(&request.order_by);
+            query.push_str(&format!(" ORDER BY {}", order_clause));
+        }
+
+        let pagination = self.build_pagination_clause(request.limit, request.offset);
+        query.push_str(&format!(" {}", pagination));
+
+        query
+    }
+}
+
 impl CRUDQueryBuilder for IgniteQueryBuilder {
     fn build_insert_query(
         &self,
@@ -306,6 +342,41 @@ impl CRUDQueryBuilder for IgniteQueryBuilder {
         }
     }
 
+    fn build_insert_query_bound(
+        &self,
+        table: &str,
+        schema: Option<&str>,
+        row: &HashMap<String, serde_json::Value>,
+        table_schema: &TableSchema,
+    ) -> Result<BoundQuery> {
+        let table_name = self.crud_table_name(table, schema);
+        bound_query::build_insert(self, &table_name, row, table_schema)
+    }
+
+    fn build_update_query_bound(
+        &self,
+        table: &str,
+        schema: Option<&str>,
+        edited_row: &EditedRow,
+        primary_keys: &[&String],
+        table_schema: &TableSchema,
+    ) -> Result<BoundQuery> {
+        let table_name = self.crud_table_name(table, schema);
+        bound_query::build_update(self, &table_name, edited_row, primary_keys, table_schema)
+    }
+
+    fn build_delete_query_bound(
+        &self,
+        table: &str,
+        schema: Option<&str>,
+        row: &HashMap<String, serde_json::Value>,
+        primary_keys: &[&String],
+        table_schema: &TableSchema,
+    ) -> Result<BoundQuery> {
+        let table_name = self.crud_table_name(table, schema);
+        bound_query::build_delete(self, &table_name, row, primary_keys, table_schema)
+    }
+
     fn escape_sql_string(&self, s: &str) -> String {
         s.replace("'", "''")
     }
@@ -1,10 +1,16 @@
+pub mod bound_query;
+pub mod ddl;
+pub mod explain;
 pub mod ignite;
 pub mod mongodb;
 pub mod mssql;
 pub mod mysql;
 pub mod postgres;
 pub mod redis;
+pub mod sql_splitter;
 pub mod traits;
+pub mod transaction;
+pub mod type_mapping;
 
 // Re-export traits and factory functions for easy access
 pub use ignite::shutdown_bridge;
@@ -1,9 +1,11 @@
-use crate::db::traits::DatabaseConnection;
+use crate::db::traits::{
+    send_stream_event, stream_query_result, DatabaseConnection, QueryStreamSender,
+};
 use crate::models::{connection::*, query_result::*, schema::*};
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use mongodb::{
-    bson::{doc, Document},
+    bson::{doc, Bson, Document},
     options::ClientOptions,
     Client,
 };
@@ -24,6 +26,23 @@ impl MongoDBConnection {
     }
 }
 
+/// Convert a BSON value to JSON for the result grid
+fn bson_to_json(value: &Bson) -> serde_json::Value {
+    match value {
+        Bson::Double(v) => serde_json::json!(v),
+        Bson::String(v) => serde_json::json!(v),
+        Bson::Array(arr) => serde_json::to_value(arr).unwrap_or(serde_json::Value::Null),
+        Bson::Document(doc) => serde_json::to_value(doc).unwrap_or(serde_json::Value::Null),
+        Bson::Boolean(v) => serde_json::json!(v),
+        Bson::Null => serde_json::Value::Null,
+        Bson::Int32(v) => serde_json::json!(v),
+        Bson::Int64(v) => serde_json::json!(v),
+        Bson::ObjectId(oid) => serde_json::json!(oid.to_hex()),
+        Bson::DateTime(dt) => serde_json::json!(dt.to_string()),
+        _ => serde_json::json!(value.to_string()),
+    }
+}
+
 impl Default for MongoDBConnection {
     fn default() -> Self {
         Self::new()
@@ -156,7 +175,6 @@ impl DatabaseConnection for MongoDBConnection {
                 let mut cursor = collection.find(filter).await?;
 
                 use futures::stream::TryStreamExt;
-                use mongodb::bson::Bson;
 
                 while let Some(result) = cursor.try_next().await? {
                     let mut row_map = HashMap::new();
@@ -166,25 +184,7 @@ impl DatabaseConnection for MongoDBConnection {
                             columns.push(key.clone());
                         }
 
-                        let json_value = match value {
-                            Bson::Double(v) => serde_json::json!(v),
-                            Bson::String(v) => serde_json::json!(v),
-                            Bson::Array(arr) => {
-                                serde_json::to_value(arr).unwrap_or(serde_json::Value::Null)
-                            }
-                            Bson::Document(doc) => {
-                                serde_json::to_value(doc).unwrap_or(serde_json::Value::Null)
-                            }
-                            Bson::Boolean(v) => serde_json::json!(v),
-                            Bson::Null => serde_json::Value::Null,
-                            Bson::Int32(v) => serde_json::json!(v),
-                            Bson::Int64(v) => serde_json::json!(v),
-                            Bson::ObjectId(oid) => serde_json::json!(oid.to_hex()),
-                            Bson::DateTime(dt) => serde_json::json!(dt.to_string()),
-                            _ => serde_json::json!(value.to_string()),
-                        };
-
-                        row_map.insert(key.clone(), json_value);
+                        row_map.insert(key.clone(), bson_to_json(value));
                     }
                     rows.push(row_map);
                 }
@@ -246,6 +246,103 @@ impl DatabaseConnection for MongoDBConnection {
         })
     }
 
+    async fn execute_query_streaming(
+        &mut self,
+        query: &str,
+        batch_size: usize,
+        sink: &QueryStreamSender,
+    ) -> Result<u64> {
+        let query_doc: serde_json::Value =
+            serde_json::from_str(query).map_err(|e| anyhow!("Invalid JSON query: {}", e))?;
+
+        // Only find() has a cursor worth streaming; write operations return no rows
+        if query_doc["operation"].as_str().unwrap_or("find") != "find" {
+            let result = self.execute_query(query).await?;
+            return stream_query_result(result, batch_size, sink).await;
+        }
+
+        let client = self
+            .client
+            .as_ref()
+            .ok_or_else(|| anyhow!("Not connected"))?;
+        let db_name = query_doc["db"]
+            .as_str()
+            .or(self.current_database.as_deref())
+            .ok_or_else(|| anyhow!("Database name not specified"))?;
+        let collection_name = query_doc["collection"]
+            .as_str()
+            .ok_or_else(|| anyhow!("Collection name not specified"))?;
+
+        let filter: Document = if let Some(query_filter) = query_doc.get("query") {
+            serde_json::from_value(query_filter.clone())
+                .map_err(|e| anyhow!("Invalid filter: {}", e))?
+        } else {
+            doc! {}
+        };
+
+        use futures::stream::TryStreamExt;
+
+        let collection = client
+            .database(db_name)
+            .collection::<Document>(collection_name);
+        let mut cursor = collection.find(filter).await?;
+
+        let mut columns: Vec<String> = Vec::new();
+        let mut batch = Vec::with_capacity(batch_size);
+        let mut row_count = 0u64;
+
+        while let Some(document) = cursor.try_next().await? {
+            let mut row_map = HashMap::new();
+            let mut new_columns = false;
+
+            for (key, value) in document.iter() {
+                if columns.iter().all(|c| c != key) {
+                    columns.push(key.clone());
+                    new_columns = true;
+                }
+                row_map.insert(key.clone(), bson_to_json(value));
+            }
+
+            // Documents are schemaless, so announce fields as they first appear
+            if new_columns {
+                send_stream_event(
+                    sink,
+                    QueryStreamEvent::Columns {
+                        columns: columns.clone(),
+                        column_display_names: None,
+                        column_types: None,
+                    },
+                )
+                .await?;
+            }
+
+            batch.push(row_map);
+            row_count += 1;
+            if batch.len() >= batch_size {
+                let rows = std::mem::take(&mut batch);
+                send_stream_event(sink, QueryStreamEvent::Rows { rows }).await?;
+            }
+        }
+
+        if columns.is_empty() {
+            send_stream_event(
+                sink,
+                QueryStreamEvent::Columns {
+                    columns,
+                    column_display_names: None,
+                    column_types: None,
+                },
+            )
+            .await?;
+        }
+
+        if !batch.is_empty() {
+            send_stream_event(sink, QueryStreamEvent::Rows { rows: batch }).await?;
+        }
+
+        Ok(row_count)
+    }
+
     async fn get_databases(&mut self) -> Result<Vec<Database>> {
         let client = self
             .client
@@ -337,6 +434,8 @@ impl DatabaseConnection for MongoDBConnection {
                     default_value: None,
                     is_primary_key: name == "_id",
                     is_auto_increment: false,
+                    is_generated: false,
+                    default_is_expression: false,
                 }
             })
             .collect();
@@ -346,6 +445,7 @@ impl DatabaseConnection for MongoDBConnection {
             columns,
             indexes: vec![],
             foreign_keys: vec![],
+            has_triggers: false,
         })
     }
 
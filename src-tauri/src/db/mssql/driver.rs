@@ -1,34 +1,57 @@
+use crate::db::bound_query::BoundQuery;
+use crate::db::explain::parse_mssql_showplan;
 use crate::db::mssql::metadata_ops::MSSQLMetadataOps;
-use crate::db::mssql::type_converter::{row_value_to_json_typed, MssqlColType};
-use crate::db::traits::DatabaseConnection;
-use crate::models::{connection::*, query_result::*, schema::*};
+use crate::db::mssql::type_converter::{map_mssql_type, row_value_to_json_typed, MssqlColType};
+use crate::db::sql_splitter::SqlDialect;
+use crate::db::traits::{
+    send_stream_event, DatabaseConnection, KnownSessions, QueryStreamSender, SessionGuard,
+    SessionTracker,
+};
+use crate::db::transaction::{scratch_transaction_sql, SessionConn, TransactionCommand};
+use crate::models::{
+    connection::*,
+    explain::{ExecutionPlan, PlanNode},
+    query_result::*,
+    schema::*,
+};
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use bb8::Pool;
 use bb8_tiberius::ConnectionManager;
+use futures::TryStreamExt;
 use std::collections::HashMap;
-use std::time::Instant;
-use tiberius::{AuthMethod, Config};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tiberius::{AuthMethod, Config, QueryItem};
 
 pub struct MSSQLConnection {
     pool: Option<Pool<ConnectionManager>>,
+    session: SessionTracker,
+    known_sessions: KnownSessions,
+    /// Limit of the current timed command, applied as the session's LOCK_TIMEOUT
+    statement_timeout: Option<Duration>,
+    /// Session pinned by an open manual transaction
+    pinned: Option<PinnedClient>,
 }
 
 impl MSSQLConnection {
     pub fn new() -> Self {
-        Self { pool: None }
-    }
-}
-
-impl Default for MSSQLConnection {
-    fn default() -> Self {
-        Self::new()
+        Self {
+            pool: None,
+            session: SessionTracker::default(),
+            known_sessions: KnownSessions::default(),
+            statement_timeout: None,
+            pinned: None,
+        }
     }
-}
 
-#[async_trait]
-impl DatabaseConnection for MSSQLConnection {
-    async fn connect(&mut self, config: &ConnectionConfig) -> Result<()> {
+    /// Build the client pool. A single-session pool holds one physical connection that
+    /// is never recycled, so session state survives between statements.
+    async fn connect_pool(
+        &mut self,
+        config: &ConnectionConfig,
+        single_session: bool,
+    ) -> Result<()> {
         let mut tiberius_config = Config::new();
 
         tiberius_config.host(&config.host);
@@ -53,8 +76,19 @@ impl DatabaseConnection for MSSQLConnection {
         }
 
         let manager = ConnectionManager::new(tiberius_config);
-        let pool = Pool::builder()
-            .max_size(15)
+        let builder = if single_session {
+            // Its SPID is read once, whenever the pool opens it
+            Pool::builder()
+                .max_size(1)
+                .idle_timeout(None)
+                .max_lifetime(None)
+                .connection_customizer(Box::new(RecordSpid(
+                    self.known_sessions.single_connection(),
+                )))
+        } else {
+            Pool::builder().max_size(15)
+        };
+        let pool = builder
             .build(manager)
             .await
             .map_err(|e| anyhow!("Failed to create connection pool: {}", e))?;
@@ -62,70 +96,281 @@ impl DatabaseConnection for MSSQLConnection {
         self.pool = Some(pool);
         Ok(())
     }
+}
 
-    async fn disconnect(&mut self) -> Result<()> {
-        if let Some(pool) = self.pool.take() {
-            drop(pool);
-        }
+/// Records the SPID of each connection a single-session pool opens
+#[derive(Debug)]
+struct RecordSpid(Arc<Mutex<Option<i64>>>);
+
+#[async_trait]
+impl bb8::CustomizeConnection<bb8_tiberius::rt::Client, bb8_tiberius::Error> for RecordSpid {
+    async fn on_acquire(
+        &self,
+        conn: &mut bb8_tiberius::rt::Client,
+    ) -> std::result::Result<(), bb8_tiberius::Error> {
+        *self.0.lock().unwrap() = spid(conn).await?;
         Ok(())
     }
+}
 
-    async fn test_connection(&mut self) -> Result<bool> {
-        if let Some(pool) = &self.pool {
-            let mut conn = pool
-                .get()
-                .await
-                .map_err(|e| anyhow!("Failed to get connection from pool: {}", e))?;
-            conn.simple_query("SELECT 1").await?;
-            Ok(true)
-        } else {
-            Err(anyhow!("Not connected"))
+async fn spid(conn: &mut bb8_tiberius::rt::Client) -> tiberius::Result<Option<i64>> {
+    let row = conn
+        .simple_query("SELECT CAST(@@SPID AS SMALLINT)")
+        .await?
+        .into_row()
+        .await?;
+    Ok(row.and_then(|row| row.get::<i16, _>(0)).map(i64::from))
+}
+
+/// Publish the SPID of the connection a batch runs on so it can be killed, reading it
+/// from the server only when it is not known yet
+async fn track_session(
+    session: &SessionTracker,
+    known: &mut KnownSessions,
+    conn: &mut SessionConn<'_, PinnedClient>,
+) -> Result<SessionGuard> {
+    let pinned = conn.is_pinned();
+    let spid = match known.get(pinned) {
+        Some(spid) => spid,
+        None => {
+            let spid = spid(conn)
+                .await?
+                .ok_or_else(|| anyhow!("Failed to read session id"))?;
+            known.remember(pinned, spid);
+            spid
+        }
+    };
+    Ok(session.track(spid))
+}
+
+/// Set the session's LOCK_TIMEOUT for a timed batch, so a statement waiting on a lock
+/// fails on the server once the command's timeout passes. Returns the previous value to
+/// restore afterwards.
+async fn set_lock_timeout(
+    conn: &mut bb8_tiberius::rt::Client,
+    timeout: Option<Duration>,
+) -> Result<Option<i32>> {
+    let Some(timeout) = timeout else {
+        return Ok(None);
+    };
+    let sql = format!(
+        "SELECT @@LOCK_TIMEOUT; SET LOCK_TIMEOUT {}",
+        timeout.as_millis().min(i32::MAX as u128)
+    );
+    let previous = conn
+        .simple_query(sql)
+        .await?
+        .into_row()
+        .await?
+        .and_then(|row| row.get::<i32, _>(0))
+        .unwrap_or(-1);
+    Ok(Some(previous))
+}
+
+/// Put back the LOCK_TIMEOUT a timed batch replaced
+async fn restore_lock_timeout(conn: &mut bb8_tiberius::rt::Client, previous: Option<i32>) {
+    if let Some(previous) = previous {
+        let sql = format!("SET LOCK_TIMEOUT {}", previous);
+        if let Ok(stream) = conn.simple_query(sql).await {
+            let _ = stream.into_results().await;
         }
     }
+}
 
-    async fn execute_update(&mut self, query: &str) -> Result<u64> {
-        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("Not connected"))?;
-        let mut conn = pool
-            .get()
-            .await
-            .map_err(|e| anyhow!("Failed to get connection from pool: {}", e))?;
+/// Run a batch and collect every result set it produced
+async fn run_batch(conn: &mut bb8_tiberius::rt::Client, query: &str) -> Result<Vec<QueryResult>> {
+    let start = Instant::now();
 
-        // For DML statements (INSERT, UPDATE, DELETE), execute and get total result sets
-        let stream = conn.query(query, &[]).await?;
-        let mut total_affected: u64 = 0;
+    // Run as a plain batch so every result set comes back, each announced by its
+    // metadata even when it has no rows
+    let mut result_sets: Vec<(ResultColumns, Vec<HashMap<String, serde_json::Value>>)> = Vec::new();
+    let mut stream = conn.simple_query(query).await?;
+    while let Some(item) = stream.try_next().await? {
+        match item {
+            QueryItem::Metadata(meta) => {
+                result_sets.push((ResultColumns::from_columns(meta.columns()), Vec::new()));
+            }
+            QueryItem::Row(row) => {
+                if let Some((columns, rows)) = result_sets.last_mut() {
+                    rows.push(columns.row_to_map(&row));
+                }
+            }
+        }
+    }
+    drop(stream);
+    let execution_time = start.elapsed().as_millis();
+
+    if result_sets.is_empty() {
+        // The batch only changed data or printed messages. Row counts do not come
+        // through the stream, but the session keeps the last one.
+        let rows_affected = conn
+            .simple_query("SELECT CAST(@@ROWCOUNT AS BIGINT)")
+            .await?
+            .into_row()
+            .await?
+            .and_then(|row| row.get::<i64, _>(0))
+            .map(|count| count as u64);
+        return Ok(vec![ResultColumns::from_columns(&[]).into_query_result(
+            Vec::new(),
+            rows_affected,
+            execution_time,
+        )]);
+    }
 
-        // Process all result sets
-        if let Ok(results) = stream.into_results().await {
-            for result in results {
-                total_affected += result.len() as u64;
+    Ok(result_sets
+        .into_iter()
+        .map(|(columns, rows)| columns.into_query_result(rows, None, execution_time))
+        .collect())
+}
+
+/// Stream the rows of a query's first result set to the frontend in batches, returning
+/// how many were sent
+async fn stream_first_result(
+    conn: &mut bb8_tiberius::rt::Client,
+    query: &BoundQuery,
+    batch_size: usize,
+    sink: &QueryStreamSender,
+) -> Result<u64> {
+    let mut stream = bind_params(query).query(&mut *conn).await?;
+    let mut result_columns: Option<ResultColumns> = None;
+    let mut batch = Vec::with_capacity(batch_size);
+    let mut row_count = 0u64;
+
+    // Only the first result set is streamed, matching execute_query
+    while let Some(item) = stream.try_next().await? {
+        match item {
+            QueryItem::Metadata(meta) if meta.result_index() == 0 => {
+                let described = ResultColumns::from_columns(meta.columns());
+                send_stream_event(
+                    sink,
+                    QueryStreamEvent::Columns {
+                        columns: described.columns.clone(),
+                        column_display_names: Some(described.display_names.clone()),
+                        column_types: Some(described.column_types.clone()),
+                    },
+                )
+                .await?;
+                result_columns = Some(described);
             }
+            QueryItem::Row(row) if row.result_index() == 0 => {
+                let Some(described) = &result_columns else {
+                    continue;
+                };
+                batch.push(described.row_to_map(&row));
+                row_count += 1;
+                if batch.len() >= batch_size {
+                    let rows = std::mem::take(&mut batch);
+                    send_stream_event(sink, QueryStreamEvent::Rows { rows }).await?;
+                }
+            }
+            _ => {}
         }
+    }
 
-        Ok(total_affected)
+    if result_columns.is_none() {
+        send_stream_event(
+            sink,
+            QueryStreamEvent::Columns {
+                columns: Vec::new(),
+                column_display_names: None,
+                column_types: None,
+            },
+        )
+        .await?;
     }
 
-    async fn execute_query(&mut self, query: &str) -> Result<QueryResult> {
-        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("Not connected"))?;
+    if !batch.is_empty() {
+        send_stream_event(sink, QueryStreamEvent::Rows { rows: batch }).await?;
+    }
 
-        let mut conn = pool
-            .get()
-            .await
-            .map_err(|e| anyhow!("Failed to get connection from pool: {}", e))?;
-        let start = Instant::now();
+    Ok(row_count)
+}
 
-        let stream = conn.query(query, &[]).await?;
-        let rows = stream.into_first_result().await?;
-        let execution_time = start.elapsed().as_millis();
+/// Build a tiberius query with JSON parameters bound to @P1, @P2, ... Non-integer
+/// numbers are sent as strings so DECIMAL values keep their full precision.
+fn bind_params(query: &BoundQuery) -> tiberius::Query<'_> {
+    let mut q = tiberius::Query::new(query.sql.as_str());
+    for param in &query.params {
+        match param {
+            serde_json::Value::Null => q.bind(None::<String>),
+            serde_json::Value::Bool(b) => q.bind(*b),
+            serde_json::Value::Number(n) => match n.as_i64() {
+                Some(i) => q.bind(i),
+                None => q.bind(n.to_string()),
+            },
+            serde_json::Value::String(s) => q.bind(s.as_str()),
+            other => q.bind(other.to_string()),
+        }
+    }
+    q
+}
 
-        // Extract columns from first row if available, or create empty vec
-        let stmt_columns: Vec<_> = if !rows.is_empty() {
-            rows[0].columns().to_vec()
-        } else {
-            // For empty results, we need to query the metadata another way
-            // For now, return empty columns (this will be improved)
-            Vec::new()
-        };
+/// Pooled client that can outlive the borrow of the pool, so it can be pinned
+type PinnedClient = bb8::PooledConnection<'static, ConnectionManager>;
 
+/// Pick the session pinned by an open transaction, or check out a pooled connection
+async fn session_conn<'a>(
+    pool: &Pool<ConnectionManager>,
+    pinned: &'a mut Option<PinnedClient>,
+) -> Result<SessionConn<'a, PinnedClient>> {
+    match pinned {
+        Some(conn) => Ok(SessionConn::Pinned(conn)),
+        None => Ok(SessionConn::Pooled(pool.get_owned().await.map_err(
+            |e| anyhow!("Failed to get connection from pool: {}", e),
+        )?)),
+    }
+}
+
+/// Run a batch under SHOWPLAN_XML / STATISTICS XML and collect the plan documents.
+/// With STATISTICS XML the batch's own result sets come back too and are skipped.
+async fn collect_showplans(
+    conn: &mut bb8_tiberius::rt::Client,
+    query: &str,
+    analyze: bool,
+) -> Result<Vec<String>> {
+    let mut plans = Vec::new();
+    let mut is_plan_set = !analyze;
+    let mut stream = conn.simple_query(query).await?;
+    while let Some(item) = stream.try_next().await? {
+        match item {
+            QueryItem::Metadata(meta) => {
+                is_plan_set = !analyze
+                    || meta
+                        .columns()
+                        .first()
+                        .is_some_and(|c| c.name().contains("Showplan"));
+            }
+            QueryItem::Row(row) if is_plan_set => {
+                let xml = match row.try_get::<&str, _>(0) {
+                    Ok(text) => text.map(str::to_string),
+                    Err(_) => row
+                        .try_get::<&tiberius::xml::XmlData, _>(0)?
+                        .map(|xml| xml.to_string()),
+                };
+                plans.extend(xml);
+            }
+            QueryItem::Row(_) => {}
+        }
+    }
+    Ok(plans)
+}
+
+impl Default for MSSQLConnection {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Column metadata of a result set, computed once and reused for every row
+struct ResultColumns {
+    columns: Vec<String>,
+    display_names: Vec<String>,
+    column_types: HashMap<String, String>,
+    col_types: Vec<MssqlColType>,
+}
+
+impl ResultColumns {
+    fn from_columns(stmt_columns: &[tiberius::Column]) -> Self {
         let mut column_name_counts: HashMap<String, usize> = HashMap::new();
         let mut display_names = Vec::new();
         let columns: Vec<String> = stmt_columns
@@ -160,64 +405,127 @@ impl DatabaseConnection for MSSQLConnection {
                 } else {
                     format!("{}_{}", base_name, count)
                 };
-                let col_type = match type_name.as_str() {
-                    t if t.contains("VARCHAR")
-                        || t.contains("CHAR")
-                        || t.contains("TEXT")
-                        || t.contains("NVARCHAR")
-                        || t.contains("NCHAR")
-                        || t.contains("NTEXT") =>
-                    {
-                        MssqlColType::String
-                    }
-                    t if t.contains("BIGINT") || t.contains("INT8") => MssqlColType::Int64,
-                    t if t.contains("SMALLINT") || t.contains("INT2") => MssqlColType::Int16,
-                    t if t.contains("TINYINT") => MssqlColType::UInt8,
-                    t if t.contains("INT") || t.contains("INT4") => MssqlColType::Int32,
-                    t if t.contains("REAL") || t.contains("FLOAT4") => MssqlColType::Float32,
-                    t if t.contains("FLOAT") || t.contains("DOUBLE") || t.contains("FLOAT8") => {
-                        MssqlColType::Float64
-                    }
-                    t if t.contains("BIT") || t.contains("BOOL") => MssqlColType::Boolean,
-                    t if t.contains("UNIQUEIDENTIFIER") || t.contains("UUID") => MssqlColType::Uuid,
-                    t if t.contains("DATETIME")
-                        || t.contains("TIMESTAMP")
-                        || t.contains("SMALLDATETIME") =>
-                    {
-                        MssqlColType::DateTime
-                    }
-                    t if t.contains("DATE") => MssqlColType::Date,
-                    t if t.contains("TIME") => MssqlColType::Time,
-                    t if t.contains("BINARY") || t.contains("VARBINARY") || t.contains("IMAGE") => {
-                        MssqlColType::Binary
-                    }
-                    t if t.contains("DECIMAL")
-                        || t.contains("NUMERIC")
-                        || t.contains("MONEY")
-                        || t.contains("SMALLMONEY") =>
-                    {
-                        MssqlColType::Decimal
-                    }
-                    _ => MssqlColType::Unknown,
-                };
+                let col_type = map_mssql_type(&type_name);
                 column_types.insert(col_name, type_name);
                 col_type
             })
             .collect();
 
-        let mut result_rows = Vec::with_capacity(rows.len());
+        Self {
+            columns,
+            display_names,
+            column_types,
+            col_types: col_type_map,
+        }
+    }
 
-        // Process rows only if there are any (col_type_map will be empty for empty result set)
-        if !rows.is_empty() && !col_type_map.is_empty() {
-            for row in rows {
-                let mut row_map = HashMap::with_capacity(columns.len());
-                for (i, col_name) in columns.iter().enumerate() {
-                    let value = row_value_to_json_typed(&row, i, col_type_map[i]);
-                    row_map.insert(col_name.clone(), value);
-                }
-                result_rows.push(row_map);
-            }
+    fn row_to_map(&self, row: &tiberius::Row) -> HashMap<String, serde_json::Value> {
+        let mut row_map = HashMap::with_capacity(self.columns.len());
+        for (i, col_name) in self.columns.iter().enumerate() {
+            let value = row_value_to_json_typed(row, i, self.col_types[i]);
+            row_map.insert(col_name.clone(), value);
         }
+        row_map
+    }
+
+    /// Package mapped rows as a result set described by these columns
+    fn into_query_result(
+        self,
+        rows: Vec<HashMap<String, serde_json::Value>>,
+        rows_affected: Option<u64>,
+        execution_time: u128,
+    ) -> QueryResult {
+        QueryResult {
+            columns: self.columns,
+            column_display_names: Some(self.display_names),
+            column_types: Some(self.column_types),
+            rows,
+            rows_affected,
+            execution_time,
+            final_query: None,
+        }
+    }
+}
+
+#[async_trait]
+impl DatabaseConnection for MSSQLConnection {
+    async fn connect(&mut self, config: &ConnectionConfig) -> Result<()> {
+        self.connect_pool(config, false).await
+    }
+
+    async fn connect_session(&mut self, config: &ConnectionConfig) -> Result<()> {
+        self.connect_pool(config, true).await
+    }
+
+    async fn disconnect(&mut self) -> Result<()> {
+        // Dropping the pinned session closes it, which rolls back any open transaction
+        self.pinned = None;
+        self.known_sessions.unpin();
+        if let Some(pool) = self.pool.take() {
+            drop(pool);
+        }
+        Ok(())
+    }
+
+    async fn test_connection(&mut self) -> Result<bool> {
+        if let Some(pool) = &self.pool {
+            let mut conn = pool
+                .get()
+                .await
+                .map_err(|e| anyhow!("Failed to get connection from pool: {}", e))?;
+            conn.simple_query("SELECT 1").await?;
+            Ok(true)
+        } else {
+            Err(anyhow!("Not connected"))
+        }
+    }
+
+    async fn execute_update(&mut self, query: &str) -> Result<u64> {
+        self.execute_update_bound(&BoundQuery::new(query)).await
+    }
+
+    async fn execute_query(&mut self, query: &str) -> Result<QueryResult> {
+        self.execute_query_bound(&BoundQuery::new(query)).await
+    }
+
+    async fn execute_update_bound(&mut self, query: &BoundQuery) -> Result<u64> {
+        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("Not connected"))?;
+        let mut conn = session_conn(pool, &mut self.pinned).await?;
+        let _session = track_session(&self.session, &mut self.known_sessions, &mut conn).await?;
+
+        // For DML statements (INSERT, UPDATE, DELETE), sum the row counts of every statement
+        let result = bind_params(query).execute(&mut *conn).await?;
+        let total_affected: u64 = result.rows_affected().iter().sum();
+
+        Ok(total_affected)
+    }
+
+    async fn execute_query_bound(&mut self, query: &BoundQuery) -> Result<QueryResult> {
+        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("Not connected"))?;
+
+        let mut conn = session_conn(pool, &mut self.pinned).await?;
+        let _session = track_session(&self.session, &mut self.known_sessions, &mut conn).await?;
+        let start = Instant::now();
+
+        let stream = bind_params(query).query(&mut *conn).await?;
+        let rows = stream.into_first_result().await?;
+        let execution_time = start.elapsed().as_millis();
+
+        // Extract columns from first row if available, or create empty vec
+        // (empty results carry no metadata through into_first_result)
+        let result_columns =
+            ResultColumns::from_columns(rows.first().map(|row| row.columns()).unwrap_or_default());
+
+        let result_rows: Vec<_> = rows
+            .iter()
+            .map(|row| result_columns.row_to_map(row))
+            .collect();
+        let ResultColumns {
+            columns,
+            display_names,
+            column_types,
+            ..
+        } = result_columns;
 
         Ok(QueryResult {
             columns,
@@ -230,6 +538,71 @@ impl DatabaseConnection for MSSQLConnection {
         })
     }
 
+    async fn execute_query_multi(&mut self, query: &str) -> Result<Vec<QueryResult>> {
+        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("Not connected"))?;
+
+        let mut conn = session_conn(pool, &mut self.pinned).await?;
+        let _session = track_session(&self.session, &mut self.known_sessions, &mut conn).await?;
+        let previous_timeout = set_lock_timeout(&mut conn, self.statement_timeout).await?;
+        let result_sets = run_batch(&mut conn, query).await;
+        restore_lock_timeout(&mut conn, previous_timeout).await;
+        result_sets
+    }
+
+    async fn explain(&mut self, query: &str, analyze: bool) -> Result<ExecutionPlan> {
+        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("Not connected"))?;
+        let in_transaction = self.pinned.is_some();
+        let mut conn = session_conn(pool, &mut self.pinned).await?;
+
+        // SHOWPLAN only compiles the batch; STATISTICS XML runs it and appends the
+        // actual plan as an extra result set, so its changes are undone afterwards.
+        // Both SET options must be alone in their batch.
+        let (enable, disable) = if analyze {
+            ("SET STATISTICS XML ON", "SET STATISTICS XML OFF")
+        } else {
+            ("SET SHOWPLAN_XML ON", "SET SHOWPLAN_XML OFF")
+        };
+        let scratch = analyze.then(|| scratch_transaction_sql(SqlDialect::MSSQL, in_transaction));
+        if let Some((begin, _)) = &scratch {
+            conn.simple_query(begin.as_str())
+                .await?
+                .into_results()
+                .await?;
+        }
+        conn.simple_query(enable).await?.into_results().await?;
+        let plans = collect_showplans(&mut conn, query, analyze).await;
+        conn.simple_query(disable).await?.into_results().await?;
+        if let Some((_, undo)) = scratch {
+            for statement in undo {
+                conn.simple_query(statement).await?.into_results().await?;
+            }
+        }
+
+        let plans = plans?;
+        if plans.is_empty() {
+            return Err(anyhow!("SQL Server returned no execution plan"));
+        }
+
+        // Each statement of the batch may come with its own showplan document
+        let mut parsed = plans
+            .iter()
+            .map(|xml| parse_mssql_showplan(xml, analyze))
+            .collect::<Result<Vec<_>>>()?;
+        if parsed.len() == 1 {
+            return Ok(parsed.remove(0));
+        }
+        let mut root = PlanNode::new("Batch");
+        root.children = parsed.iter().map(|p| p.root.clone()).collect();
+        Ok(ExecutionPlan {
+            root,
+            analyzed: analyze,
+            planning_time_ms: None,
+            execution_time_ms: None,
+            format: "xml".to_string(),
+            raw: plans.join("\n"),
+        })
+    }
+
     async fn get_databases(&mut self) -> Result<Vec<Database>> {
         let pool = self.pool.as_ref().ok_or_else(|| anyhow!("Not connected"))?;
 
@@ -321,7 +694,10 @@ impl DatabaseConnection for MSSQLConnection {
                 IS_NULLABLE as is_nullable,
                 COLUMN_DEFAULT as column_default,
                 CHARACTER_MAXIMUM_LENGTH as max_length,
-                COLUMNPROPERTY(object_id(TABLE_SCHEMA+'.'+TABLE_NAME), COLUMN_NAME, 'IsIdentity') as is_identity
+                CAST(NUMERIC_PRECISION AS int) as numeric_precision,
+                CAST(NUMERIC_SCALE AS int) as numeric_scale,
+                COLUMNPROPERTY(object_id(TABLE_SCHEMA+'.'+TABLE_NAME), COLUMN_NAME, 'IsIdentity') as is_identity,
+                COLUMNPROPERTY(object_id(TABLE_SCHEMA+'.'+TABLE_NAME), COLUMN_NAME, 'IsComputed') as is_computed
             FROM [{database}].INFORMATION_SCHEMA.COLUMNS 
             WHERE TABLE_NAME = '{table_name}' AND TABLE_SCHEMA = '{schema}'
             ORDER BY ORDINAL_POSITION"
@@ -339,16 +715,37 @@ impl DatabaseConnection for MSSQLConnection {
                 let default: Option<String> =
                     row.get::<&str, _>("column_default").map(|s| s.to_string());
                 let max_length: Option<i32> = row.get("max_length");
+                let numeric_precision: Option<i32> = row.get("numeric_precision");
+                let numeric_scale: Option<i32> = row.get("numeric_scale");
                 let is_identity: Option<i32> = row.get("is_identity");
+                let is_computed: Option<i32> = row.get("is_computed");
+                // rowversion is reported as timestamp; the server sets both
+                let is_row_version = matches!(
+                    data_type.to_lowercase().as_str(),
+                    "timestamp" | "rowversion"
+                );
 
-                let data_type_display = if let Some(len) = max_length {
-                    if len > 0 {
+                // Only sized types carry their size, so the type is valid in DDL as it is
+                let data_type_display = match (data_type.to_lowercase().as_str(), max_length) {
+                    (
+                        "char" | "varchar" | "nchar" | "nvarchar" | "binary" | "varbinary",
+                        Some(-1),
+                    ) => {
+                        format!("{}(max)", data_type)
+                    }
+                    (
+                        "char" | "varchar" | "nchar" | "nvarchar" | "binary" | "varbinary",
+                        Some(len),
+                    ) if len > 0 => {
                         format!("{}({})", data_type, len)
-                    } else {
-                        data_type.clone()
                     }
-                } else {
-                    data_type.clone()
+                    ("decimal" | "numeric", _) => match (numeric_precision, numeric_scale) {
+                        (Some(precision), Some(scale)) => {
+                            format!("{}({},{})", data_type, precision, scale)
+                        }
+                        _ => data_type.clone(),
+                    },
+                    _ => data_type.clone(),
                 };
 
                 Some(Column {
@@ -358,6 +755,8 @@ impl DatabaseConnection for MSSQLConnection {
                     default_value: default,
                     is_primary_key: false,
                     is_auto_increment: is_identity.unwrap_or(0) == 1,
+                    is_generated: is_computed.unwrap_or(0) == 1 || is_row_version,
+                    default_is_expression: false,
                 })
             })
             .collect::<Vec<Column>>();
@@ -465,14 +864,90 @@ impl DatabaseConnection for MSSQLConnection {
             })
             .collect();
 
+        let trigger_query = format!(
+            "SELECT COUNT(*) AS trigger_count
+            FROM [{database}].sys.triggers
+            WHERE parent_id = OBJECT_ID('[{database}].[{schema}].[{table_name}]') AND is_disabled = 0"
+        );
+
+        let trigger_stream = conn.query(trigger_query, &[]).await?;
+        let has_triggers = trigger_stream
+            .into_row()
+            .await?
+            .and_then(|row| row.get::<i32, _>("trigger_count"))
+            .is_some_and(|count| count > 0);
+
         Ok(TableSchema {
             table_name: table.to_string(),
             columns,
             indexes,
             foreign_keys,
+            has_triggers,
         })
     }
 
+    async fn get_table_relationships(
+        &mut self,
+        database: &str,
+        table: &str,
+    ) -> Result<Vec<TableRelationship>> {
+        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("Not connected"))?;
+        let mut conn = pool
+            .get()
+            .await
+            .map_err(|e| anyhow!("Failed to get connection from pool: {}", e))?;
+
+        let (schema, table_name) = table.split_once('.').unwrap_or(("dbo", table));
+        let database = database.replace(']', "]]");
+        let query = format!(
+            "SELECT
+                fk.name as constraint_name,
+                ps.name as table_schema,
+                pt.name as table_name,
+                rs.name as referenced_table_schema,
+                COL_NAME(fkc.parent_object_id, fkc.parent_column_id) as column_name,
+                rt.name as referenced_table_name,
+                COL_NAME(fkc.referenced_object_id, fkc.referenced_column_id) as referenced_column_name,
+                fk.delete_referential_action_desc as delete_rule,
+                fk.update_referential_action_desc as update_rule,
+                CASE WHEN pt.name = @P2 AND ps.name = @P1 THEN 'FOREIGN_KEY' ELSE 'REFERENCED_BY' END as relationship_type
+            FROM [{database}].sys.foreign_keys fk
+            INNER JOIN [{database}].sys.foreign_key_columns fkc ON fk.object_id = fkc.constraint_object_id
+            INNER JOIN [{database}].sys.tables pt ON fk.parent_object_id = pt.object_id
+            INNER JOIN [{database}].sys.schemas ps ON pt.schema_id = ps.schema_id
+            INNER JOIN [{database}].sys.tables rt ON fk.referenced_object_id = rt.object_id
+            INNER JOIN [{database}].sys.schemas rs ON rt.schema_id = rs.schema_id
+            WHERE (ps.name = @P1 AND pt.name = @P2) OR (rs.name = @P1 AND rt.name = @P2)
+            ORDER BY fk.name, fkc.constraint_column_id"
+        );
+
+        let stream = conn.query(query, &[&schema, &table_name]).await?;
+        let rows = stream.into_first_result().await?;
+
+        let relationships = rows
+            .iter()
+            .filter_map(|row| {
+                let text = |column: &str| row.get::<&str, _>(column).map(|s| s.to_string());
+                Some(TableRelationship {
+                    constraint_name: text("constraint_name")?,
+                    table_name: text("table_name")?,
+                    column_name: text("column_name")?,
+                    referenced_table_name: text("referenced_table_name")?,
+                    referenced_column_name: text("referenced_column_name")?,
+                    relationship_type: text("relationship_type")?,
+                    table_schema: text("table_schema"),
+                    referenced_table_schema: text("referenced_table_schema"),
+                    owner: Some(schema.to_string()),
+                    ref_object_type: Some("TABLE".to_string()),
+                    on_delete: text("delete_rule"),
+                    on_update: text("update_rule"),
+                })
+            })
+            .collect();
+
+        Ok(relationships)
+    }
+
     async fn get_views(&mut self, database: &str, schema: Option<&str>) -> Result<Vec<View>> {
         let pool = self
             .pool
@@ -513,6 +988,159 @@ impl DatabaseConnection for MSSQLConnection {
         MSSQLMetadataOps::get_triggers(&pool, database, schema).await
     }
 
+    async fn get_procedure_source(
+        &mut self,
+        database: &str,
+        procedure_name: &str,
+        _procedure_type: Option<String>,
+        schema: Option<String>,
+    ) -> Result<String> {
+        let pool = self
+            .pool
+            .as_ref()
+            .ok_or_else(|| anyhow!("Not connected"))?
+            .clone();
+        let schema = schema.as_deref().unwrap_or("dbo");
+        Ok(
+            MSSQLMetadataOps::get_module_definition(&pool, database, schema, procedure_name)
+                .await?
+                .unwrap_or_else(|| "-- Source code not available".to_string()),
+        )
+    }
+
+    async fn get_view_source(
+        &mut self,
+        database: &str,
+        view: &str,
+        schema: Option<&str>,
+    ) -> Result<Option<String>> {
+        let pool = self
+            .pool
+            .as_ref()
+            .ok_or_else(|| anyhow!("Not connected"))?
+            .clone();
+        MSSQLMetadataOps::get_module_definition(&pool, database, schema.unwrap_or("dbo"), view)
+            .await
+    }
+
+    async fn get_trigger_source(
+        &mut self,
+        database: &str,
+        trigger: &str,
+        _table: &str,
+        schema: Option<&str>,
+    ) -> Result<Option<String>> {
+        let pool = self
+            .pool
+            .as_ref()
+            .ok_or_else(|| anyhow!("Not connected"))?
+            .clone();
+        MSSQLMetadataOps::get_module_definition(&pool, database, schema.unwrap_or("dbo"), trigger)
+            .await
+    }
+
+    async fn execute_query_streaming(
+        &mut self,
+        query: &str,
+        batch_size: usize,
+        sink: &QueryStreamSender,
+    ) -> Result<u64> {
+        self.execute_query_streaming_bound(&BoundQuery::new(query), batch_size, sink)
+            .await
+    }
+
+    async fn execute_query_streaming_bound(
+        &mut self,
+        query: &BoundQuery,
+        batch_size: usize,
+        sink: &QueryStreamSender,
+    ) -> Result<u64> {
+        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("Not connected"))?;
+        let mut conn = session_conn(pool, &mut self.pinned).await?;
+        let _session = track_session(&self.session, &mut self.known_sessions, &mut conn).await?;
+        let previous_timeout = set_lock_timeout(&mut conn, self.statement_timeout).await?;
+        let streamed = stream_first_result(&mut conn, query, batch_size, sink).await;
+        restore_lock_timeout(&mut conn, previous_timeout).await;
+        streamed
+    }
+
+    async fn transaction_control(&mut self, command: &TransactionCommand) -> Result<()> {
+        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("Not connected"))?;
+
+        // SQL Server has no RELEASE SAVEPOINT; savepoints simply end with the transaction
+        let Some(sql) = command.sql(SqlDialect::MSSQL) else {
+            return match self.pinned {
+                Some(_) => Ok(()),
+                None => Err(anyhow!("No transaction is open")),
+            };
+        };
+
+        // Run as a plain batch: a transaction opened inside sp_executesql would not
+        // outlive the RPC call
+        match command {
+            TransactionCommand::Begin(_) => {
+                if self.pinned.is_some() {
+                    return Err(anyhow!("A transaction is already open"));
+                }
+                let mut conn = pool
+                    .get_owned()
+                    .await
+                    .map_err(|e| anyhow!("Failed to get connection from pool: {}", e))?;
+                conn.simple_query(sql).await?.into_results().await?;
+                self.pinned = Some(conn);
+            }
+            _ => {
+                let conn = self
+                    .pinned
+                    .as_mut()
+                    .ok_or_else(|| anyhow!("No transaction is open"))?;
+                conn.simple_query(sql).await?.into_results().await?;
+                if matches!(
+                    command,
+                    TransactionCommand::Commit | TransactionCommand::Rollback
+                ) {
+                    self.pinned = None;
+                    self.known_sessions.unpin();
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn in_transaction(&self) -> bool {
+        self.pinned.is_some()
+    }
+
+    fn supports_transactions(&self) -> bool {
+        true
+    }
+
+    fn set_statement_timeout(&mut self, timeout: Option<Duration>) {
+        self.statement_timeout = timeout;
+    }
+
+    fn set_session_tracker(&mut self, tracker: SessionTracker) {
+        self.session = tracker;
+    }
+
+    async fn cancel(&mut self, session_id: i64) -> Result<()> {
+        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("Not connected"))?;
+        let mut conn = pool
+            .get()
+            .await
+            .map_err(|e| anyhow!("Failed to get connection from pool: {}", e))?;
+
+        // tiberius cannot send a TDS attention, so the whole session is ended. Its open
+        // transaction rolls back and the pool reopens the cancelled connection.
+        conn.execute(format!("KILL {}", session_id), &[]).await?;
+        Ok(())
+    }
+
+    fn cancel_ends_session(&self) -> bool {
+        true
+    }
+
     fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
         self
     }
@@ -226,4 +226,27 @@ impl MSSQLMetadataOps {
 
         Ok(triggers)
     }
+
+    /// Definition of a view, procedure, function or trigger as it was created
+    pub async fn get_module_definition(
+        pool: &Pool<ConnectionManager>,
+        database: &str,
+        schema: &str,
+        name: &str,
+    ) -> Result<Option<String>> {
+        let mut conn = pool.get().await?;
+        let database = database.replace(']', "]]");
+
+        let query = format!(
+            "SELECT m.definition
+            FROM [{database}].sys.sql_modules m
+            INNER JOIN [{database}].sys.objects o ON m.object_id = o.object_id
+            INNER JOIN [{database}].sys.schemas s ON o.schema_id = s.schema_id
+            WHERE s.name = @P1 AND o.name = @P2"
+        );
+
+        let stream = conn.query(query, &[&schema, &name]).await?;
+        let row = stream.into_row().await?;
+        Ok(row.and_then(|row| row.get::<&str, _>("definition").map(|s| s.to_string())))
+    }
 }
@@ -1,6 +1,8 @@
+use crate::db::bound_query::{self, BoundQuery, InsertReadBack};
 use crate::db::mysql::query_builder::MySQLQueryBuilder;
 use crate::db::traits::{CRUDQueryBuilder, QueryBuilder};
 use crate::models::save_request::EditedRow;
+use crate::models::schema::{Column, TableSchema};
 use crate::models::table_request::*;
 use anyhow::Result;
 use std::collections::HashMap;
@@ -9,41 +11,36 @@ pub struct MSSQLQueryBuilder;
 
 impl QueryBuilder for MSSQLQueryBuilder {
     fn build_select_query(&self, request: &QueryRequest) -> Result<String> {
-        let table = self.format_table_name(request);
-
-        let order_clause = if request.order_by.is_empty() {
-            "ORDER BY (SELECT NULL)".to_string()
+        let where_clause = if request.filters.is_empty() {
+            None
         } else {
-            format!("ORDER BY {}", self.build_order_by_clause(&request.order_by))
+            Some(self.build_where_clause(&request.filters)?)
         };
 
-        // Handle SELECT clause with TOP for SQL Server 2008 compatibility
-        let select_clause = if request.offset == 0 && request.limit > 0 {
-            // SQL Server 2008: use TOP
-            format!("SELECT TOP {} *", request.limit)
+        Ok(self.assemble_select(request, where_clause))
+    }
+
+    fn build_select_query_bound(
+        &self,
+        request: &QueryRequest,
+        table_schema: Option<&TableSchema>,
+    ) -> Result<BoundQuery> {
+        let mut params = Vec::new();
+        let where_clause = if request.filters.is_empty() {
+            None
         } else {
-            // SQL Server 2012+ or no limit
-            "SELECT *".to_string()
+            Some(bound_query::build_where_clause(
+                self,
+                &request.filters,
+                table_schema,
+                &mut params,
+            )?)
         };
 
-        let mut query = format!("{} FROM {}", select_clause, table);
-
-        if !request.filters.is_empty() {
-            let where_clause = self.build_where_clause(&request.filters)?;
-            query.push_str(&format!(" WHERE {}", where_clause));
-        }
-
-        query.push_str(&format!(" {}", order_clause));
-
-        // Add OFFSET...FETCH only if offset > 0 (SQL Server 2012+)
-        if request.offset > 0 {
-            query.push_str(&format!(
-                " OFFSET {} ROWS FETCH NEXT {} ROWS ONLY",
-                request.offset, request.limit
-            ));
-        }
-
-        Ok(query)
+        Ok(BoundQuery {
+            sql: self.assemble_select(request, where_clause),
+            params,
+        })
     }
 
     fn quote_identifier(&self, identifier: &str) -> String {
@@ -114,218 +111,269 @@ impl QueryBuilder for MSSQLQueryBuilder {
             "".to_string()
         }
     }
-}
 
-impl CRUDQueryBuilder for MSSQLQueryBuilder {
-    fn build_insert_query(
+    fn placeholder(
         &self,
-        table: &str,
-        _schema: Option<&str>,
-        row: &HashMap<String, serde_json::Value>,
-        table_schema: &crate::models::schema::TableSchema,
-    ) -> Result<String> {
-        if row.is_empty() {
-            anyhow::bail!("Cannot insert empty row");
-        }
+        index: usize,
+        _column_type: Option<&str>,
+        _value: &serde_json::Value,
+    ) -> String {
+        format!("@P{}", index)
+    }
+
+    fn supports_equality(&self, data_type: &str) -> bool {
+        // Legacy LOB types cannot be compared at all
+        let lower = data_type.to_lowercase();
+        !matches!(lower.as_str(), "text" | "ntext" | "sql_variant")
+            && bound_query::comparable_type(data_type)
+    }
+
+    fn is_row_version_column(&self, column: &Column) -> bool {
+        let data_type = column.data_type.to_lowercase();
+        data_type == "timestamp" || data_type == "rowversion"
+    }
 
-        // Get column order from table_schema to ensure consistent column order
-        let columns: Vec<String> = table_schema
+    fn row_version_condition(
+        &self,
+        table_schema: &TableSchema,
+        original: &HashMap<String, serde_json::Value>,
+        params: &mut Vec<serde_json::Value>,
+    ) -> Option<String> {
+        // A rowversion column can only be matched when it was loaded as a 0x... hex string
+        let column = table_schema
             .columns
             .iter()
-            .filter_map(|col| {
-                if row.contains_key(&col.name) {
-                    Some(col.name.clone())
-                } else {
-                    None
-                }
-            })
-            .collect();
+            .find(|col| self.is_row_version_column(col))?;
+        let version = original.get(&column.name)?.as_str()?;
+        if !version.starts_with("0x") {
+            return None;
+        }
+        params.push(serde_json::Value::String(version.to_string()));
+        Some(format!(
+            "{} = CONVERT(BINARY(8), @P{}, 1)",
+            self.quote_identifier(&column.name),
+            params.len()
+        ))
+    }
 
-        let columns_str = columns
-            .iter()
-            .map(|c| self.quote_identifier(c))
-            .collect::<Vec<_>>()
-            .join(", ");
+    fn row_locator(&self) -> Option<&'static str> {
+        // Undocumented file:page:slot address of the row, stable until the row moves
+        Some("CONVERT(VARCHAR(20), %%physloc%%, 1)")
+    }
 
-        let values_str = columns
-            .iter()
-            .map(|col| self.format_value(&row[col]))
-            .collect::<Vec<_>>()
-            .join(", ");
+    fn row_locator_condition(&self, value: &str) -> Option<String> {
+        Some(format!("%%physloc%% = CONVERT(BINARY(8), {}, 1)", value))
+    }
+}
 
-        let table_name = self.format_table_name_for_crud(table, _schema);
+impl CRUDQueryBuilder for MSSQLQueryBuilder {
+    // Unicode literals, so text outside the database code page survives
+    fn format_value(&self, val: &serde_json::Value) -> String {
+        match val {
+            serde_json::Value::Null => "NULL".to_string(),
+            serde_json::Value::Bool(b) => if *b { "1" } else { "0" }.to_string(),
+            serde_json::Value::Number(n) => n.to_string(),
+            serde_json::Value::String(s) => format!("N'{}'", self.escape_sql_string(s)),
+            serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
+                format!("N'{}'", self.escape_sql_string(&val.to_string()))
+            }
+        }
+    }
 
-        Ok(format!(
-            "INSERT INTO {} ({}) VALUES ({});",
-            table_name, columns_str, values_str
-        ))
+    fn crud_table_name(&self, table: &str, schema: Option<&str>) -> String {
+        self.format_table_name_for_crud(table, schema)
     }
 
-    fn build_update_query(
+    fn build_insert_query_bound(
         &self,
         table: &str,
-        _schema: Option<&str>,
-        edited_row: &EditedRow,
-        primary_keys: &[&String],
-        table_schema: &crate::models::schema::TableSchema,
-    ) -> Result<String> {
-        let updated_data = &edited_row.updated_data;
-        let original_data = &edited_row.original_data;
+        schema: Option<&str>,
+        row: &HashMap<String, serde_json::Value>,
+        table_schema: &TableSchema,
+    ) -> Result<BoundQuery> {
+        let table_name = self.crud_table_name(table, schema);
+        bound_query::build_insert(self, &table_name, row, table_schema)
+    }
 
-        if updated_data.is_empty() {
-            anyhow::bail!("Cannot update with no columns");
+    fn build_insert_returning_bound(
+        &self,
+        table: &str,
+        schema: Option<&str>,
+        row: &HashMap<String, serde_json::Value>,
+        table_schema: &TableSchema,
+    ) -> Result<(BoundQuery, InsertReadBack)> {
+        // OUTPUT without INTO is rejected on tables with enabled triggers
+        if table_schema.has_triggers {
+            return Ok((
+                self.build_insert_query_bound(table, schema, row, table_schema)?,
+                InsertReadBack::Unavailable,
+            ));
         }
+        let table_name = self.crud_table_name(table, schema);
+        let query = bound_query::build_insert_returning(
+            self,
+            &table_name,
+            row,
+            table_schema,
+            " OUTPUT INSERTED.*",
+            "",
+        )?;
+        Ok((query, InsertReadBack::Returning))
+    }
 
-        // Build SET clause using table_schema column order (exclude primary keys)
-        let set_parts: Result<Vec<String>> = table_schema
-            .columns
-            .iter()
-            .filter(|col| {
-                updated_data.contains_key(&col.name)
-                    && !primary_keys
-                        .iter()
-                        .any(|pk| pk.as_str() == col.name.as_str())
-            })
-            .map(|col| {
-                let val = &updated_data[&col.name];
-                Ok(format!(
-                    "{} = {}",
-                    self.quote_identifier(&col.name),
-                    self.format_value(val)
-                ))
-            })
-            .collect();
-
-        let set_clause = set_parts?.join(", ");
-
-        if set_clause.is_empty() {
-            anyhow::bail!("No columns to update (all are primary keys)");
+    fn build_insert_batch_bound(
+        &self,
+        table: &str,
+        schema: Option<&str>,
+        columns: &[String],
+        rows: &[Vec<serde_json::Value>],
+        table_schema: &TableSchema,
+        upsert: bool,
+    ) -> Result<BoundQuery> {
+        let table_name = self.crud_table_name(table, schema);
+        if !upsert {
+            return bound_query::build_insert_batch(
+                self,
+                &table_name,
+                columns,
+                rows,
+                table_schema,
+                "",
+            );
         }
 
-        // Build WHERE clause
-        let where_clause = if !primary_keys.is_empty() {
-            // Use primary keys
-            primary_keys
-                .iter()
-                .map(|pk| {
-                    let val = original_data.get(pk.as_str()).ok_or_else(|| {
-                        anyhow::anyhow!("Primary key {} not found in original data", pk)
-                    })?;
-                    Ok(format!(
-                        "{} {}",
-                        self.quote_identifier(pk),
-                        self.format_where_condition(val)
-                    ))
-                })
-                .collect::<Result<Vec<_>>>()?
-                .join(" AND ")
+        // SQL Server has no INSERT ... ON CONFLICT, so the rows are merged in
+        let (keys, updates) = bound_query::upsert_columns(columns, table_schema)?;
+        let mut params = Vec::new();
+        let values = bound_query::bind_row_values(self, columns, rows, table_schema, &mut params);
+        let quoted: Vec<String> = columns.iter().map(|c| self.quote_identifier(c)).collect();
+        let source = |c: &String| format!("source.{}", self.quote_identifier(c));
+        let target = |c: &String| format!("target.{}", self.quote_identifier(c));
+        let on: Vec<String> = keys
+            .iter()
+            .map(|c| format!("{} = {}", target(c), source(c)))
+            .collect();
+        let set: Vec<String> = updates
+            .iter()
+            .map(|c| format!("{} = {}", target(c), source(c)))
+            .collect();
+        let matched = if set.is_empty() {
+            String::new()
         } else {
-            // Fallback: use all original columns
-            original_data
-                .iter()
-                .map(|(col, val)| {
-                    Ok(format!(
-                        "{} {}",
-                        self.quote_identifier(col),
-                        self.format_where_condition(val)
-                    ))
-                })
-                .collect::<Result<Vec<_>>>()?
-                .join(" AND ")
+            format!(" WHEN MATCHED THEN UPDATE SET {}", set.join(", "))
         };
+        Ok(BoundQuery {
+            sql: format!(
+                "MERGE INTO {} AS target USING (VALUES {}) AS source ({}) ON {}{} \
+                 WHEN NOT MATCHED THEN INSERT ({}) VALUES ({});",
+                table_name,
+                values.join(", "),
+                quoted.join(", "),
+                on.join(" AND "),
+                matched,
+                quoted.join(", "),
+                columns.iter().map(source).collect::<Vec<_>>().join(", ")
+            ),
+            params,
+        })
+    }
 
-        if where_clause.is_empty() {
-            anyhow::bail!("Cannot generate WHERE clause");
-        }
+    fn max_batch_rows(&self, column_count: usize) -> usize {
+        // At most 2100 parameters per request and 1000 rows per VALUES list
+        (2_099 / column_count.max(1)).clamp(1, 1_000)
+    }
 
-        let table_name = self.format_table_name_for_crud(table, _schema);
+    fn build_update_query_bound(
+        &self,
+        table: &str,
+        schema: Option<&str>,
+        edited_row: &EditedRow,
+        primary_keys: &[&String],
+        table_schema: &TableSchema,
+    ) -> Result<BoundQuery> {
+        let table_name = self.crud_table_name(table, schema);
+        bound_query::build_update(self, &table_name, edited_row, primary_keys, table_schema)
+    }
 
-        Ok(format!(
-            "UPDATE {} SET {} WHERE {};",
-            table_name, set_clause, where_clause
-        ))
+    fn build_update_query_checked_bound(
+        &self,
+        table: &str,
+        schema: Option<&str>,
+        edited_row: &EditedRow,
+        primary_keys: &[&String],
+        table_schema: &TableSchema,
+    ) -> Result<BoundQuery> {
+        let table_name = self.crud_table_name(table, schema);
+        bound_query::build_update_checked(self, &table_name, edited_row, primary_keys, table_schema)
+    }
+
+    fn build_select_row_bound(
+        &self,
+        table: &str,
+        schema: Option<&str>,
+        row: &HashMap<String, serde_json::Value>,
+        primary_keys: &[&String],
+        table_schema: &TableSchema,
+    ) -> Result<BoundQuery> {
+        let table_name = self.crud_table_name(table, schema);
+        bound_query::build_select_row(self, &table_name, row, primary_keys, table_schema)
     }
 
-    fn build_delete_query(
+    fn build_delete_query_bound(
         &self,
         table: &str,
-        _schema: Option<&str>,
+        schema: Option<&str>,
         row: &HashMap<String, serde_json::Value>,
         primary_keys: &[&String],
-    ) -> Result<String> {
-        // Build WHERE clause
-        let where_clause = if !primary_keys.is_empty() {
-            // Use primary keys
-            primary_keys
-                .iter()
-                .map(|pk| {
-                    let val = row
-                        .get(pk.as_str())
-                        .ok_or_else(|| anyhow::anyhow!("Primary key {} not found in row", pk))?;
-                    Ok(format!(
-                        "{} {}",
-                        self.quote_identifier(pk),
-                        self.format_where_condition(val)
-                    ))
-                })
-                .collect::<Result<Vec<_>>>()?
-                .join(" AND ")
+        table_schema: &TableSchema,
+    ) -> Result<BoundQuery> {
+        let table_name = self.crud_table_name(table, schema);
+        bound_query::build_delete(self, &table_name, row, primary_keys, table_schema)
+    }
+
+    fn escape_sql_string(&self, s: &str) -> String {
+        s.replace("'", "''")
+    }
+}
+
+impl MSSQLQueryBuilder {
+    fn assemble_select(&self, request: &QueryRequest, where_clause: Option<String>) -> String {
+        let table = self.format_table_name(request);
+
+        let order_clause = if request.order_by.is_empty() {
+            "ORDER BY (SELECT NULL)".to_string()
         } else {
-            // Fallback: use all columns from row
-            row.iter()
-                .map(|(col, val)| {
-                    Ok(format!(
-                        "{} {}",
-                        self.quote_identifier(col),
-                        self.format_where_condition(val)
-                    ))
-                })
-                .collect::<Result<Vec<_>>>()?
-                .join(" AND ")
+            format!("ORDER BY {}", self.build_order_by_clause(&request.order_by))
         };
 
-        if where_clause.is_empty() {
-            anyhow::bail!("Cannot generate WHERE clause for DELETE");
-        }
-
-        let table_name = self.format_table_name_for_crud(table, _schema);
+        // Handle SELECT clause with TOP for SQL Server 2008 compatibility
+        let select_clause = if request.offset == 0 && request.limit > 0 {
+            // SQL Server 2008: use TOP
+            format!("SELECT TOP {} *", request.limit)
+        } else {
+            // SQL Server 2012+ or no limit
+            "SELECT *".to_string()
+        };
 
-        Ok(format!(
-            "DELETE FROM {} WHERE {};",
-            table_name, where_clause
-        ))
-    }
+        let mut query = format!("{} FROM {}", select_clause, table);
 
-    fn format_value(&self, val: &serde_json::Value) -> String {
-        match val {
-            serde_json::Value::Null => "NULL".to_string(),
-            serde_json::Value::Bool(b) => if *b { "1" } else { "0" }.to_string(),
-            serde_json::Value::Number(n) => n.to_string(),
-            serde_json::Value::String(s) => format!("'{}'", self.escape_sql_string(s)),
-            serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
-                format!("'{}'", self.escape_sql_string(&val.to_string()))
-            }
+        if let Some(where_clause) = where_clause {
+            query.push_str(&format!(" WHERE {}", where_clause));
         }
-    }
 
-    fn format_where_condition(&self, val: &serde_json::Value) -> String {
-        match val {
-            serde_json::Value::Null => "IS NULL".to_string(),
-            serde_json::Value::Bool(b) => if *b { "= 1" } else { "= 0" }.to_string(),
-            serde_json::Value::Number(n) => format!("= {}", n),
-            serde_json::Value::String(s) => format!("= '{}'", self.escape_sql_string(s)),
-            serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
-                format!("= '{}'", self.escape_sql_string(&val.to_string()))
-            }
+        query.push_str(&format!(" {}", order_clause));
+
+        // Add OFFSET...FETCH only if offset > 0 (SQL Server 2012+)
+        if request.offset > 0 {
+            query.push_str(&format!(
+                " OFFSET {} ROWS FETCH NEXT {} ROWS ONLY",
+                request.offset, request.limit
+            ));
         }
-    }
 
-    fn escape_sql_string(&self, s: &str) -> String {
-        s.replace("'", "''")
+        query
     }
-}
 
-impl MSSQLQueryBuilder {
     fn format_table_name_for_crud(&self, table: &str, schema: Option<&str>) -> String {
         let quoted_table = self.quote_identifier(table);
 
@@ -20,6 +20,47 @@ pub enum MssqlColType {
     Unknown,
 }
 
+/// Map an upper-case tiberius column type name to enum (called once per column)
+pub fn map_mssql_type(type_name: &str) -> MssqlColType {
+    match type_name {
+        t if t.contains("VARCHAR")
+            || t.contains("CHAR")
+            || t.contains("TEXT")
+            || t.contains("NVARCHAR")
+            || t.contains("NCHAR")
+            || t.contains("NTEXT") =>
+        {
+            MssqlColType::String
+        }
+        t if t.contains("BIGINT") || t.contains("INT8") => MssqlColType::Int64,
+        t if t.contains("SMALLINT") || t.contains("INT2") => MssqlColType::Int16,
+        t if t.contains("TINYINT") => MssqlColType::UInt8,
+        t if t.contains("INT") || t.contains("INT4") => MssqlColType::Int32,
+        t if t.contains("REAL") || t.contains("FLOAT4") => MssqlColType::Float32,
+        t if t.contains("FLOAT") || t.contains("DOUBLE") || t.contains("FLOAT8") => {
+            MssqlColType::Float64
+        }
+        t if t.contains("BIT") || t.contains("BOOL") => MssqlColType::Boolean,
+        t if t.contains("UNIQUEIDENTIFIER") || t.contains("UUID") => MssqlColType::Uuid,
+        t if t.contains("DATETIME") || t.contains("TIMESTAMP") || t.contains("SMALLDATETIME") => {
+            MssqlColType::DateTime
+        }
+        t if t.contains("DATE") => MssqlColType::Date,
+        t if t.contains("TIME") => MssqlColType::Time,
+        t if t.contains("BINARY") || t.contains("VARBINARY") || t.contains("IMAGE") => {
+            MssqlColType::Binary
+        }
+        t if t.contains("DECIMAL")
+            || t.contains("NUMERIC")
+            || t.contains("MONEY")
+            || t.contains("SMALLMONEY") =>
+        {
+            MssqlColType::Decimal
+        }
+        _ => MssqlColType::Unknown,
+    }
+}
+
 /// Optimized helper function using pre-computed type
 pub fn row_value_to_json_typed(
     row: &Row,
@@ -1,103 +1,232 @@
+use crate::db::bound_query::BoundQuery;
+use crate::db::explain::{parse_mysql_json_plan, parse_mysql_tree_plan};
 use crate::db::mysql::metadata_ops::MySqlMetadataOps;
-use crate::db::traits::DatabaseConnection;
-use crate::models::{connection::*, query_result::*, schema::*};
+use crate::db::mysql::type_converter::{MySqlColType, MySqlTypeConverter};
+use crate::db::sql_splitter::SqlDialect;
+use crate::db::traits::{
+    send_stream_event, DatabaseConnection, KnownSessions, QueryStreamSender, SessionGuard,
+    SessionTracker,
+};
+use crate::db::transaction::{
+    finish_transaction_command, scratch_transaction_sql, sqlx_session_conn, sqlx_transaction_conn,
+    SessionConn, TransactionCommand,
+};
+use crate::models::{connection::*, explain::ExecutionPlan, query_result::*, schema::*};
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
-use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
-use sqlx::{Column as SqlxColumn, Executor, MySqlPool, Row, Statement, TypeInfo};
+use futures::TryStreamExt;
+use sqlx::mysql::{
+    MySql, MySqlArguments, MySqlColumn, MySqlPoolOptions, MySqlQueryResult, MySqlRow,
+};
+use sqlx::pool::PoolConnection;
+use sqlx::query::Query;
+use sqlx::{Column as SqlxColumn, Executor, MySqlConnection, MySqlPool, Row, Statement, TypeInfo};
+use std::borrow::Cow;
 use std::collections::HashMap;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 pub struct MySQLConnection {
     pool: Option<MySqlPool>,
+    session: SessionTracker,
+    known_sessions: KnownSessions,
+    /// Limit of the current timed command, passed to the server as a SELECT hint
+    statement_timeout: Option<Duration>,
+    /// Session pinned by an open manual transaction
+    pinned: Option<PoolConnection<MySql>>,
 }
 
 impl MySQLConnection {
     pub fn new() -> Self {
-        Self { pool: None }
-    }
-}
-
-impl Default for MySQLConnection {
-    fn default() -> Self {
-        Self::new()
+        Self {
+            pool: None,
+            session: SessionTracker::default(),
+            known_sessions: KnownSessions::default(),
+            statement_timeout: None,
+            pinned: None,
+        }
     }
-}
 
-// Define column type categories for efficient lookup
-#[derive(Clone, Copy)]
-enum ColType {
-    DateTime,
-    Date,
-    Time,
-    Integer,
-    Float,
-    Boolean,
-    String,
-    Blob,
-    Unknown,
-}
-
-#[async_trait]
-impl DatabaseConnection for MySQLConnection {
-    async fn connect(&mut self, config: &ConnectionConfig) -> Result<()> {
-        let url = format!(
+    fn connection_url(config: &ConnectionConfig) -> String {
+        format!(
             "mysql://{}:{}@{}:{}/{}",
             config.username.as_ref().unwrap_or(&"root".to_string()),
             config.password.as_ref().unwrap_or(&"".to_string()),
             config.host,
             config.port,
             config.database.as_ref().unwrap_or(&"".to_string())
-        );
+        )
+    }
 
-        self.pool = Some(MySqlPool::connect(&url).await?);
-        Ok(())
+    /// Run `SHOW CREATE <kind>` on an object and read the statement from `column`.
+    /// `None` when the object is of another kind, whose statement is in another column.
+    async fn show_create(
+        &self,
+        kind: &str,
+        database: &str,
+        name: &str,
+        column: &str,
+    ) -> Result<Option<String>> {
+        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("Not connected"))?;
+        let query = format!(
+            "SHOW CREATE {} `{}`.`{}`",
+            kind,
+            database.replace('`', "``"),
+            name.replace('`', "``")
+        );
+        let row = sqlx::query(&query).fetch_one(pool).await?;
+        Ok(show_create_text(&row, column))
     }
+}
 
-    async fn disconnect(&mut self) -> Result<()> {
-        if let Some(pool) = &self.pool {
-            pool.close().await;
-            self.pool = None;
-        }
-        Ok(())
+/// Text of a `SHOW CREATE` column, which some servers send as binary
+fn show_create_text(row: &MySqlRow, column: &str) -> Option<String> {
+    row.try_get::<Option<String>, _>(column)
+        .ok()
+        .flatten()
+        .or_else(|| {
+            row.try_get::<Option<Vec<u8>>, _>(column)
+                .ok()
+                .flatten()
+                .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+        })
+}
+
+impl Default for MySQLConnection {
+    fn default() -> Self {
+        Self::new()
     }
+}
 
-    async fn test_connection(&mut self) -> Result<bool> {
-        if let Some(pool) = &self.pool {
-            sqlx::query("SELECT 1").fetch_one(pool).await?;
-            Ok(true)
-        } else {
-            Err(anyhow!("Not connected"))
-        }
+async fn connection_id(conn: &mut MySqlConnection) -> Result<i64, sqlx::Error> {
+    let id: u64 = sqlx::query_scalar("SELECT CONNECTION_ID()")
+        .fetch_one(conn)
+        .await?;
+    Ok(id as i64)
+}
+
+/// Connection id of the connection a statement runs on, so the statement can be
+/// killed. Read from the server only when it is not known yet.
+async fn session_id(
+    known: &mut KnownSessions,
+    conn: &mut SessionConn<'_, PoolConnection<MySql>>,
+) -> Result<i64> {
+    let pinned = conn.is_pinned();
+    if let Some(id) = known.get(pinned) {
+        return Ok(id);
     }
+    let id = connection_id(conn).await?;
+    known.remember(pinned, id);
+    Ok(id)
+}
 
-    async fn execute_update(&mut self, query: &str) -> Result<u64> {
-        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("Not connected"))?;
-        let result = sqlx::query(query).execute(pool).await?;
-        Ok(result.rows_affected())
+/// Publish the connection id of the connection a statement runs on
+async fn track_session(
+    session: &SessionTracker,
+    known: &mut KnownSessions,
+    conn: &mut SessionConn<'_, PoolConnection<MySql>>,
+) -> Result<SessionGuard> {
+    Ok(session.track(session_id(known, conn).await?))
+}
+
+/// Add a MAX_EXECUTION_TIME optimizer hint to a SELECT so the server stops it once the
+/// command's timeout passes. Other statements do not accept the hint and run unchanged.
+fn with_execution_time_hint(sql: &str, timeout: Option<Duration>) -> Cow<'_, str> {
+    let Some(timeout) = timeout else {
+        return Cow::Borrowed(sql);
+    };
+    let statement = sql.trim_start();
+    let is_select = statement
+        .get(..6)
+        .is_some_and(|keyword| keyword.eq_ignore_ascii_case("select"))
+        && !statement[6..].starts_with(|c: char| c.is_alphanumeric() || c == '_');
+    if !is_select {
+        return Cow::Borrowed(sql);
     }
+    let (select, rest) = sql.split_at(sql.len() - statement.len() + 6);
+    Cow::Owned(format!(
+        "{} /*+ MAX_EXECUTION_TIME({}) */{}",
+        select,
+        timeout.as_millis(),
+        rest
+    ))
+}
 
-    async fn execute_query(&mut self, query: &str) -> Result<QueryResult> {
-        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("Not connected"))?;
-        let start = Instant::now();
+/// Run a query with bound parameters on one connection and collect its rows, publishing
+/// `session_id` as the session running it
+async fn fetch_result(
+    conn: &mut MySqlConnection,
+    session: &SessionTracker,
+    session_id: i64,
+    query: &BoundQuery,
+) -> Result<QueryResult> {
+    let session = session.track(session_id);
+    let start = Instant::now();
 
-        // Execute query first
-        let rows = sqlx::query(query).fetch_all(pool).await?;
-        let execution_time = start.elapsed().as_millis();
+    // Execute query first
+    let rows = bind_params(sqlx::query(&query.sql), &query.params)
+        .fetch_all(&mut *conn)
+        .await?;
+    let execution_time = start.elapsed().as_millis();
+    drop(session);
 
-        // Extract columns from first row if available, otherwise prepare to get metadata
-        let stmt_columns_vec: Vec<_> = if !rows.is_empty() {
-            rows[0].columns().to_vec()
-        } else {
-            // For empty results, prepare statement to get column metadata
-            let prepared = pool.prepare(query).await?;
-            prepared.columns().to_vec()
-        };
+    // Extract columns from first row if available, otherwise prepare to get metadata
+    let result_columns = if !rows.is_empty() {
+        ResultColumns::from_columns(rows[0].columns())
+    } else {
+        // For empty results, prepare statement to get column metadata
+        let prepared = (&mut *conn).prepare(query.sql.as_str()).await?;
+        ResultColumns::from_columns(prepared.columns())
+    };
+
+    let result_rows: Vec<_> = rows
+        .iter()
+        .map(|row| result_columns.row_to_map(row))
+        .collect();
+    let ResultColumns {
+        columns,
+        display_names,
+        column_types,
+        ..
+    } = result_columns;
 
+    Ok(QueryResult {
+        columns,
+        column_display_names: Some(display_names),
+        column_types: Some(column_types),
+        rows: result_rows,
+        rows_affected: None,
+        execution_time,
+        final_query: None,
+    })
+}
+
+/// Send the headers of a streamed result set
+async fn send_columns(sink: &QueryStreamSender, columns: &ResultColumns) -> Result<()> {
+    send_stream_event(
+        sink,
+        QueryStreamEvent::Columns {
+            columns: columns.columns.clone(),
+            column_display_names: Some(columns.display_names.clone()),
+            column_types: Some(columns.column_types.clone()),
+        },
+    )
+    .await
+}
+
+/// Column metadata of a result set, computed once and reused for every row
+struct ResultColumns {
+    columns: Vec<String>,
+    display_names: Vec<String>,
+    column_types: HashMap<String, String>,
+    col_types: Vec<MySqlColType>,
+}
+
+impl ResultColumns {
+    fn from_columns(stmt_columns: &[MySqlColumn]) -> Self {
         // Handle duplicate column names by adding numeric suffix
         let mut column_name_counts: HashMap<String, usize> = HashMap::new();
         let mut display_names = Vec::new();
-        let columns: Vec<String> = stmt_columns_vec
+        let columns: Vec<String> = stmt_columns
             .iter()
             .map(|c| {
                 let base_name = SqlxColumn::name(c).to_string();
@@ -117,7 +246,7 @@ impl DatabaseConnection for MySQLConnection {
 
         // Pre-compute column types once
         let mut column_name_counts_reset: HashMap<String, usize> = HashMap::new();
-        let col_type_map: Vec<ColType> = stmt_columns_vec
+        let col_type_map: Vec<MySqlColType> = stmt_columns
             .iter()
             .map(|col: &sqlx::mysql::MySqlColumn| {
                 let base_name = SqlxColumn::name(col).to_string();
@@ -134,88 +263,273 @@ impl DatabaseConnection for MySQLConnection {
                 };
                 column_types.insert(col_name, type_name.clone());
 
-                match type_name.as_str() {
-                    "DATETIME" | "TIMESTAMP" => ColType::DateTime,
-                    "DATE" => ColType::Date,
-                    "TIME" => ColType::Time,
-                    "TINYINT" | "SMALLINT" | "MEDIUMINT" | "INT" | "BIGINT" => ColType::Integer,
-                    "FLOAT" | "DOUBLE" | "DECIMAL" => ColType::Float,
-                    "BOOLEAN" | "BOOL" => ColType::Boolean,
-                    "VARCHAR" | "CHAR" | "TEXT" | "TINYTEXT" | "MEDIUMTEXT" | "LONGTEXT"
-                    | "ENUM" | "SET" => ColType::String,
-                    "BLOB" | "MEDIUMBLOB" | "LONGBLOB" | "BINARY" | "VARBINARY" | "TINYBLOB" => {
-                        ColType::Blob
-                    }
-                    _ => ColType::Unknown,
-                }
+                MySqlTypeConverter::map_type(&type_name)
             })
             .collect();
 
-        let mut result_rows = Vec::with_capacity(rows.len());
-
-        // Process rows only if there are any (col_type_map will be empty for empty result set)
-        if !rows.is_empty() && !col_type_map.is_empty() {
-            for row in rows {
-                let mut row_map = HashMap::with_capacity(columns.len());
-                for (i, col) in columns.iter().enumerate() {
-                    let value = match col_type_map[i] {
-                        ColType::DateTime => row
-                            .try_get::<NaiveDateTime, _>(i)
-                            .map(|v| serde_json::json!(v.format("%Y-%m-%d %H:%M:%S").to_string()))
-                            .unwrap_or(serde_json::Value::Null),
-                        ColType::Date => row
-                            .try_get::<NaiveDate, _>(i)
-                            .map(|v| serde_json::json!(v.format("%Y-%m-%d").to_string()))
-                            .unwrap_or(serde_json::Value::Null),
-                        ColType::Time => row
-                            .try_get::<NaiveTime, _>(i)
-                            .map(|v| serde_json::json!(v.format("%H:%M:%S").to_string()))
-                            .unwrap_or(serde_json::Value::Null),
-                        ColType::Integer => row
-                            .try_get::<i64, _>(i)
-                            .map(|v| serde_json::json!(v))
-                            .unwrap_or(serde_json::Value::Null),
-                        ColType::Float => row
-                            .try_get::<f64, _>(i)
-                            .map(|v| serde_json::json!(v))
-                            .unwrap_or(serde_json::Value::Null),
-                        ColType::Boolean => row
-                            .try_get::<bool, _>(i)
-                            .map(|v| serde_json::json!(v))
-                            .unwrap_or(serde_json::Value::Null),
-                        ColType::String => row
-                            .try_get::<String, _>(i)
-                            .map(|v| serde_json::json!(v))
-                            .unwrap_or(serde_json::Value::Null),
-                        ColType::Blob => row
-                            .try_get::<Vec<u8>, _>(i)
-                            .map(|v| serde_json::json!(format!("[BLOB {} bytes]", v.len())))
-                            .unwrap_or(serde_json::Value::Null),
-                        ColType::Unknown => row
-                            .try_get::<String, _>(i)
-                            .map(|v| serde_json::json!(v))
-                            .or_else(|_| {
-                                row.try_get::<Vec<u8>, _>(i).map(|v| {
-                                    serde_json::json!(format!("[BINARY {} bytes]", v.len()))
-                                })
-                            })
-                            .unwrap_or(serde_json::Value::Null),
-                    };
-                    row_map.insert(col.clone(), value);
-                }
-                result_rows.push(row_map);
-            }
+        Self {
+            columns,
+            display_names,
+            column_types,
+            col_types: col_type_map,
         }
+    }
 
-        Ok(QueryResult {
-            columns,
-            column_display_names: Some(display_names),
-            column_types: Some(column_types),
-            rows: result_rows,
-            rows_affected: None,
+    fn row_to_map(&self, row: &MySqlRow) -> HashMap<String, serde_json::Value> {
+        let mut row_map = HashMap::with_capacity(self.columns.len());
+        for (i, col) in self.columns.iter().enumerate() {
+            let value = MySqlTypeConverter::extract_value_typed(row, i, self.col_types[i]);
+            row_map.insert(col.clone(), value);
+        }
+        row_map
+    }
+
+    /// Package mapped rows as a result set described by these columns
+    fn into_query_result(
+        self,
+        rows: Vec<HashMap<String, serde_json::Value>>,
+        rows_affected: Option<u64>,
+        execution_time: u128,
+    ) -> QueryResult {
+        QueryResult {
+            columns: self.columns,
+            column_display_names: Some(self.display_names),
+            column_types: Some(self.column_types),
+            rows,
+            rows_affected,
             execution_time,
             final_query: None,
-        })
+        }
+    }
+}
+
+/// Bind JSON parameters with their natural MySQL types. Non-integer numbers are sent as
+/// strings so DECIMAL values keep their full precision.
+fn bind_params<'q>(
+    mut query: Query<'q, MySql, MySqlArguments>,
+    params: &[serde_json::Value],
+) -> Query<'q, MySql, MySqlArguments> {
+    for param in params {
+        query = match param {
+            serde_json::Value::Null => query.bind(None::<String>),
+            serde_json::Value::Bool(b) => query.bind(*b),
+            serde_json::Value::Number(n) => {
+                if let Some(i) = n.as_i64() {
+                    query.bind(i)
+                } else if let Some(u) = n.as_u64() {
+                    query.bind(u)
+                } else {
+                    query.bind(n.to_string())
+                }
+            }
+            serde_json::Value::String(s) => query.bind(s.clone()),
+            other => query.bind(other.to_string()),
+        };
+    }
+    query
+}
+
+#[async_trait]
+impl DatabaseConnection for MySQLConnection {
+    async fn connect(&mut self, config: &ConnectionConfig) -> Result<()> {
+        self.pool = Some(MySqlPool::connect(&Self::connection_url(config)).await?);
+        Ok(())
+    }
+
+    async fn connect_session(&mut self, config: &ConnectionConfig) -> Result<()> {
+        // One physical connection that is never recycled, so session state survives.
+        // Its id is read once, whenever the pool opens it.
+        let known_id = self.known_sessions.single_connection();
+        let pool = MySqlPoolOptions::new()
+            .max_connections(1)
+            .min_connections(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+            .after_connect(move |conn, _meta| {
+                let known_id = known_id.clone();
+                Box::pin(async move {
+                    *known_id.lock().unwrap() = Some(connection_id(conn).await?);
+                    Ok(())
+                })
+            })
+            .connect(&Self::connection_url(config))
+            .await?;
+        self.pool = Some(pool);
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> Result<()> {
+        // Return the pinned session first; closing the pool waits for it and ends
+        // any open transaction with a rollback
+        self.pinned = None;
+        self.known_sessions.unpin();
+        if let Some(pool) = &self.pool {
+            pool.close().await;
+            self.pool = None;
+        }
+        Ok(())
+    }
+
+    async fn test_connection(&mut self) -> Result<bool> {
+        if let Some(pool) = &self.pool {
+            sqlx::query("SELECT 1").fetch_one(pool).await?;
+            Ok(true)
+        } else {
+            Err(anyhow!("Not connected"))
+        }
+    }
+
+    async fn execute_update(&mut self, query: &str) -> Result<u64> {
+        self.execute_update_bound(&BoundQuery::new(query)).await
+    }
+
+    async fn execute_query(&mut self, query: &str) -> Result<QueryResult> {
+        self.execute_query_bound(&BoundQuery::new(query)).await
+    }
+
+    async fn execute_raw(&mut self, query: &str) -> Result<u64> {
+        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("Not connected"))?;
+        let mut conn = sqlx_session_conn(pool, &mut self.pinned).await?;
+        let _session = track_session(&self.session, &mut self.known_sessions, &mut conn).await?;
+        // A plain string carries no arguments, so it goes over the text protocol
+        let result = (&mut *conn).execute(query).await?;
+        Ok(result.rows_affected())
+    }
+
+    async fn execute_update_bound(&mut self, query: &BoundQuery) -> Result<u64> {
+        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("Not connected"))?;
+        let mut conn = sqlx_session_conn(pool, &mut self.pinned).await?;
+        let _session = track_session(&self.session, &mut self.known_sessions, &mut conn).await?;
+        let result = bind_params(sqlx::query(&query.sql), &query.params)
+            .execute(&mut *conn)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    async fn execute_insert_read_back(
+        &mut self,
+        insert: &BoundQuery,
+        select: &BoundQuery,
+    ) -> Result<(u64, QueryResult)> {
+        // LAST_INSERT_ID() is per connection, so both statements share one
+        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("Not connected"))?;
+        let mut conn = sqlx_session_conn(pool, &mut self.pinned).await?;
+        let session_id = session_id(&mut self.known_sessions, &mut conn).await?;
+        let affected = {
+            let _session = self.session.track(session_id);
+            bind_params(sqlx::query(&insert.sql), &insert.params)
+                .execute(&mut *conn)
+                .await?
+                .rows_affected()
+        };
+        let stored = fetch_result(&mut conn, &self.session, session_id, select).await?;
+        Ok((affected, stored))
+    }
+
+    async fn execute_query_bound(&mut self, query: &BoundQuery) -> Result<QueryResult> {
+        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("Not connected"))?;
+        let mut conn = sqlx_session_conn(pool, &mut self.pinned).await?;
+        let session_id = session_id(&mut self.known_sessions, &mut conn).await?;
+        fetch_result(&mut conn, &self.session, session_id, query).await
+    }
+
+    async fn execute_query_multi(&mut self, query: &str) -> Result<Vec<QueryResult>> {
+        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("Not connected"))?;
+        let mut conn = sqlx_session_conn(pool, &mut self.pinned).await?;
+        let session = track_session(&self.session, &mut self.known_sessions, &mut conn).await?;
+        let query = with_execution_time_hint(query, self.statement_timeout);
+        let query = query.as_ref();
+        let start = Instant::now();
+
+        // A plain string runs over the text protocol, where each result set is
+        // followed by its own status packet
+        let mut groups: Vec<(Vec<MySqlRow>, MySqlQueryResult)> = Vec::new();
+        let mut rows = Vec::new();
+        {
+            let mut stream = (&mut *conn).fetch_many(query);
+            while let Some(item) = stream.try_next().await? {
+                match item {
+                    sqlx::Either::Left(done) => groups.push((std::mem::take(&mut rows), done)),
+                    sqlx::Either::Right(row) => rows.push(row),
+                }
+            }
+        }
+        let execution_time = start.elapsed().as_millis();
+        drop(session);
+
+        // The driver does not report the columns of an empty result set, only its
+        // status packet. A set that counted nothing and is followed by another status,
+        // such as the final one of a CALL, is still a result set, just without rows.
+        let last = groups.len().saturating_sub(1);
+        let mut result_sets: Vec<QueryResult> = groups
+            .iter()
+            .enumerate()
+            .filter(|(index, (rows, done))| {
+                !rows.is_empty()
+                    || (*index < last && done.rows_affected() == 0 && done.last_insert_id() == 0)
+            })
+            .map(|(_, (rows, _))| {
+                let columns = match rows.first() {
+                    Some(row) => ResultColumns::from_columns(row.columns()),
+                    None => ResultColumns::from_columns(&[]),
+                };
+                let mapped = rows.iter().map(|row| columns.row_to_map(row)).collect();
+                columns.into_query_result(mapped, None, execution_time)
+            })
+            .collect();
+
+        if result_sets.is_empty() {
+            // Nothing returned rows; describe the statement for its column names
+            let rows_affected = groups.iter().map(|(_, done)| done.rows_affected()).sum();
+            let columns = match (&mut *conn).prepare(query).await {
+                Ok(prepared) => ResultColumns::from_columns(prepared.columns()),
+                Err(_) => ResultColumns::from_columns(&[]),
+            };
+            result_sets.push(columns.into_query_result(
+                Vec::new(),
+                Some(rows_affected),
+                execution_time,
+            ));
+        }
+
+        Ok(result_sets)
+    }
+
+    async fn explain(&mut self, query: &str, analyze: bool) -> Result<ExecutionPlan> {
+        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("Not connected"))?;
+        let in_transaction = self.pinned.is_some();
+        let mut conn = sqlx_session_conn(pool, &mut self.pinned).await?;
+        let query = query.trim().trim_end_matches(';');
+
+        // ANALYZE really runs the statement, so undo whatever it changes. Its plan is
+        // only available as a text tree.
+        let sql = if analyze {
+            format!("EXPLAIN ANALYZE {}", query)
+        } else {
+            format!("EXPLAIN FORMAT=JSON {}", query)
+        };
+        let scratch = analyze.then(|| scratch_transaction_sql(SqlDialect::MySQL, in_transaction));
+        if let Some((begin, _)) = &scratch {
+            (&mut *conn).execute(begin.as_str()).await?;
+        }
+        let row = (&mut *conn).fetch_one(sql.as_str()).await;
+        if let Some((_, undo)) = scratch {
+            for statement in undo {
+                (&mut *conn).execute(statement.as_str()).await?;
+            }
+        }
+
+        let row = row?;
+        let raw: String = match row.try_get(0) {
+            Ok(text) => text,
+            Err(_) => String::from_utf8_lossy(&row.try_get::<Vec<u8>, _>(0)?).to_string(),
+        };
+
+        if analyze {
+            parse_mysql_tree_plan(&raw)
+        } else {
+            parse_mysql_json_plan(&raw)
+        }
     }
 
     async fn get_databases(&mut self) -> Result<Vec<Database>> {
@@ -285,6 +599,11 @@ impl DatabaseConnection for MySQLConnection {
                     default_value: default,
                     is_primary_key: key == "PRI",
                     is_auto_increment: extra.contains("auto_increment"),
+                    // DEFAULT_GENERATED only marks an expression default
+                    is_generated: ["VIRTUAL GENERATED", "STORED GENERATED", "PERSISTENT"]
+                        .iter()
+                        .any(|kind| extra.to_uppercase().contains(kind)),
+                    default_is_expression: extra.to_uppercase().contains("DEFAULT_GENERATED"),
                 }
             })
             .collect();
@@ -401,6 +720,7 @@ impl DatabaseConnection for MySQLConnection {
             columns,
             indexes,
             foreign_keys,
+            has_triggers: false,
         })
     }
 
@@ -460,6 +780,9 @@ impl DatabaseConnection for MySQLConnection {
                     referenced_table_name,
                     referenced_column_name,
                     relationship_type,
+                    // The schema is the database
+                    table_schema: None,
+                    referenced_table_schema: None,
                     owner,
                     ref_object_type: Some("TABLE".to_string()),
                     on_delete,
@@ -490,6 +813,33 @@ impl DatabaseConnection for MySQLConnection {
         MySqlMetadataOps::get_procedures(pool, database).await
     }
 
+    async fn get_table_ddl(&mut self, database: &str, table: &str) -> Result<Option<String>> {
+        self.show_create("TABLE", database, table, "Create Table")
+            .await
+            .map(|ddl| ddl.map(|ddl| format!("{};\n", ddl)))
+    }
+
+    async fn get_view_source(
+        &mut self,
+        database: &str,
+        view: &str,
+        _schema: Option<&str>,
+    ) -> Result<Option<String>> {
+        self.show_create("VIEW", database, view, "Create View")
+            .await
+    }
+
+    async fn get_trigger_source(
+        &mut self,
+        database: &str,
+        trigger: &str,
+        _table: &str,
+        _schema: Option<&str>,
+    ) -> Result<Option<String>> {
+        self.show_create("TRIGGER", database, trigger, "SQL Original Statement")
+            .await
+    }
+
     async fn get_procedure_source(
         &mut self,
         database: &str,
@@ -499,12 +849,33 @@ impl DatabaseConnection for MySQLConnection {
     ) -> Result<String> {
         let pool = self.pool.as_ref().ok_or_else(|| anyhow!("Not connected"))?;
 
-        // For MySQL, use INFORMATION_SCHEMA to get routine definition
         let routine_type = match procedure_type.as_deref() {
             Some("PROCEDURE") => "PROCEDURE",
             _ => "FUNCTION",
         };
 
+        // The complete CREATE statement, which needs privileges on the routine
+        let show_query = format!(
+            "SHOW CREATE {} `{}`.`{}`",
+            routine_type,
+            database.replace('`', "``"),
+            procedure_name.replace('`', "``")
+        );
+        let column = format!(
+            "Create {}",
+            if routine_type == "PROCEDURE" {
+                "Procedure"
+            } else {
+                "Function"
+            }
+        );
+        if let Ok(Some(row)) = sqlx::query(&show_query).fetch_optional(pool).await {
+            if let Some(source) = show_create_text(&row, &column) {
+                return Ok(source);
+            }
+        }
+
+        // Otherwise just the body, from INFORMATION_SCHEMA
         let query = format!(
             "SELECT ROUTINE_DEFINITION FROM INFORMATION_SCHEMA.ROUTINES 
              WHERE ROUTINE_SCHEMA = '{}' AND ROUTINE_NAME = '{}' AND ROUTINE_TYPE = '{}'",
@@ -621,7 +992,156 @@ impl DatabaseConnection for MySQLConnection {
         Ok(statistics)
     }
 
+    async fn execute_query_streaming(
+        &mut self,
+        query: &str,
+        batch_size: usize,
+        sink: &QueryStreamSender,
+    ) -> Result<u64> {
+        self.execute_query_streaming_bound(&BoundQuery::new(query), batch_size, sink)
+            .await
+    }
+
+    async fn execute_query_streaming_bound(
+        &mut self,
+        query: &BoundQuery,
+        batch_size: usize,
+        sink: &QueryStreamSender,
+    ) -> Result<u64> {
+        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("Not connected"))?;
+        let mut conn = sqlx_session_conn(pool, &mut self.pinned).await?;
+        let _session = track_session(&self.session, &mut self.known_sessions, &mut conn).await?;
+        let sql = with_execution_time_hint(&query.sql, self.statement_timeout);
+
+        // Describe up front so the grid can render headers before the first row arrives.
+        // Statements the server cannot prepare are described from their first row, as
+        // fetch_result does, and run over the text protocol when they have no parameters.
+        let prepared = (&mut *conn).prepare(sql.as_ref()).await;
+        let mut result_columns = match &prepared {
+            Ok(prepared) => Some(ResultColumns::from_columns(prepared.columns())),
+            Err(e) => {
+                tracing::debug!("Streaming an unprepared statement: {}", e);
+                None
+            }
+        };
+        if let Some(described) = &result_columns {
+            send_columns(sink, described).await?;
+        }
+
+        let mut stream = if prepared.is_err() && query.params.is_empty() {
+            (&mut *conn).fetch(sql.as_ref())
+        } else {
+            bind_params(sqlx::query(&sql), &query.params).fetch(&mut *conn)
+        };
+        let mut batch = Vec::with_capacity(batch_size);
+        let mut row_count = 0u64;
+
+        while let Some(row) = stream.try_next().await? {
+            let described = match &mut result_columns {
+                Some(described) => described,
+                None => {
+                    let described = ResultColumns::from_columns(row.columns());
+                    send_columns(sink, &described).await?;
+                    result_columns.insert(described)
+                }
+            };
+            batch.push(described.row_to_map(&row));
+            row_count += 1;
+            if batch.len() >= batch_size {
+                let rows = std::mem::take(&mut batch);
+                send_stream_event(sink, QueryStreamEvent::Rows { rows }).await?;
+            }
+        }
+
+        if result_columns.is_none() {
+            send_columns(sink, &ResultColumns::from_columns(&[])).await?;
+        }
+
+        if !batch.is_empty() {
+            send_stream_event(sink, QueryStreamEvent::Rows { rows: batch }).await?;
+        }
+
+        Ok(row_count)
+    }
+
+    async fn transaction_control(&mut self, command: &TransactionCommand) -> Result<()> {
+        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("Not connected"))?;
+        let sql = command
+            .sql(SqlDialect::MySQL)
+            .ok_or_else(|| anyhow!("{:?} is not supported by this database", command))?;
+
+        // A plain string runs over the text protocol; transaction control statements
+        // cannot be prepared everywhere
+        let mut conn = sqlx_transaction_conn(pool, &mut self.pinned, command).await?;
+        (&mut *conn).execute(sql.as_str()).await?;
+        let acquired = match conn {
+            SessionConn::Pooled(conn) => Some(conn),
+            SessionConn::Pinned(_) => None,
+        };
+        finish_transaction_command(&mut self.pinned, acquired, command);
+        if self.pinned.is_none() {
+            self.known_sessions.unpin();
+        }
+        Ok(())
+    }
+
+    fn in_transaction(&self) -> bool {
+        self.pinned.is_some()
+    }
+
+    fn supports_transactions(&self) -> bool {
+        true
+    }
+
+    fn set_statement_timeout(&mut self, timeout: Option<Duration>) {
+        self.statement_timeout = timeout;
+    }
+
+    fn set_session_tracker(&mut self, tracker: SessionTracker) {
+        self.session = tracker;
+    }
+
+    async fn cancel(&mut self, session_id: i64) -> Result<()> {
+        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("Not connected"))?;
+        sqlx::query(&format!("KILL QUERY {}", session_id))
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
     fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_execution_time_hint_on_select() {
+        let timeout = Some(Duration::from_secs(5));
+        assert_eq!(
+            with_execution_time_hint("  select * from t", timeout),
+            "  select /*+ MAX_EXECUTION_TIME(5000) */ * from t"
+        );
+        assert_eq!(
+            with_execution_time_hint("SELECT\n1", timeout),
+            "SELECT /*+ MAX_EXECUTION_TIME(5000) */\n1"
+        );
+    }
+
+    #[test]
+    fn test_execution_time_hint_skips_other_statements() {
+        let timeout = Some(Duration::from_secs(5));
+        assert_eq!(
+            with_execution_time_hint("UPDATE t SET a = 1", timeout),
+            "UPDATE t SET a = 1"
+        );
+        assert_eq!(
+            with_execution_time_hint("selected_rows()", timeout),
+            "selected_rows()"
+        );
+        assert_eq!(with_execution_time_hint("SELECT 1", None), "SELECT 1");
+    }
+}
@@ -1,6 +1,7 @@
 pub mod driver;
 pub mod metadata_ops;
 pub mod query_builder;
+pub mod type_converter;
 
 pub use driver::MySQLConnection;
 pub use query_builder::MySQLQueryBuilder;
@@ -1,5 +1,7 @@
+use crate::db::bound_query::{self, BoundQuery, InsertReadBack};
 use crate::db::traits::{CRUDQueryBuilder, QueryBuilder};
 use crate::models::save_request::EditedRow;
+use crate::models::schema::TableSchema;
 use crate::models::table_request::*;
 use anyhow::Result;
 use std::collections::HashMap;
@@ -9,23 +11,36 @@ pub struct MySQLQueryBuilder;
 
 impl QueryBuilder for MySQLQueryBuilder {
     fn build_select_query(&self, request: &QueryRequest) -> Result<String> {
-        let table = self.format_table_name(request);
-        let mut query = format!("SELECT * FROM {}", table);
-
-        if !request.filters.is_empty() {
-            let where_clause = self.build_where_clause(&request.filters)?;
-            query.push_str(&format!(" WHERE {}", where_clause));
-        }
+        let where_clause = if request.filters.is_empty() {
+            None
+        } else {
+            Some(self.build_where_clause(&request.filters)?)
+        };
 
-        if !request.order_by.is_empty() {
-            let order_clause = self.build_order_by_clause(&request.order_by);
-            query.push_str(&format!(" ORDER BY {}", order_clause));
-        }
+        Ok(self.assemble_select(request, where_clause))
+    }
 
-        let pagination = self.build_pagination_clause(request.limit, request.offset);
-        query.push_str(&format!(" {}", pagination));
+    fn build_select_query_bound(
+        &self,
+        request: &QueryRequest,
+        table_schema: Option<&TableSchema>,
+    ) -> Result<BoundQuery> {
+        let mut params = Vec::new();
+        let where_clause = if request.filters.is_empty() {
+            None
+        } else {
+            Some(bound_query::build_where_clause(
+                self,
+                &request.filters,
+                table_schema,
+                &mut params,
+            )?)
+        };
 
-        Ok(query)
+        Ok(BoundQuery {
+            sql: self.assemble_select(request, where_clause),
+            params,
+        })
     }
 
     fn quote_identifier(&self, identifier: &str) -> String {
@@ -76,11 +91,39 @@ impl QueryBuilder for MySQLQueryBuilder {
     }
 
     fn build_pagination_clause(&self, limit: usize, offset: usize) -> String {
+        // A limit of 0 reads every row
+        if limit == 0 {
+            return String::new();
+        }
         format!("LIMIT {} OFFSET {}", limit, offset)
     }
+
+    fn single_row_limit(&self) -> Option<&'static str> {
+        // MySQL has no stable row address, but UPDATE and DELETE accept LIMIT
+        Some(" LIMIT 1")
+    }
 }
 
 impl MySQLQueryBuilder {
+    fn assemble_select(&self, request: &QueryRequest, where_clause: Option<String>) -> String {
+        let table = self.format_table_name(request);
+        let mut query = format!("SELECT * FROM {}", table);
+
+        if let Some(where_clause) = where_clause {
+            query.push_str(&format!(" WHERE {}", where_clause));
+        }
+
+        if !request.order_by.is_empty() {
+            let order_clause = self.build_order_by_clause(&request.order_by);
+            query.push_str(&format!(" ORDER BY {}", order_clause));
+        }
+
+        let pagination = self.build_pagination_clause(request.limit, request.offset);
+        query.push_str(&format!(" {}", pagination));
+
+        query
+    }
+
     pub fn build_filter_condition(&self, filter: &Filter) -> Result<String> {
         let column = self.quote_identifier(&filter.column);
 
@@ -191,206 +234,150 @@ impl MySQLQueryBuilder {
 }
 
 impl CRUDQueryBuilder for MySQLQueryBuilder {
-    fn build_insert_query(
-        &self,
-        table: &str,
-        _schema: Option<&str>,
-        row: &HashMap<String, serde_json::Value>,
-        table_schema: &crate::models::schema::TableSchema,
-    ) -> Result<String> {
-        if row.is_empty() {
-            anyhow::bail!("Cannot insert empty row");
+    fn format_value(&self, val: &serde_json::Value) -> String {
+        match val {
+            serde_json::Value::Null => "NULL".to_string(),
+            serde_json::Value::Bool(b) => if *b { "1" } else { "0" }.to_string(),
+            serde_json::Value::Number(n) => n.to_string(),
+            serde_json::Value::String(s) => format!("'{}'", self.escape_sql_string(s)),
+            serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
+                format!("'{}'", self.escape_sql_string(&val.to_string()))
+            }
         }
+    }
 
-        // Get column order from table_schema to ensure consistent column order
-        let columns: Vec<String> = table_schema
-            .columns
-            .iter()
-            .filter_map(|col| {
-                if row.contains_key(&col.name) {
-                    Some(col.name.clone())
-                } else {
-                    None
-                }
-            })
-            .collect();
-
-        let columns_str = columns
-            .iter()
-            .map(|c| self.quote_identifier(c))
-            .collect::<Vec<_>>()
-            .join(", ");
-
-        let values_str = columns
-            .iter()
-            .map(|col| self.format_value(&row[col]))
-            .collect::<Vec<_>>()
-            .join(", ");
-
-        Ok(format!(
-            "INSERT INTO {} ({}) VALUES ({});",
-            self.quote_identifier(table),
-            columns_str,
-            values_str
-        ))
+    fn crud_table_name(&self, table: &str, _schema: Option<&str>) -> String {
+        self.quote_identifier(table)
     }
 
-    fn build_update_query(
+    fn build_insert_query_bound(
         &self,
         table: &str,
-        _schema: Option<&str>,
-        edited_row: &EditedRow,
-        primary_keys: &[&String],
-        table_schema: &crate::models::schema::TableSchema,
-    ) -> Result<String> {
-        let updated_data = &edited_row.updated_data;
-        let original_data = &edited_row.original_data;
-
-        if updated_data.is_empty() {
-            anyhow::bail!("Cannot update with no columns");
-        }
+        schema: Option<&str>,
+        row: &HashMap<String, serde_json::Value>,
+        table_schema: &TableSchema,
+    ) -> Result<BoundQuery> {
+        let table_name = self.crud_table_name(table, schema);
+        bound_query::build_insert(self, &table_name, row, table_schema)
+    }
 
-        // Build SET clause using table_schema column order (exclude primary keys)
-        let set_parts: Result<Vec<String>> = table_schema
+    fn build_insert_returning_bound(
+        &self,
+        table: &str,
+        schema: Option<&str>,
+        row: &HashMap<String, serde_json::Value>,
+        table_schema: &TableSchema,
+    ) -> Result<(BoundQuery, InsertReadBack)> {
+        let table_name = self.crud_table_name(table, schema);
+        let query = bound_query::build_insert(self, &table_name, row, table_schema)?;
+
+        // LAST_INSERT_ID() only reflects generated values, not an id given explicitly
+        let generated = table_schema.columns.iter().find(|col| {
+            col.is_auto_increment && row.get(&col.name).is_none_or(|value| value.is_null())
+        });
+        let primary_keys: Vec<&String> = table_schema
             .columns
             .iter()
-            .filter(|col| {
-                updated_data.contains_key(&col.name)
-                    && !primary_keys
-                        .iter()
-                        .any(|pk| pk.as_str() == col.name.as_str())
-            })
-            .map(|col| {
-                let val = &updated_data[&col.name];
-                Ok(format!(
-                    "{} = {}",
-                    self.quote_identifier(&col.name),
-                    self.format_value(val)
-                ))
-            })
+            .filter(|col| col.is_primary_key)
+            .map(|col| &col.name)
             .collect();
-
-        let set_clause = set_parts?.join(", ");
-
-        if set_clause.is_empty() {
-            anyhow::bail!("No columns to update (all are primary keys)");
-        }
-
-        // Build WHERE clause
-        let where_clause = if !primary_keys.is_empty() {
-            // Use primary keys
-            primary_keys
+        let read_back = if let Some(col) = generated {
+            InsertReadBack::Select(BoundQuery::new(format!(
+                "SELECT * FROM {} WHERE {} = LAST_INSERT_ID()",
+                table_name,
+                self.quote_identifier(&col.name)
+            )))
+        } else if !primary_keys.is_empty()
+            && primary_keys
                 .iter()
-                .map(|pk| {
-                    let val = original_data.get(pk.as_str()).ok_or_else(|| {
-                        anyhow::anyhow!("Primary key {} not found in original data", pk)
-                    })?;
-                    Ok(format!(
-                        "{} {}",
-                        self.quote_identifier(pk),
-                        self.format_where_condition(val)
-                    ))
-                })
-                .collect::<Result<Vec<_>>>()?
-                .join(" AND ")
+                .all(|pk| row.get(pk.as_str()).is_some_and(|value| !value.is_null()))
+        {
+            InsertReadBack::Select(bound_query::build_select_row(
+                self,
+                &table_name,
+                row,
+                &primary_keys,
+                table_schema,
+            )?)
         } else {
-            // Fallback: use all original columns
-            original_data
-                .iter()
-                .map(|(col, val)| {
-                    Ok(format!(
-                        "{} {}",
-                        self.quote_identifier(col),
-                        self.format_where_condition(val)
-                    ))
-                })
-                .collect::<Result<Vec<_>>>()?
-                .join(" AND ")
+            InsertReadBack::Unavailable
         };
-
-        if where_clause.is_empty() {
-            anyhow::bail!("Cannot generate WHERE clause");
-        }
-
-        Ok(format!(
-            "UPDATE {} SET {} WHERE {};",
-            self.quote_identifier(table),
-            set_clause,
-            where_clause
-        ))
+        Ok((query, read_back))
     }
 
-    fn build_delete_query(
+    fn build_insert_batch_bound(
         &self,
         table: &str,
-        _schema: Option<&str>,
-        row: &HashMap<String, serde_json::Value>,
-        primary_keys: &[&String],
-    ) -> Result<String> {
-        // Build WHERE clause
-        let where_clause = if !primary_keys.is_empty() {
-            // Use primary keys
-            primary_keys
+        schema: Option<&str>,
+        columns: &[String],
+        rows: &[Vec<serde_json::Value>],
+        table_schema: &TableSchema,
+        upsert: bool,
+    ) -> Result<BoundQuery> {
+        let suffix = if upsert {
+            let (keys, updates) = bound_query::upsert_columns(columns, table_schema)?;
+            // Assigning a key to itself leaves rows that only have key columns as they are
+            let assignments: Vec<String> = if updates.is_empty() { &keys } else { &updates }
                 .iter()
-                .map(|pk| {
-                    let val = row
-                        .get(pk.as_str())
-                        .ok_or_else(|| anyhow::anyhow!("Primary key {} not found in row", pk))?;
-                    Ok(format!(
-                        "{} {}",
-                        self.quote_identifier(pk),
-                        self.format_where_condition(val)
-                    ))
+                .map(|c| {
+                    let col = self.quote_identifier(c);
+                    format!("{} = VALUES({})", col, col)
                 })
-                .collect::<Result<Vec<_>>>()?
-                .join(" AND ")
+                .collect();
+            format!(" ON DUPLICATE KEY UPDATE {}", assignments.join(", "))
         } else {
-            // Fallback: use all columns from row
-            row.iter()
-                .map(|(col, val)| {
-                    Ok(format!(
-                        "{} {}",
-                        self.quote_identifier(col),
-                        self.format_where_condition(val)
-                    ))
-                })
-                .collect::<Result<Vec<_>>>()?
-                .join(" AND ")
+            String::new()
         };
+        let table_name = self.crud_table_name(table, schema);
+        bound_query::build_insert_batch(self, &table_name, columns, rows, table_schema, &suffix)
+    }
 
-        if where_clause.is_empty() {
-            anyhow::bail!("Cannot generate WHERE clause for DELETE");
-        }
+    fn build_update_query_bound(
+        &self,
+        table: &str,
+        schema: Option<&str>,
+        edited_row: &EditedRow,
+        primary_keys: &[&String],
+        table_schema: &TableSchema,
+    ) -> Result<BoundQuery> {
+        let table_name = self.crud_table_name(table, schema);
+        bound_query::build_update(self, &table_name, edited_row, primary_keys, table_schema)
+    }
 
-        Ok(format!(
-            "DELETE FROM {} WHERE {};",
-            self.quote_identifier(table),
-            where_clause
-        ))
+    fn build_update_query_checked_bound(
+        &self,
+        table: &str,
+        schema: Option<&str>,
+        edited_row: &EditedRow,
+        primary_keys: &[&String],
+        table_schema: &TableSchema,
+    ) -> Result<BoundQuery> {
+        let table_name = self.crud_table_name(table, schema);
+        bound_query::build_update_checked(self, &table_name, edited_row, primary_keys, table_schema)
     }
 
-    fn format_value(&self, val: &serde_json::Value) -> String {
-        match val {
-            serde_json::Value::Null => "NULL".to_string(),
-            serde_json::Value::Bool(b) => if *b { "1" } else { "0" }.to_string(),
-            serde_json::Value::Number(n) => n.to_string(),
-            serde_json::Value::String(s) => format!("'{}'", self.escape_sql_string(s)),
-            serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
-                format!("'{}'", self.escape_sql_string(&val.to_string()))
-            }
-        }
+    fn build_select_row_bound(
+        &self,
+        table: &str,
+        schema: Option<&str>,
+        row: &HashMap<String, serde_json::Value>,
+        primary_keys: &[&String],
+        table_schema: &TableSchema,
+    ) -> Result<BoundQuery> {
+        let table_name = self.crud_table_name(table, schema);
+        bound_query::build_select_row(self, &table_name, row, primary_keys, table_schema)
     }
 
-    fn format_where_condition(&self, val: &serde_json::Value) -> String {
-        match val {
-            serde_json::Value::Null => "IS NULL".to_string(),
-            serde_json::Value::Bool(b) => if *b { "= 1" } else { "= 0" }.to_string(),
-            serde_json::Value::Number(n) => format!("= {}", n),
-            serde_json::Value::String(s) => format!("= '{}'", self.escape_sql_string(s)),
-            serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
-                format!("= '{}'", self.escape_sql_string(&val.to_string()))
-            }
-        }
+    fn build_delete_query_bound(
+        &self,
+        table: &str,
+        schema: Option<&str>,
+        row: &HashMap<String, serde_json::Value>,
+        primary_keys: &[&String],
+        table_schema: &TableSchema,
+    ) -> Result<BoundQuery> {
+        let table_name = self.crud_table_name(table, schema);
+        bound_query::build_delete(self, &table_name, row, primary_keys, table_schema)
     }
 
     fn escape_sql_string(&self, s: &str) -> String {
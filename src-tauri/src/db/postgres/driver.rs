@@ -1,88 +1,170 @@
+use crate::db::bound_query::BoundQuery;
+use crate::db::explain::parse_postgres_plan;
 use crate::db::postgres::metadata_ops::*;
 use crate::db::postgres::type_converter::{extract_pg_value_typed, map_pg_type, PgColType};
-use crate::db::traits::DatabaseConnection;
-use crate::models::{connection::*, query_result::*, schema::*};
+use crate::db::sql_splitter::SqlDialect;
+use crate::db::traits::{
+    send_stream_event, DatabaseConnection, KnownSessions, QueryStreamSender, SessionGuard,
+    SessionTracker,
+};
+use crate::db::transaction::{
+    finish_transaction_command, scratch_transaction_sql, sqlx_session_conn, sqlx_transaction_conn,
+    SessionConn, TransactionCommand,
+};
+use crate::models::{connection::*, explain::ExecutionPlan, query_result::*, schema::*};
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
-use sqlx::{Column as SqlxColumn, Executor, PgPool, Row, Statement, TypeInfo};
+use futures::TryStreamExt;
+use sqlx::pool::PoolConnection;
+use sqlx::postgres::{PgArguments, PgColumn, PgPoolOptions, PgRow, Postgres};
+use sqlx::query::Query;
+use sqlx::{Column as SqlxColumn, Executor, PgConnection, PgPool, Row, Statement, TypeInfo};
 use std::collections::HashMap;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 pub struct PostgresConnection {
     pool: Option<PgPool>,
+    session: SessionTracker,
+    known_sessions: KnownSessions,
+    /// Limit of the current timed command, applied as the server's statement_timeout
+    statement_timeout: Option<Duration>,
+    /// Session pinned by an open manual transaction
+    pinned: Option<PoolConnection<Postgres>>,
 }
 
 impl PostgresConnection {
     pub fn new() -> Self {
-        Self { pool: None }
+        Self {
+            pool: None,
+            session: SessionTracker::default(),
+            known_sessions: KnownSessions::default(),
+            statement_timeout: None,
+            pinned: None,
+        }
     }
-}
 
-impl Default for PostgresConnection {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
-#[async_trait]
-impl DatabaseConnection for PostgresConnection {
-    async fn connect(&mut self, config: &ConnectionConfig) -> Result<()> {
-        let url = format!(
+    fn connection_url(config: &ConnectionConfig) -> String {
+        format!(
             "postgres://{}:{}@{}:{}/{}",
             config.username.as_ref().unwrap_or(&"postgres".to_string()),
             config.password.as_ref().unwrap_or(&"".to_string()),
             config.host,
             config.port,
             config.database.as_ref().unwrap_or(&"postgres".to_string())
-        );
-
-        self.pool = Some(PgPool::connect(&url).await?);
-        Ok(())
+        )
     }
+}
 
-    async fn disconnect(&mut self) -> Result<()> {
-        if let Some(pool) = &self.pool {
-            pool.close().await;
-            self.pool = None;
+async fn backend_pid(conn: &mut PgConnection) -> Result<i64, sqlx::Error> {
+    let pid: i32 = sqlx::query_scalar("SELECT pg_backend_pid()")
+        .fetch_one(conn)
+        .await?;
+    Ok(pid as i64)
+}
+
+/// Publish the backend PID of the connection a statement runs on so it can be
+/// cancelled, reading it from the server only when it is not known yet
+async fn track_session(
+    session: &SessionTracker,
+    known: &mut KnownSessions,
+    conn: &mut SessionConn<'_, PoolConnection<Postgres>>,
+) -> Result<SessionGuard> {
+    let pinned = conn.is_pinned();
+    let pid = match known.get(pinned) {
+        Some(pid) => pid,
+        None => {
+            let pid = backend_pid(conn).await?;
+            known.remember(pinned, pid);
+            pid
         }
-        Ok(())
+    };
+    Ok(session.track(pid))
+}
+
+/// Set the server's statement_timeout for a timed command, returning the session's
+/// previous value to restore once the command's statement is done
+async fn set_statement_timeout(
+    conn: &mut PgConnection,
+    timeout: Option<Duration>,
+) -> Result<Option<String>, sqlx::Error> {
+    let Some(timeout) = timeout else {
+        return Ok(None);
+    };
+    // The subquery reads the current value before the outer select replaces it
+    let (previous, _): (String, String) = sqlx::query_as(
+        "SELECT previous, set_config('statement_timeout', $1, false) \
+         FROM (SELECT current_setting('statement_timeout') AS previous OFFSET 0) AS current",
+    )
+    .bind(timeout.as_millis().to_string())
+    .fetch_one(conn)
+    .await?;
+    Ok(Some(previous))
+}
+
+/// Put back the statement_timeout a timed command replaced. This only fails in an
+/// aborted transaction, whose rollback restores the value anyway.
+async fn restore_statement_timeout(conn: &mut PgConnection, previous: Option<String>) {
+    if let Some(previous) = previous {
+        let _ = sqlx::query("SELECT set_config('statement_timeout', $1, false)")
+            .bind(previous)
+            .execute(conn)
+            .await;
     }
+}
 
-    async fn test_connection(&mut self) -> Result<bool> {
-        if let Some(pool) = &self.pool {
-            sqlx::query("SELECT 1").fetch_one(pool).await?;
-            Ok(true)
-        } else {
-            Err(anyhow!("Not connected"))
+/// Stream the rows of a query to the frontend in batches, returning how many were sent
+async fn stream_rows(
+    conn: &mut PgConnection,
+    query: &BoundQuery,
+    batch_size: usize,
+    sink: &QueryStreamSender,
+) -> Result<u64> {
+    // Describe up front so the grid can render headers before the first row arrives
+    let prepared = (&mut *conn).prepare(query.sql.as_str()).await?;
+    let result_columns = ResultColumns::from_columns(prepared.columns());
+    send_stream_event(
+        sink,
+        QueryStreamEvent::Columns {
+            columns: result_columns.columns.clone(),
+            column_display_names: Some(result_columns.display_names.clone()),
+            column_types: Some(result_columns.column_types.clone()),
+        },
+    )
+    .await?;
+
+    let mut stream = bind_params(sqlx::query(&query.sql), &query.params).fetch(&mut *conn);
+    let mut batch = Vec::with_capacity(batch_size);
+    let mut row_count = 0u64;
+
+    while let Some(row) = stream.try_next().await? {
+        batch.push(result_columns.row_to_map(&row));
+        row_count += 1;
+        if batch.len() >= batch_size {
+            let rows = std::mem::take(&mut batch);
+            send_stream_event(sink, QueryStreamEvent::Rows { rows }).await?;
         }
     }
 
-    async fn execute_update(&mut self, query: &str) -> Result<u64> {
-        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("Not connected"))?;
-        let result = sqlx::query(query).execute(pool).await?;
-        Ok(result.rows_affected())
+    if !batch.is_empty() {
+        send_stream_event(sink, QueryStreamEvent::Rows { rows: batch }).await?;
     }
 
-    async fn execute_query(&mut self, query: &str) -> Result<QueryResult> {
-        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("Not connected"))?;
-        let start = Instant::now();
-
-        // Execute query first
-        let rows = sqlx::query(query).fetch_all(pool).await?;
-        let execution_time = start.elapsed().as_millis();
+    Ok(row_count)
+}
 
-        // Extract columns from first row if available, otherwise prepare to get metadata
-        let stmt_columns_vec: Vec<_> = if !rows.is_empty() {
-            rows[0].columns().to_vec()
-        } else {
-            // For empty results, prepare statement to get column metadata
-            let prepared = pool.prepare(query).await?;
-            prepared.columns().to_vec()
-        };
+/// Column metadata of a result set, computed once and reused for every row
+struct ResultColumns {
+    columns: Vec<String>,
+    display_names: Vec<String>,
+    column_types: HashMap<String, String>,
+    type_info: Vec<(String, bool, PgColType)>,
+}
 
+impl ResultColumns {
+    fn from_columns(stmt_columns: &[PgColumn]) -> Self {
         let mut column_name_counts: HashMap<String, usize> = HashMap::new();
         let mut display_names = Vec::new();
-        let columns: Vec<String> = stmt_columns_vec
+        let columns: Vec<String> = stmt_columns
             .iter()
             .map(|c| {
                 let base_name = SqlxColumn::name(c).to_string();
@@ -97,7 +179,7 @@ impl DatabaseConnection for PostgresConnection {
             })
             .collect();
 
-        let col_type_info: Vec<(String, bool, PgColType)> = stmt_columns_vec
+        let type_info: Vec<(String, bool, PgColType)> = stmt_columns
             .iter()
             .map(|col| {
                 let type_name = col.type_info().name();
@@ -112,33 +194,221 @@ impl DatabaseConnection for PostgresConnection {
                 (base_type, is_array, col_type)
             })
             .collect();
+        let column_types = columns
+            .iter()
+            .zip(stmt_columns)
+            .map(|(name, col)| (name.clone(), col.type_info().name().to_string()))
+            .collect();
+
+        Self {
+            columns,
+            display_names,
+            column_types,
+            type_info,
+        }
+    }
+
+    fn row_to_map(&self, row: &PgRow) -> HashMap<String, serde_json::Value> {
+        let mut row_map = HashMap::with_capacity(self.columns.len());
+        for (i, col) in self.columns.iter().enumerate() {
+            let (base_type, is_array, col_type) = &self.type_info[i];
+            let value = extract_pg_value_typed(row, i, *col_type, *is_array, base_type);
+            row_map.insert(col.clone(), value);
+        }
+        row_map
+    }
+}
+
+/// Bind JSON parameters as text; the query builder casts each placeholder to its column type
+fn bind_params<'q>(
+    mut query: Query<'q, Postgres, PgArguments>,
+    params: &[serde_json::Value],
+) -> Query<'q, Postgres, PgArguments> {
+    for param in params {
+        let text = match param {
+            serde_json::Value::Null => None,
+            serde_json::Value::String(s) => Some(s.clone()),
+            other => Some(other.to_string()),
+        };
+        query = query.bind(text);
+    }
+    query
+}
+
+/// Field of a `COPY ... WITH (FORMAT csv)` row: NULL unquoted and empty, anything else
+/// quoted, so empty text stays distinct from NULL
+fn copy_csv_field(value: &serde_json::Value) -> String {
+    let text = match value {
+        serde_json::Value::Null => return String::new(),
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    format!("\"{}\"", text.replace('"', "\"\""))
+}
+
+impl Default for PostgresConnection {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl DatabaseConnection for PostgresConnection {
+    async fn connect(&mut self, config: &ConnectionConfig) -> Result<()> {
+        self.pool = Some(PgPool::connect(&Self::connection_url(config)).await?);
+        Ok(())
+    }
+
+    async fn connect_session(&mut self, config: &ConnectionConfig) -> Result<()> {
+        // One physical connection that is never recycled, so session state survives.
+        // Its PID is read once, whenever the pool opens it.
+        let known_pid = self.known_sessions.single_connection();
+        let pool = PgPoolOptions::new()
+            .max_connections(1)
+            .min_connections(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+            .after_connect(move |conn, _meta| {
+                let known_pid = known_pid.clone();
+                Box::pin(async move {
+                    *known_pid.lock().unwrap() = Some(backend_pid(conn).await?);
+                    Ok(())
+                })
+            })
+            .connect(&Self::connection_url(config))
+            .await?;
+        self.pool = Some(pool);
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> Result<()> {
+        // Return the pinned session first; closing the pool waits for it and ends
+        // any open transaction with a rollback
+        self.pinned = None;
+        self.known_sessions.unpin();
+        if let Some(pool) = &self.pool {
+            pool.close().await;
+            self.pool = None;
+        }
+        Ok(())
+    }
+
+    async fn test_connection(&mut self) -> Result<bool> {
+        if let Some(pool) = &self.pool {
+            sqlx::query("SELECT 1").fetch_one(pool).await?;
+            Ok(true)
+        } else {
+            Err(anyhow!("Not connected"))
+        }
+    }
+
+    async fn execute_update(&mut self, query: &str) -> Result<u64> {
+        self.execute_update_bound(&BoundQuery::new(query)).await
+    }
+
+    async fn execute_query(&mut self, query: &str) -> Result<QueryResult> {
+        self.execute_query_bound(&BoundQuery::new(query)).await
+    }
 
-        let mut result_rows = Vec::with_capacity(rows.len());
+    async fn execute_update_bound(&mut self, query: &BoundQuery) -> Result<u64> {
+        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("Not connected"))?;
+        let mut conn = sqlx_session_conn(pool, &mut self.pinned).await?;
+        let _session = track_session(&self.session, &mut self.known_sessions, &mut conn).await?;
+        let result = bind_params(sqlx::query(&query.sql), &query.params)
+            .execute(&mut *conn)
+            .await?;
+        Ok(result.rows_affected())
+    }
 
-        // Process rows only if there are any (col_type_info will be empty for empty result set)
-        if !rows.is_empty() && !col_type_info.is_empty() {
-            for row in rows {
-                let mut row_map = HashMap::with_capacity(columns.len());
-                for (i, col) in columns.iter().enumerate() {
-                    let (base_type, is_array, col_type) = &col_type_info[i];
-                    let value = extract_pg_value_typed(&row, i, *col_type, *is_array, base_type);
-                    row_map.insert(col.clone(), value);
+    async fn execute_query_bound(&mut self, query: &BoundQuery) -> Result<QueryResult> {
+        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("Not connected"))?;
+        let mut conn = sqlx_session_conn(pool, &mut self.pinned).await?;
+        let session = track_session(&self.session, &mut self.known_sessions, &mut conn).await?;
+        let previous_timeout = set_statement_timeout(&mut conn, self.statement_timeout).await?;
+        let start = Instant::now();
+
+        // Execute query first, keeping the completion count for statements without rows
+        let mut rows = Vec::new();
+        let mut rows_affected = 0;
+        let fetched: Result<(), sqlx::Error> = async {
+            let mut stream =
+                (&mut *conn).fetch_many(bind_params(sqlx::query(&query.sql), &query.params));
+            while let Some(item) = stream.try_next().await? {
+                match item {
+                    sqlx::Either::Left(done) => rows_affected += done.rows_affected(),
+                    sqlx::Either::Right(row) => rows.push(row),
                 }
-                result_rows.push(row_map);
             }
+            Ok(())
         }
+        .await;
+        let execution_time = start.elapsed().as_millis();
+        drop(session);
+        restore_statement_timeout(&mut conn, previous_timeout).await;
+        fetched?;
+
+        // Extract columns from first row if available, otherwise prepare to get metadata
+        let result_columns = if !rows.is_empty() {
+            ResultColumns::from_columns(rows[0].columns())
+        } else {
+            // For empty results, prepare statement to get column metadata
+            let prepared = (&mut *conn).prepare(query.sql.as_str()).await?;
+            ResultColumns::from_columns(prepared.columns())
+        };
+
+        let result_rows: Vec<_> = rows
+            .iter()
+            .map(|row| result_columns.row_to_map(row))
+            .collect();
+        let ResultColumns {
+            columns,
+            display_names,
+            column_types,
+            ..
+        } = result_columns;
+        // Statements that return no columns, such as DML and DDL, report a count instead
+        let rows_affected = columns.is_empty().then_some(rows_affected);
 
         Ok(QueryResult {
             columns,
             column_display_names: Some(display_names),
-            column_types: None,
+            column_types: Some(column_types),
             rows: result_rows,
-            rows_affected: None,
+            rows_affected,
             execution_time,
             final_query: None,
         })
     }
 
+    async fn explain(&mut self, query: &str, analyze: bool) -> Result<ExecutionPlan> {
+        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("Not connected"))?;
+        let in_transaction = self.pinned.is_some();
+        let mut conn = sqlx_session_conn(pool, &mut self.pinned).await?;
+        let query = query.trim().trim_end_matches(';');
+
+        if !analyze {
+            let sql = format!("EXPLAIN (FORMAT JSON) {}", query);
+            let plan: serde_json::Value = sqlx::query_scalar(&sql).fetch_one(&mut *conn).await?;
+            return parse_postgres_plan(&plan.to_string(), false);
+        }
+
+        // ANALYZE really runs the statement, so undo whatever it changes
+        let (begin, undo) = scratch_transaction_sql(SqlDialect::PostgreSQL, in_transaction);
+        (&mut *conn).execute(begin.as_str()).await?;
+        if let Some(timeout) = self.statement_timeout {
+            let sql = format!("SET LOCAL statement_timeout = {}", timeout.as_millis());
+            (&mut *conn).execute(sql.as_str()).await?;
+        }
+        let sql = format!("EXPLAIN (FORMAT JSON, ANALYZE, BUFFERS) {}", query);
+        let plan: Result<serde_json::Value, sqlx::Error> =
+            sqlx::query_scalar(&sql).fetch_one(&mut *conn).await;
+        for statement in undo {
+            (&mut *conn).execute(statement.as_str()).await?;
+        }
+
+        parse_postgres_plan(&plan?.to_string(), true)
+    }
+
     async fn get_databases(&mut self) -> Result<Vec<Database>> {
         let pool = self.pool.as_ref().ok_or_else(|| anyhow!("Not connected"))?;
         let rows = sqlx::query(
@@ -200,8 +470,9 @@ impl DatabaseConnection for PostgresConnection {
         let table_lower = table_name.to_lowercase();
 
         let query = format!(
-            "SELECT c.column_name, c.data_type, c.character_maximum_length, c.numeric_precision,
+            "SELECT c.column_name, c.data_type, c.udt_name, c.character_maximum_length, c.numeric_precision,
                     c.numeric_scale, c.is_nullable, c.column_default, c.ordinal_position,
+                    c.is_identity, c.is_generated,
                     COALESCE(tc.constraint_type = 'PRIMARY KEY', false) as is_primary
             FROM information_schema.columns c
             LEFT JOIN information_schema.constraint_column_usage ccu 
@@ -227,8 +498,16 @@ impl DatabaseConnection for PostgresConnection {
                 let char_max_length: Option<i32> = row.try_get("character_maximum_length").ok();
                 let numeric_precision: Option<i32> = row.try_get("numeric_precision").ok();
                 let numeric_scale: Option<i32> = row.try_get("numeric_scale").ok();
+                let udt_name: String = row.try_get("udt_name").unwrap_or_default();
 
                 data_type = match data_type.as_str() {
+                    // Enums, domains and composites: use the actual type name
+                    "USER-DEFINED" if !udt_name.is_empty() => udt_name,
+                    // Array types are reported as "_elem" in udt_name
+                    "ARRAY" => match udt_name.strip_prefix('_') {
+                        Some(element) => format!("{}[]", element),
+                        None => data_type,
+                    },
                     "character varying" => {
                         if let Some(length) = char_max_length {
                             format!("varchar({})", length)
@@ -260,10 +539,14 @@ impl DatabaseConnection for PostgresConnection {
                     _ => data_type,
                 };
 
-                let is_auto_increment = default_value
-                    .as_ref()
-                    .map(|v| v.contains("nextval"))
-                    .unwrap_or(false);
+                // Serial columns default to nextval(); identity columns have no default
+                let is_identity: String = row.try_get("is_identity").unwrap_or_default();
+                let is_auto_increment = is_identity == "YES"
+                    || default_value
+                        .as_ref()
+                        .map(|v| v.contains("nextval"))
+                        .unwrap_or(false);
+                let is_generated: String = row.try_get("is_generated").unwrap_or_default();
 
                 Column {
                     name,
@@ -272,6 +555,8 @@ impl DatabaseConnection for PostgresConnection {
                     default_value,
                     is_primary_key,
                     is_auto_increment,
+                    is_generated: is_generated == "ALWAYS",
+                    default_is_expression: false,
                 }
             })
             .collect();
@@ -321,10 +606,12 @@ impl DatabaseConnection for PostgresConnection {
             FROM information_schema.table_constraints AS tc
             JOIN information_schema.key_column_usage AS kcu
                 ON tc.constraint_name = kcu.constraint_name AND tc.table_schema = kcu.table_schema
-            JOIN information_schema.constraint_column_usage AS ccu
-                ON ccu.constraint_name = tc.constraint_name AND ccu.table_schema = tc.table_schema
-            LEFT JOIN information_schema.referential_constraints AS rc
+            JOIN information_schema.referential_constraints AS rc
                 ON tc.constraint_name = rc.constraint_name AND tc.table_schema = rc.constraint_schema
+            JOIN information_schema.key_column_usage AS ccu
+                ON ccu.constraint_name = rc.unique_constraint_name
+                AND ccu.constraint_schema = rc.unique_constraint_schema
+                AND ccu.ordinal_position = kcu.position_in_unique_constraint
             WHERE tc.constraint_type = 'FOREIGN KEY' AND tc.table_schema = '{}' AND tc.table_name = '{}'",
             schema_lower, table_lower
         );
@@ -361,6 +648,7 @@ impl DatabaseConnection for PostgresConnection {
             columns,
             indexes,
             foreign_keys,
+            has_triggers: false,
         })
     }
 
@@ -382,29 +670,35 @@ impl DatabaseConnection for PostgresConnection {
         let table_lower = table_name.to_lowercase();
 
         let query = format!(
-            "SELECT tc.constraint_name, tc.table_name, kcu.column_name,
+            "SELECT tc.constraint_name, tc.table_schema, tc.table_name, kcu.column_name,
+                    ccu.table_schema AS referenced_table_schema,
                     ccu.table_name AS referenced_table_name, ccu.column_name AS referenced_column_name,
                     rc.update_rule, rc.delete_rule, 'FOREIGN_KEY' as relationship_type
             FROM information_schema.table_constraints AS tc
             JOIN information_schema.key_column_usage AS kcu
                 ON tc.constraint_name = kcu.constraint_name AND tc.table_schema = kcu.table_schema
-            JOIN information_schema.constraint_column_usage AS ccu
-                ON ccu.constraint_name = tc.constraint_name AND ccu.table_schema = tc.table_schema
-            LEFT JOIN information_schema.referential_constraints AS rc
+            JOIN information_schema.referential_constraints AS rc
                 ON tc.constraint_name = rc.constraint_name AND tc.table_schema = rc.constraint_schema
+            JOIN information_schema.key_column_usage AS ccu
+                ON ccu.constraint_name = rc.unique_constraint_name
+                AND ccu.constraint_schema = rc.unique_constraint_schema
+                AND ccu.ordinal_position = kcu.position_in_unique_constraint
             WHERE tc.constraint_type = 'FOREIGN KEY' AND tc.table_schema = '{}' AND tc.table_name = '{}'
             UNION ALL
-            SELECT tc.constraint_name, tc.table_name, kcu.column_name,
+            SELECT tc.constraint_name, tc.table_schema, tc.table_name, kcu.column_name,
+                    ccu.table_schema AS referenced_table_schema,
                     ccu.table_name AS referenced_table_name, ccu.column_name AS referenced_column_name,
                     rc.update_rule, rc.delete_rule, 'REFERENCED_BY' as relationship_type
             FROM information_schema.table_constraints AS tc
             JOIN information_schema.key_column_usage AS kcu
                 ON tc.constraint_name = kcu.constraint_name AND tc.table_schema = kcu.table_schema
-            JOIN information_schema.constraint_column_usage AS ccu
-                ON ccu.constraint_name = tc.constraint_name AND ccu.table_schema = tc.table_schema
-            LEFT JOIN information_schema.referential_constraints AS rc
+            JOIN information_schema.referential_constraints AS rc
                 ON tc.constraint_name = rc.constraint_name AND tc.table_schema = rc.constraint_schema
-            WHERE tc.constraint_type = 'FOREIGN KEY' AND tc.table_schema = '{}' AND ccu.table_name = '{}'",
+            JOIN information_schema.key_column_usage AS ccu
+                ON ccu.constraint_name = rc.unique_constraint_name
+                AND ccu.constraint_schema = rc.unique_constraint_schema
+                AND ccu.ordinal_position = kcu.position_in_unique_constraint
+            WHERE tc.constraint_type = 'FOREIGN KEY' AND ccu.table_schema = '{}' AND ccu.table_name = '{}'",
             schema_lower, table_lower, schema_lower, table_lower
         );
 
@@ -432,6 +726,8 @@ impl DatabaseConnection for PostgresConnection {
                     referenced_table_name,
                     referenced_column_name,
                     relationship_type,
+                    table_schema: row.try_get("table_schema").ok(),
+                    referenced_table_schema: row.try_get("referenced_table_schema").ok(),
                     owner: Some(schema.clone()),
                     ref_object_type: Some("TABLE".to_string()),
                     on_delete,
@@ -471,6 +767,62 @@ impl DatabaseConnection for PostgresConnection {
         PostgresMetadataOps::get_procedures(pool, schema).await
     }
 
+    async fn get_view_source(
+        &mut self,
+        _database: &str,
+        view: &str,
+        schema: Option<&str>,
+    ) -> Result<Option<String>> {
+        let pool = self
+            .pool
+            .as_ref()
+            .ok_or_else(|| anyhow!("Not connected to database"))?;
+        PostgresMetadataOps::get_view_source(pool, schema.unwrap_or("public"), view).await
+    }
+
+    async fn get_trigger_source(
+        &mut self,
+        _database: &str,
+        trigger: &str,
+        table: &str,
+        schema: Option<&str>,
+    ) -> Result<Option<String>> {
+        let pool = self
+            .pool
+            .as_ref()
+            .ok_or_else(|| anyhow!("Not connected to database"))?;
+        PostgresMetadataOps::get_trigger_source(pool, schema.unwrap_or("public"), table, trigger)
+            .await
+    }
+
+    async fn get_table_ddl(&mut self, _database: &str, table: &str) -> Result<Option<String>> {
+        let pool = self
+            .pool
+            .as_ref()
+            .ok_or_else(|| anyhow!("Not connected to database"))?;
+
+        let (schema, table_name) = table.split_once('.').unwrap_or(("public", table));
+        PostgresMetadataOps::get_table_ddl(pool, schema, table_name)
+            .await
+            .map(Some)
+    }
+
+    async fn get_table_ddl_split(
+        &mut self,
+        _database: &str,
+        table: &str,
+    ) -> Result<Option<(String, Vec<String>)>> {
+        let pool = self
+            .pool
+            .as_ref()
+            .ok_or_else(|| anyhow!("Not connected to database"))?;
+
+        let (schema, table_name) = table.split_once('.').unwrap_or(("public", table));
+        PostgresMetadataOps::get_table_ddl_split(pool, schema, table_name)
+            .await
+            .map(Some)
+    }
+
     async fn get_procedure_source(
         &mut self,
         _database: &str,
@@ -568,6 +920,115 @@ impl DatabaseConnection for PostgresConnection {
         PostgresMetadataOps::get_triggers(pool, schema).await
     }
 
+    async fn execute_query_streaming(
+        &mut self,
+        query: &str,
+        batch_size: usize,
+        sink: &QueryStreamSender,
+    ) -> Result<u64> {
+        self.execute_query_streaming_bound(&BoundQuery::new(query), batch_size, sink)
+            .await
+    }
+
+    async fn execute_query_streaming_bound(
+        &mut self,
+        query: &BoundQuery,
+        batch_size: usize,
+        sink: &QueryStreamSender,
+    ) -> Result<u64> {
+        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("Not connected"))?;
+        let mut conn = sqlx_session_conn(pool, &mut self.pinned).await?;
+        let _session = track_session(&self.session, &mut self.known_sessions, &mut conn).await?;
+        let previous_timeout = set_statement_timeout(&mut conn, self.statement_timeout).await?;
+        let streamed = stream_rows(&mut conn, query, batch_size, sink).await;
+        restore_statement_timeout(&mut conn, previous_timeout).await;
+        streamed
+    }
+
+    async fn transaction_control(&mut self, command: &TransactionCommand) -> Result<()> {
+        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("Not connected"))?;
+        let sql = command
+            .sql(SqlDialect::PostgreSQL)
+            .ok_or_else(|| anyhow!("{:?} is not supported by this database", command))?;
+
+        // A plain string runs over the text protocol; transaction control statements
+        // cannot be prepared everywhere
+        let mut conn = sqlx_transaction_conn(pool, &mut self.pinned, command).await?;
+        (&mut *conn).execute(sql.as_str()).await?;
+        let acquired = match conn {
+            SessionConn::Pooled(conn) => Some(conn),
+            SessionConn::Pinned(_) => None,
+        };
+        finish_transaction_command(&mut self.pinned, acquired, command);
+        if self.pinned.is_none() {
+            self.known_sessions.unpin();
+        }
+        Ok(())
+    }
+
+    fn in_transaction(&self) -> bool {
+        self.pinned.is_some()
+    }
+
+    fn supports_transactions(&self) -> bool {
+        true
+    }
+
+    async fn copy_in(
+        &mut self,
+        table_name: &str,
+        columns: &[String],
+        rows: &[Vec<serde_json::Value>],
+    ) -> Result<u64> {
+        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("Not connected"))?;
+        let mut conn = sqlx_session_conn(pool, &mut self.pinned).await?;
+        let _session = track_session(&self.session, &mut self.known_sessions, &mut conn).await?;
+
+        let mut data = String::new();
+        for row in rows {
+            let fields: Vec<String> = row.iter().map(copy_csv_field).collect();
+            data.push_str(&fields.join(","));
+            data.push('\n');
+        }
+        let statement = format!(
+            "COPY {} ({}) FROM STDIN WITH (FORMAT csv)",
+            table_name,
+            columns.join(", ")
+        );
+        let mut copy = conn.copy_in_raw(&statement).await?;
+        if let Err(e) = copy.send(data.into_bytes()).await {
+            copy.abort(e.to_string()).await.ok();
+            return Err(e.into());
+        }
+        Ok(copy.finish().await?)
+    }
+
+    fn supports_copy_in(&self) -> bool {
+        true
+    }
+
+    fn set_statement_timeout(&mut self, timeout: Option<Duration>) {
+        self.statement_timeout = timeout;
+    }
+
+    fn set_session_tracker(&mut self, tracker: SessionTracker) {
+        self.session = tracker;
+    }
+
+    async fn cancel(&mut self, session_id: i64) -> Result<()> {
+        let pool = self.pool.as_ref().ok_or_else(|| anyhow!("Not connected"))?;
+        let cancelled: bool = sqlx::query_scalar("SELECT pg_cancel_backend($1)")
+            .bind(session_id as i32)
+            .fetch_one(pool)
+            .await?;
+
+        if cancelled {
+            Ok(())
+        } else {
+            Err(anyhow!("Backend {} could not be cancelled", session_id))
+        }
+    }
+
     fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
         self
     }
@@ -167,4 +167,264 @@ impl PostgresMetadataOps {
 
         Ok(triggers)
     }
+
+    /// `CREATE OR REPLACE VIEW` statement of a view, `None` when there is no such view
+    pub async fn get_view_source(
+        pool: &PgPool,
+        schema: &str,
+        view: &str,
+    ) -> Result<Option<String>> {
+        let view_name = format!(
+            "\"{}\".\"{}\"",
+            schema.replace('"', "\"\""),
+            view.replace('"', "\"\"")
+        );
+        let definition: Option<String> = sqlx::query_scalar(
+            "SELECT pg_get_viewdef(c.oid, true)
+            FROM pg_class c JOIN pg_namespace n ON c.relnamespace = n.oid
+            WHERE n.nspname = $1 AND c.relname = $2 AND c.relkind = 'v'",
+        )
+        .bind(schema)
+        .bind(view)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(definition.map(|definition| {
+            format!(
+                "CREATE OR REPLACE VIEW {} AS\n{}",
+                view_name,
+                definition.trim_end().trim_end_matches(';')
+            )
+        }))
+    }
+
+    /// `CREATE TRIGGER` statement of a trigger, `None` when the table has no such trigger
+    pub async fn get_trigger_source(
+        pool: &PgPool,
+        schema: &str,
+        table: &str,
+        trigger: &str,
+    ) -> Result<Option<String>> {
+        let source = sqlx::query_scalar(
+            "SELECT pg_get_triggerdef(t.oid, true)
+            FROM pg_trigger t
+            JOIN pg_class c ON t.tgrelid = c.oid
+            JOIN pg_namespace n ON c.relnamespace = n.oid
+            WHERE n.nspname = $1 AND c.relname = $2 AND t.tgname = $3 AND NOT t.tgisinternal",
+        )
+        .bind(schema)
+        .bind(table)
+        .bind(trigger)
+        .fetch_optional(pool)
+        .await?;
+        Ok(source)
+    }
+
+    /// Build the `CREATE TABLE` statement of a table from pg_catalog, with constraints and
+    /// indexes as the server renders them
+    pub async fn get_table_ddl(pool: &PgPool, schema: &str, table: &str) -> Result<String> {
+        let (ddl, _) = Self::table_ddl(pool, schema, table, true).await?;
+        Ok(ddl)
+    }
+
+    /// Build the `CREATE TABLE` statement of a table without its foreign keys, and the
+    /// `ALTER TABLE` statements adding them
+    pub async fn get_table_ddl_split(
+        pool: &PgPool,
+        schema: &str,
+        table: &str,
+    ) -> Result<(String, Vec<String>)> {
+        Self::table_ddl(pool, schema, table, false).await
+    }
+
+    async fn table_ddl(
+        pool: &PgPool,
+        schema: &str,
+        table: &str,
+        inline_foreign_keys: bool,
+    ) -> Result<(String, Vec<String>)> {
+        let quote = |name: &str| format!("\"{}\"", name.replace('"', "\"\""));
+        let table_name = format!("{}.{}", quote(schema), quote(table));
+
+        let columns = sqlx::query(
+            "SELECT quote_ident(a.attname) AS name,
+                    format_type(a.atttypid, a.atttypmod) AS data_type,
+                    a.attnotnull AS not_null,
+                    pg_get_expr(d.adbin, d.adrelid) AS default_expr,
+                    a.attidentity::text AS identity,
+                    a.attgenerated::text AS generated
+            FROM pg_attribute a
+            LEFT JOIN pg_attrdef d ON d.adrelid = a.attrelid AND d.adnum = a.attnum
+            WHERE a.attrelid = $1::regclass AND a.attnum > 0 AND NOT a.attisdropped
+            ORDER BY a.attnum",
+        )
+        .bind(&table_name)
+        .fetch_all(pool)
+        .await?;
+
+        let mut definitions: Vec<String> = columns
+            .iter()
+            .map(|row| {
+                let name: String = row.try_get("name").unwrap_or_default();
+                let mut data_type: String = row.try_get("data_type").unwrap_or_default();
+                let not_null: bool = row.try_get("not_null").unwrap_or(false);
+                let mut default_expr: Option<String> = row.try_get("default_expr").ok().flatten();
+                let identity: String = row.try_get("identity").unwrap_or_default();
+                let generated: String = row.try_get("generated").unwrap_or_default();
+
+                // A sequence default on an integer column is what the serial types create
+                if default_expr
+                    .as_deref()
+                    .is_some_and(|expr| expr.starts_with("nextval("))
+                {
+                    let serial = match data_type.as_str() {
+                        "smallint" => Some("smallserial"),
+                        "integer" => Some("serial"),
+                        "bigint" => Some("bigserial"),
+                        _ => None,
+                    };
+                    if let Some(serial) = serial {
+                        data_type = serial.to_string();
+                        default_expr = None;
+                    }
+                }
+
+                let mut definition = format!("{} {}", name, data_type);
+                match (identity.as_str(), generated.as_str()) {
+                    ("a", _) => definition.push_str(" GENERATED ALWAYS AS IDENTITY"),
+                    ("d", _) => definition.push_str(" GENERATED BY DEFAULT AS IDENTITY"),
+                    (_, "s") => {
+                        if let Some(expr) = default_expr.take() {
+                            definition.push_str(&format!(" GENERATED ALWAYS AS ({}) STORED", expr));
+                        }
+                    }
+                    _ => {}
+                }
+                if let Some(expr) = default_expr {
+                    definition.push_str(&format!(" DEFAULT {}", expr));
+                }
+                if not_null {
+                    definition.push_str(" NOT NULL");
+                }
+                definition
+            })
+            .collect();
+
+        let constraints = sqlx::query(
+            "SELECT quote_ident(conname) AS name, pg_get_constraintdef(oid, true) AS definition,
+                    contype::text AS kind
+            FROM pg_constraint
+            WHERE conrelid = $1::regclass AND contype IN ('p', 'u', 'c', 'f', 'x')
+            ORDER BY CASE contype WHEN 'p' THEN 0 WHEN 'u' THEN 1 WHEN 'c' THEN 2 WHEN 'x' THEN 3 ELSE 4 END,
+                     conname",
+        )
+        .bind(&table_name)
+        .fetch_all(pool)
+        .await?;
+        let mut foreign_keys = Vec::new();
+        for row in &constraints {
+            let name: String = row.try_get("name").unwrap_or_default();
+            let definition: String = row.try_get("definition").unwrap_or_default();
+            let kind: String = row.try_get("kind").unwrap_or_default();
+            if kind == "f" && !inline_foreign_keys {
+                foreign_keys.push(format!(
+                    "ALTER TABLE {} ADD CONSTRAINT {} {}",
+                    table_name, name, definition
+                ));
+            } else {
+                definitions.push(format!("CONSTRAINT {} {}", name, definition));
+            }
+        }
+
+        // Indexes behind primary key, unique and exclusion constraints come with them
+        let indexes: Vec<String> = sqlx::query_scalar(
+            "SELECT pg_get_indexdef(i.indexrelid)
+            FROM pg_index i
+            WHERE i.indrelid = $1::regclass
+              AND NOT EXISTS (
+                  SELECT 1 FROM pg_constraint c
+                  WHERE c.conrelid = i.indrelid AND c.conindid = i.indexrelid
+                    AND c.contype IN ('p', 'u', 'x'))
+            ORDER BY i.indexrelid::regclass::text",
+        )
+        .bind(&table_name)
+        .fetch_all(pool)
+        .await?;
+
+        let mut ddl = Self::column_types_ddl(pool, &table_name).await?;
+        ddl.push_str(&format!(
+            "CREATE TABLE {} (\n    {}\n);\n",
+            table_name,
+            definitions.join(",\n    ")
+        ));
+        for index in indexes {
+            ddl.push_str(&format!("\n{};\n", index));
+        }
+        Ok((ddl, foreign_keys))
+    }
+
+    /// Statements creating the enum and domain types the columns of a table use, enums
+    /// first as domains may be based on them. Tables sharing a type each carry it, so
+    /// the statements skip a type that already exists.
+    async fn column_types_ddl(pool: &PgPool, table_name: &str) -> Result<String> {
+        let types = sqlx::query(
+            "WITH used AS (
+                SELECT DISTINCT CASE WHEN t.typcategory = 'A' THEN t.typelem ELSE t.oid END AS oid
+                FROM pg_attribute a
+                JOIN pg_type t ON t.oid = a.atttypid
+                WHERE a.attrelid = $1::regclass AND a.attnum > 0 AND NOT a.attisdropped)
+            SELECT format_type(t.oid, NULL) AS name,
+                   t.typtype::text AS kind,
+                   (SELECT string_agg(quote_literal(e.enumlabel), ', ' ORDER BY e.enumsortorder)
+                    FROM pg_enum e WHERE e.enumtypid = t.oid) AS labels,
+                   format_type(t.typbasetype, t.typtypmod) AS base_type,
+                   t.typnotnull AS not_null,
+                   t.typdefault AS default_expr,
+                   (SELECT string_agg(pg_get_constraintdef(c.oid, true), ' ' ORDER BY c.conname)
+                    FROM pg_constraint c WHERE c.contypid = t.oid) AS checks
+            FROM used
+            JOIN pg_type t ON t.oid = used.oid
+            WHERE t.typtype IN ('e', 'd')
+            ORDER BY t.typtype DESC, 1",
+        )
+        .bind(table_name)
+        .fetch_all(pool)
+        .await?;
+
+        let mut ddl = String::new();
+        for row in &types {
+            let name: String = row.try_get("name").unwrap_or_default();
+            let kind: String = row.try_get("kind").unwrap_or_default();
+            let statement = if kind == "e" {
+                let labels: Option<String> = row.try_get("labels").ok().flatten();
+                format!(
+                    "CREATE TYPE {} AS ENUM ({})",
+                    name,
+                    labels.unwrap_or_default()
+                )
+            } else {
+                let base_type: String = row.try_get("base_type").unwrap_or_default();
+                let mut statement = format!("CREATE DOMAIN {} AS {}", name, base_type);
+                if let Some(expr) = row
+                    .try_get::<Option<String>, _>("default_expr")
+                    .ok()
+                    .flatten()
+                {
+                    statement.push_str(&format!(" DEFAULT {}", expr));
+                }
+                if row.try_get::<bool, _>("not_null").unwrap_or(false) {
+                    statement.push_str(" NOT NULL");
+                }
+                if let Some(checks) = row.try_get::<Option<String>, _>("checks").ok().flatten() {
+                    statement.push_str(&format!(" {}", checks));
+                }
+                statement
+            };
+            ddl.push_str(&format!(
+                "DO $$ BEGIN\n    {};\nEXCEPTION WHEN duplicate_object THEN NULL;\nEND $$;\n\n",
+                statement
+            ));
+        }
+        Ok(ddl)
+    }
 }
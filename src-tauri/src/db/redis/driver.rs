@@ -346,6 +346,8 @@ impl DatabaseConnection for RedisConnection {
                     default_value: None,
                     is_primary_key: true,
                     is_auto_increment: false,
+                    is_generated: false,
+                    default_is_expression: false,
                 },
                 Column {
                     name: "type".to_string(),
@@ -354,6 +356,8 @@ impl DatabaseConnection for RedisConnection {
                     default_value: None,
                     is_primary_key: false,
                     is_auto_increment: false,
+                    is_generated: false,
+                    default_is_expression: false,
                 },
                 Column {
                     name: "value".to_string(),
@@ -362,10 +366,13 @@ impl DatabaseConnection for RedisConnection {
                     default_value: None,
                     is_primary_key: false,
                     is_auto_increment: false,
+                    is_generated: false,
+                    default_is_expression: false,
                 },
             ],
             indexes: vec![],
             foreign_keys: vec![],
+            has_triggers: false,
         })
     }
 
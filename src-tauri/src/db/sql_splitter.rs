@@ -0,0 +1,475 @@
+use crate::models::connection::DatabaseType;
+use serde::{Deserialize, Serialize};
+
+/// SQL dialect rules used when splitting a script into statements
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SqlDialect {
+    /// ANSI-style SQL: `--` and `/* */` comments, `;` terminator
+    Generic,
+    /// MySQL: `#` comments, backslash escapes, backtick identifiers, `DELIMITER` directive
+    MySQL,
+    /// PostgreSQL: nested block comments, `E''` strings, dollar-quoted bodies
+    PostgreSQL,
+    /// SQL Server: nested block comments, `[]` identifiers, `GO` batch separator
+    MSSQL,
+}
+
+impl SqlDialect {
+    /// Get the dialect for a database type, or `None` for non-SQL databases
+    pub fn from_db_type(db_type: &DatabaseType) -> Option<Self> {
+        match db_type {
+            DatabaseType::MySQL => Some(SqlDialect::MySQL),
+            DatabaseType::PostgreSQL => Some(SqlDialect::PostgreSQL),
+            DatabaseType::MSSQL => Some(SqlDialect::MSSQL),
+            DatabaseType::Ignite => Some(SqlDialect::Generic),
+            DatabaseType::MongoDB | DatabaseType::Redis => None,
+        }
+    }
+}
+
+/// A single statement found in a script, with byte offsets into the original text
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SqlStatement {
+    pub text: String,
+    /// Byte offset of the first character of the statement
+    pub start: usize,
+    /// Byte offset just past the last character of the statement (terminator excluded)
+    pub end: usize,
+}
+
+impl SqlStatement {
+    /// Build a statement from a range of the script, trimming surrounding whitespace
+    pub fn from_range(script: &str, start: usize, end: usize) -> Self {
+        let slice = &script[start..end];
+        let leading = slice.len() - slice.trim_start().len();
+        let trimmed = slice.trim();
+
+        SqlStatement {
+            text: trimmed.to_string(),
+            start: start + leading,
+            end: start + leading + trimmed.len(),
+        }
+    }
+}
+
+/// Split a script into individual statements according to the dialect's rules.
+///
+/// - MySQL scripts honour `DELIMITER xx` lines, so procedure and trigger bodies stay intact
+/// - SQL Server scripts are split into batches on `GO` lines only (`GO n` repeats the batch)
+/// - PostgreSQL dollar-quoted bodies (`$$ ... $$`, `$tag$ ... $tag$`) are never split
+///
+/// Segments that contain nothing but whitespace and comments are dropped.
+pub fn split_statements(script: &str, dialect: SqlDialect) -> Vec<SqlStatement> {
+    Splitter::new(script, dialect).run()
+}
+
+struct Splitter<'a> {
+    script: &'a str,
+    bytes: &'a [u8],
+    dialect: SqlDialect,
+    pos: usize,
+    stmt_start: usize,
+    has_code: bool,
+    at_line_start: bool,
+    delimiter: String,
+    statements: Vec<SqlStatement>,
+}
+
+impl<'a> Splitter<'a> {
+    fn new(script: &'a str, dialect: SqlDialect) -> Self {
+        Splitter {
+            script,
+            bytes: script.as_bytes(),
+            dialect,
+            pos: 0,
+            stmt_start: 0,
+            has_code: false,
+            at_line_start: true,
+            delimiter: ";".to_string(),
+            statements: Vec::new(),
+        }
+    }
+
+    fn run(mut self) -> Vec<SqlStatement> {
+        while self.pos < self.bytes.len() {
+            let c = self.bytes[self.pos];
+
+            if c == b'\n' {
+                self.pos += 1;
+                self.at_line_start = true;
+                continue;
+            }
+
+            if c.is_ascii_whitespace() {
+                self.pos += 1;
+                continue;
+            }
+
+            if self.at_line_start && self.try_directive() {
+                continue;
+            }
+            self.at_line_start = false;
+
+            if self.dialect != SqlDialect::MSSQL && self.starts_with(&self.delimiter) {
+                self.flush(self.pos);
+                self.pos += self.delimiter.len();
+                self.stmt_start = self.pos;
+                continue;
+            }
+
+            match c {
+                b'-' if self.peek(1) == Some(b'-') && self.is_line_comment_dash() => {
+                    self.skip_line_comment();
+                }
+                b'#' if self.dialect == SqlDialect::MySQL => self.skip_line_comment(),
+                b'/' if self.peek(1) == Some(b'*') => self.skip_block_comment(),
+                b'\'' => {
+                    let backslash = self.dialect == SqlDialect::MySQL || self.is_escape_string();
+                    self.has_code = true;
+                    self.skip_quoted(b'\'', backslash);
+                }
+                b'"' => {
+                    self.has_code = true;
+                    self.skip_quoted(b'"', self.dialect == SqlDialect::MySQL);
+                }
+                b'`' if self.dialect == SqlDialect::MySQL => {
+                    self.has_code = true;
+                    self.skip_quoted(b'`', false);
+                }
+                b'[' if self.dialect == SqlDialect::MSSQL => {
+                    self.has_code = true;
+                    self.skip_quoted(b']', false);
+                }
+                b'$' if self.dialect == SqlDialect::PostgreSQL => {
+                    self.has_code = true;
+                    self.skip_dollar_quoted();
+                }
+                _ => {
+                    self.has_code = true;
+                    self.pos += 1;
+                }
+            }
+        }
+
+        self.flush(self.bytes.len());
+        self.statements
+    }
+
+    fn peek(&self, offset: usize) -> Option<u8> {
+        self.bytes.get(self.pos + offset).copied()
+    }
+
+    fn starts_with(&self, pattern: &str) -> bool {
+        self.bytes[self.pos..].starts_with(pattern.as_bytes())
+    }
+
+    /// Byte offset of the end of the current line (position of `\n` or end of script)
+    fn line_end(&self) -> usize {
+        self.bytes[self.pos..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .map(|i| self.pos + i)
+            .unwrap_or(self.bytes.len())
+    }
+
+    /// Push the pending statement ending at `end`, if it contains any code
+    fn flush(&mut self, end: usize) {
+        if self.has_code {
+            self.statements
+                .push(SqlStatement::from_range(self.script, self.stmt_start, end));
+        }
+        self.has_code = false;
+    }
+
+    /// Handle line-level client directives (`DELIMITER` for MySQL, `GO` for SQL Server)
+    fn try_directive(&mut self) -> bool {
+        let line_end = self.line_end();
+        let line = &self.script[self.pos..line_end];
+
+        match self.dialect {
+            SqlDialect::MySQL => {
+                let mut parts = line.split_whitespace();
+                let is_delimiter = parts
+                    .next()
+                    .is_some_and(|w| w.eq_ignore_ascii_case("DELIMITER"));
+                let Some(new_delimiter) = parts.next().filter(|_| is_delimiter) else {
+                    return false;
+                };
+
+                self.flush(self.pos);
+                self.delimiter = new_delimiter.to_string();
+                self.pos = line_end;
+                self.stmt_start = line_end;
+                true
+            }
+            SqlDialect::MSSQL => {
+                let Some(count) = parse_go_line(line) else {
+                    return false;
+                };
+
+                let batch_start = self.statements.len();
+                self.flush(self.pos);
+                if let Some(batch) = self.statements.get(batch_start).cloned() {
+                    for _ in 1..count {
+                        self.statements.push(batch.clone());
+                    }
+                }
+                self.pos = line_end;
+                self.stmt_start = line_end;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// MySQL only treats `--` as a comment when followed by whitespace or end of input
+    fn is_line_comment_dash(&self) -> bool {
+        if self.dialect != SqlDialect::MySQL {
+            return true;
+        }
+        self.peek(2).is_none_or(|b| b.is_ascii_whitespace())
+    }
+
+    /// PostgreSQL `E'...'` strings allow backslash escapes
+    fn is_escape_string(&self) -> bool {
+        if self.dialect != SqlDialect::PostgreSQL || self.pos == 0 {
+            return false;
+        }
+        let prev = self.bytes[self.pos - 1];
+        if prev != b'E' && prev != b'e' {
+            return false;
+        }
+        self.pos < 2 || !is_ident_byte(self.bytes[self.pos - 2])
+    }
+
+    fn skip_line_comment(&mut self) {
+        self.pos = self.line_end();
+    }
+
+    fn skip_block_comment(&mut self) {
+        let nested = matches!(self.dialect, SqlDialect::PostgreSQL | SqlDialect::MSSQL);
+        let mut depth = 0usize;
+
+        while self.pos < self.bytes.len() {
+            if self.starts_with("/*") {
+                depth += 1;
+                self.pos += 2;
+                if !nested && depth > 1 {
+                    depth = 1;
+                }
+            } else if self.starts_with("*/") {
+                depth -= 1;
+                self.pos += 2;
+                if depth == 0 {
+                    return;
+                }
+            } else {
+                self.pos += 1;
+            }
+        }
+    }
+
+    /// Skip a quoted string or identifier; a doubled closing quote is an escaped quote
+    fn skip_quoted(&mut self, close: u8, backslash_escapes: bool) {
+        self.pos += 1;
+        while self.pos < self.bytes.len() {
+            let c = self.bytes[self.pos];
+            if backslash_escapes && c == b'\\' {
+                self.pos += 2;
+                continue;
+            }
+            self.pos += 1;
+            if c == close {
+                if self.peek(0) == Some(close) {
+                    self.pos += 1;
+                } else {
+                    return;
+                }
+            }
+        }
+        self.pos = self.bytes.len();
+    }
+
+    /// Skip a PostgreSQL dollar-quoted body; a lone `$` (e.g. `$1`) is treated as code
+    fn skip_dollar_quoted(&mut self) {
+        let preceded_by_ident = self.pos > 0 && is_ident_byte(self.bytes[self.pos - 1]);
+        let tag_len = self.bytes[self.pos + 1..]
+            .iter()
+            .take_while(|&&b| is_ident_byte(b))
+            .count();
+        let starts_with_digit = self.peek(1).is_some_and(|b| b.is_ascii_digit());
+
+        if preceded_by_ident || starts_with_digit || self.peek(tag_len + 1) != Some(b'$') {
+            self.pos += 1;
+            return;
+        }
+
+        let tag = &self.script[self.pos..self.pos + tag_len + 2];
+        let body_start = self.pos + tag.len();
+        self.pos = match self.script[body_start..].find(tag) {
+            Some(i) => body_start + i + tag.len(),
+            None => self.bytes.len(),
+        };
+    }
+}
+
+fn is_ident_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b >= 0x80
+}
+
+/// Parse a SQL Server `GO` / `GO n` line, returning the repeat count
+fn parse_go_line(line: &str) -> Option<usize> {
+    let code = line.split("--").next().unwrap_or("");
+    let mut parts = code.split_whitespace();
+
+    if !parts.next()?.eq_ignore_ascii_case("GO") {
+        return None;
+    }
+
+    let count = match parts.next() {
+        Some(n) => n.parse::<usize>().ok().filter(|&n| n > 0)?,
+        None => 1,
+    };
+
+    if parts.next().is_some() {
+        return None;
+    }
+
+    Some(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(script: &str, dialect: SqlDialect) -> Vec<String> {
+        split_statements(script, dialect)
+            .into_iter()
+            .map(|s| s.text)
+            .collect()
+    }
+
+    #[test]
+    fn test_split_simple_statements_with_offsets() {
+        let script = "SELECT 1;\n  SELECT 2 ;";
+        let statements = split_statements(script, SqlDialect::Generic);
+
+        assert_eq!(statements.len(), 2);
+        assert_eq!(statements[0].text, "SELECT 1");
+        assert_eq!(&script[statements[0].start..statements[0].end], "SELECT 1");
+        assert_eq!(statements[1].text, "SELECT 2");
+        assert_eq!(statements[1].start, 12);
+        assert_eq!(&script[statements[1].start..statements[1].end], "SELECT 2");
+    }
+
+    #[test]
+    fn test_split_ignores_semicolons_in_strings_and_comments() {
+        let script = "INSERT INTO t VALUES ('a;b', 'it''s;');\n-- note; here\n/* x; y */ SELECT 2";
+        assert_eq!(
+            texts(script, SqlDialect::Generic),
+            vec![
+                "INSERT INTO t VALUES ('a;b', 'it''s;')",
+                "-- note; here\n/* x; y */ SELECT 2"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_drops_comment_only_segments() {
+        let script = "SELECT 1; -- trailing comment\n/* done */";
+        assert_eq!(texts(script, SqlDialect::Generic), vec!["SELECT 1"]);
+    }
+
+    #[test]
+    fn test_split_mysql_delimiter_blocks() {
+        let script = "DROP PROCEDURE IF EXISTS p;\nDELIMITER //\nCREATE PROCEDURE p()\nBEGIN\n  SELECT 1;\n  SELECT 2;\nEND //\nDELIMITER ;\nCALL p();";
+        assert_eq!(
+            texts(script, SqlDialect::MySQL),
+            vec![
+                "DROP PROCEDURE IF EXISTS p",
+                "CREATE PROCEDURE p()\nBEGIN\n  SELECT 1;\n  SELECT 2;\nEND",
+                "CALL p()"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_mysql_comments_and_escapes() {
+        let script = "SELECT 'a\\';b'; # hash; comment\nSELECT `c;d` FROM t; SELECT 1--1;";
+        assert_eq!(
+            texts(script, SqlDialect::MySQL),
+            vec![
+                "SELECT 'a\\';b'",
+                "# hash; comment\nSELECT `c;d` FROM t",
+                "SELECT 1--1"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_postgres_dollar_quotes() {
+        let script = "CREATE FUNCTION f() RETURNS int AS $$\nBEGIN\n  RETURN 1;\nEND;\n$$ LANGUAGE plpgsql;\nDO $body$ BEGIN PERFORM 1; END $body$;\nSELECT $1, a$b FROM t;";
+        assert_eq!(
+            texts(script, SqlDialect::PostgreSQL),
+            vec![
+                "CREATE FUNCTION f() RETURNS int AS $$\nBEGIN\n  RETURN 1;\nEND;\n$$ LANGUAGE plpgsql",
+                "DO $body$ BEGIN PERFORM 1; END $body$",
+                "SELECT $1, a$b FROM t"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_postgres_nested_comments_and_escape_strings() {
+        let script = "/* outer /* inner; */ still; */ SELECT E'a\\';b'; SELECT 2";
+        assert_eq!(
+            texts(script, SqlDialect::PostgreSQL),
+            vec![
+                "/* outer /* inner; */ still; */ SELECT E'a\\';b'",
+                "SELECT 2"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_mssql_go_batches() {
+        let script = "CREATE PROCEDURE p AS\nBEGIN\n  SELECT [a;b] FROM t;\n  SELECT 2;\nEND\nGO\nEXEC p;\ngo 2\nSELECT 'GO'\n";
+        let statements = split_statements(script, SqlDialect::MSSQL);
+
+        assert_eq!(
+            statements
+                .iter()
+                .map(|s| s.text.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "CREATE PROCEDURE p AS\nBEGIN\n  SELECT [a;b] FROM t;\n  SELECT 2;\nEND",
+                "EXEC p;",
+                "EXEC p;",
+                "SELECT 'GO'"
+            ]
+        );
+        assert_eq!(statements[1].start, statements[2].start);
+    }
+
+    #[test]
+    fn test_split_mssql_go_inside_comment_is_ignored() {
+        let script = "SELECT 1\n/*\nGO\n*/\nSELECT 2";
+        assert_eq!(
+            texts(script, SqlDialect::MSSQL),
+            vec!["SELECT 1\n/*\nGO\n*/\nSELECT 2"]
+        );
+    }
+
+    #[test]
+    fn test_split_offsets_with_multibyte_text() {
+        let script = "SELECT 'héllo';SELECT 'ü'";
+        let statements = split_statements(script, SqlDialect::Generic);
+
+        assert_eq!(statements.len(), 2);
+        assert_eq!(
+            &script[statements[1].start..statements[1].end],
+            "SELECT 'ü'"
+        );
+    }
+}
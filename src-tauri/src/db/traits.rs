@@ -1,13 +1,25 @@
+use crate::db::bound_query::{BoundQuery, InsertReadBack};
+use crate::db::transaction::TransactionCommand;
+use crate::models::explain::ExecutionPlan;
 use crate::models::{connection::*, query_result::*, save_request::*, schema::*, table_request::*};
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use std::any::Any;
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 /// Core trait for database connections
 #[async_trait]
 pub trait DatabaseConnection: Send + Sync {
     async fn connect(&mut self, config: &ConnectionConfig) -> Result<()>;
+
+    /// Connect with a single dedicated physical connection, so session state such as
+    /// `SET`, `USE` and temporary tables persists between statements. Drivers that
+    /// already hold one connection use their normal connect.
+    async fn connect_session(&mut self, config: &ConnectionConfig) -> Result<()> {
+        self.connect(config).await
+    }
     async fn disconnect(&mut self) -> Result<()>;
     async fn test_connection(&mut self) -> Result<bool>;
     async fn execute_query(&mut self, query: &str) -> Result<QueryResult>;
@@ -91,9 +103,293 @@ pub trait DatabaseConnection: Send + Sync {
         Ok("-- Source code not available".to_string())
     }
 
+    /// `CREATE VIEW` statement of a view, `None` when the engine cannot report it
+    async fn get_view_source(
+        &mut self,
+        _database: &str,
+        _view: &str,
+        _schema: Option<&str>,
+    ) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    /// `CREATE TRIGGER` statement of a trigger on `table`, `None` when the engine
+    /// cannot report it
+    async fn get_trigger_source(
+        &mut self,
+        _database: &str,
+        _trigger: &str,
+        _table: &str,
+        _schema: Option<&str>,
+    ) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    /// `CREATE TABLE` statement of a table as the server itself reports it. `None` when
+    /// the engine has no such source and the DDL has to be built from the table schema.
+    async fn get_table_ddl(&mut self, _database: &str, _table: &str) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    /// Like `get_table_ddl` but without foreign keys, which come back as `ALTER TABLE`
+    /// statements so tables referencing each other can all be created first. `None` when
+    /// the driver cannot split them out.
+    async fn get_table_ddl_split(
+        &mut self,
+        _database: &str,
+        _table: &str,
+    ) -> Result<Option<(String, Vec<String>)>> {
+        Ok(None)
+    }
+
+    /// Run a query with bound parameters. Drivers that cannot bind parameters
+    /// only accept queries that have none.
+    async fn execute_query_bound(&mut self, query: &BoundQuery) -> Result<QueryResult> {
+        if !query.params.is_empty() {
+            return Err(anyhow!(
+                "Bound parameters are not supported for this database"
+            ));
+        }
+        self.execute_query(&query.sql).await
+    }
+
+    /// Run a query that may produce several result sets, such as `EXEC` batches or
+    /// MySQL `CALL`, returning each one in order. Drivers that only see one result set
+    /// return it alone.
+    async fn execute_query_multi(&mut self, query: &str) -> Result<Vec<QueryResult>> {
+        Ok(vec![self.execute_query(query).await?])
+    }
+
+    /// Build the execution plan of a query. With `analyze` the query is actually run
+    /// (inside a transaction that is rolled back) to collect real row counts and timings.
+    async fn explain(&mut self, _query: &str, _analyze: bool) -> Result<ExecutionPlan> {
+        Err(anyhow!("EXPLAIN is not supported for this database"))
+    }
+
+    /// Run a statement without preparing it, for statements such as MySQL's
+    /// `CREATE PROCEDURE` that the prepared statement protocol refuses
+    async fn execute_raw(&mut self, query: &str) -> Result<u64> {
+        self.execute_update(query).await
+    }
+
+    /// Run a statement with bound parameters and return the number of affected rows
+    async fn execute_update_bound(&mut self, query: &BoundQuery) -> Result<u64> {
+        if !query.params.is_empty() {
+            return Err(anyhow!(
+                "Bound parameters are not supported for this database"
+            ));
+        }
+        self.execute_update(&query.sql).await
+    }
+
+    /// Run an INSERT and then the query that reads the inserted row back, on one session
+    /// so session functions such as `LAST_INSERT_ID()` see the insert. Returns the rows
+    /// affected by the INSERT and the rows read back.
+    async fn execute_insert_read_back(
+        &mut self,
+        insert: &BoundQuery,
+        select: &BoundQuery,
+    ) -> Result<(u64, QueryResult)> {
+        let affected = self.execute_update_bound(insert).await?;
+        let stored = self.execute_query_bound(select).await?;
+        Ok((affected, stored))
+    }
+
+    /// Run a query and push its columns and rows to `sink` in batches of `batch_size`,
+    /// returning the number of rows sent. Drivers without a cursor API fall back to
+    /// `execute_query` and chunk the materialized result.
+    async fn execute_query_streaming(
+        &mut self,
+        query: &str,
+        batch_size: usize,
+        sink: &QueryStreamSender,
+    ) -> Result<u64> {
+        let result = self.execute_query(query).await?;
+        stream_query_result(result, batch_size, sink).await
+    }
+
+    /// Stream a query with bound parameters, like `execute_query_streaming`
+    async fn execute_query_streaming_bound(
+        &mut self,
+        query: &BoundQuery,
+        batch_size: usize,
+        sink: &QueryStreamSender,
+    ) -> Result<u64> {
+        if !query.params.is_empty() {
+            return Err(anyhow!(
+                "Bound parameters are not supported for this database"
+            ));
+        }
+        self.execute_query_streaming(&query.sql, batch_size, sink)
+            .await
+    }
+
+    /// Run BEGIN, COMMIT, ROLLBACK or a savepoint command. `Begin` pins one physical
+    /// session that every following statement runs on until commit or rollback.
+    async fn transaction_control(&mut self, _command: &TransactionCommand) -> Result<()> {
+        Err(anyhow!("Transactions are not supported for this database"))
+    }
+
+    /// Whether a manual transaction is holding a pinned session
+    fn in_transaction(&self) -> bool {
+        false
+    }
+
+    /// Whether `transaction_control` is implemented, so edits can be applied atomically
+    fn supports_transactions(&self) -> bool {
+        false
+    }
+
+    /// Load rows into a table through the dialect's bulk path, such as PostgreSQL's
+    /// `COPY FROM STDIN`. `table_name` and `columns` are quoted for the dialect and each
+    /// row holds the values of `columns` in order. Returns the number of rows loaded.
+    async fn copy_in(
+        &mut self,
+        _table_name: &str,
+        _columns: &[String],
+        _rows: &[Vec<serde_json::Value>],
+    ) -> Result<u64> {
+        Err(anyhow!("Bulk loading is not supported for this database"))
+    }
+
+    /// Whether `copy_in` is implemented
+    fn supports_copy_in(&self) -> bool {
+        false
+    }
+
+    /// Have the server stop the user statements of the current command once they run
+    /// longer than `timeout`, until cleared with `None`. Set by the pool around commands
+    /// run with the query timeout.
+    fn set_statement_timeout(&mut self, _timeout: Option<Duration>) {}
+
+    /// Give the driver a tracker to publish the server session running each user statement
+    fn set_session_tracker(&mut self, _tracker: SessionTracker) {}
+
+    /// Cancel the statement running on another session of this server.
+    /// Called on a separate control connection, never on the busy one.
+    async fn cancel(&mut self, _session_id: i64) -> Result<()> {
+        Err(anyhow!(
+            "Query cancellation is not supported for this database"
+        ))
+    }
+
+    /// Whether `cancel` ends the whole session rather than only its statement, leaving
+    /// the cancelled connection unusable
+    fn cancel_ends_session(&self) -> bool {
+        false
+    }
+
     fn as_any_mut(&mut self) -> &mut dyn Any;
 }
 
+/// Bounded sender used by drivers to stream result batches
+pub type QueryStreamSender = tokio::sync::mpsc::Sender<QueryStreamEvent>;
+
+/// Push an event to a result stream, failing if the receiver has gone away
+pub async fn send_stream_event(sink: &QueryStreamSender, event: QueryStreamEvent) -> Result<()> {
+    sink.send(event)
+        .await
+        .map_err(|_| anyhow!("Result stream was closed"))
+}
+
+/// Push an already materialized result to a stream in batches of `batch_size`
+pub async fn stream_query_result(
+    result: QueryResult,
+    batch_size: usize,
+    sink: &QueryStreamSender,
+) -> Result<u64> {
+    send_stream_event(
+        sink,
+        QueryStreamEvent::Columns {
+            columns: result.columns,
+            column_display_names: result.column_display_names,
+            column_types: result.column_types,
+        },
+    )
+    .await?;
+
+    let row_count = result.rows.len() as u64;
+    let mut rows = result.rows.into_iter();
+    loop {
+        let batch: Vec<_> = rows.by_ref().take(batch_size.max(1)).collect();
+        if batch.is_empty() {
+            break;
+        }
+        send_stream_event(sink, QueryStreamEvent::Rows { rows: batch }).await?;
+    }
+
+    Ok(row_count)
+}
+
+/// Shared slot holding the server-side session id (backend PID, connection id, SPID)
+/// of the statement currently running on a pooled connection
+#[derive(Debug, Clone, Default)]
+pub struct SessionTracker {
+    current: Arc<Mutex<Option<i64>>>,
+}
+
+impl SessionTracker {
+    /// Session id of the statement currently running, if any
+    pub fn current(&self) -> Option<i64> {
+        *self.current.lock().unwrap()
+    }
+
+    /// Record the session running a statement until the returned guard is dropped
+    pub fn track(&self, session_id: i64) -> SessionGuard {
+        *self.current.lock().unwrap() = Some(session_id);
+        SessionGuard {
+            tracker: self.clone(),
+        }
+    }
+}
+
+/// Clears the tracked session when the statement finishes
+pub struct SessionGuard {
+    tracker: SessionTracker,
+}
+
+impl Drop for SessionGuard {
+    fn drop(&mut self) {
+        *self.tracker.current.lock().unwrap() = None;
+    }
+}
+
+/// Session ids a driver has already read from the server, so statements on the same
+/// physical connection do not ask again: the id of the session pinned by a transaction,
+/// and the id of the only connection of a tab session's pool, recorded when the pool
+/// opens that connection. Other pooled connections are looked up per statement.
+#[derive(Debug, Clone, Default)]
+pub struct KnownSessions {
+    pinned: Option<i64>,
+    single: Option<Arc<Mutex<Option<i64>>>>,
+}
+
+impl KnownSessions {
+    /// Slot a single-connection pool records the id of its connection in
+    pub fn single_connection(&mut self) -> Arc<Mutex<Option<i64>>> {
+        self.single.get_or_insert_with(Default::default).clone()
+    }
+
+    /// Id of the connection a statement runs on, when already known
+    pub fn get(&self, pinned: bool) -> Option<i64> {
+        self.pinned
+            .filter(|_| pinned)
+            .or_else(|| self.single.as_ref().and_then(|id| *id.lock().unwrap()))
+    }
+
+    /// Keep the id read for the pinned session until its transaction ends
+    pub fn remember(&mut self, pinned: bool, session_id: i64) {
+        if pinned {
+            self.pinned = Some(session_id);
+        }
+    }
+
+    /// Forget the pinned session once no transaction holds it
+    pub fn unpin(&mut self) {
+        self.pinned = None;
+    }
+}
+
 /// Trait for building SQL queries from JSON request structure
 pub trait QueryBuilder {
     /// Build a SELECT query with filters, sorting, and pagination
@@ -113,6 +409,83 @@ pub trait QueryBuilder {
 
     /// Build LIMIT and OFFSET clause
     fn build_pagination_clause(&self, limit: usize, offset: usize) -> String;
+
+    /// Build a SELECT query whose filter values are bound parameters.
+    /// `table_schema` supplies column types for databases that need typed placeholders.
+    fn build_select_query_bound(
+        &self,
+        request: &QueryRequest,
+        _table_schema: Option<&TableSchema>,
+    ) -> Result<BoundQuery> {
+        Ok(BoundQuery::new(self.build_select_query(request)?))
+    }
+
+    /// Placeholder for the parameter at 1-based `index`
+    fn placeholder(
+        &self,
+        _index: usize,
+        _column_type: Option<&str>,
+        _value: &serde_json::Value,
+    ) -> String {
+        "?".to_string()
+    }
+
+    /// Whether values of a column type can be compared with `=` to detect concurrent
+    /// changes to a row
+    fn supports_equality(&self, data_type: &str) -> bool {
+        crate::db::bound_query::comparable_type(data_type)
+    }
+
+    /// Whether a table column holds the row version `row_version_condition` compares,
+    /// so a value read before the row last changed is stale
+    fn is_row_version_column(&self, _column: &Column) -> bool {
+        false
+    }
+
+    /// Expression selected next to `*` as the hidden row version column, for dialects
+    /// whose row version is not a table column. It must change with every update.
+    fn row_version(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Condition that a row still has the version it was loaded with, binding the
+    /// version. `None` when the dialect has no row version or the row was loaded without it.
+    fn row_version_condition(
+        &self,
+        _table_schema: &TableSchema,
+        _original: &HashMap<String, serde_json::Value>,
+        _params: &mut Vec<serde_json::Value>,
+    ) -> Option<String> {
+        None
+    }
+
+    /// Expression selected next to `*` to identify the physical row of a table without a
+    /// primary key. `None` when the dialect has no such locator.
+    fn row_locator(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Condition matching the row whose locator was loaded as `value`, which is already
+    /// rendered as a placeholder or literal
+    fn row_locator_condition(&self, _value: &str) -> Option<String> {
+        None
+    }
+
+    /// Clause appended to UPDATE and DELETE statements that match a keyless row by all of
+    /// its values, so duplicate rows are changed one at a time
+    fn single_row_limit(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Column expression compared against a bound parameter
+    fn bound_column(
+        &self,
+        column: &str,
+        _column_type: Option<&str>,
+        _value: &serde_json::Value,
+    ) -> String {
+        self.quote_identifier(column)
+    }
 }
 
 /// Factory function to create a database connection based on type
@@ -127,41 +500,198 @@ pub fn create_connection(db_type: &DatabaseType) -> Box<dyn DatabaseConnection>
     }
 }
 
-/// Trait for building CRUD (INSERT, UPDATE, DELETE) queries
+/// Trait for building CRUD (INSERT, UPDATE, DELETE) queries. SQL dialects that bind
+/// values implement the `_bound` builders; the literal ones are for engines without
+/// parameters, which the `_bound` defaults fall back to.
 pub trait CRUDQueryBuilder: QueryBuilder + Send + Sync {
     /// Build an INSERT query with column ordering from table schema
     fn build_insert_query(
+        &self,
+        _table: &str,
+        _schema: Option<&str>,
+        _row: &HashMap<String, serde_json::Value>,
+        _table_schema: &crate::models::schema::TableSchema,
+    ) -> Result<String> {
+        Err(anyhow!(
+            "This database only builds statements with bound values"
+        ))
+    }
+
+    /// Build an UPDATE query with column ordering from table schema
+    fn build_update_query(
+        &self,
+        _table: &str,
+        _schema: Option<&str>,
+        _edited_row: &EditedRow,
+        _primary_keys: &[&String],
+        _table_schema: &crate::models::schema::TableSchema,
+    ) -> Result<String> {
+        Err(anyhow!(
+            "This database only builds statements with bound values"
+        ))
+    }
+
+    /// Build a DELETE query
+    fn build_delete_query(
+        &self,
+        _table: &str,
+        _schema: Option<&str>,
+        _row: &HashMap<String, serde_json::Value>,
+        _primary_keys: &[&String],
+    ) -> Result<String> {
+        Err(anyhow!(
+            "This database only builds statements with bound values"
+        ))
+    }
+
+    /// Quoted table name used by INSERT, UPDATE and DELETE statements
+    fn crud_table_name(&self, table: &str, schema: Option<&str>) -> String {
+        match schema {
+            Some(s) => format!(
+                "{}.{}",
+                self.quote_identifier(s),
+                self.quote_identifier(table)
+            ),
+            None => self.quote_identifier(table),
+        }
+    }
+
+    /// Build an INSERT query with bound values
+    fn build_insert_query_bound(
         &self,
         table: &str,
         schema: Option<&str>,
         row: &HashMap<String, serde_json::Value>,
-        table_schema: &crate::models::schema::TableSchema,
-    ) -> Result<String>;
+        table_schema: &TableSchema,
+    ) -> Result<BoundQuery> {
+        Ok(BoundQuery::new(self.build_insert_query(
+            table,
+            schema,
+            row,
+            table_schema,
+        )?))
+    }
 
-    /// Build an UPDATE query with column ordering from table schema
-    fn build_update_query(
+    /// Build an INSERT with bound values together with the way the stored row is read
+    /// back. Dialects that cannot return rows only run the INSERT.
+    fn build_insert_returning_bound(
+        &self,
+        table: &str,
+        schema: Option<&str>,
+        row: &HashMap<String, serde_json::Value>,
+        table_schema: &TableSchema,
+    ) -> Result<(BoundQuery, InsertReadBack)> {
+        Ok((
+            self.build_insert_query_bound(table, schema, row, table_schema)?,
+            InsertReadBack::Unavailable,
+        ))
+    }
+
+    /// Build one INSERT of several rows with bound values, each row holding the values of
+    /// `columns` in order. With `upsert`, rows whose primary key exists are updated.
+    fn build_insert_batch_bound(
+        &self,
+        table: &str,
+        schema: Option<&str>,
+        columns: &[String],
+        rows: &[Vec<serde_json::Value>],
+        table_schema: &TableSchema,
+        upsert: bool,
+    ) -> Result<BoundQuery> {
+        if upsert {
+            return Err(anyhow!("Upserting is not supported for this database"));
+        }
+        let table_name = self.crud_table_name(table, schema);
+        crate::db::bound_query::build_insert_batch(
+            self,
+            &table_name,
+            columns,
+            rows,
+            table_schema,
+            "",
+        )
+    }
+
+    /// Most rows `build_insert_batch_bound` puts in one statement for `column_count`
+    /// columns, within the dialect's limit on bound parameters
+    fn max_batch_rows(&self, column_count: usize) -> usize {
+        (u16::MAX as usize / column_count.max(1)).max(1)
+    }
+
+    /// Build an UPDATE query with bound values
+    fn build_update_query_bound(
         &self,
         table: &str,
         schema: Option<&str>,
         edited_row: &EditedRow,
         primary_keys: &[&String],
-        table_schema: &crate::models::schema::TableSchema,
-    ) -> Result<String>;
+        table_schema: &TableSchema,
+    ) -> Result<BoundQuery> {
+        Ok(BoundQuery::new(self.build_update_query(
+            table,
+            schema,
+            edited_row,
+            primary_keys,
+            table_schema,
+        )?))
+    }
 
-    /// Build a DELETE query
-    fn build_delete_query(
+    /// Build a DELETE query with bound values
+    fn build_delete_query_bound(
         &self,
         table: &str,
         schema: Option<&str>,
         row: &HashMap<String, serde_json::Value>,
         primary_keys: &[&String],
-    ) -> Result<String>;
+        _table_schema: &TableSchema,
+    ) -> Result<BoundQuery> {
+        Ok(BoundQuery::new(self.build_delete_query(
+            table,
+            schema,
+            row,
+            primary_keys,
+        )?))
+    }
+
+    /// Build an UPDATE that matches no row when the row changed on the server since it
+    /// was loaded
+    fn build_update_query_checked_bound(
+        &self,
+        _table: &str,
+        _schema: Option<&str>,
+        _edited_row: &EditedRow,
+        _primary_keys: &[&String],
+        _table_schema: &TableSchema,
+    ) -> Result<BoundQuery> {
+        Err(anyhow!(
+            "Conflict detection is not supported for this database"
+        ))
+    }
+
+    /// Build a SELECT of the current values of one row, identified by its keys
+    fn build_select_row_bound(
+        &self,
+        _table: &str,
+        _schema: Option<&str>,
+        _row: &HashMap<String, serde_json::Value>,
+        _primary_keys: &[&String],
+        _table_schema: &TableSchema,
+    ) -> Result<BoundQuery> {
+        Err(anyhow!(
+            "Reading a single row is not supported for this database"
+        ))
+    }
 
     /// Format a value for SQL based on its JSON type
     fn format_value(&self, val: &serde_json::Value) -> String;
 
     /// Format a WHERE condition based on JSON value
-    fn format_where_condition(&self, val: &serde_json::Value) -> String;
+    fn format_where_condition(&self, val: &serde_json::Value) -> String {
+        match val {
+            serde_json::Value::Null => "IS NULL".to_string(),
+            _ => format!("= {}", self.format_value(val)),
+        }
+    }
 
     /// Escape SQL string literals
     fn escape_sql_string(&self, s: &str) -> String;
@@ -0,0 +1,400 @@
+use crate::db::sql_splitter::SqlDialect;
+use crate::db::traits::DatabaseConnection;
+use anyhow::{anyhow, Result};
+use std::ops::{Deref, DerefMut};
+
+/// Transaction control statement run on a pinned session
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransactionCommand {
+    /// Open a transaction, named on SQL Server when the statement gave one
+    Begin(Option<String>),
+    Commit,
+    Rollback,
+    Savepoint(String),
+    RollbackToSavepoint(String),
+    ReleaseSavepoint(String),
+}
+
+impl TransactionCommand {
+    /// Build a begin command for a named transaction, rejecting names that are not plain
+    /// identifiers
+    pub fn begin_named(name: &str) -> Result<Self> {
+        validate_savepoint_name(name).map(|name| Self::Begin(Some(name.to_string())))
+    }
+
+    /// Build a savepoint command, rejecting names that are not plain identifiers
+    pub fn savepoint(name: &str) -> Result<Self> {
+        validate_savepoint_name(name).map(|name| Self::Savepoint(name.to_string()))
+    }
+
+    /// Build a rollback-to-savepoint command, rejecting names that are not plain identifiers
+    pub fn rollback_to_savepoint(name: &str) -> Result<Self> {
+        validate_savepoint_name(name).map(|name| Self::RollbackToSavepoint(name.to_string()))
+    }
+
+    /// Build a release-savepoint command, rejecting names that are not plain identifiers
+    pub fn release_savepoint(name: &str) -> Result<Self> {
+        validate_savepoint_name(name).map(|name| Self::ReleaseSavepoint(name.to_string()))
+    }
+
+    /// SQL text for this command in the given dialect. Returns `None` when the dialect
+    /// has no equivalent (SQL Server cannot release a savepoint).
+    pub fn sql(&self, dialect: SqlDialect) -> Option<String> {
+        let sql = match (self, dialect) {
+            (Self::Begin(Some(name)), SqlDialect::MSSQL) => format!("BEGIN TRANSACTION {}", name),
+            (Self::Begin(_), SqlDialect::MSSQL) => "BEGIN TRANSACTION".to_string(),
+            (Self::Begin(_), SqlDialect::MySQL) => "START TRANSACTION".to_string(),
+            (Self::Begin(_), _) => "BEGIN".to_string(),
+            (Self::Commit, SqlDialect::MSSQL) => "COMMIT TRANSACTION".to_string(),
+            (Self::Commit, _) => "COMMIT".to_string(),
+            (Self::Rollback, SqlDialect::MSSQL) => "ROLLBACK TRANSACTION".to_string(),
+            (Self::Rollback, _) => "ROLLBACK".to_string(),
+            (Self::Savepoint(name), SqlDialect::MSSQL) => format!("SAVE TRANSACTION {}", name),
+            (Self::Savepoint(name), _) => format!("SAVEPOINT {}", name),
+            (Self::RollbackToSavepoint(name), SqlDialect::MSSQL) => {
+                format!("ROLLBACK TRANSACTION {}", name)
+            }
+            (Self::RollbackToSavepoint(name), _) => format!("ROLLBACK TO SAVEPOINT {}", name),
+            (Self::ReleaseSavepoint(_), SqlDialect::MSSQL) => return None,
+            (Self::ReleaseSavepoint(name), _) => format!("RELEASE SAVEPOINT {}", name),
+        };
+        Some(sql)
+    }
+
+    /// Recognize a transaction control statement typed in the editor, so it can be
+    /// routed to the pinned session instead of an arbitrary pooled connection. `END`
+    /// commits only in PostgreSQL; elsewhere it closes a block.
+    ///
+    /// SQL Server statements may name the transaction. `transaction_name` is the name
+    /// the open transaction was begun with: rolling back to it ends the transaction,
+    /// while rolling back to any other name returns to a savepoint.
+    pub fn parse(
+        statement: &str,
+        dialect: SqlDialect,
+        transaction_name: Option<&str>,
+    ) -> Option<Self> {
+        let cleaned = statement.trim().trim_end_matches(';').trim();
+        let words: Vec<String> = cleaned
+            .split_whitespace()
+            .map(|w| w.to_uppercase())
+            .collect();
+        let words: Vec<&str> = words.iter().map(|w| w.as_str()).collect();
+        let raw_words: Vec<&str> = cleaned.split_whitespace().collect();
+
+        let mssql = dialect == SqlDialect::MSSQL;
+        match words.as_slice() {
+            ["BEGIN"] | ["BEGIN", "WORK" | "TRAN" | "TRANSACTION"] | ["START", "TRANSACTION"] => {
+                Some(Self::Begin(None))
+            }
+            ["BEGIN", "TRAN" | "TRANSACTION", _] if mssql => Self::begin_named(raw_words[2]).ok(),
+            ["COMMIT"] | ["COMMIT", "WORK" | "TRAN" | "TRANSACTION"] => Some(Self::Commit),
+            // SQL Server ignores the name of a committed transaction
+            ["COMMIT", "TRAN" | "TRANSACTION", _] if mssql => Some(Self::Commit),
+            ["END"] if dialect == SqlDialect::PostgreSQL => Some(Self::Commit),
+            ["ROLLBACK"] | ["ROLLBACK", "WORK" | "TRAN" | "TRANSACTION"] => Some(Self::Rollback),
+            ["SAVEPOINT", _] => Self::savepoint(raw_words[1]).ok(),
+            ["SAVE", "TRAN" | "TRANSACTION", _] => Self::savepoint(raw_words[2]).ok(),
+            ["ROLLBACK", "TO", "SAVEPOINT", _] => Self::rollback_to_savepoint(raw_words[3]).ok(),
+            ["ROLLBACK", "TO", _] => Self::rollback_to_savepoint(raw_words[2]).ok(),
+            // Transaction names are case sensitive on every SQL Server collation
+            ["ROLLBACK", "TRAN" | "TRANSACTION", _]
+                if mssql && transaction_name == Some(raw_words[2]) =>
+            {
+                Some(Self::Rollback)
+            }
+            ["ROLLBACK", "TRAN" | "TRANSACTION", _] => {
+                Self::rollback_to_savepoint(raw_words[2]).ok()
+            }
+            ["RELEASE", "SAVEPOINT", _] => Self::release_savepoint(raw_words[2]).ok(),
+            ["RELEASE", _] => Self::release_savepoint(raw_words[1]).ok(),
+            _ => None,
+        }
+    }
+}
+
+/// Savepoint and transaction names are spliced into SQL, so only plain identifiers are
+/// accepted
+fn validate_savepoint_name(name: &str) -> Result<&str> {
+    let mut chars = name.chars();
+    let valid = chars
+        .next()
+        .map(|c| c.is_ascii_alphabetic() || c == '_')
+        .unwrap_or(false)
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+    if valid {
+        Ok(name)
+    } else {
+        Err(anyhow!("Invalid name '{}'", name))
+    }
+}
+
+/// Savepoint name used for work that is always undone
+const SCRATCH_SAVEPOINT: &str = "rustdbgrid_scratch";
+
+/// Statements that open a throwaway unit of work on a session and undo it afterwards:
+/// a savepoint inside an already open transaction, otherwise a transaction of its own
+pub fn scratch_transaction_sql(dialect: SqlDialect, in_transaction: bool) -> (String, Vec<String>) {
+    let (begin, undo) = if in_transaction {
+        let savepoint = SCRATCH_SAVEPOINT.to_string();
+        (
+            TransactionCommand::Savepoint(savepoint.clone()),
+            vec![
+                TransactionCommand::RollbackToSavepoint(savepoint.clone()),
+                TransactionCommand::ReleaseSavepoint(savepoint),
+            ],
+        )
+    } else {
+        (
+            TransactionCommand::Begin(None),
+            vec![TransactionCommand::Rollback],
+        )
+    };
+
+    (
+        begin.sql(dialect).unwrap_or_default(),
+        undo.iter().filter_map(|c| c.sql(dialect)).collect(),
+    )
+}
+
+/// A group of statements applied together on a driver's pinned session: a transaction
+/// of its own, or a savepoint when a manual transaction is already open so the work
+/// joins it
+pub struct UnitOfWork {
+    savepoint: Option<String>,
+}
+
+impl UnitOfWork {
+    /// Start a unit of work. `savepoint` names the savepoint used inside an open
+    /// transaction and must be a plain identifier.
+    pub async fn begin(conn: &mut Box<dyn DatabaseConnection>, savepoint: &str) -> Result<Self> {
+        if conn.in_transaction() {
+            conn.transaction_control(&TransactionCommand::savepoint(savepoint)?)
+                .await?;
+            Ok(Self {
+                savepoint: Some(savepoint.to_string()),
+            })
+        } else {
+            conn.transaction_control(&TransactionCommand::Begin(None))
+                .await?;
+            Ok(Self { savepoint: None })
+        }
+    }
+
+    /// Make the work permanent, or part of the enclosing transaction
+    pub async fn commit(self, conn: &mut Box<dyn DatabaseConnection>) -> Result<()> {
+        match self.savepoint {
+            Some(name) => {
+                conn.transaction_control(&TransactionCommand::ReleaseSavepoint(name))
+                    .await
+            }
+            None => conn.transaction_control(&TransactionCommand::Commit).await,
+        }
+    }
+
+    /// Undo the work, leaving an enclosing transaction as it was before `begin`
+    pub async fn rollback(self, conn: &mut Box<dyn DatabaseConnection>) -> Result<()> {
+        match self.savepoint {
+            Some(name) => {
+                conn.transaction_control(&TransactionCommand::RollbackToSavepoint(name.clone()))
+                    .await?;
+                conn.transaction_control(&TransactionCommand::ReleaseSavepoint(name))
+                    .await
+            }
+            None => {
+                conn.transaction_control(&TransactionCommand::Rollback)
+                    .await
+            }
+        }
+    }
+}
+
+/// Connection used for a user statement: the session pinned by an open transaction,
+/// or one checked out of the pool for this statement only
+pub enum SessionConn<'a, C> {
+    Pinned(&'a mut C),
+    Pooled(C),
+}
+
+impl<C> SessionConn<'_, C> {
+    pub fn is_pinned(&self) -> bool {
+        matches!(self, SessionConn::Pinned(_))
+    }
+}
+
+impl<C: Deref> Deref for SessionConn<'_, C> {
+    type Target = C::Target;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            SessionConn::Pinned(conn) => conn,
+            SessionConn::Pooled(conn) => conn,
+        }
+    }
+}
+
+impl<C: DerefMut> DerefMut for SessionConn<'_, C> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            SessionConn::Pinned(conn) => conn,
+            SessionConn::Pooled(conn) => conn,
+        }
+    }
+}
+
+/// Pick the pinned session of a sqlx-backed driver, or check out a pooled connection
+pub async fn sqlx_session_conn<'a, DB: sqlx::Database>(
+    pool: &sqlx::Pool<DB>,
+    pinned: &'a mut Option<sqlx::pool::PoolConnection<DB>>,
+) -> Result<SessionConn<'a, sqlx::pool::PoolConnection<DB>>> {
+    match pinned {
+        Some(conn) => Ok(SessionConn::Pinned(conn)),
+        None => Ok(SessionConn::Pooled(pool.acquire().await?)),
+    }
+}
+
+/// Connection a transaction control command runs on for a sqlx-backed driver:
+/// `Begin` checks out a fresh connection to pin, anything else needs the pinned one
+pub async fn sqlx_transaction_conn<'a, DB: sqlx::Database>(
+    pool: &sqlx::Pool<DB>,
+    pinned: &'a mut Option<sqlx::pool::PoolConnection<DB>>,
+    command: &TransactionCommand,
+) -> Result<SessionConn<'a, sqlx::pool::PoolConnection<DB>>> {
+    match (command, pinned) {
+        (TransactionCommand::Begin(_), Some(_)) => Err(anyhow!("A transaction is already open")),
+        (TransactionCommand::Begin(_), None) => Ok(SessionConn::Pooled(pool.acquire().await?)),
+        (_, Some(conn)) => Ok(SessionConn::Pinned(conn)),
+        (_, None) => Err(anyhow!("No transaction is open")),
+    }
+}
+
+/// Update the pinned session after a transaction control command succeeded:
+/// `Begin` pins its connection, `Commit` and `Rollback` release it
+pub fn finish_transaction_command<C>(
+    pinned: &mut Option<C>,
+    conn: Option<C>,
+    command: &TransactionCommand,
+) {
+    match command {
+        TransactionCommand::Begin(_) => *pinned = conn,
+        TransactionCommand::Commit | TransactionCommand::Rollback => *pinned = None,
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_editor_transaction_statements() {
+        assert_eq!(
+            TransactionCommand::parse("begin;", SqlDialect::PostgreSQL, None),
+            Some(TransactionCommand::Begin(None))
+        );
+        assert_eq!(
+            TransactionCommand::parse("START TRANSACTION", SqlDialect::PostgreSQL, None),
+            Some(TransactionCommand::Begin(None))
+        );
+        assert_eq!(
+            TransactionCommand::parse("COMMIT TRAN", SqlDialect::PostgreSQL, None),
+            Some(TransactionCommand::Commit)
+        );
+        assert_eq!(
+            TransactionCommand::parse(
+                "rollback to savepoint before_fix",
+                SqlDialect::PostgreSQL,
+                None
+            ),
+            Some(TransactionCommand::RollbackToSavepoint(
+                "before_fix".to_string()
+            ))
+        );
+        assert_eq!(
+            TransactionCommand::parse("SAVE TRANSACTION sp1", SqlDialect::PostgreSQL, None),
+            Some(TransactionCommand::Savepoint("sp1".to_string()))
+        );
+        assert_eq!(
+            TransactionCommand::parse("BEGIN SELECT 1; END", SqlDialect::PostgreSQL, None),
+            None
+        );
+        assert_eq!(
+            TransactionCommand::parse("SAVEPOINT \"x; DROP\"", SqlDialect::PostgreSQL, None),
+            None
+        );
+        assert_eq!(
+            TransactionCommand::parse("END", SqlDialect::PostgreSQL, None),
+            Some(TransactionCommand::Commit)
+        );
+        assert_eq!(
+            TransactionCommand::parse("END;", SqlDialect::MySQL, None),
+            None
+        );
+        assert_eq!(
+            TransactionCommand::parse("END", SqlDialect::MSSQL, None),
+            None
+        );
+    }
+
+    #[test]
+    fn test_renders_dialect_specific_sql() {
+        let sp = TransactionCommand::savepoint("sp1").unwrap();
+        assert_eq!(
+            sp.sql(SqlDialect::MSSQL).as_deref(),
+            Some("SAVE TRANSACTION sp1")
+        );
+        assert_eq!(
+            TransactionCommand::Begin(None)
+                .sql(SqlDialect::MySQL)
+                .as_deref(),
+            Some("START TRANSACTION")
+        );
+        assert_eq!(
+            TransactionCommand::release_savepoint("sp1")
+                .unwrap()
+                .sql(SqlDialect::MSSQL),
+            None
+        );
+        assert!(TransactionCommand::savepoint("1bad").is_err());
+    }
+
+    #[test]
+    fn test_parses_named_mssql_transactions() {
+        let mssql = SqlDialect::MSSQL;
+        let begin = TransactionCommand::parse("BEGIN TRAN outer_tx;", mssql, None);
+        assert_eq!(
+            begin,
+            Some(TransactionCommand::Begin(Some("outer_tx".to_string())))
+        );
+        assert_eq!(
+            begin.unwrap().sql(mssql).as_deref(),
+            Some("BEGIN TRANSACTION outer_tx")
+        );
+        assert_eq!(
+            TransactionCommand::parse("begin transaction outer_tx", mssql, None),
+            Some(TransactionCommand::Begin(Some("outer_tx".to_string())))
+        );
+        assert_eq!(
+            TransactionCommand::parse("COMMIT TRAN outer_tx", mssql, Some("outer_tx")),
+            Some(TransactionCommand::Commit)
+        );
+        assert_eq!(
+            TransactionCommand::parse("ROLLBACK TRAN outer_tx", mssql, Some("outer_tx")),
+            Some(TransactionCommand::Rollback)
+        );
+        assert_eq!(
+            TransactionCommand::parse("ROLLBACK TRANSACTION sp1", mssql, Some("outer_tx")),
+            Some(TransactionCommand::RollbackToSavepoint("sp1".to_string()))
+        );
+        assert_eq!(
+            TransactionCommand::parse("ROLLBACK TRAN OUTER_TX", mssql, Some("outer_tx")),
+            Some(TransactionCommand::RollbackToSavepoint(
+                "OUTER_TX".to_string()
+            ))
+        );
+        assert_eq!(
+            TransactionCommand::parse("BEGIN TRAN outer_tx", SqlDialect::PostgreSQL, None),
+            None
+        );
+    }
+}
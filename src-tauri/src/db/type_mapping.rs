@@ -0,0 +1,412 @@
+use crate::db::mssql::type_converter::{map_mssql_type, MssqlColType};
+use crate::db::mysql::type_converter::{MySqlColType, MySqlTypeConverter};
+use crate::db::postgres::type_converter::{map_pg_type, PgColType};
+use crate::models::connection::DatabaseType;
+use serde_json::Value;
+
+/// Longest text MySQL keeps in a VARCHAR, counting four bytes per character
+const MYSQL_MAX_VARCHAR: u32 = 16_383;
+
+/// Longest text SQL Server keeps in an NVARCHAR(n)
+const MSSQL_MAX_NVARCHAR: u32 = 4_000;
+
+/// Length given to unbounded text columns that are part of a key, which MySQL and
+/// SQL Server cannot index
+const KEY_VARCHAR_LENGTH: u32 = 255;
+
+/// Column type shared by the SQL dialects, to carry a column from one to another.
+/// Types without a counterpart, such as arrays, enums and geometry, become text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    Boolean,
+    SmallInt,
+    Integer,
+    BigInt,
+    Real,
+    Double,
+    /// Precision and scale, when the column declares them
+    Decimal(Option<(u32, u32)>),
+    /// Fixed-length text, with its length in characters
+    Char(Option<u32>),
+    /// Variable-length text, with its longest length in characters when bounded
+    VarChar(Option<u32>),
+    Text,
+    Binary,
+    Date,
+    Time,
+    Timestamp,
+    TimestampTz,
+    Uuid,
+    Json,
+}
+
+impl ColumnType {
+    /// Type of a column as a table schema of `db_type` reports it, e.g. `varchar(255)`
+    pub fn from_data_type(db_type: &DatabaseType, data_type: &str) -> Self {
+        let lower = data_type.trim().to_lowercase();
+        if lower.ends_with("[]") {
+            return ColumnType::Text;
+        }
+        let base = lower.split('(').next().unwrap_or_default().trim();
+        let base = base.strip_suffix(" unsigned").unwrap_or(base);
+        let args: Vec<Option<u32>> = lower
+            .split_once('(')
+            .and_then(|(_, rest)| rest.split_once(')'))
+            .map(|(args, _)| args.split(',').map(|arg| arg.trim().parse().ok()).collect())
+            .unwrap_or_default();
+        let length = args.first().copied().flatten();
+        let precision = match args[..] {
+            [Some(p), Some(s)] => Some((p, s)),
+            [Some(p)] => Some((p, 0)),
+            _ => None,
+        };
+        let unsigned = lower.contains("unsigned");
+
+        match db_type {
+            DatabaseType::PostgreSQL => {
+                let base = match base {
+                    "timestamp without time zone" => "timestamp",
+                    "timestamp with time zone" => "timestamptz",
+                    "time without time zone" => "time",
+                    "time with time zone" => "timetz",
+                    "character varying" => "varchar",
+                    "character" => "char",
+                    other => other,
+                };
+                match map_pg_type(base) {
+                    PgColType::Boolean => ColumnType::Boolean,
+                    PgColType::Int16 => ColumnType::SmallInt,
+                    PgColType::Int32 => ColumnType::Integer,
+                    PgColType::Int64 | PgColType::Oid => ColumnType::BigInt,
+                    PgColType::Float32 => ColumnType::Real,
+                    PgColType::Float64 => ColumnType::Double,
+                    PgColType::Numeric => ColumnType::Decimal(precision),
+                    PgColType::Money => ColumnType::Decimal(Some((19, 2))),
+                    PgColType::String => match base {
+                        "varchar" => ColumnType::VarChar(length),
+                        "char" | "bpchar" => ColumnType::Char(length.or(Some(1))),
+                        _ => ColumnType::Text,
+                    },
+                    PgColType::Uuid => ColumnType::Uuid,
+                    PgColType::Json => ColumnType::Json,
+                    PgColType::Bytea => ColumnType::Binary,
+                    PgColType::Date => ColumnType::Date,
+                    PgColType::Time | PgColType::TimeTz => ColumnType::Time,
+                    PgColType::Timestamp => ColumnType::Timestamp,
+                    PgColType::TimestampTz => ColumnType::TimestampTz,
+                    _ => ColumnType::Text,
+                }
+            }
+            DatabaseType::MySQL => match MySqlTypeConverter::map_type(&base.to_uppercase()) {
+                MySqlColType::Integer => match base {
+                    "tinyint" if length == Some(1) => ColumnType::Boolean,
+                    "tinyint" => ColumnType::SmallInt,
+                    "smallint" if unsigned => ColumnType::Integer,
+                    "smallint" => ColumnType::SmallInt,
+                    "mediumint" => ColumnType::Integer,
+                    "int" if unsigned => ColumnType::BigInt,
+                    "int" => ColumnType::Integer,
+                    _ if unsigned => ColumnType::Decimal(Some((20, 0))),
+                    _ => ColumnType::BigInt,
+                },
+                MySqlColType::Float => match base {
+                    "float" => ColumnType::Real,
+                    "double" => ColumnType::Double,
+                    _ => ColumnType::Decimal(precision),
+                },
+                MySqlColType::Boolean => ColumnType::Boolean,
+                MySqlColType::String => match base {
+                    "varchar" => ColumnType::VarChar(length),
+                    "char" => ColumnType::Char(length.or(Some(1))),
+                    _ => ColumnType::Text,
+                },
+                MySqlColType::Blob => ColumnType::Binary,
+                MySqlColType::Date => ColumnType::Date,
+                MySqlColType::Time => ColumnType::Time,
+                MySqlColType::DateTime => ColumnType::Timestamp,
+                MySqlColType::Unknown => match base {
+                    "json" => ColumnType::Json,
+                    "numeric" => ColumnType::Decimal(precision),
+                    "real" | "double precision" => ColumnType::Double,
+                    "integer" => ColumnType::Integer,
+                    "year" => ColumnType::SmallInt,
+                    "bit" if length.unwrap_or(1) == 1 => ColumnType::Boolean,
+                    "bit" => ColumnType::BigInt,
+                    _ => ColumnType::Text,
+                },
+            },
+            DatabaseType::MSSQL => match map_mssql_type(&base.to_uppercase()) {
+                MssqlColType::String => match base {
+                    "varchar" | "nvarchar" => ColumnType::VarChar(length),
+                    "char" | "nchar" => ColumnType::Char(length.or(Some(1))),
+                    _ => ColumnType::Text,
+                },
+                MssqlColType::Int64 => ColumnType::BigInt,
+                MssqlColType::Int32 => ColumnType::Integer,
+                MssqlColType::Int16 | MssqlColType::UInt8 => ColumnType::SmallInt,
+                MssqlColType::Float32 => ColumnType::Real,
+                MssqlColType::Float64 => match length {
+                    Some(bits) if bits <= 24 => ColumnType::Real,
+                    _ => ColumnType::Double,
+                },
+                MssqlColType::Boolean => ColumnType::Boolean,
+                MssqlColType::Uuid => ColumnType::Uuid,
+                // A SQL Server timestamp is a row version rather than a point in time
+                MssqlColType::DateTime if base == "timestamp" => ColumnType::Binary,
+                MssqlColType::DateTime if base == "datetimeoffset" => ColumnType::TimestampTz,
+                MssqlColType::DateTime => ColumnType::Timestamp,
+                MssqlColType::Date => ColumnType::Date,
+                MssqlColType::Time => ColumnType::Time,
+                MssqlColType::Binary => ColumnType::Binary,
+                MssqlColType::Decimal => match base {
+                    "money" => ColumnType::Decimal(Some((19, 4))),
+                    "smallmoney" => ColumnType::Decimal(Some((10, 4))),
+                    _ => ColumnType::Decimal(precision),
+                },
+                MssqlColType::Unknown => ColumnType::Text,
+            },
+            _ => ColumnType::Text,
+        }
+    }
+
+    /// Same type, bounded so a key of MySQL or SQL Server can hold it
+    pub fn for_key(self) -> Self {
+        match self {
+            ColumnType::Text | ColumnType::Json | ColumnType::VarChar(None) => {
+                ColumnType::VarChar(Some(KEY_VARCHAR_LENGTH))
+            }
+            other => other,
+        }
+    }
+
+    /// Name of the type in the DDL of `db_type`
+    pub fn to_data_type(self, db_type: &DatabaseType) -> String {
+        match db_type {
+            DatabaseType::MySQL => self.mysql_type(),
+            DatabaseType::MSSQL => self.mssql_type(),
+            _ => self.pg_type(),
+        }
+    }
+
+    fn pg_type(self) -> String {
+        match self {
+            ColumnType::Boolean => "boolean".to_string(),
+            ColumnType::SmallInt => "smallint".to_string(),
+            ColumnType::Integer => "integer".to_string(),
+            ColumnType::BigInt => "bigint".to_string(),
+            ColumnType::Real => "real".to_string(),
+            ColumnType::Double => "double precision".to_string(),
+            ColumnType::Decimal(Some((p, s))) => format!("numeric({},{})", p, s),
+            ColumnType::Decimal(None) => "numeric".to_string(),
+            ColumnType::Char(Some(n)) => format!("char({})", n),
+            ColumnType::VarChar(Some(n)) => format!("varchar({})", n),
+            ColumnType::Char(None) | ColumnType::VarChar(None) | ColumnType::Text => {
+                "text".to_string()
+            }
+            ColumnType::Binary => "bytea".to_string(),
+            ColumnType::Date => "date".to_string(),
+            ColumnType::Time => "time".to_string(),
+            ColumnType::Timestamp => "timestamp".to_string(),
+            ColumnType::TimestampTz => "timestamptz".to_string(),
+            ColumnType::Uuid => "uuid".to_string(),
+            ColumnType::Json => "jsonb".to_string(),
+        }
+    }
+
+    fn mysql_type(self) -> String {
+        match self {
+            ColumnType::Boolean => "tinyint(1)".to_string(),
+            ColumnType::SmallInt => "smallint".to_string(),
+            ColumnType::Integer => "int".to_string(),
+            ColumnType::BigInt => "bigint".to_string(),
+            ColumnType::Real => "float".to_string(),
+            ColumnType::Double => "double".to_string(),
+            ColumnType::Decimal(Some((p, s))) if p <= 65 => format!("decimal({},{})", p, s.min(30)),
+            ColumnType::Decimal(_) => "decimal(65,30)".to_string(),
+            ColumnType::Char(Some(n)) if n <= 255 => format!("char({})", n),
+            ColumnType::Char(Some(n)) | ColumnType::VarChar(Some(n)) if n <= MYSQL_MAX_VARCHAR => {
+                format!("varchar({})", n)
+            }
+            ColumnType::Char(_) | ColumnType::VarChar(_) | ColumnType::Text => {
+                "longtext".to_string()
+            }
+            ColumnType::Binary => "longblob".to_string(),
+            ColumnType::Date => "date".to_string(),
+            ColumnType::Time => "time(6)".to_string(),
+            ColumnType::Timestamp | ColumnType::TimestampTz => "datetime(6)".to_string(),
+            ColumnType::Uuid => "char(36)".to_string(),
+            ColumnType::Json => "json".to_string(),
+        }
+    }
+
+    fn mssql_type(self) -> String {
+        match self {
+            ColumnType::Boolean => "bit".to_string(),
+            ColumnType::SmallInt => "smallint".to_string(),
+            ColumnType::Integer => "int".to_string(),
+            ColumnType::BigInt => "bigint".to_string(),
+            ColumnType::Real => "real".to_string(),
+            ColumnType::Double => "float".to_string(),
+            ColumnType::Decimal(Some((p, s))) if p <= 38 => format!("decimal({},{})", p, s),
+            ColumnType::Decimal(_) => "decimal(38,10)".to_string(),
+            ColumnType::Char(Some(n)) if n <= MSSQL_MAX_NVARCHAR => format!("nchar({})", n),
+            ColumnType::VarChar(Some(n)) if n <= MSSQL_MAX_NVARCHAR => format!("nvarchar({})", n),
+            ColumnType::Char(_) | ColumnType::VarChar(_) | ColumnType::Text | ColumnType::Json => {
+                "nvarchar(max)".to_string()
+            }
+            ColumnType::Binary => "varbinary(max)".to_string(),
+            ColumnType::Date => "date".to_string(),
+            ColumnType::Time => "time".to_string(),
+            ColumnType::Timestamp => "datetime2".to_string(),
+            ColumnType::TimestampTz => "datetimeoffset".to_string(),
+            ColumnType::Uuid => "uniqueidentifier".to_string(),
+        }
+    }
+
+    /// Value a column of this type in `db_type` is given for a value read from another
+    /// database, or why it does not fit
+    pub fn convert_value(self, value: &Value, db_type: &DatabaseType) -> Result<Value, String> {
+        let text = || match value {
+            Value::String(text) => text.clone(),
+            other => other.to_string(),
+        };
+        match (self, value) {
+            (_, Value::Null) => Ok(Value::Null),
+            (ColumnType::Boolean, Value::Bool(_)) => Ok(value.clone()),
+            (ColumnType::Boolean, Value::Number(n)) => Ok(Value::Bool(n.as_f64() != Some(0.0))),
+            (ColumnType::Boolean, _) => match text().trim().to_lowercase().as_str() {
+                "true" | "t" | "1" => Ok(Value::Bool(true)),
+                "false" | "f" | "0" => Ok(Value::Bool(false)),
+                _ => Err(format!("'{}' is not a boolean", text())),
+            },
+            (ColumnType::SmallInt | ColumnType::Integer | ColumnType::BigInt, _) => {
+                let int = match value {
+                    Value::Bool(b) => Some(i64::from(*b)),
+                    Value::Number(n) => n
+                        .as_i64()
+                        .or_else(|| n.as_f64().filter(|f| f.fract() == 0.0).map(|f| f as i64)),
+                    _ => text().trim().parse().ok(),
+                }
+                .ok_or_else(|| format!("'{}' is not an integer", text()))?;
+                let (min, max) = match self {
+                    ColumnType::SmallInt => (i16::MIN as i64, i16::MAX as i64),
+                    ColumnType::Integer => (i32::MIN as i64, i32::MAX as i64),
+                    _ => (i64::MIN, i64::MAX),
+                };
+                if !(min..=max).contains(&int) {
+                    return Err(format!("{} is out of range for {:?}", int, self));
+                }
+                Ok(Value::from(int))
+            }
+            (ColumnType::Real | ColumnType::Double, Value::Number(_)) => Ok(value.clone()),
+            (ColumnType::Real | ColumnType::Double, _) => text()
+                .trim()
+                .parse::<f64>()
+                .map(|f| {
+                    serde_json::Number::from_f64(f)
+                        .map(Value::Number)
+                        .unwrap_or_else(|| Value::String(text()))
+                })
+                .map_err(|_| format!("'{}' is not a number", text())),
+            (ColumnType::Decimal(_), _) => {
+                let text = text().trim().to_string();
+                text.parse::<f64>()
+                    .map(|_| Value::String(text.clone()))
+                    .map_err(|_| format!("'{}' is not a number", text))
+            }
+            (ColumnType::Char(Some(n)) | ColumnType::VarChar(Some(n)), _) => {
+                let text = text();
+                if text.chars().count() > n as usize {
+                    return Err(format!("'{}' is longer than {} characters", text, n));
+                }
+                Ok(Value::String(text))
+            }
+            (ColumnType::Json, Value::Object(_) | Value::Array(_)) => Ok(value.clone()),
+            (ColumnType::Binary, _) => Err("Binary values cannot be transferred".to_string()),
+            // PostgreSQL reads time zone aware timestamps as UTC with a zone name, which
+            // only PostgreSQL parses
+            (ColumnType::Timestamp | ColumnType::TimestampTz, Value::String(text)) => {
+                let Some(utc) = text.strip_suffix(" UTC") else {
+                    return Ok(value.clone());
+                };
+                Ok(Value::String(match (self, db_type) {
+                    (ColumnType::TimestampTz, DatabaseType::PostgreSQL) => text.clone(),
+                    (ColumnType::TimestampTz, DatabaseType::MSSQL) => format!("{} +00:00", utc),
+                    _ => utc.to_string(),
+                }))
+            }
+            _ => Ok(Value::String(text())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_types_map_between_dialects() {
+        let map = |from: DatabaseType, data_type: &str, to: DatabaseType| {
+            ColumnType::from_data_type(&from, data_type).to_data_type(&to)
+        };
+        use DatabaseType::{MySQL, PostgreSQL, MSSQL};
+
+        assert_eq!(map(MySQL, "tinyint(1)", PostgreSQL), "boolean");
+        assert_eq!(map(MySQL, "int(10) unsigned", PostgreSQL), "bigint");
+        assert_eq!(map(MySQL, "bigint unsigned", MSSQL), "decimal(20,0)");
+        assert_eq!(map(MySQL, "varchar(100)", MSSQL), "nvarchar(100)");
+        assert_eq!(map(MySQL, "datetime", MSSQL), "datetime2");
+        assert_eq!(map(MySQL, "json", PostgreSQL), "jsonb");
+        assert_eq!(map(MySQL, "enum('a','b')", PostgreSQL), "text");
+        assert_eq!(map(PostgreSQL, "int4", MySQL), "int");
+        assert_eq!(map(PostgreSQL, "numeric(12,2)", MSSQL), "decimal(12,2)");
+        assert_eq!(map(PostgreSQL, "numeric", MySQL), "decimal(65,30)");
+        assert_eq!(map(PostgreSQL, "text", MySQL), "longtext");
+        assert_eq!(map(PostgreSQL, "varchar(20000)", MySQL), "longtext");
+        assert_eq!(map(PostgreSQL, "timestamptz", MSSQL), "datetimeoffset");
+        assert_eq!(map(PostgreSQL, "uuid", MySQL), "char(36)");
+        assert_eq!(map(PostgreSQL, "int4[]", MSSQL), "nvarchar(max)");
+        assert_eq!(map(MSSQL, "nvarchar(max)", PostgreSQL), "text");
+        assert_eq!(map(MSSQL, "bit", MySQL), "tinyint(1)");
+        assert_eq!(map(MSSQL, "tinyint", PostgreSQL), "smallint");
+        assert_eq!(map(MSSQL, "money", PostgreSQL), "numeric(19,4)");
+        assert_eq!(map(MSSQL, "uniqueidentifier", PostgreSQL), "uuid");
+        assert_eq!(
+            ColumnType::from_data_type(&PostgreSQL, "text")
+                .for_key()
+                .to_data_type(&MSSQL),
+            "nvarchar(255)"
+        );
+    }
+
+    #[test]
+    fn test_values_convert_to_the_target_type() {
+        let convert = |column_type: ColumnType, value: Value| {
+            column_type.convert_value(&value, &DatabaseType::MSSQL)
+        };
+
+        assert_eq!(convert(ColumnType::Boolean, json!(1)), Ok(json!(true)));
+        assert_eq!(convert(ColumnType::Boolean, json!("f")), Ok(json!(false)));
+        assert_eq!(convert(ColumnType::Integer, json!("42")), Ok(json!(42)));
+        assert!(convert(ColumnType::SmallInt, json!(70000)).is_err());
+        assert_eq!(
+            convert(ColumnType::Decimal(None), json!(1.25)),
+            Ok(json!("1.25"))
+        );
+        assert!(convert(ColumnType::VarChar(Some(3)), json!("abcd")).is_err());
+        assert_eq!(
+            convert(ColumnType::Text, json!({"a": 1})),
+            Ok(json!("{\"a\":1}"))
+        );
+        assert_eq!(
+            convert(ColumnType::TimestampTz, json!("2024-01-02 03:04:05 UTC")),
+            Ok(json!("2024-01-02 03:04:05 +00:00"))
+        );
+        assert_eq!(
+            convert(ColumnType::Timestamp, json!("2024-01-02 03:04:05 UTC")),
+            Ok(json!("2024-01-02 03:04:05"))
+        );
+        assert_eq!(convert(ColumnType::Date, Value::Null), Ok(Value::Null));
+    }
+}
@@ -0,0 +1,568 @@
+use super::{ExportColumn, RowWriter};
+use crate::db::mssql::type_converter::{map_mssql_type, MssqlColType};
+use crate::db::mysql::type_converter::{MySqlColType, MySqlTypeConverter};
+use crate::db::postgres::type_converter::{map_pg_type, PgColType};
+use crate::models::connection::DatabaseType;
+use crate::models::export::ExportFormat;
+use arrow::array::{
+    ArrayRef, BooleanArray, Date32Array, Decimal128Array, Float32Array, Float64Array, Int16Array,
+    Int32Array, Int64Array, StringArray, Time64MicrosecondArray, TimestampMicrosecondArray,
+    UInt64Array, UInt8Array,
+};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit, DECIMAL128_MAX_PRECISION};
+use arrow::ipc::writer::FileWriter;
+use arrow::record_batch::RecordBatch;
+use bigdecimal::{BigDecimal, ToPrimitive};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use serde_json::Value;
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Time zone of `timestamptz` values, which the PostgreSQL driver loads in UTC
+const UTC: &str = "UTC";
+
+/// Open columnar file that record batches are appended to
+enum BatchFile {
+    Parquet(ArrowWriter<File>),
+    Arrow(FileWriter<BufWriter<File>>),
+}
+
+/// A Parquet or Arrow IPC file of one result set. The Arrow schema is derived from the
+/// column types the driver of `db_type` reports, with the nullability of the columns.
+/// Rows are buffered and written `batch_size` at a time, each batch becoming a row
+/// group of Parquet or a record batch of Arrow.
+pub struct ColumnarWriter {
+    path: PathBuf,
+    format: ExportFormat,
+    db_type: DatabaseType,
+    batch_size: usize,
+    schema: Option<SchemaRef>,
+    file: Option<BatchFile>,
+    rows: Vec<Vec<Value>>,
+}
+
+impl ColumnarWriter {
+    pub fn new(
+        path: PathBuf,
+        format: ExportFormat,
+        db_type: DatabaseType,
+        batch_size: usize,
+    ) -> Self {
+        Self {
+            path,
+            format,
+            db_type,
+            batch_size: batch_size.max(1),
+            schema: None,
+            file: None,
+            rows: Vec::new(),
+        }
+    }
+
+    fn write_batch(&mut self) -> io::Result<()> {
+        let (Some(schema), Some(file)) = (&self.schema, &mut self.file) else {
+            return Ok(());
+        };
+        if self.rows.is_empty() {
+            return Ok(());
+        }
+        let arrays = schema
+            .fields()
+            .iter()
+            .enumerate()
+            .map(|(col, field)| column_array(field, &self.rows, col))
+            .collect::<io::Result<Vec<_>>>()?;
+        let batch = RecordBatch::try_new(schema.clone(), arrays).map_err(to_io)?;
+        match file {
+            BatchFile::Parquet(writer) => {
+                writer.write(&batch).map_err(to_io)?;
+                // Close the row group so each batch is one
+                writer.flush().map_err(to_io)?;
+            }
+            BatchFile::Arrow(writer) => writer.write(&batch).map_err(to_io)?,
+        }
+        self.rows.clear();
+        Ok(())
+    }
+}
+
+impl RowWriter for ColumnarWriter {
+    fn begin(&mut self, columns: &[ExportColumn]) -> io::Result<()> {
+        if self.schema.is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "A Parquet or Arrow file holds a single result set",
+            ));
+        }
+        let schema: SchemaRef = Arc::new(Schema::new(
+            columns
+                .iter()
+                .map(|col| {
+                    Field::new(
+                        &col.name,
+                        arrow_type(&self.db_type, &col.data_type),
+                        col.nullable,
+                    )
+                })
+                .collect::<Vec<_>>(),
+        ));
+
+        let file = File::create(&self.path)?;
+        self.file = Some(match self.format {
+            ExportFormat::Arrow => {
+                BatchFile::Arrow(FileWriter::try_new(BufWriter::new(file), &schema).map_err(to_io)?)
+            }
+            _ => {
+                let properties = WriterProperties::builder()
+                    .set_max_row_group_size(self.batch_size)
+                    .set_compression(Compression::SNAPPY)
+                    .build();
+                BatchFile::Parquet(
+                    ArrowWriter::try_new(file, schema.clone(), Some(properties)).map_err(to_io)?,
+                )
+            }
+        });
+        self.schema = Some(schema);
+        Ok(())
+    }
+
+    fn write_row(&mut self, row: &[Value]) -> io::Result<()> {
+        self.rows.push(row.to_vec());
+        if self.rows.len() >= self.batch_size {
+            self.write_batch()?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        if self.file.is_none() {
+            self.begin(&[])?;
+        }
+        self.write_batch()?;
+        match self.file.take() {
+            Some(BatchFile::Parquet(writer)) => writer.close().map(|_| ()).map_err(to_io),
+            Some(BatchFile::Arrow(mut writer)) => writer.finish().map_err(to_io),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Arrow type of a column from the type name its driver reports, e.g. `INT4` from
+/// PostgreSQL or `DATETIME` from MySQL. Decimals of a declared precision up to 38
+/// digits are `Decimal128`. Arrays, other decimals, binary placeholders and types
+/// without an Arrow counterpart are written as text.
+pub fn arrow_type(db_type: &DatabaseType, data_type: &str) -> DataType {
+    // Table schemas add lengths and precisions, e.g. `varchar(255)`
+    let base = data_type.split('(').next().unwrap_or_default().trim();
+    if base.ends_with("[]") || base.starts_with('_') {
+        return DataType::Utf8;
+    }
+    if let Some(decimal) = decimal_type(data_type) {
+        return decimal;
+    }
+    match db_type {
+        DatabaseType::PostgreSQL => {
+            let lower = base.to_lowercase();
+            let base = match lower.as_str() {
+                "timestamp without time zone" => "timestamp",
+                "timestamp with time zone" => "timestamptz",
+                "time without time zone" => "time",
+                "time with time zone" => "timetz",
+                other => other,
+            };
+            pg_arrow_type(map_pg_type(base))
+        }
+        // Either `BIGINT UNSIGNED` as reported or `bigint(20) unsigned` as declared
+        DatabaseType::MySQL
+            if base.to_lowercase().starts_with("bigint")
+                && data_type.to_lowercase().contains("unsigned") =>
+        {
+            DataType::UInt64
+        }
+        DatabaseType::MySQL => mysql_arrow_type(MySqlTypeConverter::map_type(&base.to_uppercase())),
+        DatabaseType::MSSQL => mssql_arrow_type(map_mssql_type(&base.to_uppercase())),
+        _ => DataType::Utf8,
+    }
+}
+
+/// `Decimal128` of a `numeric(p,s)` or `decimal(p,s)` type, when the precision fits.
+/// Without a declared precision the values may not, so they stay text.
+fn decimal_type(data_type: &str) -> Option<DataType> {
+    let lower = data_type.to_lowercase();
+    let (base, modifiers) = lower.split_once('(')?;
+    if !matches!(base.trim(), "numeric" | "decimal" | "dec") {
+        return None;
+    }
+    let (modifiers, _) = modifiers.split_once(')')?;
+    let mut parts = modifiers.split(',').map(|part| part.trim().parse::<u8>());
+    let precision = parts.next()?.ok()?;
+    let scale = parts.next().transpose().ok()?.unwrap_or(0);
+    if precision == 0 || precision > DECIMAL128_MAX_PRECISION || scale > precision {
+        return None;
+    }
+    Some(DataType::Decimal128(precision, scale as i8))
+}
+
+fn pg_arrow_type(col_type: PgColType) -> DataType {
+    match col_type {
+        PgColType::Timestamp => DataType::Timestamp(TimeUnit::Microsecond, None),
+        PgColType::TimestampTz => DataType::Timestamp(TimeUnit::Microsecond, Some(UTC.into())),
+        PgColType::Date => DataType::Date32,
+        PgColType::Time => DataType::Time64(TimeUnit::Microsecond),
+        PgColType::Int16 => DataType::Int16,
+        PgColType::Int32 => DataType::Int32,
+        PgColType::Int64 | PgColType::Oid => DataType::Int64,
+        PgColType::Float32 => DataType::Float32,
+        PgColType::Float64 => DataType::Float64,
+        PgColType::Boolean => DataType::Boolean,
+        _ => DataType::Utf8,
+    }
+}
+
+fn mysql_arrow_type(col_type: MySqlColType) -> DataType {
+    match col_type {
+        MySqlColType::DateTime => DataType::Timestamp(TimeUnit::Microsecond, None),
+        MySqlColType::Date => DataType::Date32,
+        MySqlColType::Time => DataType::Time64(TimeUnit::Microsecond),
+        MySqlColType::Integer => DataType::Int64,
+        MySqlColType::Float => DataType::Float64,
+        MySqlColType::Boolean => DataType::Boolean,
+        _ => DataType::Utf8,
+    }
+}
+
+fn mssql_arrow_type(col_type: MssqlColType) -> DataType {
+    match col_type {
+        MssqlColType::DateTime => DataType::Timestamp(TimeUnit::Microsecond, None),
+        MssqlColType::Date => DataType::Date32,
+        MssqlColType::Time => DataType::Time64(TimeUnit::Microsecond),
+        MssqlColType::Int16 => DataType::Int16,
+        MssqlColType::Int32 => DataType::Int32,
+        MssqlColType::Int64 => DataType::Int64,
+        MssqlColType::UInt8 => DataType::UInt8,
+        MssqlColType::Float32 => DataType::Float32,
+        MssqlColType::Float64 => DataType::Float64,
+        MssqlColType::Boolean => DataType::Boolean,
+        _ => DataType::Utf8,
+    }
+}
+
+/// Arrow array of column `col` of `rows`, converting each value to the field's type
+fn column_array(field: &Field, rows: &[Vec<Value>], col: usize) -> io::Result<ArrayRef> {
+    let values = rows.iter().map(|row| row.get(col).unwrap_or(&Value::Null));
+    // Values that do not convert fail the export rather than being written as NULL
+    let convert = |to: fn(&Value) -> Option<i64>| -> io::Result<Vec<Option<i64>>> {
+        values
+            .clone()
+            .map(|value| match value {
+                Value::Null => Ok(None),
+                value => to(value).map(Some).ok_or_else(|| invalid(field, value)),
+            })
+            .collect()
+    };
+    let integers = |min: i64, max: i64| {
+        convert(|value| match value {
+            Value::Number(n) => n.as_i64(),
+            Value::String(s) => s.trim().parse().ok(),
+            Value::Bool(b) => Some(i64::from(*b)),
+            _ => None,
+        })
+        .and_then(|ints| {
+            values
+                .clone()
+                .zip(&ints)
+                .map(|(value, int)| match int {
+                    Some(i) if !(min..=max).contains(i) => Err(invalid(field, value)),
+                    _ => Ok(*int),
+                })
+                .collect::<io::Result<Vec<_>>>()
+        })
+    };
+    let floats = || -> io::Result<Vec<Option<f64>>> {
+        values
+            .clone()
+            .map(|value| match value {
+                Value::Null => Ok(None),
+                Value::Number(n) => Ok(n.as_f64()),
+                Value::String(s) => s
+                    .trim()
+                    .parse()
+                    .map(Some)
+                    .map_err(|_| invalid(field, value)),
+                _ => Err(invalid(field, value)),
+            })
+            .collect()
+    };
+
+    let array: ArrayRef = match field.data_type() {
+        DataType::Boolean => Arc::new(BooleanArray::from(
+            values
+                .clone()
+                .map(|value| match value {
+                    Value::Null => Ok(None),
+                    Value::Bool(b) => Ok(Some(*b)),
+                    Value::Number(n) => Ok(Some(n.as_f64() != Some(0.0))),
+                    Value::String(s) => match s.trim().to_lowercase().as_str() {
+                        "true" | "t" | "1" => Ok(Some(true)),
+                        "false" | "f" | "0" => Ok(Some(false)),
+                        _ => Err(invalid(field, value)),
+                    },
+                    _ => Err(invalid(field, value)),
+                })
+                .collect::<io::Result<Vec<_>>>()?,
+        )),
+        DataType::Int16 => Arc::new(Int16Array::from(
+            integers(i16::MIN.into(), i16::MAX.into())?
+                .into_iter()
+                .map(|i| i.map(|i| i as i16))
+                .collect::<Vec<_>>(),
+        )),
+        DataType::Int32 => Arc::new(Int32Array::from(
+            integers(i32::MIN.into(), i32::MAX.into())?
+                .into_iter()
+                .map(|i| i.map(|i| i as i32))
+                .collect::<Vec<_>>(),
+        )),
+        DataType::Int64 => Arc::new(Int64Array::from(integers(i64::MIN, i64::MAX)?)),
+        DataType::UInt8 => Arc::new(UInt8Array::from(
+            integers(0, u8::MAX.into())?
+                .into_iter()
+                .map(|i| i.map(|i| i as u8))
+                .collect::<Vec<_>>(),
+        )),
+        DataType::UInt64 => Arc::new(UInt64Array::from(
+            values
+                .clone()
+                .map(|value| match value {
+                    Value::Null => Ok(None),
+                    Value::Number(n) => n.as_u64().map(Some).ok_or_else(|| invalid(field, value)),
+                    Value::String(s) => s
+                        .trim()
+                        .parse()
+                        .map(Some)
+                        .map_err(|_| invalid(field, value)),
+                    _ => Err(invalid(field, value)),
+                })
+                .collect::<io::Result<Vec<_>>>()?,
+        )),
+        DataType::Decimal128(precision, scale) => Arc::new(
+            Decimal128Array::from(
+                values
+                    .clone()
+                    .map(|value| match value {
+                        Value::Null => Ok(None),
+                        value => decimal_value(value, *precision, *scale)
+                            .map(Some)
+                            .ok_or_else(|| invalid(field, value)),
+                    })
+                    .collect::<io::Result<Vec<_>>>()?,
+            )
+            .with_precision_and_scale(*precision, *scale)
+            .map_err(to_io)?,
+        ),
+        DataType::Float32 => Arc::new(Float32Array::from(
+            floats()?
+                .into_iter()
+                .map(|f| f.map(|f| f as f32))
+                .collect::<Vec<_>>(),
+        )),
+        DataType::Float64 => Arc::new(Float64Array::from(floats()?)),
+        DataType::Date32 => Arc::new(Date32Array::from(
+            convert(|value| value.as_str().and_then(parse_date).map(i64::from))?
+                .into_iter()
+                .map(|d| d.map(|d| d as i32))
+                .collect::<Vec<_>>(),
+        )),
+        DataType::Time64(_) => Arc::new(Time64MicrosecondArray::from(convert(|value| {
+            value.as_str().and_then(parse_time)
+        })?)),
+        DataType::Timestamp(_, tz) => {
+            let array = TimestampMicrosecondArray::from(convert(|value| {
+                value.as_str().and_then(parse_timestamp)
+            })?);
+            Arc::new(match tz {
+                Some(tz) => array.with_timezone(tz.as_ref()),
+                None => array,
+            })
+        }
+        _ => Arc::new(StringArray::from(
+            values
+                .map(|value| match value {
+                    Value::Null => None,
+                    Value::String(s) => Some(s.clone()),
+                    value => Some(value.to_string()),
+                })
+                .collect::<Vec<_>>(),
+        )),
+    };
+    Ok(array)
+}
+
+/// Unscaled value of a number or numeric text at `scale`, `None` when it has more
+/// fractional digits than the scale keeps or more digits than the precision allows
+fn decimal_value(value: &Value, precision: u8, scale: i8) -> Option<i128> {
+    let text = match value {
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => s.trim().to_string(),
+        _ => return None,
+    };
+    let decimal = BigDecimal::from_str(&text).ok()?;
+    let scaled = decimal.with_scale(i64::from(scale));
+    if scaled != decimal {
+        return None;
+    }
+    let (digits, _) = scaled.into_bigint_and_exponent();
+    let unscaled = digits.to_i128()?;
+    (unscaled.unsigned_abs() < 10u128.pow(u32::from(precision))).then_some(unscaled)
+}
+
+/// Days since 1970-01-01 of a `YYYY-MM-DD` date, or of the date of a timestamp
+fn parse_date(text: &str) -> Option<i32> {
+    let epoch = NaiveDate::from_ymd_opt(1970, 1, 1)?;
+    let date = NaiveDate::parse_from_str(text.get(..10)?, "%Y-%m-%d").ok()?;
+    i32::try_from((date - epoch).num_days()).ok()
+}
+
+/// Microseconds since midnight of an `HH:MM:SS` time
+fn parse_time(text: &str) -> Option<i64> {
+    let time = NaiveTime::parse_from_str(text, "%H:%M:%S%.f").ok()?;
+    Some(
+        i64::from(time.num_seconds_from_midnight()) * 1_000_000
+            + i64::from(time.nanosecond() / 1_000),
+    )
+}
+
+/// Microseconds since the epoch of a timestamp as the drivers load it, in UTC when it
+/// carries an offset or the ` UTC` suffix of PostgreSQL's `timestamptz`
+fn parse_timestamp(text: &str) -> Option<i64> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(text) {
+        return Some(dt.timestamp_micros());
+    }
+    let text = text.strip_suffix(" UTC").unwrap_or(text);
+    ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"]
+        .iter()
+        .find_map(|pattern| NaiveDateTime::parse_from_str(text, pattern).ok())
+        .map(|dt| dt.and_utc().timestamp_micros())
+}
+
+fn invalid(field: &Field, value: &Value) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!(
+            "Column '{}': cannot write {} as {}",
+            field.name(),
+            value,
+            field.data_type()
+        ),
+    )
+}
+
+fn to_io(e: impl std::fmt::Display) -> io::Error {
+    io::Error::other(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use serde_json::json;
+
+    #[test]
+    fn test_arrow_types_follow_each_driver_type() {
+        let pg = DatabaseType::PostgreSQL;
+        assert_eq!(arrow_type(&pg, "INT4"), DataType::Int32);
+        assert_eq!(
+            arrow_type(&pg, "TIMESTAMPTZ"),
+            DataType::Timestamp(TimeUnit::Microsecond, Some(UTC.into()))
+        );
+        assert_eq!(
+            arrow_type(&pg, "timestamp without time zone"),
+            DataType::Timestamp(TimeUnit::Microsecond, None)
+        );
+        assert_eq!(arrow_type(&pg, "INT4[]"), DataType::Utf8);
+        assert_eq!(arrow_type(&pg, "NUMERIC"), DataType::Utf8);
+        assert_eq!(
+            arrow_type(&pg, "numeric(20,6)"),
+            DataType::Decimal128(20, 6)
+        );
+        assert_eq!(arrow_type(&pg, "numeric(50,2)"), DataType::Utf8);
+        assert_eq!(
+            arrow_type(&DatabaseType::MySQL, "decimal(10)"),
+            DataType::Decimal128(10, 0)
+        );
+        assert_eq!(
+            arrow_type(&DatabaseType::MySQL, "BIGINT UNSIGNED"),
+            DataType::UInt64
+        );
+        assert_eq!(
+            arrow_type(&DatabaseType::MySQL, "bigint(20) unsigned"),
+            DataType::UInt64
+        );
+        assert_eq!(arrow_type(&DatabaseType::MySQL, "BIGINT"), DataType::Int64);
+        assert_eq!(arrow_type(&DatabaseType::MySQL, "date"), DataType::Date32);
+        assert_eq!(arrow_type(&DatabaseType::MSSQL, "INTN"), DataType::Int32);
+        assert_eq!(arrow_type(&DatabaseType::MSSQL, "BITN"), DataType::Boolean);
+        assert_eq!(arrow_type(&DatabaseType::MongoDB, "int"), DataType::Utf8);
+    }
+
+    #[test]
+    fn test_parquet_file_holds_one_row_group_per_batch() {
+        let path =
+            std::env::temp_dir().join(format!("rustdbgrid-{}.parquet", uuid::Uuid::new_v4()));
+        let columns = vec![
+            ExportColumn {
+                name: "id".to_string(),
+                data_type: "INT4".to_string(),
+                nullable: false,
+            },
+            ExportColumn {
+                name: "born".to_string(),
+                data_type: "DATE".to_string(),
+                nullable: true,
+            },
+        ];
+        let mut writer = ColumnarWriter::new(
+            path.clone(),
+            ExportFormat::Parquet,
+            DatabaseType::PostgreSQL,
+            2,
+        );
+        writer.begin(&columns).unwrap();
+        for id in 1..=5 {
+            let born = if id == 3 {
+                Value::Null
+            } else {
+                json!("1990-01-02")
+            };
+            writer.write_row(&[json!(id), born]).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap()).unwrap();
+        assert_eq!(builder.metadata().num_row_groups(), 3);
+        let schema = builder.schema().clone();
+        assert!(!schema.field(0).is_nullable());
+        assert_eq!(schema.field(1).data_type(), &DataType::Date32);
+        let rows: usize = builder
+            .build()
+            .unwrap()
+            .map(|batch| batch.unwrap().num_rows())
+            .sum();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(rows, 5);
+    }
+
+    #[test]
+    fn test_decimals_keep_their_exact_digits() {
+        assert_eq!(decimal_value(&json!("12.340"), 10, 3), Some(12340));
+        assert_eq!(decimal_value(&json!(-7), 5, 2), Some(-700));
+        assert_eq!(decimal_value(&json!("1.2345"), 10, 3), None);
+        assert_eq!(decimal_value(&json!("123456"), 5, 0), None);
+    }
+}
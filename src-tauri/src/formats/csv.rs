@@ -0,0 +1,124 @@
+use super::{value_text, ExportColumn, RowWriter, TextSink};
+use crate::models::export::{ExportOptions, QuoteStyle};
+use serde_json::Value;
+use std::io::{self, Write};
+
+/// Delimited text: CSV, or TSV with a tab delimiter
+pub struct CsvWriter<W: Write> {
+    sink: TextSink<W>,
+    options: ExportOptions,
+    columns: Vec<ExportColumn>,
+}
+
+impl<W: Write> CsvWriter<W> {
+    pub fn new(sink: TextSink<W>, options: ExportOptions) -> Self {
+        Self {
+            sink,
+            options,
+            columns: Vec::new(),
+        }
+    }
+
+    fn field(&self, text: &str, numeric: bool) -> String {
+        let quote = self.options.quote;
+        let needs_quotes = match self.options.quote_style {
+            QuoteStyle::Always => true,
+            QuoteStyle::NonNumeric => !numeric,
+            QuoteStyle::Never => false,
+            QuoteStyle::Minimal => text
+                .chars()
+                .any(|c| c == self.options.delimiter || c == quote || c == '\n' || c == '\r'),
+        };
+        if !needs_quotes {
+            return text.to_string();
+        }
+        let escaped = text.replace(quote, &format!("{}{}", quote, quote));
+        format!("{}{}{}", quote, escaped, quote)
+    }
+
+    fn write_line(&mut self, fields: Vec<String>) -> io::Result<()> {
+        let mut line = fields.join(&self.options.delimiter.to_string());
+        line.push('\n');
+        self.sink.write_str(&line)
+    }
+}
+
+impl<W: Write + Send> RowWriter for CsvWriter<W> {
+    fn begin(&mut self, columns: &[ExportColumn]) -> io::Result<()> {
+        self.columns = columns.to_vec();
+        if !self.options.header {
+            return Ok(());
+        }
+        let header = columns
+            .iter()
+            .map(|col| self.field(&col.name, false))
+            .collect();
+        self.write_line(header)
+    }
+
+    fn write_row(&mut self, row: &[Value]) -> io::Result<()> {
+        let fields = row
+            .iter()
+            .zip(&self.columns)
+            .map(
+                |(value, column)| match value_text(value, column, &self.options) {
+                    // NULL stays unquoted so it can be told apart from the same text
+                    None => self.options.null_value.clone(),
+                    Some(text) => self.field(&text, value.is_number() || value.is_boolean()),
+                },
+            )
+            .collect();
+        self.write_line(fields)
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.sink.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::export::TextEncoding;
+    use serde_json::json;
+
+    fn columns() -> Vec<ExportColumn> {
+        [("id", "int4"), ("note", "text"), ("created", "timestamp")]
+            .iter()
+            .map(|(name, data_type)| ExportColumn {
+                name: name.to_string(),
+                data_type: data_type.to_string(),
+                nullable: true,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_csv_quotes_only_where_needed_and_formats_dates() {
+        let mut buf = Vec::new();
+        {
+            let sink = TextSink::new(&mut buf, TextEncoding::Utf8).unwrap();
+            let mut writer = CsvWriter::new(
+                sink,
+                ExportOptions {
+                    null_value: "NULL".to_string(),
+                    date_format: Some("%d/%m/%Y".to_string()),
+                    ..ExportOptions::default()
+                },
+            );
+            writer.begin(&columns()).unwrap();
+            writer
+                .write_row(&[json!(1), json!("a, \"b\""), json!("2024-03-09 10:00:00")])
+                .unwrap();
+            writer
+                .write_row(&[json!(2), Value::Null, json!("not a date")])
+                .unwrap();
+            writer.finish().unwrap();
+        }
+
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "id,note,created\n1,\"a, \"\"b\"\"\",09/03/2024\n2,NULL,not a date\n"
+        );
+    }
+}
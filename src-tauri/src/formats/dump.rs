@@ -0,0 +1,130 @@
+use crate::models::connection::DatabaseType;
+use std::io::{self, Write};
+
+/// Writes a SQL dump laid out so the statement splitter of its dialect reads it back:
+/// `GO` ends SQL Server batches and MySQL routine bodies sit inside `DELIMITER` blocks
+pub struct DumpWriter<W: Write> {
+    out: W,
+    db_type: DatabaseType,
+}
+
+impl<W: Write> DumpWriter<W> {
+    pub fn new(out: W, db_type: DatabaseType) -> Self {
+        Self { out, db_type }
+    }
+
+    /// Opening comments and the session settings the rest of the dump relies on
+    pub fn header(&mut self, database: &str, generated_at: &str) -> io::Result<()> {
+        writeln!(self.out, "-- RustDBGrid dump of database {}", database)?;
+        writeln!(self.out, "-- Generated at {}", generated_at)?;
+        writeln!(self.out)?;
+        if self.db_type == DatabaseType::MySQL {
+            // Tables in foreign key cycles cannot all be loaded before their references
+            writeln!(self.out, "SET NAMES utf8mb4;")?;
+            writeln!(self.out, "SET FOREIGN_KEY_CHECKS = 0;")?;
+            writeln!(self.out)?;
+        }
+        Ok(())
+    }
+
+    pub fn footer(&mut self) -> io::Result<()> {
+        if self.db_type == DatabaseType::MySQL {
+            writeln!(self.out, "SET FOREIGN_KEY_CHECKS = 1;")?;
+        }
+        self.out.flush()
+    }
+
+    pub fn section(&mut self, title: &str) -> io::Result<()> {
+        writeln!(self.out, "\n--\n-- {}\n--\n", title.replace('\n', " "))
+    }
+
+    /// Write statements that are already terminated, such as a table's DDL
+    pub fn script(&mut self, sql: &str) -> io::Result<()> {
+        writeln!(self.out, "{}", sql.trim_end())?;
+        self.end_batch()
+    }
+
+    /// Write one statement. SQL Server batches go on until `end_batch`.
+    pub fn statement(&mut self, sql: &str) -> io::Result<()> {
+        writeln!(self.out, "{};", sql.trim_end().trim_end_matches(';'))
+    }
+
+    /// Write a routine, view or trigger definition, whose body may hold `;` of its own.
+    /// SQL Server wants each of them alone in a batch.
+    pub fn definition(&mut self, sql: &str) -> io::Result<()> {
+        let sql = sql.trim_end().trim_end_matches(';');
+        match self.db_type {
+            DatabaseType::MySQL => writeln!(self.out, "DELIMITER ;;\n{};;\nDELIMITER ;", sql),
+            DatabaseType::MSSQL => writeln!(self.out, "{}\nGO", sql),
+            _ => writeln!(self.out, "{};", sql),
+        }
+    }
+
+    /// End a SQL Server batch; other dialects need no separator
+    pub fn end_batch(&mut self) -> io::Result<()> {
+        match self.db_type {
+            DatabaseType::MSSQL => writeln!(self.out, "GO"),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Order tables so each comes after the tables it references, keeping the given order
+/// where references allow. `references` pairs a table with one it references. Tables
+/// caught in reference cycles are returned separately, in the given order.
+pub fn order_by_dependencies(
+    tables: &[String],
+    references: &[(String, String)],
+) -> (Vec<String>, Vec<String>) {
+    let mut ordered: Vec<String> = Vec::with_capacity(tables.len());
+    let mut pending: Vec<&String> = tables.iter().collect();
+
+    loop {
+        let ready = pending.iter().position(|table| {
+            references.iter().all(|(from, to)| {
+                from != *table || to == *table || !tables.contains(to) || ordered.contains(to)
+            })
+        });
+        match ready {
+            Some(index) => ordered.push(pending.remove(index).clone()),
+            None => break,
+        }
+    }
+
+    (ordered, pending.into_iter().cloned().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn pairs(list: &[(&str, &str)]) -> Vec<(String, String)> {
+        list.iter()
+            .map(|(from, to)| (from.to_string(), to.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_referenced_tables_come_first_and_cycles_last() {
+        let tables = names(&["a_lines", "b_orders", "c_customers", "d_self", "x", "y"]);
+        let references = pairs(&[
+            ("a_lines", "b_orders"),
+            ("b_orders", "c_customers"),
+            ("d_self", "d_self"),
+            ("x", "y"),
+            ("y", "x"),
+            ("c_customers", "elsewhere"),
+        ]);
+
+        let (ordered, cyclic) = order_by_dependencies(&tables, &references);
+        assert_eq!(
+            ordered,
+            names(&["c_customers", "b_orders", "a_lines", "d_self"])
+        );
+        assert_eq!(cyclic, names(&["x", "y"]));
+    }
+}
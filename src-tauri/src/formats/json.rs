@@ -0,0 +1,83 @@
+use super::{value_text, ExportColumn, RowWriter, TextSink};
+use crate::models::export::ExportOptions;
+use serde_json::Value;
+use std::io::{self, Write};
+
+/// Rows as JSON objects keyed by column name, in column order: a JSON array, or one
+/// object per line for NDJSON
+pub struct JsonWriter<W: Write> {
+    sink: TextSink<W>,
+    options: ExportOptions,
+    columns: Vec<ExportColumn>,
+    /// Keys already encoded as JSON strings
+    keys: Vec<String>,
+    lines: bool,
+    rows_written: u64,
+}
+
+impl<W: Write> JsonWriter<W> {
+    pub fn new(sink: TextSink<W>, options: ExportOptions, lines: bool) -> Self {
+        Self {
+            sink,
+            options,
+            columns: Vec::new(),
+            keys: Vec::new(),
+            lines,
+            rows_written: 0,
+        }
+    }
+}
+
+impl<W: Write + Send> RowWriter for JsonWriter<W> {
+    fn begin(&mut self, columns: &[ExportColumn]) -> io::Result<()> {
+        self.columns = columns.to_vec();
+        self.keys = columns
+            .iter()
+            .map(|col| serde_json::to_string(&col.name))
+            .collect::<Result<_, _>>()?;
+        if self.lines {
+            Ok(())
+        } else {
+            self.sink.write_str("[")
+        }
+    }
+
+    fn write_row(&mut self, row: &[Value]) -> io::Result<()> {
+        let fields = self
+            .keys
+            .iter()
+            .zip(row.iter().zip(&self.columns))
+            .map(|(key, (value, column))| {
+                // Strings go through the same date formatting as the text formats
+                let value = match value {
+                    Value::String(_) => value_text(value, column, &self.options)
+                        .map(Value::String)
+                        .unwrap_or(Value::Null),
+                    other => other.clone(),
+                };
+                Ok(format!("{}:{}", key, serde_json::to_string(&value)?))
+            })
+            .collect::<serde_json::Result<Vec<_>>>()?;
+        let object = format!("{{{}}}", fields.join(","));
+
+        let separator = match (self.lines, self.rows_written) {
+            (true, _) => "",
+            (false, 0) => "\n",
+            (false, _) => ",\n",
+        };
+        self.sink.write_str(separator)?;
+        self.sink.write_str(&object)?;
+        if self.lines {
+            self.sink.write_str("\n")?;
+        }
+        self.rows_written += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        if !self.lines {
+            self.sink.write_str("\n]\n")?;
+        }
+        self.sink.flush()
+    }
+}
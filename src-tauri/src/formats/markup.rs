@@ -0,0 +1,119 @@
+use super::{value_text, ExportColumn, RowWriter, TextSink};
+use crate::models::export::ExportOptions;
+use serde_json::Value;
+use std::io::{self, Write};
+
+/// A Markdown table
+pub struct MarkdownWriter<W: Write> {
+    sink: TextSink<W>,
+    options: ExportOptions,
+    columns: Vec<ExportColumn>,
+}
+
+impl<W: Write> MarkdownWriter<W> {
+    pub fn new(sink: TextSink<W>, options: ExportOptions) -> Self {
+        Self {
+            sink,
+            options,
+            columns: Vec::new(),
+        }
+    }
+
+    fn cell(text: &str) -> String {
+        text.replace('|', "\\|")
+            .replace("\r\n", "<br>")
+            .replace('\n', "<br>")
+    }
+
+    fn write_line(&mut self, cells: Vec<String>) -> io::Result<()> {
+        self.sink.write_str(&format!("| {} |\n", cells.join(" | ")))
+    }
+}
+
+impl<W: Write + Send> RowWriter for MarkdownWriter<W> {
+    fn begin(&mut self, columns: &[ExportColumn]) -> io::Result<()> {
+        self.columns = columns.to_vec();
+        // Markdown tables cannot do without their header
+        self.write_line(columns.iter().map(|col| Self::cell(&col.name)).collect())?;
+        self.write_line(columns.iter().map(|_| "---".to_string()).collect())
+    }
+
+    fn write_row(&mut self, row: &[Value]) -> io::Result<()> {
+        let cells = row
+            .iter()
+            .zip(&self.columns)
+            .map(|(value, column)| {
+                let text = value_text(value, column, &self.options)
+                    .unwrap_or_else(|| self.options.null_value.clone());
+                Self::cell(&text)
+            })
+            .collect();
+        self.write_line(cells)
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.sink.flush()
+    }
+}
+
+/// A standalone HTML document holding one table
+pub struct HtmlWriter<W: Write> {
+    sink: TextSink<W>,
+    options: ExportOptions,
+    columns: Vec<ExportColumn>,
+    title: String,
+}
+
+impl<W: Write> HtmlWriter<W> {
+    pub fn new(sink: TextSink<W>, options: ExportOptions, title: String) -> Self {
+        Self {
+            sink,
+            options,
+            columns: Vec::new(),
+            title,
+        }
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+impl<W: Write + Send> RowWriter for HtmlWriter<W> {
+    fn begin(&mut self, columns: &[ExportColumn]) -> io::Result<()> {
+        self.columns = columns.to_vec();
+        let header = columns
+            .iter()
+            .map(|col| format!("<th>{}</th>", escape_html(&col.name)))
+            .collect::<String>();
+        self.sink.write_str(&format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"{}\">\n<title>{}</title>\n</head>\n<body>\n<table>\n<thead>\n<tr>{}</tr>\n</thead>\n<tbody>\n",
+            self.options.encoding.charset(),
+            escape_html(&self.title),
+            header
+        ))
+    }
+
+    fn write_row(&mut self, row: &[Value]) -> io::Result<()> {
+        let cells = row
+            .iter()
+            .zip(&self.columns)
+            .map(|(value, column)| {
+                let text = value_text(value, column, &self.options)
+                    .unwrap_or_else(|| self.options.null_value.clone());
+                format!("<td>{}</td>", escape_html(&text))
+            })
+            .collect::<String>();
+        self.sink.write_str(&format!("<tr>{}</tr>\n", cells))
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.sink
+            .write_str("</tbody>\n</table>\n</body>\n</html>\n")?;
+        self.sink.flush()
+    }
+}
@@ -0,0 +1,209 @@
+pub mod columnar;
+pub mod csv;
+pub mod dump;
+pub mod json;
+pub mod markup;
+pub mod records;
+pub mod sql;
+pub mod xlsx;
+
+use crate::db::traits::{get_crud_query_builder, CRUDQueryBuilder};
+use crate::models::connection::DatabaseType;
+use crate::models::export::{ExportFormat, ExportOptions, TextEncoding};
+use serde_json::Value;
+use std::io::{self, Write};
+
+/// Column of an exported result
+#[derive(Debug, Clone)]
+pub struct ExportColumn {
+    pub name: String,
+    pub data_type: String,
+    /// Whether the column may hold NULL; true unless the table schema says otherwise
+    pub nullable: bool,
+}
+
+/// Writes rows in one file format. Rows arrive in column order, one batch at a time,
+/// so a writer never holds more than the row it is writing.
+pub trait RowWriter: Send {
+    /// Write what comes before the rows, such as a header line
+    fn begin(&mut self, columns: &[ExportColumn]) -> io::Result<()>;
+
+    fn write_row(&mut self, row: &[Value]) -> io::Result<()>;
+
+    /// Write what comes after the rows and flush
+    fn finish(&mut self) -> io::Result<()>;
+}
+
+/// Text output in a chosen encoding
+pub struct TextSink<W: Write> {
+    inner: W,
+    encoding: TextEncoding,
+}
+
+impl<W: Write> TextSink<W> {
+    /// Wrap a byte writer, writing the encoding's byte order mark if it has one
+    pub fn new(mut inner: W, encoding: TextEncoding) -> io::Result<Self> {
+        match encoding {
+            TextEncoding::Utf8Bom => inner.write_all(&[0xEF, 0xBB, 0xBF])?,
+            TextEncoding::Utf16le => inner.write_all(&[0xFF, 0xFE])?,
+            TextEncoding::Utf8 | TextEncoding::Latin1 => {}
+        }
+        Ok(Self { inner, encoding })
+    }
+
+    pub fn write_str(&mut self, text: &str) -> io::Result<()> {
+        match self.encoding {
+            TextEncoding::Utf8 | TextEncoding::Utf8Bom => self.inner.write_all(text.as_bytes()),
+            TextEncoding::Utf16le => {
+                let bytes: Vec<u8> = text.encode_utf16().flat_map(u16::to_le_bytes).collect();
+                self.inner.write_all(&bytes)
+            }
+            TextEncoding::Latin1 => {
+                let bytes: Vec<u8> = text
+                    .chars()
+                    .map(|c| u8::try_from(u32::from(c)).unwrap_or(b'?'))
+                    .collect();
+                self.inner.write_all(&bytes)
+            }
+        }
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Create the writer of a file at `path` holding rows of a `db_type` database, with a
+/// sheet named after `table` for XLSX
+pub fn create_file_writer(
+    format: ExportFormat,
+    options: &ExportOptions,
+    path: &str,
+    db_type: &DatabaseType,
+    (table, schema): (&str, Option<&str>),
+) -> io::Result<Box<dyn RowWriter>> {
+    match format {
+        ExportFormat::Xlsx => {
+            return Ok(Box::new(xlsx::XlsxWriter::new(
+                path.into(),
+                options.clone(),
+                vec![table.to_string()],
+            )))
+        }
+        ExportFormat::Parquet | ExportFormat::Arrow => {
+            return Ok(Box::new(columnar::ColumnarWriter::new(
+                path.into(),
+                format,
+                db_type.clone(),
+                options.row_group_size,
+            )))
+        }
+        _ => {}
+    }
+    let file = std::fs::File::create(path)?;
+    let sink = TextSink::new(io::BufWriter::new(file), options.encoding)?;
+    let crud_builder = get_crud_query_builder(db_type);
+    create_writer(format, options, sink, crud_builder, (table, schema))
+}
+
+/// Create the writer of a text format. SQL INSERT statements target `table` in
+/// `schema`, quoted for the dialect of `crud_builder`.
+pub fn create_writer<'a, W: Write + Send + 'a>(
+    format: ExportFormat,
+    options: &ExportOptions,
+    sink: TextSink<W>,
+    crud_builder: Box<dyn CRUDQueryBuilder>,
+    (table, schema): (&str, Option<&str>),
+) -> io::Result<Box<dyn RowWriter + 'a>> {
+    Ok(match format {
+        ExportFormat::Csv => Box::new(csv::CsvWriter::new(sink, options.clone())),
+        ExportFormat::Tsv => Box::new(csv::CsvWriter::new(
+            sink,
+            ExportOptions {
+                delimiter: '\t',
+                ..options.clone()
+            },
+        )),
+        ExportFormat::Json => Box::new(json::JsonWriter::new(sink, options.clone(), false)),
+        ExportFormat::Ndjson => Box::new(json::JsonWriter::new(sink, options.clone(), true)),
+        ExportFormat::SqlInsert => {
+            let table_name = crud_builder.crud_table_name(table, schema);
+            Box::new(sql::SqlInsertWriter::new(sink, crud_builder, table_name))
+        }
+        ExportFormat::Markdown => Box::new(markup::MarkdownWriter::new(sink, options.clone())),
+        ExportFormat::Html => Box::new(markup::HtmlWriter::new(
+            sink,
+            options.clone(),
+            table.to_string(),
+        )),
+        ExportFormat::WhereIn => Box::new(sql::WhereInWriter::new(sink, crud_builder)),
+        ExportFormat::Xlsx | ExportFormat::Parquet | ExportFormat::Arrow => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "{:?} is a binary format and cannot be written as text",
+                    format
+                ),
+            ))
+        }
+    })
+}
+
+/// Whether a column type holds dates or times that `date_format` applies to
+fn is_temporal(data_type: &str) -> bool {
+    let lower = data_type.to_lowercase();
+    (lower.contains("date") || lower.contains("time")) && !lower.contains("interval")
+}
+
+/// Whether a chrono format string only holds valid specifiers. Formatting with an
+/// invalid one panics.
+pub fn valid_date_format(format: &str) -> bool {
+    chrono::format::StrftimeItems::new(format)
+        .all(|item| !matches!(item, chrono::format::Item::Error))
+}
+
+/// Reformat a date or time value loaded as text. `None` when it does not parse, or
+/// when the format asks for a part the value lacks, such as the hour of a date.
+fn format_temporal(value: &str, format: &str) -> Option<String> {
+    use chrono::{DateTime, NaiveDate, NaiveDateTime};
+    use std::fmt::Write as _;
+
+    // Display of a chrono format fails rather than returning text it cannot produce
+    let render = |formatted: &dyn std::fmt::Display| {
+        let mut out = String::new();
+        write!(out, "{}", formatted).ok().map(|_| out)
+    };
+
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return render(&dt.format(format));
+    }
+    if let Some(dt) = ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"]
+        .iter()
+        .find_map(|pattern| NaiveDateTime::parse_from_str(value, pattern).ok())
+    {
+        return render(&dt.format(format));
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| render(&d.format(format)))
+}
+
+/// Text of a non-NULL value for the text formats: strings as they are (dates in the
+/// requested format), numbers and booleans as literals and nested values as JSON.
+/// `None` for NULL.
+pub fn value_text(value: &Value, column: &ExportColumn, options: &ExportOptions) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(s) => Some(
+            options
+                .date_format
+                .as_deref()
+                .filter(|_| is_temporal(&column.data_type))
+                .and_then(|format| format_temporal(s, format))
+                .unwrap_or_else(|| s.clone()),
+        ),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        Value::Array(_) | Value::Object(_) => Some(value.to_string()),
+    }
+}
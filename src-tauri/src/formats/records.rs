@@ -0,0 +1,306 @@
+use crate::models::import::{FileImportOptions, FilePreview, ImportFormat};
+use anyhow::{anyhow, Result};
+use serde_json::{Map, Value};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Lines};
+
+/// Lines of an NDJSON file read ahead to collect the keys that name its columns
+const NDJSON_SAMPLE_LINES: usize = 1000;
+
+/// An NDJSON line parsed as an object, or why it could not be
+type ObjectLine = std::result::Result<Map<String, Value>, String>;
+
+/// A row of a file with the line it starts on, or why it could not be read
+#[derive(Debug)]
+pub struct Record {
+    pub line: u64,
+    pub values: std::result::Result<Vec<Value>, String>,
+}
+
+/// Reads the rows of a CSV or NDJSON file one at a time, each holding its values in
+/// the order of `headers`. CSV fields are read as text; missing trailing fields are
+/// NULL. The columns of NDJSON are the keys of its first lines, in order of first
+/// appearance, and keys only later lines have are ignored. Rows already read, such as
+/// those of a worksheet, are passed on as they are.
+pub struct RecordReader {
+    headers: Vec<String>,
+    source: Source,
+}
+
+enum Source {
+    Csv {
+        reader: csv::Reader<File>,
+        /// First row, read to count the columns of a file without a header line
+        first: Option<Record>,
+    },
+    Ndjson {
+        lines: Lines<BufReader<File>>,
+        line: u64,
+        sample: VecDeque<(u64, ObjectLine)>,
+    },
+    Rows(std::vec::IntoIter<Record>),
+}
+
+impl RecordReader {
+    pub fn open(path: &str, options: &FileImportOptions) -> Result<Self> {
+        match options.format {
+            ImportFormat::Csv => Self::open_csv(path, options),
+            ImportFormat::Ndjson => Self::open_ndjson(path),
+        }
+    }
+
+    fn open_csv(path: &str, options: &FileImportOptions) -> Result<Self> {
+        let ascii = |c: char, what: &str| {
+            u8::try_from(c)
+                .ok()
+                .filter(u8::is_ascii)
+                .ok_or_else(|| anyhow!("The {} must be an ASCII character", what))
+        };
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(ascii(options.delimiter, "delimiter")?)
+            .quote(ascii(options.quote, "quote")?)
+            .has_headers(options.header)
+            .flexible(true)
+            .from_path(path)?;
+
+        let (headers, first) = if options.header {
+            let headers = reader
+                .headers()?
+                .iter()
+                .enumerate()
+                .map(|(index, name)| match name.trim() {
+                    "" => format!("column{}", index + 1),
+                    name => name.to_string(),
+                })
+                .collect();
+            (headers, None)
+        } else {
+            let mut record = csv::StringRecord::new();
+            let first = match reader.read_record(&mut record) {
+                Ok(true) => Some(csv_record(&record, record.len())),
+                Ok(false) => None,
+                Err(e) => Some(csv_error(e)?),
+            };
+            let width = first
+                .as_ref()
+                .and_then(|first| first.values.as_ref().ok())
+                .map_or(0, Vec::len);
+            let headers = (1..=width).map(|n| format!("column{}", n)).collect();
+            (headers, first)
+        };
+
+        Ok(Self {
+            headers,
+            source: Source::Csv { reader, first },
+        })
+    }
+
+    fn open_ndjson(path: &str) -> Result<Self> {
+        let mut lines = BufReader::new(File::open(path)?).lines();
+        let mut line = 0;
+        let mut sample = VecDeque::new();
+        let mut headers: Vec<String> = Vec::new();
+        while sample.len() < NDJSON_SAMPLE_LINES {
+            let Some(object) = next_object(&mut lines, &mut line)? else {
+                break;
+            };
+            if let Ok(object) = &object {
+                for key in object.keys() {
+                    if !headers.contains(key) {
+                        headers.push(key.clone());
+                    }
+                }
+            }
+            sample.push_back((line, object));
+        }
+
+        Ok(Self {
+            headers,
+            source: Source::Ndjson {
+                lines,
+                line,
+                sample,
+            },
+        })
+    }
+
+    /// Reader over rows already in memory, each with the line it was read from
+    pub fn from_rows(headers: Vec<String>, rows: Vec<(u64, Vec<Value>)>) -> Self {
+        let records: Vec<Record> = rows
+            .into_iter()
+            .map(|(line, values)| Record {
+                line,
+                values: Ok(values),
+            })
+            .collect();
+        Self {
+            headers,
+            source: Source::Rows(records.into_iter()),
+        }
+    }
+
+    pub fn headers(&self) -> &[String] {
+        &self.headers
+    }
+
+    /// Next row of the file, `None` at its end. Fails only when the file cannot be read;
+    /// a malformed row comes back as a record holding its error.
+    pub fn next_record(&mut self) -> Result<Option<Record>> {
+        let width = self.headers.len();
+        match &mut self.source {
+            Source::Csv { reader, first } => {
+                if let Some(first) = first.take() {
+                    return Ok(Some(first));
+                }
+                let mut record = csv::StringRecord::new();
+                match reader.read_record(&mut record) {
+                    Ok(true) => Ok(Some(csv_record(&record, width))),
+                    Ok(false) => Ok(None),
+                    Err(e) => csv_error(e).map(Some),
+                }
+            }
+            Source::Ndjson {
+                lines,
+                line,
+                sample,
+            } => {
+                let (line, object) = match sample.pop_front() {
+                    Some(sampled) => sampled,
+                    None => match next_object(lines, line)? {
+                        Some(object) => (*line, object),
+                        None => return Ok(None),
+                    },
+                };
+                let values = object.map(|mut object| {
+                    self.headers
+                        .iter()
+                        .map(|key| object.remove(key).unwrap_or(Value::Null))
+                        .collect()
+                });
+                Ok(Some(Record { line, values }))
+            }
+            Source::Rows(rows) => Ok(rows.next()),
+        }
+    }
+}
+
+fn csv_record(record: &csv::StringRecord, width: usize) -> Record {
+    let mut values: Vec<Value> = record
+        .iter()
+        .take(width)
+        .map(|field| Value::String(field.to_string()))
+        .collect();
+    values.resize(width, Value::Null);
+    Record {
+        line: record.position().map_or(0, |pos| pos.line()),
+        values: Ok(values),
+    }
+}
+
+/// A row that failed to parse, such as one that is not valid UTF-8; I/O errors fail
+fn csv_error(e: csv::Error) -> Result<Record> {
+    if e.is_io_error() {
+        return Err(e.into());
+    }
+    Ok(Record {
+        line: e.position().map_or(0, |pos| pos.line()),
+        values: Err(e.to_string()),
+    })
+}
+
+/// Next non-blank line of an NDJSON file as an object, counting lines in `line`
+fn next_object(lines: &mut Lines<BufReader<File>>, line: &mut u64) -> Result<Option<ObjectLine>> {
+    for text in lines.by_ref() {
+        let text = text?;
+        *line += 1;
+        let text = text.trim_start_matches('\u{feff}').trim();
+        if text.is_empty() {
+            continue;
+        }
+        return Ok(Some(match serde_json::from_str(text) {
+            Ok(Value::Object(object)) => Ok(object),
+            Ok(_) => Err("Expected a JSON object".to_string()),
+            Err(e) => Err(e.to_string()),
+        }));
+    }
+    Ok(None)
+}
+
+/// Column names and up to `limit` rows of a file, leaving out rows that do not parse
+pub fn read_preview(path: &str, options: &FileImportOptions, limit: usize) -> Result<FilePreview> {
+    let mut reader = RecordReader::open(path, options)?;
+    let mut rows = Vec::new();
+    while rows.len() < limit {
+        match reader.next_record()? {
+            Some(Record {
+                values: Ok(values), ..
+            }) => rows.push(values),
+            Some(_) => {}
+            None => break,
+        }
+    }
+    Ok(FilePreview {
+        headers: reader.headers().to_vec(),
+        rows,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn temp_file(extension: &str, content: &str) -> String {
+        let path =
+            std::env::temp_dir().join(format!("rustdbgrid-{}.{}", uuid::Uuid::new_v4(), extension));
+        std::fs::write(&path, content).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn test_csv_and_ndjson_rows_follow_the_column_order() {
+        let csv = temp_file("csv", "\u{feff}id;note\n1;\"a;b\"\n2\n");
+        let options = FileImportOptions {
+            delimiter: ';',
+            ..Default::default()
+        };
+        let preview = read_preview(&csv, &options, 10).unwrap();
+        std::fs::remove_file(&csv).ok();
+        assert_eq!(preview.headers, vec!["id", "note"]);
+        assert_eq!(
+            preview.rows,
+            vec![
+                vec![json!("1"), json!("a;b")],
+                vec![json!("2"), Value::Null]
+            ]
+        );
+
+        let ndjson = temp_file(
+            "ndjson",
+            "{\"id\": 1, \"tags\": [\"x\"]}\n\nnot json\n{\"note\": null, \"id\": 2}\n",
+        );
+        let options = FileImportOptions {
+            format: ImportFormat::Ndjson,
+            ..Default::default()
+        };
+        let mut reader = RecordReader::open(&ndjson, &options).unwrap();
+        let mut records = Vec::new();
+        while let Some(record) = reader.next_record().unwrap() {
+            records.push(record);
+        }
+        std::fs::remove_file(&ndjson).ok();
+        assert_eq!(reader.headers(), ["id", "tags", "note"]);
+        assert_eq!(records.len(), 3);
+        assert_eq!(
+            records[0].values.as_ref().unwrap(),
+            &vec![json!(1), json!(["x"]), Value::Null]
+        );
+        assert_eq!(records[1].line, 3);
+        assert!(records[1].values.is_err());
+        assert_eq!(
+            records[2].values.as_ref().unwrap(),
+            &vec![json!(2), Value::Null, Value::Null]
+        );
+    }
+}
@@ -0,0 +1,169 @@
+use super::{ExportColumn, RowWriter, TextSink};
+use crate::db::traits::CRUDQueryBuilder;
+use serde_json::Value;
+use std::io::{self, Write};
+
+/// One INSERT statement per row, with literals in the dialect of the source database
+pub struct SqlInsertWriter<W: Write> {
+    sink: TextSink<W>,
+    crud_builder: Box<dyn CRUDQueryBuilder>,
+    table_name: String,
+    /// `INSERT INTO table (columns) VALUES `, shared by every row
+    prefix: String,
+}
+
+impl<W: Write> SqlInsertWriter<W> {
+    pub fn new(
+        sink: TextSink<W>,
+        crud_builder: Box<dyn CRUDQueryBuilder>,
+        table_name: String,
+    ) -> Self {
+        Self {
+            sink,
+            crud_builder,
+            table_name,
+            prefix: String::new(),
+        }
+    }
+}
+
+impl<W: Write + Send> RowWriter for SqlInsertWriter<W> {
+    fn begin(&mut self, columns: &[ExportColumn]) -> io::Result<()> {
+        let names = columns
+            .iter()
+            .map(|col| self.crud_builder.quote_identifier(&col.name))
+            .collect::<Vec<_>>()
+            .join(", ");
+        self.prefix = format!("INSERT INTO {} ({}) VALUES ", self.table_name, names);
+        Ok(())
+    }
+
+    fn write_row(&mut self, row: &[Value]) -> io::Result<()> {
+        let values = row
+            .iter()
+            .map(|value| self.crud_builder.format_value(value))
+            .collect::<Vec<_>>()
+            .join(", ");
+        self.sink
+            .write_str(&format!("{}({});\n", self.prefix, values))
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.sink.flush()
+    }
+}
+
+/// A `WHERE` condition matching the rows: one `IN` list of distinct values per column,
+/// joined with `AND`. Values are collected until `finish`, since a list spans every row.
+pub struct WhereInWriter<W: Write> {
+    sink: TextSink<W>,
+    crud_builder: Box<dyn CRUDQueryBuilder>,
+    columns: Vec<String>,
+    /// Distinct literals of each column, in the order they first appear
+    values: Vec<Vec<String>>,
+    /// Whether each column holds a NULL, which `IN` cannot match
+    nulls: Vec<bool>,
+}
+
+impl<W: Write> WhereInWriter<W> {
+    pub fn new(sink: TextSink<W>, crud_builder: Box<dyn CRUDQueryBuilder>) -> Self {
+        Self {
+            sink,
+            crud_builder,
+            columns: Vec::new(),
+            values: Vec::new(),
+            nulls: Vec::new(),
+        }
+    }
+
+    fn condition(&self, index: usize) -> String {
+        let column = &self.columns[index];
+        let values = &self.values[index];
+        match (values.is_empty(), self.nulls[index]) {
+            (true, _) => format!("{} IS NULL", column),
+            (false, false) => format!("{} IN ({})", column, values.join(", ")),
+            (false, true) => format!(
+                "({} IN ({}) OR {} IS NULL)",
+                column,
+                values.join(", "),
+                column
+            ),
+        }
+    }
+}
+
+impl<W: Write + Send> RowWriter for WhereInWriter<W> {
+    fn begin(&mut self, columns: &[ExportColumn]) -> io::Result<()> {
+        self.columns = columns
+            .iter()
+            .map(|col| self.crud_builder.quote_identifier(&col.name))
+            .collect();
+        self.values = vec![Vec::new(); columns.len()];
+        self.nulls = vec![false; columns.len()];
+        Ok(())
+    }
+
+    fn write_row(&mut self, row: &[Value]) -> io::Result<()> {
+        for (index, value) in row.iter().enumerate().take(self.columns.len()) {
+            if value.is_null() {
+                self.nulls[index] = true;
+                continue;
+            }
+            let literal = self.crud_builder.format_value(value);
+            if !self.values[index].contains(&literal) {
+                self.values[index].push(literal);
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        // Columns without any row have nothing to match
+        let conditions: Vec<String> = (0..self.columns.len())
+            .filter(|&index| !self.values[index].is_empty() || self.nulls[index])
+            .map(|index| self.condition(index))
+            .collect();
+        if !conditions.is_empty() {
+            self.sink
+                .write_str(&format!("WHERE {}\n", conditions.join("\n  AND ")))?;
+        }
+        self.sink.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::traits::get_crud_query_builder;
+    use crate::models::connection::DatabaseType;
+    use crate::models::export::TextEncoding;
+    use serde_json::json;
+
+    #[test]
+    fn test_where_in_lists_distinct_values_and_nulls() {
+        let columns: Vec<ExportColumn> = ["id", "status"]
+            .iter()
+            .map(|name| ExportColumn {
+                name: name.to_string(),
+                data_type: String::new(),
+                nullable: true,
+            })
+            .collect();
+        let mut buf = Vec::new();
+        {
+            let sink = TextSink::new(&mut buf, TextEncoding::Utf8).unwrap();
+            let mut writer =
+                WhereInWriter::new(sink, get_crud_query_builder(&DatabaseType::PostgreSQL));
+            writer.begin(&columns).unwrap();
+            writer.write_row(&[json!(1), json!("new")]).unwrap();
+            writer.write_row(&[json!(2), Value::Null]).unwrap();
+            writer.write_row(&[json!(1), json!("it's")]).unwrap();
+            writer.finish().unwrap();
+        }
+
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "WHERE \"id\" IN (1, 2)\n  AND (\"status\" IN ('new', 'it''s') OR \"status\" IS NULL)\n"
+        );
+    }
+}
@@ -0,0 +1,499 @@
+use super::{ExportColumn, RowWriter};
+use crate::models::export::ExportOptions;
+use crate::models::import::SheetPreview;
+use anyhow::{anyhow, Result};
+use calamine::{open_workbook_auto, Data, Reader};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use rust_xlsxwriter::{Format, Workbook, Worksheet, XlsxError};
+use serde_json::Value;
+use std::io;
+use std::path::PathBuf;
+
+/// Most rows a worksheet holds, the header included
+const MAX_SHEET_ROWS: u32 = 1_048_576;
+/// Longest text a cell holds
+const MAX_CELL_CHARS: usize = 32_767;
+/// Column widths in characters, fitted to the longest value between these bounds
+const MIN_COLUMN_WIDTH: usize = 6;
+const MAX_COLUMN_WIDTH: usize = 60;
+/// Integers beyond this lose digits as Excel numbers, so they are written as text
+const MAX_EXACT_INTEGER: f64 = 9_007_199_254_740_992.0;
+
+/// An Excel workbook. Each `begin` starts a new worksheet, so a writer can hold several
+/// result sets; sheets are named from `sheet_names` in order. Numbers, booleans and the
+/// values of date and time columns are written as typed cells. Sheets are written in
+/// constant memory mode: each finished row goes to a temporary file rather than staying
+/// in memory until the workbook is saved.
+pub struct XlsxWriter {
+    workbook: Workbook,
+    path: PathBuf,
+    options: ExportOptions,
+    sheet_names: Vec<String>,
+    sheets: usize,
+    columns: Vec<ExportColumn>,
+    /// Widest value of each column of the current sheet, in characters
+    widths: Vec<usize>,
+    row: u32,
+    header_format: Format,
+    date_format: Format,
+    datetime_format: Format,
+    time_format: Format,
+}
+
+impl XlsxWriter {
+    pub fn new(path: PathBuf, options: ExportOptions, sheet_names: Vec<String>) -> Self {
+        Self {
+            workbook: Workbook::new(),
+            path,
+            options,
+            sheet_names,
+            sheets: 0,
+            columns: Vec::new(),
+            widths: Vec::new(),
+            row: 0,
+            header_format: Format::new().set_bold(),
+            date_format: Format::new().set_num_format("yyyy-mm-dd"),
+            datetime_format: Format::new().set_num_format("yyyy-mm-dd hh:mm:ss"),
+            time_format: Format::new().set_num_format("hh:mm:ss"),
+        }
+    }
+
+    fn sheet(&mut self) -> io::Result<&mut Worksheet> {
+        self.workbook
+            .worksheet_from_index(self.sheets - 1)
+            .map_err(to_io)
+    }
+
+    /// Name of the next sheet, unique within the workbook as Excel requires
+    fn next_sheet_name(&mut self) -> String {
+        let base = self
+            .sheet_names
+            .get(self.sheets)
+            .map(|name| sheet_name(name))
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| format!("Sheet{}", self.sheets + 1));
+        let taken: Vec<String> = self
+            .workbook
+            .worksheets()
+            .iter()
+            .map(|sheet| sheet.name().to_lowercase())
+            .collect();
+        let mut name = base.clone();
+        let mut n = 2;
+        while taken.contains(&name.to_lowercase()) {
+            let suffix = format!(" ({})", n);
+            name = format!(
+                "{}{}",
+                base.chars().take(31 - suffix.len()).collect::<String>(),
+                suffix
+            );
+            n += 1;
+        }
+        name
+    }
+
+    /// Fit the columns of the current sheet to their widest value
+    fn end_sheet(&mut self) -> io::Result<()> {
+        if self.sheets == 0 {
+            return Ok(());
+        }
+        let widths = std::mem::take(&mut self.widths);
+        let sheet = self.sheet()?;
+        for (col, width) in widths.into_iter().enumerate() {
+            let width = width.clamp(MIN_COLUMN_WIDTH, MAX_COLUMN_WIDTH) + 2;
+            sheet
+                .set_column_width(col as u16, width as f64)
+                .map_err(to_io)?;
+        }
+        Ok(())
+    }
+
+    fn write_cell(&mut self, col: usize, value: &Value) -> io::Result<()> {
+        let (row, col_num) = (self.row, col as u16);
+        let cell = Cell::of(value, &self.columns[col].data_type);
+        let width = cell.width(&self.options.null_value);
+        let null_value = &self.options.null_value;
+        let sheet = self
+            .workbook
+            .worksheet_from_index(self.sheets - 1)
+            .map_err(to_io)?;
+        match cell {
+            Cell::Empty if null_value.is_empty() => Ok(sheet),
+            Cell::Empty => sheet.write_string(row, col_num, null_value),
+            Cell::Number(n) => sheet.write_number(row, col_num, n),
+            Cell::Bool(b) => sheet.write_boolean(row, col_num, b),
+            Cell::Date(d) => sheet.write_datetime_with_format(row, col_num, d, &self.date_format),
+            Cell::DateTime(dt) => {
+                sheet.write_datetime_with_format(row, col_num, dt, &self.datetime_format)
+            }
+            Cell::Time(t) => sheet.write_datetime_with_format(row, col_num, t, &self.time_format),
+            Cell::Text(text) => sheet.write_string(row, col_num, text),
+        }
+        .map_err(to_io)?;
+
+        if let Some(widest) = self.widths.get_mut(col) {
+            *widest = (*widest).max(width);
+        }
+        Ok(())
+    }
+}
+
+impl RowWriter for XlsxWriter {
+    fn begin(&mut self, columns: &[ExportColumn]) -> io::Result<()> {
+        self.end_sheet()?;
+        let name = self.next_sheet_name();
+        self.sheets += 1;
+        let sheet = self.workbook.add_worksheet_with_constant_memory();
+        sheet.set_name(name).map_err(to_io)?;
+
+        for (col, column) in columns.iter().enumerate() {
+            sheet
+                .write_string_with_format(0, col as u16, &column.name, &self.header_format)
+                .map_err(to_io)?;
+        }
+        if !columns.is_empty() {
+            sheet.set_freeze_panes(1, 0).map_err(to_io)?;
+        }
+
+        self.columns = columns.to_vec();
+        self.widths = columns.iter().map(|col| col.name.chars().count()).collect();
+        self.row = 1;
+        Ok(())
+    }
+
+    fn write_row(&mut self, row: &[Value]) -> io::Result<()> {
+        if self.row >= MAX_SHEET_ROWS {
+            return Err(io::Error::other(format!(
+                "A worksheet holds at most {} rows",
+                MAX_SHEET_ROWS - 1
+            )));
+        }
+        for (col, value) in row.iter().enumerate().take(self.columns.len()) {
+            self.write_cell(col, value)?;
+        }
+        self.row += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.end_sheet()?;
+        if self.sheets == 0 {
+            self.workbook.add_worksheet();
+        }
+        self.workbook.save(&self.path).map_err(to_io)
+    }
+}
+
+/// Value of a cell as Excel stores it
+#[derive(Debug, PartialEq)]
+enum Cell {
+    Empty,
+    Number(f64),
+    Bool(bool),
+    Date(NaiveDate),
+    DateTime(NaiveDateTime),
+    Time(NaiveTime),
+    Text(String),
+}
+
+impl Cell {
+    /// Drivers load decimals and dates as text, so the column type decides whether a
+    /// string is written as a number or a date
+    fn of(value: &Value, data_type: &str) -> Self {
+        match value {
+            Value::Null => Cell::Empty,
+            Value::Bool(b) => Cell::Bool(*b),
+            // Compared as integers: near the limit, f64 rounds the value onto it
+            Value::Number(n) => match (n.as_i64(), n.as_u64(), n.as_f64()) {
+                (Some(i), _, _) if i.unsigned_abs() > MAX_EXACT_INTEGER as u64 => {
+                    Cell::Text(n.to_string())
+                }
+                (None, Some(u), _) if u > MAX_EXACT_INTEGER as u64 => Cell::Text(n.to_string()),
+                (_, _, Some(f)) => Cell::Number(f),
+                _ => Cell::Text(n.to_string()),
+            },
+            Value::String(s) => {
+                let lower = data_type.to_lowercase();
+                if is_numeric_type(&lower) {
+                    if let Some(n) = s
+                        .trim()
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|n| n.is_finite() && n.abs() <= MAX_EXACT_INTEGER)
+                    {
+                        return Cell::Number(n);
+                    }
+                } else if lower.contains("date") || lower.contains("time") {
+                    if let Some(cell) = temporal_cell(s) {
+                        return cell;
+                    }
+                }
+                Cell::Text(truncate(s))
+            }
+            Value::Array(_) | Value::Object(_) => Cell::Text(truncate(&value.to_string())),
+        }
+    }
+
+    /// Characters the cell shows, for fitting the column width
+    fn width(&self, null_value: &str) -> usize {
+        match self {
+            Cell::Empty => null_value.chars().count(),
+            Cell::Number(n) => n.to_string().len(),
+            Cell::Bool(_) => 5,
+            Cell::Date(_) => 10,
+            Cell::DateTime(_) => 19,
+            Cell::Time(_) => 8,
+            Cell::Text(text) => text
+                .lines()
+                .map(|line| line.chars().count())
+                .max()
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// Whether a column type holds numbers, by its base name
+fn is_numeric_type(lower: &str) -> bool {
+    let base = lower.split('(').next().unwrap_or_default().trim();
+    matches!(
+        base,
+        "int"
+            | "integer"
+            | "tinyint"
+            | "smallint"
+            | "mediumint"
+            | "bigint"
+            | "int2"
+            | "int4"
+            | "int8"
+            | "decimal"
+            | "numeric"
+            | "number"
+            | "float"
+            | "float4"
+            | "float8"
+            | "double"
+            | "double precision"
+            | "real"
+            | "money"
+            | "smallmoney"
+            | "serial"
+            | "bigserial"
+    ) || base.ends_with(" unsigned")
+}
+
+/// A date, date and time or time of day loaded as text. Offsets are dropped, keeping
+/// the time as the server reported it.
+fn temporal_cell(value: &str) -> Option<Cell> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some(Cell::DateTime(dt.naive_local()));
+    }
+    if let Some(dt) = ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"]
+        .iter()
+        .find_map(|pattern| NaiveDateTime::parse_from_str(value, pattern).ok())
+    {
+        return Some(Cell::DateTime(dt));
+    }
+    if let Ok(d) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Some(Cell::Date(d));
+    }
+    NaiveTime::parse_from_str(value, "%H:%M:%S%.f")
+        .ok()
+        .map(Cell::Time)
+}
+
+fn truncate(text: &str) -> String {
+    text.chars().take(MAX_CELL_CHARS).collect()
+}
+
+/// A worksheet name Excel accepts: at most 31 characters, none of `[]:*?/\`
+fn sheet_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '[' | ']' | ':' | '*' | '?' | '/' | '\\' => '_',
+            c => c,
+        })
+        .take(31)
+        .collect::<String>()
+        .trim_matches('\'')
+        .to_string()
+}
+
+fn to_io(e: XlsxError) -> io::Error {
+    io::Error::other(e.to_string())
+}
+
+/// Read the rows of a worksheet below its header row, at most `limit` of them. Reads
+/// the first sheet when `sheet` is unset; `header_row` counts from 1. Also reads XLS
+/// and ODS workbooks.
+pub fn read_sheet(
+    path: &str,
+    sheet: Option<&str>,
+    header_row: u32,
+    limit: Option<usize>,
+) -> Result<SheetPreview> {
+    let mut workbook =
+        open_workbook_auto(path).map_err(|e| anyhow!("Failed to open {}: {}", path, e))?;
+    let sheets = workbook.sheet_names();
+    let sheet = match sheet {
+        Some(name) => sheets
+            .iter()
+            .find(|sheet| sheet.as_str() == name)
+            .cloned()
+            .ok_or_else(|| anyhow!("Worksheet '{}' not found", name))?,
+        None => sheets
+            .first()
+            .cloned()
+            .ok_or_else(|| anyhow!("The workbook has no worksheets"))?,
+    };
+    let range = workbook
+        .worksheet_range(&sheet)
+        .map_err(|e| anyhow!("Failed to read worksheet '{}': {}", sheet, e))?;
+
+    // The range starts at the first cell holding data rather than at A1
+    let first_row = range.start().map(|(row, _)| row).unwrap_or(0);
+    let header_index = header_row
+        .checked_sub(1)
+        .and_then(|row| row.checked_sub(first_row))
+        .ok_or_else(|| anyhow!("Row {} of '{}' holds no column names", header_row, sheet))?
+        as usize;
+    let mut rows = range.rows().skip(header_index);
+    let headers: Vec<String> = rows
+        .next()
+        .ok_or_else(|| anyhow!("Row {} of '{}' holds no column names", header_row, sheet))?
+        .iter()
+        .enumerate()
+        .map(|(col, cell)| match cell_value(cell) {
+            Value::Null => format!("Column {}", col + 1),
+            Value::String(text) => text.trim().to_string(),
+            other => other.to_string(),
+        })
+        .collect();
+    let rows = rows
+        .take(limit.unwrap_or(usize::MAX))
+        .map(|row| row.iter().map(cell_value).collect())
+        .collect();
+
+    Ok(SheetPreview {
+        sheets,
+        sheet,
+        headers,
+        rows,
+    })
+}
+
+/// Value of a cell as the bound parameters of an INSERT take it. Whole numbers become
+/// integers and dates ISO 8601 text, which every dialect casts to its date types.
+fn cell_value(cell: &Data) -> Value {
+    match cell {
+        Data::Empty | Data::Error(_) => Value::Null,
+        Data::Int(i) => Value::from(*i),
+        Data::Float(f) if f.fract() == 0.0 && f.abs() <= MAX_EXACT_INTEGER => {
+            Value::from(*f as i64)
+        }
+        Data::Float(f) => serde_json::Number::from_f64(*f)
+            .map(Value::Number)
+            .unwrap_or(Value::Null),
+        Data::String(s) => Value::String(s.clone()),
+        Data::Bool(b) => Value::Bool(*b),
+        Data::DateTime(dt) if dt.is_duration() => dt
+            .as_duration()
+            .map(|d| {
+                let secs = d.num_seconds();
+                format!(
+                    "{:02}:{:02}:{:02}",
+                    secs / 3600,
+                    secs % 3600 / 60,
+                    secs % 60
+                )
+            })
+            .map(Value::String)
+            .unwrap_or(Value::Null),
+        Data::DateTime(dt) => match dt.as_datetime() {
+            // Excel keeps times of day as fractions of a day without a date
+            Some(value) if dt.as_f64() < 1.0 => {
+                Value::String(value.format("%H:%M:%S%.f").to_string())
+            }
+            Some(value) if value.num_seconds_from_midnight() == 0 && value.nanosecond() == 0 => {
+                Value::String(value.format("%Y-%m-%d").to_string())
+            }
+            Some(value) => Value::String(value.format("%Y-%m-%d %H:%M:%S%.f").to_string()),
+            None => Value::Null,
+        },
+        Data::DateTimeIso(s) | Data::DurationIso(s) => Value::String(s.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_cells_are_typed_by_value_and_column_type() {
+        assert_eq!(
+            Cell::of(&json!("12.50"), "numeric(10,2)"),
+            Cell::Number(12.5)
+        );
+        assert_eq!(
+            Cell::of(&json!("2024-03-09"), "date"),
+            Cell::Date(NaiveDate::from_ymd_opt(2024, 3, 9).unwrap())
+        );
+        assert_eq!(
+            Cell::of(&json!("2024-03-09 10:15:00"), "timestamp"),
+            Cell::DateTime(
+                NaiveDate::from_ymd_opt(2024, 3, 9)
+                    .unwrap()
+                    .and_hms_opt(10, 15, 0)
+                    .unwrap()
+            )
+        );
+        assert_eq!(
+            Cell::of(&json!("2024-03-09"), "varchar(20)"),
+            Cell::Text("2024-03-09".to_string())
+        );
+        assert_eq!(
+            Cell::of(&json!(9_007_199_254_740_993u64), "bigint"),
+            Cell::Text("9007199254740993".to_string())
+        );
+        assert_eq!(Cell::of(&Value::Null, "int"), Cell::Empty);
+    }
+
+    #[test]
+    fn test_written_workbook_reads_back_from_its_header_row() {
+        let path = std::env::temp_dir().join(format!("rustdbgrid-{}.xlsx", uuid::Uuid::new_v4()));
+        let columns: Vec<ExportColumn> = [("id", "int"), ("price", "numeric"), ("born", "date")]
+            .iter()
+            .map(|(name, data_type)| ExportColumn {
+                name: name.to_string(),
+                data_type: data_type.to_string(),
+                nullable: true,
+            })
+            .collect();
+        let mut writer = XlsxWriter::new(
+            path.clone(),
+            ExportOptions::default(),
+            vec!["people".to_string()],
+        );
+        writer.begin(&columns).unwrap();
+        writer
+            .write_row(&[json!(1), json!("9.75"), json!("1990-05-17")])
+            .unwrap();
+        writer
+            .write_row(&[json!(2), Value::Null, json!("2001-01-02")])
+            .unwrap();
+        writer.finish().unwrap();
+
+        let sheet = read_sheet(path.to_str().unwrap(), None, 1, None).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(sheet.sheets, vec!["people".to_string()]);
+        assert_eq!(sheet.headers, vec!["id", "price", "born"]);
+        assert_eq!(
+            sheet.rows,
+            vec![
+                vec![json!(1), json!(9.75), json!("1990-05-17")],
+                vec![json!(2), Value::Null, json!("2001-01-02")],
+            ]
+        );
+    }
+}
@@ -1,4 +1,5 @@
 pub mod commands;
 pub mod db;
+pub mod formats;
 pub mod models;
 pub mod utils;
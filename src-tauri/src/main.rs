@@ -3,10 +3,14 @@
 
 mod commands;
 mod db;
+mod formats;
 mod models;
 mod utils;
 
-use commands::{app, connection, connection_info, export, logging, query, schema, settings, table};
+use commands::{
+    app, connection, connection_info, dump, export, import, logging, query, schema, settings,
+    table, transaction, transfer,
+};
 
 fn main() {
     // Initialize tracing logger
@@ -37,12 +41,18 @@ fn main() {
             connection::get_storage_info,
             connection::connect_to_database,
             connection::disconnect_from_database,
+            connection::close_tab_session,
             connection::is_database_connected,
             connection::get_connected_databases,
             connection_info::get_connections_info,
             connection_info::get_connection_for_edit,
             query::get_filter_values,
             query::get_distinct_values,
+            query::execute_script,
+            query::cancel_query,
+            query::execute_query_stream,
+            query::execute_query_multi,
+            query::explain_query,
             query::save_query,
             query::load_queries,
             query::delete_query,
@@ -56,12 +66,26 @@ fn main() {
             table::load_table_data,
             table::generate_sql,
             table::save_data,
+            table::preview_save,
+            table::preview_delete_impact,
+            table::list_edit_history,
+            table::revert_edit,
             schema::get_database_object,
             schema::get_properties_object,
             export::export_schema,
             export::export_data,
+            export::export_query,
+            export::export_query_xlsx,
             export::copy_schema,
             export::copy_data,
+            dump::dump_database,
+            dump::restore_sql_file,
+            import::preview_xlsx,
+            import::import_xlsx,
+            import::preview_file,
+            import::import_file,
+            transfer::preview_transfer,
+            transfer::transfer_table,
             logging::log_from_frontend,
             logging::log_from_bridge,
             logging::get_log_info,
@@ -74,6 +98,13 @@ fn main() {
             settings::open_path_in_explorer,
             settings::delete_file,
             settings::rename_file,
+            transaction::begin_transaction,
+            transaction::commit_transaction,
+            transaction::rollback_transaction,
+            transaction::create_savepoint,
+            transaction::rollback_to_savepoint,
+            transaction::release_savepoint,
+            transaction::get_transaction_state,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -62,6 +62,9 @@ pub struct ConnectionConfig {
     pub password: Option<String>,
     pub database: Option<String>,
     pub ssl: bool,
+    /// Query timeout in seconds overriding the application setting; 0 disables it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query_timeout_secs: Option<u64>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+
+/// What a foreign key does to referencing rows when the referenced row is deleted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum OnDeleteAction {
+    Cascade,
+    SetNull,
+    SetDefault,
+    Restrict,
+    NoAction,
+}
+
+impl OnDeleteAction {
+    /// Parse a referential action as reported by `information_schema`. A missing rule
+    /// is the SQL default, NO ACTION.
+    pub fn parse(rule: Option<&str>) -> Self {
+        match rule
+            .map(|r| r.trim().to_uppercase().replace(' ', "_"))
+            .as_deref()
+        {
+            Some("CASCADE") => Self::Cascade,
+            Some("SET_NULL") => Self::SetNull,
+            Some("SET_DEFAULT") => Self::SetDefault,
+            Some("RESTRICT") => Self::Restrict,
+            _ => Self::NoAction,
+        }
+    }
+
+    /// Whether referencing rows make the delete fail
+    pub fn blocks(&self) -> bool {
+        matches!(self, Self::Restrict | Self::NoAction)
+    }
+}
+
+/// Rows of one referencing table reached by deleting the selected rows
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DependentRows {
+    pub table: String,
+    /// Schema of the referencing table, for databases with schemas
+    #[serde(default)]
+    pub schema: Option<String>,
+    pub constraint_name: String,
+    /// Referencing columns of the foreign key
+    pub columns: Vec<String>,
+    pub on_delete: OnDeleteAction,
+    pub row_count: u64,
+    /// Tables from the edited table down to the one this table references
+    pub path: Vec<String>,
+    /// Whether these rows make the delete fail
+    pub blocks_delete: bool,
+}
+
+/// Rows in other tables that a delete would reach through foreign keys
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeleteImpact {
+    /// Referencing rows per foreign key, following CASCADE chains depth first
+    pub dependents: Vec<DependentRows>,
+    /// Whether no RESTRICT or NO ACTION foreign key is referenced by a deleted row
+    pub can_delete: bool,
+    /// Whether a cascade chain was cut off at the depth limit
+    pub truncated: bool,
+}
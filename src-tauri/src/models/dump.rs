@@ -0,0 +1,109 @@
+use serde::{Deserialize, Serialize};
+
+/// What a database dump includes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DumpOptions {
+    /// Only dump objects of this schema (PostgreSQL, SQL Server); all schemas when unset
+    pub schema: Option<String>,
+    /// Write `INSERT` statements for the rows of every table
+    pub include_data: bool,
+    pub include_views: bool,
+    /// Procedures and functions
+    pub include_routines: bool,
+    pub include_triggers: bool,
+    /// Drop each table before creating it
+    pub drop_existing: bool,
+}
+
+impl Default for DumpOptions {
+    fn default() -> Self {
+        Self {
+            schema: None,
+            include_data: true,
+            include_views: true,
+            include_routines: true,
+            include_triggers: true,
+            drop_existing: false,
+        }
+    }
+}
+
+/// Kinds of objects written to a dump
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DumpObjectKind {
+    Table,
+    View,
+    Routine,
+    Trigger,
+}
+
+/// Progress of a dump, pushed to the frontend while it is written
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum DumpEvent {
+    /// An object is being written; `index` counts from 1 up to `total` objects
+    Object {
+        kind: DumpObjectKind,
+        name: String,
+        index: usize,
+        total: usize,
+    },
+    /// Rows of the current table written so far
+    Rows { table: String, rows: u64 },
+}
+
+/// Outcome of a database dump
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DumpSummary {
+    pub path: String,
+    pub tables: usize,
+    pub rows: u64,
+    pub views: usize,
+    pub routines: usize,
+    pub triggers: usize,
+    /// Objects left out because the server did not report their source
+    pub skipped: Vec<String>,
+    /// Tables whose foreign keys form a cycle, so no order restores them without
+    /// violations; they are written last
+    pub cyclic_tables: Vec<String>,
+    pub execution_time: u128, // milliseconds
+}
+
+/// Progress of a restore, pushed to the frontend while statements run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum RestoreEvent {
+    /// `executed` of `total` statements have run, `failed` of them with an error
+    Progress {
+        executed: usize,
+        failed: usize,
+        total: usize,
+    },
+    /// A statement failed
+    Failed(RestoreError),
+}
+
+/// A statement of a restore that failed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestoreError {
+    /// Position of the statement in the file, from 0
+    pub index: usize,
+    /// Start of the statement, shortened for display
+    pub statement: String,
+    pub error: String,
+}
+
+/// Outcome of restoring a SQL file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestoreSummary {
+    pub path: String,
+    pub total: usize,
+    pub executed: usize,
+    pub failed: usize,
+    pub errors: Vec<RestoreError>,
+    /// Whether the restore stopped at the first error rather than running every statement
+    pub aborted: bool,
+    pub execution_time: u128, // milliseconds
+}
@@ -0,0 +1,246 @@
+use crate::models::save_request::{EditedRow, MaterializedRow, SaveOperation, SaveRequest};
+use crate::models::schema::TableSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::SystemTime;
+
+/// A saved grid edit, kept with the change set that undoes it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EditHistoryEntry {
+    pub id: String,
+    pub connection_id: String,
+    pub database: String,
+    pub table: String,
+    pub schema: Option<String>,
+    pub saved_at: u64,
+    pub inserted_rows: usize,
+    pub updated_rows: usize,
+    pub deleted_rows: usize,
+    /// Deletes for the inserted rows, old values for the updated ones and re-inserts
+    /// for the deleted ones. `None` when the edit cannot be undone.
+    pub inverse: Option<SaveRequest>,
+    /// Why the edit cannot be undone
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub not_revertible: Option<String>,
+    /// Set once the inverse has been applied
+    pub reverted_at: Option<u64>,
+}
+
+impl EditHistoryEntry {
+    pub fn new(
+        (connection_id, database, table, schema): (String, String, String, Option<String>),
+        save_request: &SaveRequest,
+        applied: &[(SaveOperation, usize)],
+        materialized: &[MaterializedRow],
+        table_schema: &TableSchema,
+    ) -> Self {
+        let count = |op: SaveOperation| applied.iter().filter(|(o, _)| *o == op).count();
+        let (inverse, not_revertible) =
+            match inverse_change_set(save_request, applied, materialized, table_schema) {
+                Ok(inverse) => (Some(inverse), None),
+                Err(reason) => (None, Some(reason)),
+            };
+
+        EditHistoryEntry {
+            id: uuid::Uuid::new_v4().to_string(),
+            connection_id,
+            database,
+            table,
+            schema,
+            saved_at: now_secs(),
+            inserted_rows: count(SaveOperation::Insert),
+            updated_rows: count(SaveOperation::Update),
+            deleted_rows: count(SaveOperation::Delete),
+            inverse,
+            not_revertible,
+            reverted_at: None,
+        }
+    }
+
+    pub fn mark_reverted(&mut self) {
+        self.reverted_at = Some(now_secs());
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Build the save request that undoes the applied rows of `save_request`. Saves run
+/// inserts, updates and deletes in that order, so undoing them in reverse means
+/// re-inserting deleted rows, restoring updated ones and then deleting inserted ones,
+/// which is again the order a save runs in. Restored updates are conflict-checked
+/// against the values the save wrote.
+///
+/// Fails when the inverse would not restore the rows as they were: deleted rows whose
+/// table generates values cannot be inserted again with them, and inserted rows the
+/// save did not read back cannot be told apart from others.
+pub fn inverse_change_set(
+    save_request: &SaveRequest,
+    applied: &[(SaveOperation, usize)],
+    materialized: &[MaterializedRow],
+    table_schema: &TableSchema,
+) -> Result<SaveRequest, String> {
+    let rows = |op: SaveOperation| {
+        applied
+            .iter()
+            .filter(move |(o, _)| *o == op)
+            .map(|(_, idx)| *idx)
+    };
+
+    let generated: Vec<&str> = table_schema
+        .columns
+        .iter()
+        .filter(|col| col.is_auto_increment || col.is_generated)
+        .map(|col| col.name.as_str())
+        .collect();
+    if rows(SaveOperation::Delete).next().is_some() && !generated.is_empty() {
+        return Err(format!(
+            "Deleted rows cannot be restored with the values the server generated for {}",
+            generated.join(", ")
+        ));
+    }
+    let new_rows = rows(SaveOperation::Delete)
+        .filter_map(|idx| save_request.deleted_rows.get(idx).cloned())
+        .collect();
+
+    let edited_rows = rows(SaveOperation::Update)
+        .filter_map(|idx| save_request.edited_rows.get(idx))
+        .map(|edited| {
+            let mut saved = edited.original_data.clone();
+            saved.extend(edited.updated_data.clone());
+            let restored: HashMap<_, _> = edited
+                .updated_data
+                .keys()
+                .filter_map(|col| {
+                    edited
+                        .original_data
+                        .get(col)
+                        .map(|value| (col.clone(), value.clone()))
+                })
+                .collect();
+            EditedRow {
+                original_data: saved,
+                updated_data: restored,
+            }
+        })
+        .filter(|edited| !edited.updated_data.is_empty())
+        .collect();
+
+    // Inserted rows are deleted as the server stored them, with generated keys
+    let deleted_rows = rows(SaveOperation::Insert)
+        .map(|idx| {
+            materialized
+                .iter()
+                .find(|m| m.row_index == idx)
+                .map(|m| m.values.clone())
+                .ok_or_else(|| {
+                    "Inserted rows cannot be deleted again: the server did not return them"
+                        .to_string()
+                })
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(SaveRequest {
+        new_rows,
+        edited_rows,
+        deleted_rows,
+        best_effort: false,
+        detect_conflicts: true,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::schema::{test_column, Column};
+    use serde_json::json;
+
+    fn users_schema(id_generated: bool) -> TableSchema {
+        let column = |name: &str, is_auto_increment: bool| Column {
+            is_primary_key: name == "id",
+            is_auto_increment,
+            ..test_column(name, "int", false, None)
+        };
+        TableSchema {
+            table_name: "users".to_string(),
+            columns: vec![column("id", id_generated), column("name", false)],
+            indexes: Vec::new(),
+            foreign_keys: Vec::new(),
+            has_triggers: false,
+        }
+    }
+
+    #[test]
+    fn test_inverse_undoes_only_the_applied_rows() {
+        let save_request = SaveRequest {
+            new_rows: vec![HashMap::from([("name".to_string(), json!("new"))])],
+            edited_rows: vec![
+                EditedRow {
+                    original_data: HashMap::from([
+                        ("id".to_string(), json!(1)),
+                        ("name".to_string(), json!("old")),
+                    ]),
+                    updated_data: HashMap::from([("name".to_string(), json!("renamed"))]),
+                },
+                EditedRow {
+                    original_data: HashMap::from([("id".to_string(), json!(2))]),
+                    updated_data: HashMap::from([("name".to_string(), json!("failed"))]),
+                },
+            ],
+            deleted_rows: vec![HashMap::from([("id".to_string(), json!(3))])],
+            best_effort: true,
+            detect_conflicts: false,
+        };
+        let applied = [
+            (SaveOperation::Insert, 0),
+            (SaveOperation::Update, 0),
+            (SaveOperation::Delete, 0),
+        ];
+        let materialized = [MaterializedRow {
+            row_index: 0,
+            values: HashMap::from([
+                ("id".to_string(), json!(9)),
+                ("name".to_string(), json!("new")),
+            ]),
+        }];
+
+        let inverse =
+            inverse_change_set(&save_request, &applied, &materialized, &users_schema(false))
+                .unwrap();
+
+        assert_eq!(inverse.new_rows, save_request.deleted_rows);
+        assert_eq!(inverse.edited_rows.len(), 1);
+        assert_eq!(
+            inverse.edited_rows[0].original_data["name"],
+            json!("renamed")
+        );
+        assert_eq!(
+            inverse.edited_rows[0].updated_data,
+            HashMap::from([("name".to_string(), json!("old"))])
+        );
+        assert_eq!(inverse.deleted_rows, vec![materialized[0].values.clone()]);
+        assert!(inverse.detect_conflicts && !inverse.best_effort);
+    }
+
+    #[test]
+    fn test_refuses_inverses_that_cannot_restore_rows() {
+        let save_request = SaveRequest {
+            new_rows: vec![HashMap::from([("name".to_string(), json!("new"))])],
+            edited_rows: Vec::new(),
+            deleted_rows: vec![HashMap::from([("id".to_string(), json!(3))])],
+            best_effort: false,
+            detect_conflicts: false,
+        };
+
+        let deleted = [(SaveOperation::Delete, 0)];
+        assert!(inverse_change_set(&save_request, &deleted, &[], &users_schema(true)).is_err());
+        assert!(inverse_change_set(&save_request, &deleted, &[], &users_schema(false)).is_ok());
+
+        let inserted = [(SaveOperation::Insert, 0)];
+        assert!(inverse_change_set(&save_request, &inserted, &[], &users_schema(false)).is_err());
+    }
+}
@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Execution plan of a query, normalized across database engines
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionPlan {
+    pub root: PlanNode,
+    /// Whether the query was actually run, so `actual_*` figures are filled in
+    pub analyzed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub planning_time_ms: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub execution_time_ms: Option<f64>,
+    /// Format of `raw`: "json", "tree" or "xml"
+    pub format: String,
+    /// Plan exactly as the server returned it, for copying into external tools
+    pub raw: String,
+}
+
+/// One operator in a plan tree
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlanNode {
+    pub node_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub relation: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimated_rows: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actual_rows: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimated_cost: Option<f64>,
+    /// Time spent in this node including its children, in milliseconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actual_time_ms: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub loops: Option<f64>,
+    /// Engine-specific attributes that have no common field
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub details: BTreeMap<String, serde_json::Value>,
+    pub children: Vec<PlanNode>,
+}
+
+impl PlanNode {
+    pub fn new(node_type: impl Into<String>) -> Self {
+        Self {
+            node_type: node_type.into(),
+            ..Default::default()
+        }
+    }
+}
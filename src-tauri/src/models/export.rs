@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// File formats rows can be exported to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    Csv,
+    Tsv,
+    Json,
+    /// One JSON object per line
+    Ndjson,
+    SqlInsert,
+    Markdown,
+    Html,
+    /// `WHERE col IN (...)` condition listing the distinct values of each column
+    WhereIn,
+    /// Excel workbook with typed cells
+    Xlsx,
+    /// Apache Parquet, typed from the column types the driver reports
+    Parquet,
+    /// Apache Arrow IPC file, typed like Parquet
+    Arrow,
+}
+
+/// When CSV and TSV fields are wrapped in quotes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuoteStyle {
+    /// Only fields containing the delimiter, the quote or a line break
+    Minimal,
+    Always,
+    /// Every field that is not a number or boolean
+    NonNumeric,
+    Never,
+}
+
+/// Character encoding of exported text files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TextEncoding {
+    Utf8,
+    /// UTF-8 with a byte order mark, which spreadsheet applications use to detect it
+    Utf8Bom,
+    /// UTF-16 little endian with a byte order mark
+    Utf16le,
+    /// ISO-8859-1; characters outside it are written as `?`
+    Latin1,
+}
+
+impl TextEncoding {
+    /// Name of the encoding as declared in HTML
+    pub fn charset(&self) -> &'static str {
+        match self {
+            Self::Utf8 | Self::Utf8Bom => "utf-8",
+            Self::Utf16le => "utf-16le",
+            Self::Latin1 => "iso-8859-1",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportOptions {
+    /// Field separator of CSV; TSV always separates fields with a tab
+    pub delimiter: char,
+    pub quote: char,
+    pub quote_style: QuoteStyle,
+    /// Write column names before the rows (CSV, TSV)
+    pub header: bool,
+    /// Text written for NULL by CSV, TSV, Markdown and HTML
+    pub null_value: String,
+    /// chrono format for values of date and time columns, e.g. `%d/%m/%Y`. Values are
+    /// written as loaded when unset or when they do not parse as a date.
+    pub date_format: Option<String>,
+    pub encoding: TextEncoding,
+    /// Rows per row group of Parquet and per record batch of Arrow files
+    pub row_group_size: usize,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            quote: '"',
+            quote_style: QuoteStyle::Minimal,
+            header: true,
+            null_value: String::new(),
+            date_format: None,
+            encoding: TextEncoding::Utf8,
+            row_group_size: 10_000,
+        }
+    }
+}
+
+/// Outcome of an export written to a file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportSummary {
+    pub path: String,
+    pub format: ExportFormat,
+    pub rows_written: u64,
+    pub execution_time: u128, // milliseconds
+}
+
+/// Rows picked in the grid, shaped like the `QueryResult` they were loaded from
+#[derive(Debug, Clone, Deserialize)]
+pub struct ClipboardSelection {
+    /// Selected columns, in the order they are copied
+    pub columns: Vec<String>,
+    #[serde(default)]
+    pub column_types: Option<HashMap<String, String>>,
+    pub rows: Vec<HashMap<String, serde_json::Value>>,
+}
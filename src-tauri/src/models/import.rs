@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Which rows of a workbook are imported and where their columns go
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct XlsxImportOptions {
+    /// Worksheet to read; the first one when unset
+    pub sheet: Option<String>,
+    /// Row holding the column names, counted from 1 as Excel shows it. The rows below
+    /// it are imported.
+    pub header_row: u32,
+    /// Target column of each source column whose name differs from it; an empty target
+    /// leaves the source column out. Other source columns go to the table column of the
+    /// same name, ignoring case.
+    pub mapping: HashMap<String, String>,
+}
+
+impl Default for XlsxImportOptions {
+    fn default() -> Self {
+        Self {
+            sheet: None,
+            header_row: 1,
+            mapping: HashMap::new(),
+        }
+    }
+}
+
+/// Text file formats rows can be imported from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportFormat {
+    Csv,
+    /// One JSON object per line, its keys naming the columns
+    Ndjson,
+}
+
+/// How the rows of a CSV or NDJSON file are read, converted and inserted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FileImportOptions {
+    pub format: ImportFormat,
+    /// Field separator of CSV, an ASCII character
+    pub delimiter: char,
+    pub quote: char,
+    /// The first CSV line holds the column names; otherwise columns are named
+    /// `column1`, `column2`, ...
+    pub header: bool,
+    /// Text read as NULL, such as `NULL` or `\N`; none when empty
+    pub null_value: String,
+    /// Read empty text as NULL in text columns too. Other columns always do.
+    pub empty_as_null: bool,
+    /// Target column of each source column, as for `XlsxImportOptions`
+    pub mapping: HashMap<String, String>,
+    /// Rows per INSERT statement or COPY
+    pub batch_size: usize,
+    /// Delete every row of the table before importing, in the same transaction. Not
+    /// allowed with `error_log`.
+    pub truncate: bool,
+    /// Update the rows whose primary key already exists instead of failing on them
+    pub upsert: bool,
+    /// File the rows that fail are written to with their line and error. Failing rows
+    /// are then skipped; without it the first one undoes the whole import.
+    pub error_log: Option<String>,
+}
+
+impl Default for FileImportOptions {
+    fn default() -> Self {
+        Self {
+            format: ImportFormat::Csv,
+            delimiter: ',',
+            quote: '"',
+            header: true,
+            null_value: String::new(),
+            empty_as_null: false,
+            mapping: HashMap::new(),
+            batch_size: 500,
+            truncate: false,
+            upsert: false,
+            error_log: None,
+        }
+    }
+}
+
+/// First rows of a CSV or NDJSON file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilePreview {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<serde_json::Value>>,
+}
+
+/// Rows of a worksheet below its header row
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SheetPreview {
+    /// Every worksheet of the workbook, to choose from
+    pub sheets: Vec<String>,
+    pub sheet: String,
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<serde_json::Value>>,
+}
+
+/// A source column and the table column it is imported into
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnMapping {
+    pub source: String,
+    pub target: String,
+}
+
+/// Outcome of an import
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportSummary {
+    pub rows_read: usize,
+    pub rows_imported: u64,
+    /// Rows skipped because they failed, written to the error log
+    pub rows_failed: usize,
+    pub mapped_columns: Vec<ColumnMapping>,
+    /// Source columns that match no column of the table and were left out
+    pub unmapped_columns: Vec<String>,
+    pub execution_time: u128, // milliseconds
+}
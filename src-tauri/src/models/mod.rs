@@ -1,7 +1,16 @@
 pub mod connection;
+pub mod delete_impact;
 pub mod distinct_values_request;
+pub mod dump;
+pub mod edit_history;
+pub mod explain;
+pub mod export;
+pub mod import;
 pub mod query_result;
 pub mod save_request;
 pub mod saved_query;
 pub mod schema;
+pub mod script_request;
 pub mod table_request;
+pub mod transaction;
+pub mod transfer;
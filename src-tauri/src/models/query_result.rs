@@ -15,6 +15,46 @@ pub struct QueryResult {
     pub final_query: Option<String>,
 }
 
+/// Informational message the server sent while a statement ran, such as SQL Server
+/// PRINT / low-severity RAISERROR output or a PostgreSQL NOTICE
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerMessage {
+    pub level: String,
+    pub message: String,
+}
+
+/// Everything one execution produced: each result set in order plus server messages
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MultiQueryResult {
+    pub result_sets: Vec<QueryResult>,
+    pub messages: Vec<ServerMessage>,
+    pub execution_time: u128,
+}
+
+/// Streaming counterpart of `QueryResult`, pushed to the frontend in order:
+/// one `Columns`, any number of `Rows` batches, then `Finished`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum QueryStreamEvent {
+    /// Result set columns; schemaless sources (MongoDB) may resend an extended list
+    Columns {
+        columns: Vec<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        column_display_names: Option<Vec<String>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        column_types: Option<HashMap<String, String>>,
+    },
+    /// Next batch of rows
+    Rows {
+        rows: Vec<HashMap<String, serde_json::Value>>,
+    },
+    /// All rows have been sent
+    Finished {
+        row_count: u64,
+        execution_time: u128,
+    },
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FilterValuesResult {
     pub values: Vec<String>,
@@ -6,6 +6,152 @@ pub struct SaveRequest {
     pub new_rows: Vec<HashMap<String, serde_json::Value>>,
     pub edited_rows: Vec<EditedRow>,
     pub deleted_rows: Vec<HashMap<String, serde_json::Value>>,
+    /// Apply each row on its own and keep the ones that succeed, instead of one
+    /// transaction. Required for databases without transactions.
+    #[serde(default)]
+    pub best_effort: bool,
+    /// Only update rows that are unchanged on the server since they were loaded, and
+    /// report the others as conflicts
+    #[serde(default)]
+    pub detect_conflicts: bool,
+}
+
+/// Kind of change a save statement applies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SaveOperation {
+    Insert,
+    Update,
+    Delete,
+}
+
+impl SaveOperation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SaveOperation::Insert => "insert",
+            SaveOperation::Update => "update",
+            SaveOperation::Delete => "delete",
+        }
+    }
+}
+
+/// Edited row that changed on the server since it was loaded, so it was not updated
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveConflict {
+    /// Index of the row within `edited_rows`
+    pub row_index: usize,
+    /// Row as it is now on the server, or `None` when it was deleted
+    pub current_values: Option<HashMap<String, serde_json::Value>>,
+    /// Edited columns whose server value no longer matches the loaded one
+    pub changed_columns: Vec<String>,
+}
+
+impl SaveConflict {
+    /// Compare the current server row with the loaded values of the edited columns
+    pub fn new(
+        row_index: usize,
+        edited_row: &EditedRow,
+        current_values: Option<HashMap<String, serde_json::Value>>,
+    ) -> Self {
+        let mut changed_columns: Vec<String> = match &current_values {
+            Some(current) => edited_row
+                .updated_data
+                .keys()
+                .filter(|col| current.get(*col) != edited_row.original_data.get(*col))
+                .cloned()
+                .collect(),
+            None => Vec::new(),
+        };
+        changed_columns.sort();
+
+        Self {
+            row_index,
+            current_values,
+            changed_columns,
+        }
+    }
+}
+
+/// Row that stopped an all-or-nothing save
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveFailure {
+    pub operation: SaveOperation,
+    /// Index of the row within `new_rows`, `edited_rows` or `deleted_rows`
+    pub row_index: usize,
+    pub error: String,
+}
+
+/// Inserted row as the server stored it, with generated ids, defaults and trigger
+/// changes filled in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaterializedRow {
+    /// Index of the row within `new_rows`
+    pub row_index: usize,
+    pub values: HashMap<String, serde_json::Value>,
+}
+
+/// Outcome of one statement of a save dry run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatementPreview {
+    pub operation: SaveOperation,
+    /// Index of the row within `new_rows`, `edited_rows` or `deleted_rows`
+    pub row_index: usize,
+    pub sql: String,
+    /// Rows the statement changed, when it ran
+    pub affected_rows: Option<u64>,
+    pub error: Option<String>,
+    /// Constraint named in the error, such as a violated foreign key
+    pub constraint: Option<String>,
+}
+
+impl StatementPreview {
+    pub fn applied(operation: SaveOperation, row_index: usize, sql: String, rows: u64) -> Self {
+        Self {
+            operation,
+            row_index,
+            sql,
+            affected_rows: Some(rows),
+            error: None,
+            constraint: None,
+        }
+    }
+
+    pub fn failed(operation: SaveOperation, row_index: usize, sql: String, error: String) -> Self {
+        Self {
+            operation,
+            row_index,
+            sql,
+            affected_rows: None,
+            constraint: violated_constraint(&error),
+            error: Some(error),
+        }
+    }
+}
+
+/// Effect of a save request, measured in a transaction that was rolled back
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavePreview {
+    pub statements: Vec<StatementPreview>,
+    /// Whether every statement ran without error
+    pub would_succeed: bool,
+}
+
+/// Name of the constraint a database error reports as violated, as quoted by
+/// PostgreSQL (`constraint "fk"`), SQL Server (`constraint 'PK'`) or MySQL
+/// (`CONSTRAINT `fk``, `for key 'PRIMARY'`)
+pub fn violated_constraint(error: &str) -> Option<String> {
+    let lower = error.to_ascii_lowercase();
+    ["constraint ", "for key "].iter().find_map(|marker| {
+        lower.match_indices(marker).find_map(|(start, _)| {
+            let rest = &error[start + marker.len()..];
+            let quote = rest
+                .chars()
+                .next()
+                .filter(|c| matches!(c, '"' | '\'' | '`'))?;
+            let name = &rest[1..];
+            name.find(quote).map(|end| name[..end].to_string())
+        })
+    })
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,7 +162,7 @@ pub struct EditedRow {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SaveResponse {
-    pub status: String, // "success" | "partial" | "error"
+    pub status: String, // "success" | "partial" | "conflict" | "unsupported" | "error"
     pub message: String,
     pub affected_rows: i64,
     pub executed_queries: Vec<String>,
@@ -28,6 +174,13 @@ pub struct SaveResponse {
     pub deleted_rows: Option<i64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub errors: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failed_row: Option<SaveFailure>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conflicts: Vec<SaveConflict>,
+    /// Stored values of the inserted rows that could be read back
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub materialized_rows: Vec<MaterializedRow>,
 }
 
 impl SaveResponse {
@@ -48,6 +201,9 @@ impl SaveResponse {
             updated_rows: Some(updated_rows),
             deleted_rows: Some(deleted_rows),
             errors: None,
+            failed_row: None,
+            conflicts: Vec::new(),
+            materialized_rows: Vec::new(),
         }
     }
 
@@ -69,6 +225,9 @@ impl SaveResponse {
             updated_rows: Some(updated_rows),
             deleted_rows: Some(deleted_rows),
             errors: Some(errors),
+            failed_row: None,
+            conflicts: Vec::new(),
+            materialized_rows: Vec::new(),
         }
     }
 
@@ -82,6 +241,86 @@ impl SaveResponse {
             updated_rows: None,
             deleted_rows: None,
             errors: None,
+            failed_row: None,
+            conflicts: Vec::new(),
+            materialized_rows: Vec::new(),
+        }
+    }
+
+    /// The database cannot apply the rows in one transaction; nothing was changed and
+    /// the rows can only be saved with best effort, one at a time
+    pub fn transactions_unsupported() -> Self {
+        SaveResponse {
+            status: "unsupported".to_string(),
+            ..Self::error(
+                "This database does not support transactions. Save with best effort to apply each row on its own"
+                    .to_string(),
+            )
         }
     }
+
+    /// The save was rolled back because of one row; nothing was changed
+    pub fn rolled_back(failure: SaveFailure) -> Self {
+        SaveResponse {
+            message: format!(
+                "Failed to {} row {}: {}. No changes were saved",
+                failure.operation.as_str(),
+                failure.row_index,
+                failure.error
+            ),
+            errors: Some(vec![failure.error.clone()]),
+            failed_row: Some(failure),
+            ..Self::error(String::new())
+        }
+    }
+
+    /// Edited rows changed on the server meanwhile, so the save was rolled back
+    pub fn conflicted(conflicts: Vec<SaveConflict>) -> Self {
+        SaveResponse {
+            status: "conflict".to_string(),
+            message: format!(
+                "{} row(s) were changed by someone else since they were loaded. No changes were saved",
+                conflicts.len()
+            ),
+            conflicts,
+            ..Self::error(String::new())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_violated_constraint_names_are_read_from_each_dialect() {
+        assert_eq!(
+            violated_constraint(
+                "error returned from database: update or delete on table \"customers\" \
+                 violates foreign key constraint \"fk_orders_customer\" on table \"orders\""
+            )
+            .as_deref(),
+            Some("fk_orders_customer")
+        );
+        assert_eq!(
+            violated_constraint(
+                "The DELETE statement conflicted with the REFERENCE constraint \"FK_Orders\"."
+            )
+            .as_deref(),
+            Some("FK_Orders")
+        );
+        assert_eq!(
+            violated_constraint(
+                "Cannot add or update a child row: a foreign key constraint fails \
+                 (`shop`.`orders`, CONSTRAINT `fk_orders_customer` FOREIGN KEY (`customer_id`))"
+            )
+            .as_deref(),
+            Some("fk_orders_customer")
+        );
+        assert_eq!(
+            violated_constraint("Duplicate entry '7' for key 'orders.PRIMARY'").as_deref(),
+            Some("orders.PRIMARY")
+        );
+        assert_eq!(violated_constraint("connection reset"), None);
+    }
 }
@@ -64,6 +64,34 @@ pub struct Column {
     pub default_value: Option<String>,
     pub is_primary_key: bool,
     pub is_auto_increment: bool,
+    /// Value computed by the server (generated, computed or row version column), which
+    /// statements cannot write
+    #[serde(default)]
+    pub is_generated: bool,
+    /// Whether `default_value` is an expression rather than a literal value. Only
+    /// MySQL needs this, as it reports both bare; other databases report expressions.
+    #[serde(default)]
+    pub default_is_expression: bool,
+}
+
+/// Column without a key, for tests to build table schemas from
+#[cfg(test)]
+pub(crate) fn test_column(
+    name: &str,
+    data_type: &str,
+    nullable: bool,
+    default: Option<&str>,
+) -> Column {
+    Column {
+        name: name.to_string(),
+        data_type: data_type.to_string(),
+        nullable,
+        default_value: default.map(str::to_string),
+        is_primary_key: false,
+        is_auto_increment: false,
+        is_generated: false,
+        default_is_expression: false,
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -72,6 +100,10 @@ pub struct TableSchema {
     pub columns: Vec<Column>,
     pub indexes: Vec<Index>,
     pub foreign_keys: Vec<ForeignKey>,
+    /// Whether the table has enabled triggers, which some dialects must know before
+    /// returning rows from DML
+    #[serde(default)]
+    pub has_triggers: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -105,6 +137,12 @@ pub struct TableRelationship {
     pub referenced_table_name: String,
     pub referenced_column_name: String,
     pub relationship_type: String, // "FOREIGN_KEY", "REFERENCED_BY", etc.
+    /// Schema of the referencing table, where the database has schemas
+    #[serde(default)]
+    pub table_schema: Option<String>,
+    /// Schema of the referenced table, where the database has schemas
+    #[serde(default)]
+    pub referenced_table_schema: Option<String>,
     pub owner: Option<String>,
     pub ref_object_type: Option<String>,
     pub on_delete: Option<String>,
@@ -0,0 +1,203 @@
+use crate::models::query_result::{QueryResult, ServerMessage};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Request structure for running a raw SQL script from the editor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptRequest {
+    pub connection_id: String,
+    /// Editor tab running the script; its dedicated session keeps `SET`, `USE` and
+    /// temporary tables between runs. Without it the shared connection is used.
+    #[serde(default)]
+    pub tab_id: Option<String>,
+    pub script: String,
+    /// Stop at the first failing statement instead of running the rest of the script
+    #[serde(default = "default_stop_on_error")]
+    pub stop_on_error: bool,
+}
+
+fn default_stop_on_error() -> bool {
+    true
+}
+
+/// Response structure for a script execution, one entry per statement
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScriptResponse {
+    pub results: Vec<StatementResult>,
+    pub executed_count: usize,
+    pub failed_count: usize,
+    pub skipped_count: usize,
+    pub execution_time: u128,
+    /// Whether a manual transaction is still open on the connection after the script
+    pub in_transaction: bool,
+}
+
+/// Outcome of a single statement in a script
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StatementResult {
+    pub index: usize,
+    pub statement: String,
+    /// Byte offset of the statement in the submitted script
+    pub start: usize,
+    /// Byte offset just past the end of the statement in the submitted script
+    pub end: usize,
+    pub columns: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column_display_names: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column_types: Option<HashMap<String, String>>,
+    pub rows: Vec<Vec<serde_json::Value>>,
+    pub rows_affected: Option<u64>,
+    pub execution_time: u128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Result sets after the first, for statements such as `EXEC` or `CALL`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_result_sets: Vec<ResultSet>,
+    /// Informational messages the server sent while the statement ran
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub messages: Vec<ServerMessage>,
+}
+
+/// One result set with rows in column order
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResultSet {
+    pub columns: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column_display_names: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column_types: Option<HashMap<String, String>>,
+    pub rows: Vec<Vec<serde_json::Value>>,
+    pub rows_affected: Option<u64>,
+}
+
+impl From<QueryResult> for ResultSet {
+    fn from(result: QueryResult) -> Self {
+        let rows = result
+            .rows
+            .iter()
+            .map(|row| {
+                // Maintain column order
+                result
+                    .columns
+                    .iter()
+                    .map(|col| row.get(col).cloned().unwrap_or(serde_json::Value::Null))
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            columns: result.columns,
+            column_display_names: result.column_display_names,
+            column_types: result.column_types,
+            rows,
+            rows_affected: result.rows_affected,
+        }
+    }
+}
+
+impl StatementResult {
+    /// Build a result entry for a statement that returned a result set
+    pub fn from_query(
+        index: usize,
+        statement: String,
+        result: QueryResult,
+        execution_time: u128,
+    ) -> Self {
+        let set = ResultSet::from(result);
+
+        Self {
+            index,
+            statement,
+            start: 0,
+            end: 0,
+            columns: set.columns,
+            column_display_names: set.column_display_names,
+            column_types: set.column_types,
+            rows: set.rows,
+            rows_affected: set.rows_affected,
+            execution_time,
+            error: None,
+            extra_result_sets: Vec::new(),
+            messages: Vec::new(),
+        }
+    }
+
+    /// Build a result entry for a statement that returned several result sets; the
+    /// first one fills the main fields
+    pub fn from_results(
+        index: usize,
+        statement: String,
+        results: Vec<QueryResult>,
+        execution_time: u128,
+    ) -> Self {
+        let mut results = results.into_iter();
+        let mut entry = match results.next() {
+            Some(first) => Self::from_query(index, statement, first, execution_time),
+            None => Self::from_update(index, statement, 0, execution_time),
+        };
+        entry.extra_result_sets = results.map(ResultSet::from).collect();
+        entry
+    }
+
+    /// Build a result entry for a statement that only reports affected rows
+    pub fn from_update(
+        index: usize,
+        statement: String,
+        rows_affected: u64,
+        execution_time: u128,
+    ) -> Self {
+        Self {
+            index,
+            statement,
+            start: 0,
+            end: 0,
+            columns: Vec::new(),
+            column_display_names: None,
+            column_types: None,
+            rows: Vec::new(),
+            rows_affected: Some(rows_affected),
+            execution_time,
+            error: None,
+            extra_result_sets: Vec::new(),
+            messages: Vec::new(),
+        }
+    }
+
+    /// Build a result entry for a failed statement
+    pub fn from_error(
+        index: usize,
+        statement: String,
+        error: String,
+        execution_time: u128,
+    ) -> Self {
+        Self {
+            index,
+            statement,
+            start: 0,
+            end: 0,
+            columns: Vec::new(),
+            column_display_names: None,
+            column_types: None,
+            rows: Vec::new(),
+            rows_affected: None,
+            execution_time,
+            error: Some(error),
+            extra_result_sets: Vec::new(),
+            messages: Vec::new(),
+        }
+    }
+
+    /// Attach the server messages captured while the statement ran
+    pub fn with_messages(mut self, messages: Vec<ServerMessage>) -> Self {
+        self.messages = messages;
+        self
+    }
+
+    /// Attach the statement's byte offsets in the original script
+    pub fn with_offsets(mut self, start: usize, end: usize) -> Self {
+        self.start = start;
+        self.end = end;
+        self
+    }
+}
@@ -16,6 +16,7 @@ pub struct QueryRequest {
     pub database: Option<String>,
     pub schema: Option<String>,
     pub table: String,
+    /// Rows per page; 0 reads every row
     pub limit: usize,
     pub offset: usize,
     #[serde(default)]
@@ -86,6 +87,17 @@ pub struct TableDataResponse {
     pub final_query: String,
     pub has_more_data: bool,
     pub execution_time: u128, // milliseconds
+    /// Hidden column identifying rows of a table without a primary key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub row_locator: Option<String>,
+    /// Hidden column holding the version of each row, sent back with the original
+    /// values of an edited row so a save detects changes made since it was loaded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub row_version: Option<String>,
+    /// Set when the table has no primary key or row locator, so an edit matches rows by
+    /// their values and may hit a duplicate instead
+    #[serde(default)]
+    pub ambiguous_edits: bool,
 }
 
 /// Column information including name and data type
@@ -110,6 +122,22 @@ impl TableDataResponse {
             final_query,
             has_more_data,
             execution_time: execution_time.as_millis(),
+            row_locator: None,
+            row_version: None,
+            ambiguous_edits: false,
         }
     }
+
+    /// Record how rows are identified, and checked for changes, when saving
+    pub fn with_row_identity(
+        mut self,
+        row_locator: Option<String>,
+        row_version: Option<String>,
+        ambiguous_edits: bool,
+    ) -> Self {
+        self.row_locator = row_locator;
+        self.row_version = row_version;
+        self.ambiguous_edits = ambiguous_edits;
+        self
+    }
 }
@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+
+/// Manual transaction state of a connection, reported to the frontend after every
+/// transaction command
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TransactionState {
+    pub connection_id: String,
+    /// Editor tab whose dedicated session holds the transaction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tab_id: Option<String>,
+    pub active: bool,
+    /// Open savepoints, oldest first
+    pub savepoints: Vec<String>,
+    /// Unix timestamp in milliseconds of the BEGIN
+    pub started_at: Option<i64>,
+    /// Name the transaction was begun with, on SQL Server
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+/// What to do with an open transaction when its connection is closed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransactionAction {
+    Commit,
+    Rollback,
+}
@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+
+/// A table on one of the open connections
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableLocation {
+    pub connection_id: String,
+    pub database: String,
+    /// Schema of the table (PostgreSQL, SQL Server)
+    pub schema: Option<String>,
+    pub table: String,
+}
+
+/// A table to copy from one connection to another
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferRequest {
+    pub source: TableLocation,
+    pub target: TableLocation,
+    #[serde(default)]
+    pub options: TransferOptions,
+}
+
+/// How a table is copied to another connection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TransferOptions {
+    /// Create the target table from the source's columns, with types mapped to the
+    /// target's dialect; otherwise rows go into an existing table by column name
+    pub create_table: bool,
+    /// Drop the target table before creating it
+    pub drop_existing: bool,
+    /// Delete the rows of an existing target table first
+    pub truncate: bool,
+    /// Rows read from the source and written to the target at a time
+    pub batch_size: usize,
+}
+
+impl Default for TransferOptions {
+    fn default() -> Self {
+        Self {
+            create_table: true,
+            drop_existing: false,
+            truncate: false,
+            batch_size: 1000,
+        }
+    }
+}
+
+/// A column of a transfer with its type on each side
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferColumn {
+    pub name: String,
+    pub source_type: String,
+    pub target_type: String,
+}
+
+/// Columns of a transfer and the DDL creating its target table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferPlan {
+    pub columns: Vec<TransferColumn>,
+    /// Source columns left out, with the reason
+    pub skipped_columns: Vec<String>,
+    /// Empty when the rows go into an existing table
+    pub ddl: String,
+}
+
+/// A source row that could not be written to the target
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferFailure {
+    /// Position of the row in the source, from 1
+    pub row: u64,
+    /// Column whose value did not convert, none when the target refused the row
+    pub column: Option<String>,
+    pub error: String,
+}
+
+/// Progress of a transfer, pushed to the frontend while rows are copied
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum TransferEvent {
+    /// Rows read from the source so far, of `total` when the source could count them
+    Progress {
+        rows_read: u64,
+        rows_written: u64,
+        rows_failed: u64,
+        total: Option<u64>,
+    },
+    /// A row was left out
+    Failed(TransferFailure),
+}
+
+/// Outcome of a transfer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferSummary {
+    pub plan: TransferPlan,
+    pub rows_read: u64,
+    pub rows_written: u64,
+    pub rows_failed: u64,
+    /// The first failures; later ones are only counted
+    pub failures: Vec<TransferFailure>,
+    /// Indexes of the source the target could not be given
+    pub warnings: Vec<String>,
+    pub execution_time: u128, // milliseconds
+}
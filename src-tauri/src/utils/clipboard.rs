@@ -1,2 +1,24 @@
-// Clipboard utilities
-// TODO: Implement clipboard functionality using system clipboard
+use anyhow::{anyhow, Result};
+use arboard::Clipboard;
+use std::sync::Mutex;
+
+lazy_static::lazy_static! {
+    // On X11 and Wayland copied text is served by its owner, so the handle is kept
+    // open for the life of the app rather than dropped after each copy
+    static ref CLIPBOARD: Mutex<Option<Clipboard>> = Mutex::new(None);
+}
+
+/// Put text on the system clipboard
+pub fn write_text(text: &str) -> Result<()> {
+    let mut guard = CLIPBOARD
+        .lock()
+        .map_err(|_| anyhow!("Clipboard is unavailable"))?;
+    let clipboard = match guard.take() {
+        Some(clipboard) => clipboard,
+        None => Clipboard::new().map_err(|e| anyhow!("Failed to open clipboard: {}", e))?,
+    };
+    guard
+        .insert(clipboard)
+        .set_text(text)
+        .map_err(|e| anyhow!("Failed to write to clipboard: {}", e))
+}
@@ -1,28 +1,129 @@
-use crate::db::traits::DatabaseConnection;
+use crate::db::traits::{DatabaseConnection, SessionTracker};
+use crate::db::transaction::TransactionCommand;
 use crate::models::connection::ConnectionConfig;
+use crate::models::transaction::TransactionState;
+use futures::future::AbortHandle;
+use futures::FutureExt;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 
+/// Time a timed-out statement gets to return after being cancelled on the server,
+/// before the request is dropped
+const CANCEL_GRACE: Duration = Duration::from_secs(5);
+
+/// Control connection of a database, opened on the first server-side cancel and kept
+/// for later ones. Shared by the connection's driver and its tab sessions.
+type ControlConnection = Arc<Mutex<Option<Box<dyn DatabaseConnection>>>>;
+
 /// Wrapper for connection with metadata
 struct PooledConnection {
     connection: Arc<Mutex<Box<dyn DatabaseConnection>>>,
     last_used: Instant,
+    /// Config used to open a separate control connection for cancellation
+    config: ConnectionConfig,
+    /// Connection cancelling statements of this entry on the server
+    control: ControlConnection,
+    /// Server session running the current statement, published by the driver
+    session: SessionTracker,
+    /// Abort handle of the operation currently holding the connection
+    in_flight: Arc<std::sync::Mutex<Option<AbortHandle>>>,
+    /// Set when the current operation was dropped or its session ended by a cancel,
+    /// leaving the connection unusable
+    interrupted: Arc<AtomicBool>,
+    /// Whether the driver's server-side cancel ends the session (SQL Server `KILL`)
+    cancel_ends_session: bool,
+    /// Manual transaction open on the driver's pinned session, if any
+    transaction: TransactionState,
+}
+
+/// What an operation needs from its pool entry once the map lock is released
+struct PooledHandles {
+    connection: Arc<Mutex<Box<dyn DatabaseConnection>>>,
+    in_flight: Arc<std::sync::Mutex<Option<AbortHandle>>>,
+    interrupted: Arc<AtomicBool>,
+    cancel_ends_session: bool,
+    session: SessionTracker,
+    config: ConnectionConfig,
+    control: ControlConnection,
+}
+
+impl PooledHandles {
+    fn of(pooled: &PooledConnection) -> Self {
+        Self {
+            connection: pooled.connection.clone(),
+            in_flight: pooled.in_flight.clone(),
+            interrupted: pooled.interrupted.clone(),
+            cancel_ends_session: pooled.cancel_ends_session,
+            session: pooled.session.clone(),
+            config: pooled.config.clone(),
+            control: pooled.control.clone(),
+        }
+    }
+}
+
+/// Pool entry key: the shared driver of a connection, or the dedicated one of an editor tab
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct PoolKey {
+    connection_id: String,
+    tab_id: Option<String>,
+}
+
+impl PoolKey {
+    fn new(connection_id: &str, tab_id: Option<&str>) -> Self {
+        Self {
+            connection_id: connection_id.to_string(),
+            tab_id: tab_id.map(str::to_string),
+        }
+    }
+
+    fn shared(connection_id: &str) -> Self {
+        Self::new(connection_id, None)
+    }
 }
 
-/// Connection pool manager
+/// Connection pool manager.
+///
+/// Each connection has a shared driver used for grid browsing and metadata. Editor tabs
+/// can additionally own a driver holding a single physical connection, so `SET`, `USE`
+/// and temporary tables persist between their executions.
 pub struct ConnectionPool {
-    connections: Mutex<HashMap<String, PooledConnection>>,
+    connections: Mutex<HashMap<PoolKey, PooledConnection>>,
+    /// Query timeout of the application settings, read on first use
+    settings_timeout_secs: std::sync::Mutex<Option<u64>>,
 }
 
 impl ConnectionPool {
     pub fn new() -> Self {
         Self {
             connections: Mutex::new(HashMap::new()),
+            settings_timeout_secs: std::sync::Mutex::new(None),
         }
     }
 
+    /// Forget the cached application settings, so the next query reads them again
+    pub fn invalidate_settings(&self) {
+        *self.settings_timeout_secs.lock().unwrap() = None;
+    }
+
+    /// Query timeout of a connection: its own, else the application setting
+    fn query_timeout(&self, config: &ConnectionConfig) -> Option<Duration> {
+        let secs = config.query_timeout_secs.unwrap_or_else(|| {
+            *self
+                .settings_timeout_secs
+                .lock()
+                .unwrap()
+                .get_or_insert_with(|| {
+                    crate::utils::settings::load_settings()
+                        .unwrap_or_default()
+                        .query_timeout_secs
+                })
+        });
+        (secs > 0).then(|| Duration::from_secs(secs))
+    }
+
     /// Connect to database and add to pool
     pub async fn connect(&self, config: ConnectionConfig) -> Result<(), String> {
         let connection_id = config.id.clone();
@@ -34,11 +135,39 @@ impl ConnectionPool {
             connection_id
         );
 
+        let pooled = Self::open(config, None, ControlConnection::default()).await?;
+
+        let mut connections = self.connections.lock().await;
+        connections.insert(PoolKey::shared(&connection_id), pooled);
+
+        tracing::info!(
+            "✅ [CONNECTION POOL] Successfully connected to '{}'. Total connections: {}",
+            connection_name,
+            connections.len()
+        );
+
+        Ok(())
+    }
+
+    /// Open a driver for a pool entry. Tab entries get a single dedicated session.
+    async fn open(
+        config: ConnectionConfig,
+        tab_id: Option<&str>,
+        control: ControlConnection,
+    ) -> Result<PooledConnection, String> {
+        let connection_name = config.name.clone();
+
         // Create new connection
         let mut conn = crate::db::traits::create_connection(&config.db_type);
+        let session = SessionTracker::default();
+        conn.set_session_tracker(session.clone());
 
         // Connect to database
-        conn.connect(&config).await.map_err(|e| {
+        let connected = match tab_id {
+            Some(_) => conn.connect_session(&config).await,
+            None => conn.connect(&config).await,
+        };
+        connected.map_err(|e| {
             tracing::error!(
                 "❌ [CONNECTION POOL] Failed to connect to '{}': {}",
                 connection_name,
@@ -57,24 +186,25 @@ impl ConnectionPool {
             format!("Connection test failed: {}", e)
         })?;
 
-        // Add to pool
-        let pooled = PooledConnection {
+        let cancel_ends_session = conn.cancel_ends_session();
+        Ok(PooledConnection {
             connection: Arc::new(Mutex::new(conn)),
             last_used: Instant::now(),
-        };
-
-        let mut connections = self.connections.lock().await;
-        connections.insert(connection_id.clone(), pooled);
-
-        tracing::info!(
-            "✅ [CONNECTION POOL] Successfully connected to '{}'. Total connections: {}",
-            connection_name,
-            connections.len()
-        );
-
-        Ok(())
+            transaction: TransactionState {
+                connection_id: config.id.clone(),
+                tab_id: tab_id.map(str::to_string),
+                ..Default::default()
+            },
+            config,
+            control,
+            session,
+            in_flight: Arc::new(std::sync::Mutex::new(None)),
+            interrupted: Arc::new(AtomicBool::new(false)),
+            cancel_ends_session,
+        })
     }
-    /// Disconnect and remove from pool
+
+    /// Disconnect and remove from pool, closing the tab sessions of the connection too
     pub async fn disconnect(&self, connection_id: &str) -> Result<(), String> {
         tracing::info!(
             "🔌 [CONNECTION POOL] Disconnecting from '{}' ...",
@@ -82,20 +212,31 @@ impl ConnectionPool {
         );
 
         let mut connections = self.connections.lock().await;
+        let keys: Vec<PoolKey> = connections
+            .keys()
+            .filter(|k| k.connection_id == connection_id)
+            .cloned()
+            .collect();
+        let removed: Vec<PooledConnection> =
+            keys.iter().filter_map(|k| connections.remove(k)).collect();
+        let remaining = connections.len();
+        drop(connections); // Release lock before async operation
 
-        if let Some(pooled) = connections.remove(connection_id) {
-            let remaining = connections.len();
-            drop(connections); // Release lock before async operation
-
-            let mut conn = pooled.connection.lock().await;
-            conn.disconnect().await.map_err(|e| {
-                tracing::error!(
-                    "❌ [CONNECTION POOL] Failed to disconnect from '{}': {}",
-                    connection_id,
-                    e
-                );
-                format!("Failed to disconnect: {}", e)
-            })?;
+        if !removed.is_empty() {
+            if let Some(mut control) = removed[0].control.lock().await.take() {
+                let _ = control.disconnect().await;
+            }
+            for pooled in removed {
+                let mut conn = pooled.connection.lock().await;
+                conn.disconnect().await.map_err(|e| {
+                    tracing::error!(
+                        "❌ [CONNECTION POOL] Failed to disconnect from '{}': {}",
+                        connection_id,
+                        e
+                    );
+                    format!("Failed to disconnect: {}", e)
+                })?;
+            }
 
             tracing::info!("✅ [CONNECTION POOL] Successfully disconnected from '{}'. Remaining connections: {}", connection_id, remaining);
         } else {
@@ -107,16 +248,56 @@ impl ConnectionPool {
 
         Ok(())
     }
+
+    /// Close the dedicated sessions an editor tab owns. An open transaction in them is
+    /// rolled back by the server when the connection closes.
+    pub async fn close_tab_sessions(&self, tab_id: &str) -> Result<(), String> {
+        let removed: Vec<(PoolKey, PooledConnection)> = {
+            let mut connections = self.connections.lock().await;
+            let keys: Vec<PoolKey> = connections
+                .keys()
+                .filter(|k| k.tab_id.as_deref() == Some(tab_id))
+                .cloned()
+                .collect();
+            keys.into_iter()
+                .filter_map(|k| connections.remove(&k).map(|p| (k, p)))
+                .collect()
+        };
+
+        for (key, pooled) in removed {
+            let mut conn = pooled.connection.lock().await;
+            if let Err(e) = conn.disconnect().await {
+                tracing::warn!(
+                    "⚠️  [CONNECTION POOL] Failed to close session of tab '{}' on '{}': {}",
+                    tab_id,
+                    key.connection_id,
+                    e
+                );
+            }
+            tracing::info!(
+                "✅ [CONNECTION POOL] Closed session of tab '{}' on '{}'",
+                tab_id,
+                key.connection_id
+            );
+        }
+
+        Ok(())
+    }
+
     /// Check if connection exists and is alive
     pub async fn is_connected(&self, connection_id: &str) -> bool {
         let connections = self.connections.lock().await;
-        connections.contains_key(connection_id)
+        connections.contains_key(&PoolKey::shared(connection_id))
     }
 
     /// Get all connected connection IDs
     pub async fn get_connected_ids(&self) -> Vec<String> {
         let connections = self.connections.lock().await;
-        connections.keys().cloned().collect()
+        connections
+            .keys()
+            .filter(|k| k.tab_id.is_none())
+            .map(|k| k.connection_id.clone())
+            .collect()
     }
 
     /// Execute operation with connection from pool
@@ -125,6 +306,54 @@ impl ConnectionPool {
         connection_id: &str,
         operation: F,
     ) -> Result<T, String>
+    where
+        F: FnOnce(
+            &mut Box<dyn DatabaseConnection>,
+        ) -> futures::future::BoxFuture<'_, Result<T, anyhow::Error>>,
+    {
+        self.with_session(connection_id, None, operation).await
+    }
+
+    /// Execute operation on the dedicated session of an editor tab, opening it on first
+    /// use. Without a tab the shared connection is used.
+    pub async fn with_session<F, T>(
+        &self,
+        connection_id: &str,
+        tab_id: Option<&str>,
+        operation: F,
+    ) -> Result<T, String>
+    where
+        F: FnOnce(
+            &mut Box<dyn DatabaseConnection>,
+        ) -> futures::future::BoxFuture<'_, Result<T, anyhow::Error>>,
+    {
+        self.run(connection_id, tab_id, false, operation).await
+    }
+
+    /// Execute a user's query like `with_session`, cancelling it once it runs past the
+    /// query timeout in effect when it starts
+    pub async fn with_query_timeout<F, T>(
+        &self,
+        connection_id: &str,
+        tab_id: Option<&str>,
+        operation: F,
+    ) -> Result<T, String>
+    where
+        F: FnOnce(
+            &mut Box<dyn DatabaseConnection>,
+        ) -> futures::future::BoxFuture<'_, Result<T, anyhow::Error>>,
+    {
+        self.run(connection_id, tab_id, true, operation).await
+    }
+
+    /// Run an operation on a pool entry, with the query timeout when `timed`
+    async fn run<F, T>(
+        &self,
+        connection_id: &str,
+        tab_id: Option<&str>,
+        timed: bool,
+        operation: F,
+    ) -> Result<T, String>
     where
         F: FnOnce(
             &mut Box<dyn DatabaseConnection>,
@@ -135,31 +364,96 @@ impl ConnectionPool {
             connection_id
         );
 
-        // Get connection Arc from pool (without removing it)
-        let connection_arc = {
-            let connections = self.connections.lock().await;
-            connections
-                .get(connection_id)
-                .map(|p| p.connection.clone())
-                .ok_or_else(|| {
-                    tracing::error!(
-                        "❌ [CONNECTION POOL] Connection '{}' not found in pool",
-                        connection_id
-                    );
-                    format!("Connection '{}' not found", connection_id)
-                })?
+        let key = PoolKey::new(connection_id, tab_id);
+        let handles = self.entry(&key).await?;
+        let limit = if timed {
+            self.query_timeout(&handles.config)
+        } else {
+            None
         };
 
         // Lock the connection for this operation
-        let mut conn = connection_arc.lock().await;
+        let mut conn = handles.connection.lock().await;
+        conn.set_statement_timeout(limit);
+
+        // Execute operation, keeping an abort handle so cancel_query can drop it
+        handles.interrupted.store(false, Ordering::SeqCst);
+        let (operation, abort_handle) = futures::future::abortable(operation(&mut conn));
+        *handles.in_flight.lock().unwrap() = Some(abort_handle);
+        let cancelled = |_| {
+            handles.interrupted.store(true, Ordering::SeqCst);
+            Err(anyhow::anyhow!("Query was cancelled"))
+        };
+        let result = match limit {
+            None => operation.await.unwrap_or_else(cancelled),
+            Some(limit) => {
+                futures::pin_mut!(operation);
+                match tokio::time::timeout(limit, &mut operation).await {
+                    Ok(result) => result.unwrap_or_else(cancelled),
+                    Err(_) => {
+                        tracing::warn!(
+                            "⏱️ [CONNECTION POOL] Query on '{}' exceeded its {}s timeout",
+                            connection_id,
+                            limit.as_secs()
+                        );
+                        // Stop the statement on the server, then let it return so the
+                        // connection is left in a clean state. Otherwise it is reopened.
+                        let stopped = match handles.session.current() {
+                            Some(session_id) => match Self::cancel_on_server(
+                                &handles.control,
+                                &handles.config,
+                                session_id,
+                            )
+                            .await
+                            {
+                                Ok(()) => {
+                                    tokio::time::timeout(CANCEL_GRACE, &mut operation)
+                                        .await
+                                        .is_ok()
+                                        && !handles.cancel_ends_session
+                                }
+                                Err(e) => {
+                                    tracing::warn!(
+                                        "⚠️  [CONNECTION POOL] Server-side cancel failed for '{}': {}",
+                                        connection_id,
+                                        e
+                                    );
+                                    false
+                                }
+                            },
+                            None => false,
+                        };
+                        if !stopped {
+                            handles.interrupted.store(true, Ordering::SeqCst);
+                        }
+                        Err(anyhow::anyhow!(
+                            "Query timed out after {} seconds",
+                            limit.as_secs()
+                        ))
+                    }
+                }
+            }
+        };
+        *handles.in_flight.lock().unwrap() = None;
+        conn.set_statement_timeout(None);
+        drop(conn);
 
-        // Execute operation
-        let result = operation(&mut conn).await;
+        let result = if handles.interrupted.swap(false, Ordering::SeqCst) {
+            self.reopen(&key, &handles.connection).await;
+            result.map_err(|e| {
+                anyhow::anyhow!(
+                    "{}. The session was closed and reopened, rolling back any open transaction",
+                    e
+                )
+            })
+        } else {
+            result
+        };
 
         // Update last used timestamp
         {
             let mut connections = self.connections.lock().await;
-            if let Some(pooled) = connections.get_mut(connection_id) {
+            if let Some(pooled) = connections.get_mut(&key) {
                 pooled.last_used = Instant::now();
             }
         }
@@ -178,6 +472,308 @@ impl ConnectionPool {
 
         result.map_err(|e| e.to_string())
     }
+
+    /// Replace a pool entry whose session a cancel left mid-statement or ended with a fresh
+    /// one. If reconnecting fails the entry stays removed and is reopened on next use.
+    async fn reopen(&self, key: &PoolKey, connection: &Arc<Mutex<Box<dyn DatabaseConnection>>>) {
+        let stale = {
+            let mut connections = self.connections.lock().await;
+            match connections.get(key) {
+                Some(pooled) if Arc::ptr_eq(&pooled.connection, connection) => {
+                    connections.remove(key)
+                }
+                _ => None,
+            }
+        };
+        let Some(stale) = stale else {
+            return;
+        };
+        let _ = stale.connection.lock().await.disconnect().await;
+
+        tracing::info!(
+            "🔌 [CONNECTION POOL] Reopening interrupted session of '{}'",
+            key.connection_id
+        );
+        match Self::open(stale.config, key.tab_id.as_deref(), stale.control).await {
+            Ok(pooled) => {
+                self.connections
+                    .lock()
+                    .await
+                    .entry(key.clone())
+                    .or_insert(pooled);
+            }
+            Err(e) => tracing::warn!(
+                "⚠️  [CONNECTION POOL] Failed to reopen session of '{}': {}",
+                key.connection_id,
+                e
+            ),
+        }
+    }
+
+    /// Handles of a pool entry, opening a tab session on first use
+    async fn entry(&self, key: &PoolKey) -> Result<PooledHandles, String> {
+        let (config, control) = {
+            let connections = self.connections.lock().await;
+            if let Some(pooled) = connections.get(key) {
+                return Ok(PooledHandles::of(pooled));
+            }
+            // Tab sessions reuse the config and control connection of the connection
+            // they belong to
+            connections
+                .get(&PoolKey::shared(&key.connection_id))
+                .filter(|_| key.tab_id.is_some())
+                .map(|p| (p.config.clone(), p.control.clone()))
+                .ok_or_else(|| {
+                    tracing::error!(
+                        "❌ [CONNECTION POOL] Connection '{}' not found in pool",
+                        key.connection_id
+                    );
+                    format!("Connection '{}' not found", key.connection_id)
+                })?
+        };
+
+        tracing::info!(
+            "🔌 [CONNECTION POOL] Opening session for tab '{}' on '{}'",
+            key.tab_id.as_deref().unwrap_or_default(),
+            key.connection_id
+        );
+        let pooled = Self::open(config, key.tab_id.as_deref(), control).await?;
+
+        // Another request for the same tab may have opened a session meanwhile
+        let mut connections = self.connections.lock().await;
+        let pooled = connections.entry(key.clone()).or_insert(pooled);
+        Ok(PooledHandles::of(pooled))
+    }
+
+    /// Open the dedicated session of a tab on another database of the connection, for
+    /// servers whose sessions cannot switch databases (PostgreSQL). A session the tab
+    /// already had is closed.
+    pub async fn open_session_on_database(
+        &self,
+        connection_id: &str,
+        tab_id: &str,
+        database: &str,
+    ) -> Result<(), String> {
+        let (mut config, control) = {
+            let connections = self.connections.lock().await;
+            connections
+                .get(&PoolKey::shared(connection_id))
+                .map(|p| (p.config.clone(), p.control.clone()))
+                .ok_or_else(|| format!("Connection '{}' not found", connection_id))?
+        };
+        config.database = Some(database.to_string());
+
+        tracing::info!(
+            "🔌 [CONNECTION POOL] Opening session for tab '{}' on database '{}' of '{}'",
+            tab_id,
+            database,
+            connection_id
+        );
+        let pooled = Self::open(config, Some(tab_id), control).await?;
+        let previous = self
+            .connections
+            .lock()
+            .await
+            .insert(PoolKey::new(connection_id, Some(tab_id)), pooled);
+        if let Some(previous) = previous {
+            let _ = previous.connection.lock().await.disconnect().await;
+        }
+        Ok(())
+    }
+
+    /// Run a transaction control command on the connection's pinned session and return
+    /// the updated transaction state
+    pub async fn transaction_control(
+        &self,
+        connection_id: &str,
+        tab_id: Option<&str>,
+        command: TransactionCommand,
+    ) -> Result<TransactionState, String> {
+        let operation = command.clone();
+        let result = self
+            .with_session(connection_id, tab_id, move |conn| {
+                async move {
+                    let result = conn.transaction_control(&operation).await;
+                    Ok((result, conn.in_transaction()))
+                }
+                .boxed()
+            })
+            .await?;
+        let (result, in_transaction) = result;
+
+        let mut connections = self.connections.lock().await;
+        let pooled = connections
+            .get_mut(&PoolKey::new(connection_id, tab_id))
+            .ok_or_else(|| format!("Connection '{}' not found", connection_id))?;
+        let state = &mut pooled.transaction;
+
+        if result.is_ok() {
+            match &command {
+                TransactionCommand::Begin(name) => {
+                    state.started_at = Some(chrono::Utc::now().timestamp_millis());
+                    state.name = name.clone();
+                }
+                TransactionCommand::Savepoint(name) => state.savepoints.push(name.clone()),
+                // Rolling back to a savepoint keeps it; later ones are gone
+                TransactionCommand::RollbackToSavepoint(name) => {
+                    if let Some(pos) = state.savepoints.iter().rposition(|s| s == name) {
+                        state.savepoints.truncate(pos + 1);
+                    }
+                }
+                TransactionCommand::ReleaseSavepoint(name) => {
+                    if let Some(pos) = state.savepoints.iter().rposition(|s| s == name) {
+                        state.savepoints.truncate(pos);
+                    }
+                }
+                TransactionCommand::Commit | TransactionCommand::Rollback => {}
+            }
+        }
+
+        // The driver knows whether its session is still pinned, even after a failure
+        state.active = in_transaction;
+        if !in_transaction {
+            state.savepoints.clear();
+            state.started_at = None;
+            state.name = None;
+        }
+        let state = state.clone();
+        drop(connections);
+
+        result.map_err(|e| {
+            tracing::error!(
+                "❌ [CONNECTION POOL] {:?} failed on '{}': {}",
+                command,
+                connection_id,
+                e
+            );
+            e.to_string()
+        })?;
+
+        tracing::info!(
+            "✅ [CONNECTION POOL] {:?} on '{}'. Transaction active: {}",
+            command,
+            connection_id,
+            state.active
+        );
+
+        Ok(state)
+    }
+
+    /// Current manual transaction state of a connection or one of its tab sessions. A
+    /// tab that has not opened its session yet has no transaction.
+    pub async fn transaction_state(
+        &self,
+        connection_id: &str,
+        tab_id: Option<&str>,
+    ) -> Result<TransactionState, String> {
+        let connections = self.connections.lock().await;
+        if let Some(pooled) = connections.get(&PoolKey::new(connection_id, tab_id)) {
+            return Ok(pooled.transaction.clone());
+        }
+        if tab_id.is_some() && connections.contains_key(&PoolKey::shared(connection_id)) {
+            return Ok(TransactionState {
+                connection_id: connection_id.to_string(),
+                tab_id: tab_id.map(str::to_string),
+                ..Default::default()
+            });
+        }
+        Err(format!("Connection '{}' not found", connection_id))
+    }
+
+    /// Transactions still open on a connection, across the shared driver and tab sessions
+    pub async fn open_transactions(&self, connection_id: &str) -> Vec<TransactionState> {
+        let connections = self.connections.lock().await;
+        connections
+            .iter()
+            .filter(|(k, p)| k.connection_id == connection_id && p.transaction.active)
+            .map(|(_, p)| p.transaction.clone())
+            .collect()
+    }
+
+    /// Cancel the operation currently running on a connection.
+    ///
+    /// When the driver has published a server session id, the statement is cancelled
+    /// server-side from the control connection. Otherwise (or if that fails) the
+    /// in-flight request is dropped. Either way a connection left unusable is reopened
+    /// once the operation returns.
+    pub async fn cancel(&self, connection_id: &str, tab_id: Option<&str>) -> Result<(), String> {
+        let (config, control, session, in_flight, interrupted, cancel_ends_session) = {
+            let connections = self.connections.lock().await;
+            let pooled = connections
+                .get(&PoolKey::new(connection_id, tab_id))
+                .ok_or_else(|| format!("Connection '{}' not found", connection_id))?;
+            (
+                pooled.config.clone(),
+                pooled.control.clone(),
+                pooled.session.clone(),
+                pooled.in_flight.clone(),
+                pooled.interrupted.clone(),
+                pooled.cancel_ends_session,
+            )
+        };
+
+        let abort_handle = in_flight
+            .lock()
+            .unwrap()
+            .clone()
+            .ok_or_else(|| "No running query to cancel".to_string())?;
+
+        if let Some(session_id) = session.current() {
+            match Self::cancel_on_server(&control, &config, session_id).await {
+                Ok(()) => {
+                    if cancel_ends_session {
+                        interrupted.store(true, Ordering::SeqCst);
+                    }
+                    tracing::info!(
+                        "🛑 [CONNECTION POOL] Cancelled session {} on '{}'",
+                        session_id,
+                        connection_id
+                    );
+                    return Ok(());
+                }
+                Err(e) => tracing::warn!(
+                    "⚠️  [CONNECTION POOL] Server-side cancel failed for '{}': {}. Dropping request instead",
+                    connection_id,
+                    e
+                ),
+            }
+        }
+
+        interrupted.store(true, Ordering::SeqCst);
+        abort_handle.abort();
+        tracing::info!(
+            "🛑 [CONNECTION POOL] Dropped in-flight request on '{}'",
+            connection_id
+        );
+
+        Ok(())
+    }
+
+    /// Cancel the statement of the given server session from the control connection,
+    /// opening it on first use. A connection whose cancel failed is not kept.
+    async fn cancel_on_server(
+        control: &ControlConnection,
+        config: &ConnectionConfig,
+        session_id: i64,
+    ) -> anyhow::Result<()> {
+        let mut slot = control.lock().await;
+        let mut conn = match slot.take() {
+            Some(conn) => conn,
+            None => {
+                let mut conn = crate::db::traits::create_connection(&config.db_type);
+                conn.connect(config).await?;
+                conn
+            }
+        };
+        let result = conn.cancel(session_id).await;
+        match &result {
+            Ok(()) => *slot = Some(conn),
+            Err(_) => {
+                let _ = conn.disconnect().await;
+            }
+        }
+        result
+    }
 }
 
 impl Default for ConnectionPool {
@@ -0,0 +1,140 @@
+use crate::models::edit_history::EditHistoryEntry;
+use crate::utils::{encryption, storage};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+const HISTORY_FILE: &str = "edit_history.json";
+/// The journal holds row values, so it is encrypted like stored passwords
+const ENCRYPTION_SALT: &str = "rustdbgrid_history_v1";
+/// Oldest entries are dropped beyond this many
+const MAX_ENTRIES: usize = 200;
+
+/// Serializes read-modify-write cycles of the journal file
+static JOURNAL_LOCK: Mutex<()> = Mutex::new(());
+
+/// Get the edit history file path
+pub fn get_history_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let app_data = dirs::config_dir().ok_or("Could not find config directory")?;
+    let app_dir = app_data.join("rustdbgrid");
+
+    // Create directory if it doesn't exist
+    if !app_dir.exists() {
+        fs::create_dir_all(&app_dir)?;
+    }
+
+    Ok(app_dir.join(HISTORY_FILE))
+}
+
+fn read_entries() -> Result<Vec<EditHistoryEntry>, Box<dyn std::error::Error>> {
+    let path = get_history_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(&path)?;
+    let content = content.trim();
+    // Journals written before encryption are plain JSON; encrypt them in place so their
+    // rows no longer sit readable on disk
+    if content.starts_with('[') {
+        if let Ok(entries) = serde_json::from_str::<Vec<EditHistoryEntry>>(content) {
+            write_entries(&entries)?;
+            tracing::info!("📜 [HISTORY] Encrypted {} journal entries", entries.len());
+            return Ok(entries);
+        }
+    }
+
+    let decrypted = encryption::decrypt(
+        content,
+        &storage::get_encryption_password(),
+        ENCRYPTION_SALT,
+    )
+    .and_then(|json| Ok(serde_json::from_str(&json)?));
+    match decrypted {
+        Ok(entries) => Ok(entries),
+        Err(e) => {
+            // Set an unreadable journal aside, so later edits can still be recorded
+            let bad = path.with_extension("json.bad");
+            fs::rename(&path, &bad)?;
+            tracing::warn!(
+                "⚠️ [HISTORY] Unreadable edit journal moved to {}: {}",
+                bad.display(),
+                e
+            );
+            Ok(Vec::new())
+        }
+    }
+}
+
+fn write_entries(entries: &[EditHistoryEntry]) -> Result<(), Box<dyn std::error::Error>> {
+    let path = get_history_path()?;
+    let encrypted = encryption::encrypt(
+        &serde_json::to_string(entries)?,
+        &storage::get_encryption_password(),
+        ENCRYPTION_SALT,
+    )?;
+    fs::write(&path, encrypted)?;
+    Ok(())
+}
+
+/// Load the journal, oldest entry first
+pub fn load_history() -> Result<Vec<EditHistoryEntry>, Box<dyn std::error::Error>> {
+    let _guard = JOURNAL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    read_entries()
+}
+
+/// Append an entry, dropping the oldest ones past the journal limit
+pub fn record(entry: EditHistoryEntry) -> Result<(), Box<dyn std::error::Error>> {
+    let _guard = JOURNAL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut entries = read_entries()?;
+    entries.push(entry);
+    if entries.len() > MAX_ENTRIES {
+        entries.drain(..entries.len() - MAX_ENTRIES);
+    }
+    write_entries(&entries)?;
+
+    tracing::debug!("📜 [HISTORY] Recorded edit, {} in journal", entries.len());
+    Ok(())
+}
+
+/// Mark an entry as reverted before its inverse is applied, so only one revert of it
+/// runs. Returns the entry; `cancel_revert` clears the mark if the revert fails.
+pub fn begin_revert(id: &str) -> Result<EditHistoryEntry, Box<dyn std::error::Error>> {
+    let _guard = JOURNAL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut entries = read_entries()?;
+    let entry = entries
+        .iter_mut()
+        .find(|e| e.id == id)
+        .ok_or_else(|| format!("Edit '{}' not found in history", id))?;
+    if entry.reverted_at.is_some() {
+        return Err("This edit has already been reverted".into());
+    }
+    if let Some(reason) = &entry.not_revertible {
+        return Err(format!("This edit cannot be reverted: {}", reason).into());
+    }
+    entry.mark_reverted();
+    let entry = entry.clone();
+    write_entries(&entries)?;
+    Ok(entry)
+}
+
+/// Keep the revert mark of an edit whose inverse was only partly applied, and refuse
+/// further reverts of it with `reason`
+pub fn finish_partial_revert(id: &str, reason: String) -> Result<(), Box<dyn std::error::Error>> {
+    let _guard = JOURNAL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut entries = read_entries()?;
+    if let Some(entry) = entries.iter_mut().find(|e| e.id == id) {
+        entry.not_revertible = Some(reason);
+    }
+    write_entries(&entries)
+}
+
+/// Clear the revert mark `begin_revert` set
+pub fn cancel_revert(id: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let _guard = JOURNAL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut entries = read_entries()?;
+    if let Some(entry) = entries.iter_mut().find(|e| e.id == id) {
+        entry.reverted_at = None;
+    }
+    write_entries(&entries)
+}
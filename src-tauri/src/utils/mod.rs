@@ -1,6 +1,9 @@
 pub mod clipboard;
 pub mod connection_pool;
+pub mod edit_history;
 pub mod encryption;
+pub mod server_messages;
 pub mod settings;
 pub mod storage;
+pub mod table_reader;
 pub mod tracing_logger;
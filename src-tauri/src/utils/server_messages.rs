@@ -0,0 +1,187 @@
+use crate::models::query_result::ServerMessage;
+use std::future::Future;
+use std::sync::Mutex;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::layer::{Context, Layer};
+
+/// Target sqlx logs PostgreSQL NOTICE / RAISE messages under
+const PG_NOTICE_TARGET: &str = "sqlx::postgres::notice";
+
+/// Target tiberius logs INFO tokens (PRINT, RAISERROR with severity <= 10) under
+const TIBERIUS_TOKEN_TARGET: &str = "tiberius::tds::stream::token";
+
+/// tiberius logs environment changes and login acknowledgements under the same target
+/// and level as INFO tokens. tiberius 0.12 neither exports its token types nor yields INFO
+/// tokens from `QueryStream`, so these lines can only be told apart by their text.
+const TIBERIUS_STATUS_PREFIXES: &[&str] = &[
+    "Database change from",
+    "Packet size change",
+    "SQL collation change",
+    "Begin transaction",
+    "Commit transaction",
+    "Rollback transaction",
+    "Defect transaction",
+    "Server requested routing",
+    "Fallback mirror server",
+    "Ignored env change",
+    "FeatureExtAck with",
+];
+
+/// Whether a tiberius INFO line is a status line rather than a server message. The
+/// login acknowledgement is logged as `"{program name} version {number}"`.
+fn is_tiberius_status(message: &str) -> bool {
+    TIBERIUS_STATUS_PREFIXES
+        .iter()
+        .any(|prefix| message.starts_with(prefix))
+        || message
+            .rsplit_once(" version ")
+            .is_some_and(|(_, version)| {
+                !version.is_empty() && version.chars().all(|c| c.is_ascii_digit())
+            })
+}
+
+tokio::task_local! {
+    static MESSAGES: Mutex<Vec<ServerMessage>>;
+}
+
+/// Run a database operation and collect the informational messages the server sent
+/// while it ran. Drivers only log these, so they are picked up by `ServerMessageLayer`
+/// on the task that polls the operation.
+pub async fn capture_messages<F: Future>(operation: F) -> (F::Output, Vec<ServerMessage>) {
+    MESSAGES
+        .scope(Mutex::new(Vec::new()), async move {
+            let output = operation.await;
+            let messages = MESSAGES.with(|m| std::mem::take(&mut *m.lock().unwrap()));
+            (output, messages)
+        })
+        .await
+}
+
+/// Tracing layer that turns driver log events for server messages into `ServerMessage`s
+/// for the operation running under `capture_messages`
+pub struct ServerMessageLayer;
+
+impl ServerMessageLayer {
+    /// Per-layer filter letting driver messages through whatever level the logs are
+    /// filtered to
+    pub fn filter() -> Targets {
+        Targets::new()
+            .with_target(PG_NOTICE_TARGET, Level::TRACE)
+            .with_target(TIBERIUS_TOKEN_TARGET, Level::INFO)
+    }
+}
+
+impl<S: Subscriber> Layer<S> for ServerMessageLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let target = metadata.target();
+        let is_tiberius =
+            target == TIBERIUS_TOKEN_TARGET && *metadata.level() == tracing::Level::INFO;
+        if target != PG_NOTICE_TARGET && !is_tiberius {
+            return;
+        }
+
+        let mut visitor = MessageVisitor(None);
+        event.record(&mut visitor);
+        let Some(message) = visitor.0 else {
+            return;
+        };
+        if is_tiberius && is_tiberius_status(&message) {
+            return;
+        }
+
+        let level = metadata.level().as_str().to_lowercase();
+        let _ = MESSAGES.try_with(|m| {
+            m.lock().unwrap().push(ServerMessage { level, message });
+        });
+    }
+}
+
+struct MessageVisitor(Option<String>);
+
+impl Visit for MessageVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.0 = Some(value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.0 = Some(format!("{:?}", value));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn test_captures_driver_messages_for_the_running_operation() {
+        let subscriber = tracing_subscriber::registry().with(ServerMessageLayer);
+        let _guard = tracing::subscriber::set_default(subscriber);
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        let (_, messages) = runtime.block_on(capture_messages(async {
+            tracing::info!(target: "sqlx::postgres::notice", message = "table does not exist, skipping");
+            tracing::info!(target: "tiberius::tds::stream::token", "{}", "Database change from 'a' to 'b'");
+            tracing::info!(target: "tiberius::tds::stream::token", "{} version {}", "Microsoft SQL Server", 1946157056u32);
+            tracing::info!(target: "tiberius::tds::stream::token", "FeatureExtAck with {} features", 1);
+            tracing::info!(target: "tiberius::tds::stream::token", "{}", "Rows copied: 3");
+            tracing::info!("unrelated log line");
+        }));
+
+        let texts: Vec<&str> = messages.iter().map(|m| m.message.as_str()).collect();
+        assert_eq!(
+            texts,
+            vec!["table does not exist, skipping", "Rows copied: 3"]
+        );
+        assert_eq!(messages[0].level, "info");
+
+        // Outside a capture scope messages are only logged
+        runtime.block_on(async {
+            tracing::info!(target: "sqlx::postgres::notice", message = "ignored");
+        });
+    }
+
+    #[test]
+    fn test_tells_tiberius_status_lines_from_messages() {
+        assert!(is_tiberius_status(
+            "Microsoft SQL Server version 1946157056"
+        ));
+        assert!(is_tiberius_status("FeatureExtAck with 2 features"));
+        assert!(is_tiberius_status("Commit transaction"));
+        assert!(!is_tiberius_status("Upgrading to version 2.1"));
+        assert!(!is_tiberius_status("version 3 of the report is ready"));
+        assert!(!is_tiberius_status("Rows copied: 3"));
+    }
+
+    #[test]
+    fn test_captures_messages_below_the_log_level() {
+        let subscriber = tracing_subscriber::registry()
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .with_writer(std::io::sink)
+                    .with_filter(tracing_subscriber::EnvFilter::new("warn")),
+            )
+            .with(ServerMessageLayer.with_filter(ServerMessageLayer::filter()));
+        let _guard = tracing::subscriber::set_default(subscriber);
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        let (_, messages) = runtime.block_on(capture_messages(async {
+            tracing::info!(target: "sqlx::postgres::notice", message = "relation exists");
+            tracing::debug!(target: "sqlx::postgres::notice", message = "debug notice");
+        }));
+
+        let texts: Vec<&str> = messages.iter().map(|m| m.message.as_str()).collect();
+        assert_eq!(texts, vec!["relation exists", "debug notice"]);
+    }
+}
@@ -41,6 +41,10 @@ pub struct AppSettings {
     /// Last used connection ID
     #[serde(default)]
     pub last_connection_id: Option<String>,
+
+    /// Query timeout in seconds for connections without their own (0 = no timeout)
+    #[serde(default)]
+    pub query_timeout_secs: u64,
 }
 
 fn default_theme() -> String {
@@ -71,6 +75,7 @@ impl Default for AppSettings {
             confirm_delete: true,
             auto_connect: false,
             last_connection_id: None,
+            query_timeout_secs: 0,
         }
     }
 }
@@ -173,6 +178,11 @@ pub fn update_setting(
         "last_connection_id" => {
             settings.last_connection_id = value.as_str().map(|s| s.to_string());
         }
+        "query_timeout_secs" => {
+            if let Some(v) = value.as_u64() {
+                settings.query_timeout_secs = v;
+            }
+        }
         _ => {
             return Err(format!("Unknown setting key: {}", key).into());
         }
@@ -18,6 +18,8 @@ struct StoredConnection {
     pub password_encrypted: Option<String>, // Password yang sudah dienkripsi
     pub database: Option<String>,
     pub ssl: bool,
+    #[serde(default)]
+    pub query_timeout_secs: Option<u64>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -40,7 +42,7 @@ pub fn get_storage_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
 }
 
 /// Generate encryption password from machine-specific data
-fn get_encryption_password() -> String {
+pub(crate) fn get_encryption_password() -> String {
     // Use machine-specific data as password
     // In production, you might want to use more secure methods
     let hostname = hostname::get()
@@ -76,6 +78,7 @@ fn to_stored_connection(
         password_encrypted,
         database: config.database.clone(),
         ssl: config.ssl,
+        query_timeout_secs: config.query_timeout_secs,
     })
 }
 
@@ -116,6 +119,7 @@ fn from_stored_connection(
         password,
         database: stored.database.clone(),
         ssl: stored.ssl,
+        query_timeout_secs: stored.query_timeout_secs,
     })
 }
 
@@ -198,6 +202,7 @@ mod tests {
             password: Some("secret_password".to_string()),
             database: Some("testdb".to_string()),
             ssl: false,
+            query_timeout_secs: Some(30),
         };
 
         let stored = to_stored_connection(&config).unwrap();
@@ -206,5 +211,6 @@ mod tests {
         assert_eq!(config.id, recovered.id);
         assert_eq!(config.name, recovered.name);
         assert_eq!(config.password, recovered.password);
+        assert_eq!(config.query_timeout_secs, recovered.query_timeout_secs);
     }
 }
@@ -0,0 +1,228 @@
+use crate::commands::connection::ConnectionStore;
+use crate::db::bound_query;
+use crate::db::traits::get_query_builder;
+use crate::formats::ExportColumn;
+use crate::models::connection::DatabaseType;
+use crate::models::query_result::QueryStreamEvent;
+use crate::models::schema::TableSchema;
+use crate::models::table_request::QueryRequest;
+use futures::FutureExt;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Name drivers take for a table: schema-qualified, except on MySQL where the schema
+/// is the database
+pub(crate) fn driver_table_name(
+    db_type: &DatabaseType,
+    table: &str,
+    schema: Option<&str>,
+) -> String {
+    match schema {
+        Some(schema) if *db_type != DatabaseType::MySQL => format!("{}.{}", schema, table),
+        _ => table.to_string(),
+    }
+}
+
+/// Schema of a table, named as its driver expects. Read on `session` when given,
+/// otherwise on the shared connection.
+pub(crate) async fn fetch_table_schema(
+    state: &ConnectionStore,
+    connection_id: &str,
+    session: Option<&str>,
+    db_type: &DatabaseType,
+    database: &str,
+    table: &str,
+    schema: Option<&str>,
+) -> Result<TableSchema, String> {
+    let qualified = driver_table_name(db_type, table, schema);
+    let db = database.to_string();
+    state
+        .pool
+        .with_session(connection_id, session, |conn| {
+            async move { conn.get_table_schema(&db, &qualified).await }.boxed()
+        })
+        .await
+        .map_err(|e| format!("Failed to get table schema: {}", e))
+}
+
+/// Reads every row matching a request's filters and sort through the driver's cursor,
+/// in one query on an editor-style session so other work on the connection is not
+/// blocked. The request's limit and offset describe a grid page and are ignored.
+pub(crate) struct TableReader<'a> {
+    /// The streaming query, until it has sent its last batch
+    stream: Option<futures::future::BoxFuture<'a, Result<u64, String>>>,
+    events: tokio::sync::mpsc::Receiver<QueryStreamEvent>,
+    table_schema: TableSchema,
+    columns: Vec<ExportColumn>,
+    started: bool,
+}
+
+/// What a `TableReader` waited for
+enum ReaderStep {
+    Event(Option<QueryStreamEvent>),
+    Finished(Result<u64, String>),
+}
+
+impl<'a> TableReader<'a> {
+    pub(crate) async fn open(
+        state: &'a ConnectionStore,
+        connection_id: &'a str,
+        session: &'a str,
+        request: &QueryRequest,
+        batch_size: usize,
+    ) -> Result<Self, String> {
+        let table_schema = fetch_table_schema(
+            state,
+            connection_id,
+            Some(session),
+            &request.db_type,
+            request.database.as_deref().unwrap_or_default(),
+            &request.table,
+            request.schema.as_deref(),
+        )
+        .await?;
+
+        let mut query = request.clone();
+        query.limit = 0;
+        query.offset = 0;
+        let bound = get_query_builder(&query.db_type)
+            .build_select_query_bound(&query, Some(&table_schema))
+            .map_err(|e| format!("Failed to build query: {}", e))?;
+
+        // Bounded so a slow writer applies backpressure to the database cursor
+        let (tx, events) = tokio::sync::mpsc::channel::<QueryStreamEvent>(4);
+        let stream = state
+            .pool
+            .with_session(connection_id, Some(session), move |conn| {
+                async move {
+                    conn.execute_query_streaming_bound(&bound, batch_size.max(1), &tx)
+                        .await
+                }
+                .boxed()
+            })
+            .boxed();
+
+        Ok(Self {
+            stream: Some(stream),
+            events,
+            table_schema,
+            columns: Vec::new(),
+            started: false,
+        })
+    }
+
+    /// Next batch of rows with the result columns, `None` once every row was read. The
+    /// first batch is returned even when the table is empty.
+    pub(crate) async fn next_batch(
+        &mut self,
+    ) -> Result<Option<(&[ExportColumn], Vec<Vec<Value>>)>, String> {
+        loop {
+            // Poll the query alongside the channel, so it keeps producing while the
+            // caller waits here
+            let step = match self.stream.as_mut() {
+                Some(stream) => tokio::select! {
+                    event = self.events.recv() => ReaderStep::Event(event),
+                    result = stream => ReaderStep::Finished(result),
+                },
+                None => ReaderStep::Event(self.events.recv().await),
+            };
+
+            match step {
+                // Batches sent before the query finished are still in the channel
+                ReaderStep::Finished(result) => {
+                    self.stream = None;
+                    result?;
+                }
+                ReaderStep::Event(Some(QueryStreamEvent::Columns {
+                    columns,
+                    column_types,
+                    ..
+                })) => {
+                    self.columns =
+                        result_columns(&columns, column_types.as_ref(), Some(&self.table_schema));
+                }
+                ReaderStep::Event(Some(QueryStreamEvent::Rows { rows })) => {
+                    self.started = true;
+                    let rows = rows
+                        .iter()
+                        .map(|row| {
+                            self.columns
+                                .iter()
+                                .map(|col| row.get(&col.name).cloned().unwrap_or(Value::Null))
+                                .collect()
+                        })
+                        .collect();
+                    return Ok(Some((&self.columns, rows)));
+                }
+                ReaderStep::Event(Some(_)) => {}
+                ReaderStep::Event(None) => {
+                    if self.started {
+                        return Ok(None);
+                    }
+                    self.started = true;
+                    if self.columns.is_empty() {
+                        self.columns = schema_columns(&self.table_schema);
+                    }
+                    return Ok(Some((&self.columns, Vec::new())));
+                }
+            }
+        }
+    }
+}
+
+/// Columns of a table schema, in table order
+pub(crate) fn schema_columns(table_schema: &TableSchema) -> Vec<ExportColumn> {
+    table_schema
+        .columns
+        .iter()
+        .map(|col| ExportColumn {
+            name: col.name.clone(),
+            data_type: col.data_type.clone(),
+            nullable: col.nullable,
+        })
+        .collect()
+}
+
+/// Result columns with the type the driver reported, or the table column's type. The
+/// table's type wins when it names the same type, as it carries the precision, scale
+/// and signedness that reported type names lack.
+pub(crate) fn result_columns(
+    names: &[String],
+    column_types: Option<&HashMap<String, String>>,
+    table_schema: Option<&TableSchema>,
+) -> Vec<ExportColumn> {
+    names
+        .iter()
+        .map(|name| {
+            let declared = bound_query::column_type(table_schema, name);
+            let reported = column_types.and_then(|types| types.get(name));
+            let data_type = match (reported, declared) {
+                (Some(reported), Some(declared)) if same_base_type(reported, declared) => {
+                    declared.to_string()
+                }
+                (Some(reported), _) => reported.clone(),
+                (None, declared) => declared.unwrap_or_default().to_string(),
+            };
+            ExportColumn {
+                name: name.clone(),
+                data_type,
+                nullable: table_schema
+                    .and_then(|schema| schema.columns.iter().find(|col| col.name == *name))
+                    .is_none_or(|col| col.nullable),
+            }
+        })
+        .collect()
+}
+
+/// Whether a reported type name is a declared column type without its modifiers. SQL
+/// Server reports nullable variants such as `DECIMALN`.
+fn same_base_type(reported: &str, declared: &str) -> bool {
+    let declared = declared
+        .split('(')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase();
+    let reported = reported.to_lowercase();
+    reported == declared || reported.strip_suffix('n') == Some(declared.as_str())
+}
@@ -1,6 +1,7 @@
+use crate::utils::server_messages::ServerMessageLayer;
 use std::path::PathBuf;
 use tracing_appender::rolling::{RollingFileAppender, Rotation};
-use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer};
 
 /// Get log directory path (sama dengan folder settings/connections)
 pub fn get_log_directory() -> Result<PathBuf, Box<dyn std::error::Error>> {
@@ -76,18 +77,18 @@ pub fn init_tracing() -> Result<(), Box<dyn std::error::Error>> {
         .with_ansi(true)
         .event_format(format.clone());
 
-    // Initialize subscriber
+    // Initialize subscriber. The level only filters the logs; server messages must
+    // reach their layer at any level.
     #[cfg(debug_assertions)]
     tracing_subscriber::registry()
-        .with(filter)
-        .with(rust_file_layer)
-        .with(stdout_layer)
+        .with(rust_file_layer.and_then(stdout_layer).with_filter(filter))
+        .with(ServerMessageLayer.with_filter(ServerMessageLayer::filter()))
         .init();
 
     #[cfg(not(debug_assertions))]
     tracing_subscriber::registry()
-        .with(filter)
-        .with(rust_file_layer)
+        .with(rust_file_layer.with_filter(filter))
+        .with(ServerMessageLayer.with_filter(ServerMessageLayer::filter()))
         .init();
 
     tracing::info!("✅ [TRACING] Logging system initialized");
@@ -1,4 +1,4 @@
-import { invoke } from "@tauri-apps/api/core";
+import { Channel, invoke } from "@tauri-apps/api/core";
 
 export async function testConnection(config) {
   return await invoke("test_connection", { config });
@@ -40,8 +40,18 @@ export async function connectToDatabase(connectionId) {
   return await invoke("connect_to_database", { connectionId });
 }
 
-export async function disconnectFromDatabase(connectionId) {
-  return await invoke("disconnect_from_database", { connectionId });
+/**
+ * Disconnect from a database
+ * @param {string} connectionId - Connection ID
+ * @param {"commit"|"rollback"|null} transactionAction - What to do with an open manual
+ *   transaction. Disconnecting with an open transaction and no action fails.
+ * @returns {Promise<void>}
+ */
+export async function disconnectFromDatabase(connectionId, transactionAction = null) {
+  return await invoke("disconnect_from_database", {
+    connectionId,
+    transactionAction,
+  });
 }
 
 export async function isDatabaseConnected(connectionId) {
@@ -98,6 +108,387 @@ export async function loadTableDataRaw(
   return await invoke("load_table_data", { request });
 }
 
+/**
+ * Export every row matching a table query to a file, streamed from the server
+ * @param {Object} params
+ * @param {string} params.connectionId - Connection ID
+ * @param {Object} params.query - Query request (db_type, database, schema, table, filters, order_by);
+ *   its limit and offset are ignored
+ * @param {string} params.path - Destination file path
+ * @param {string} params.format - csv, tsv, json, ndjson, sql_insert, markdown, html,
+ *   xlsx, parquet or arrow
+ * @param {Object} [params.options] - delimiter, quote, quote_style, header, null_value,
+ *   date_format, encoding and row_group_size
+ * @returns {Promise<Object>} Summary with path, format, rows_written and execution_time
+ */
+export async function exportData({ connectionId, query, path, format, options }) {
+  try {
+    return await invoke("export_data", {
+      connectionId,
+      query,
+      path,
+      format,
+      options,
+    });
+  } catch (error) {
+    console.error("❌ Error exporting data:", error);
+    throw error;
+  }
+}
+
+/**
+ * Run a query and export its result set to a file as the rows stream from the server
+ * @param {Object} params
+ * @param {string} params.connectionId - Connection ID
+ * @param {string|null} [params.tabId] - Editor tab whose session runs the query
+ * @param {string} params.query - SQL to run
+ * @param {string} params.path - Destination file path
+ * @param {string} params.format - Export format, as for exportData
+ * @param {Object} [params.options] - Export options, as for exportData
+ * @returns {Promise<Object>} Summary with path, format, rows_written and execution_time
+ */
+export async function exportQuery({
+  connectionId,
+  tabId = null,
+  query,
+  path,
+  format,
+  options,
+}) {
+  try {
+    return await invoke("export_query", {
+      connectionId,
+      tabId,
+      query,
+      path,
+      format,
+      options,
+    });
+  } catch (error) {
+    console.error("❌ Error exporting query result:", error);
+    throw error;
+  }
+}
+
+/**
+ * Run a query and export every result set it produces to an Excel workbook,
+ * one sheet per result set
+ * @param {Object} params
+ * @param {string} params.connectionId - Connection ID
+ * @param {string|null} [params.tabId] - Editor tab whose session runs the query
+ * @param {string} params.query - SQL to run
+ * @param {string} params.path - Destination .xlsx path
+ * @param {Object} [params.options] - Export options such as null_value
+ * @returns {Promise<Object>} Export summary
+ */
+export async function exportQueryXlsx({
+  connectionId,
+  tabId = null,
+  query,
+  path,
+  options,
+}) {
+  try {
+    return await invoke("export_query_xlsx", {
+      connectionId,
+      tabId,
+      query,
+      path,
+      options,
+    });
+  } catch (error) {
+    console.error("❌ Error exporting query results:", error);
+    throw error;
+  }
+}
+
+/**
+ * Read the worksheets of a workbook and the first rows below a header row
+ * @param {Object} params
+ * @param {string} params.path - Workbook path (.xlsx, .xls, .ods)
+ * @param {string|null} [params.sheet] - Worksheet name; the first one when omitted
+ * @param {number} [params.headerRow] - Row holding the column names, from 1
+ * @param {number} [params.limit] - Rows to read
+ * @returns {Promise<Object>} { sheets, sheet, headers, rows }
+ */
+export async function previewXlsx({ path, sheet = null, headerRow = 1, limit }) {
+  try {
+    return await invoke("preview_xlsx", { path, sheet, headerRow, limit });
+  } catch (error) {
+    console.error("❌ Error reading workbook:", error);
+    throw error;
+  }
+}
+
+/**
+ * Insert the rows of a worksheet into a table in one transaction
+ * @param {Object} params
+ * @param {string} params.connectionId - Connection ID
+ * @param {string} params.database - Database name
+ * @param {string} params.table - Table name
+ * @param {string|null} [params.schema] - Schema name (PostgreSQL, MSSQL)
+ * @param {string} params.path - Workbook path
+ * @param {Object} [params.options] - { sheet, header_row, mapping } where mapping
+ *   maps source columns to table columns, "" leaving one out
+ * @returns {Promise<Object>} Import summary
+ */
+export async function importXlsx({
+  connectionId,
+  database,
+  table,
+  schema = null,
+  path,
+  options,
+}) {
+  try {
+    return await invoke("import_xlsx", {
+      connectionId,
+      database,
+      table,
+      schema,
+      path,
+      options,
+    });
+  } catch (error) {
+    console.error("❌ Error importing workbook:", error);
+    throw error;
+  }
+}
+
+/**
+ * Read the column names and first rows of a CSV or NDJSON file
+ * @param {Object} params
+ * @param {string} params.path - File path
+ * @param {Object} [params.options] - Import options; format, delimiter, quote and
+ *   header decide how the file is read
+ * @param {number} [params.limit] - Rows to read
+ * @returns {Promise<Object>} { headers, rows }
+ */
+export async function previewFile({ path, options, limit }) {
+  try {
+    return await invoke("preview_file", { path, options, limit });
+  } catch (error) {
+    console.error("❌ Error reading file:", error);
+    throw error;
+  }
+}
+
+/**
+ * Insert the rows of a CSV or NDJSON file into a table in batches
+ * @param {Object} params
+ * @param {string} params.connectionId - Connection ID
+ * @param {string} params.database - Database name
+ * @param {string} params.table - Table name
+ * @param {string|null} [params.schema] - Schema name (PostgreSQL, MSSQL)
+ * @param {string} params.path - File path
+ * @param {Object} [params.options] - { format, delimiter, quote, header, null_value,
+ *   empty_as_null, mapping, batch_size, truncate, upsert, error_log }; without an
+ *   error_log the import stops and is undone at the first failing row
+ * @returns {Promise<Object>} Import summary
+ */
+export async function importFile({
+  connectionId,
+  database,
+  table,
+  schema = null,
+  path,
+  options,
+}) {
+  try {
+    return await invoke("import_file", {
+      connectionId,
+      database,
+      table,
+      schema,
+      path,
+      options,
+    });
+  } catch (error) {
+    console.error("❌ Error importing file:", error);
+    throw error;
+  }
+}
+
+/**
+ * Get the CREATE TABLE DDL of a table, with its indexes and foreign keys
+ * @param {Object} params
+ * @param {string} params.connectionId - Connection ID
+ * @param {string} params.database - Database name
+ * @param {string} params.table - Table name
+ * @param {string|null} [params.schema] - Schema name (PostgreSQL, MSSQL)
+ * @returns {Promise<string>} DDL statements
+ */
+export async function exportSchema({ connectionId, database, table, schema = null }) {
+  try {
+    return await invoke("export_schema", { connectionId, database, table, schema });
+  } catch (error) {
+    console.error("❌ Error exporting schema:", error);
+    throw error;
+  }
+}
+
+/**
+ * Copy the CREATE TABLE DDL of a table to the clipboard
+ * @param {Object} params
+ * @param {string} params.connectionId - Connection ID
+ * @param {string} params.database - Database name
+ * @param {string} params.table - Table name
+ * @param {string|null} [params.schema] - Schema name (PostgreSQL, MSSQL)
+ * @returns {Promise<string>} The copied DDL
+ */
+export async function copySchema({ connectionId, database, table, schema = null }) {
+  try {
+    return await invoke("copy_schema", { connectionId, database, table, schema });
+  } catch (error) {
+    console.error("❌ Error copying schema:", error);
+    throw error;
+  }
+}
+
+/**
+ * Copy grid rows to the clipboard, serialized by the backend
+ * @param {Object} params
+ * @param {string} params.dbType - Database type, for SQL literals and quoting
+ * @param {string} params.table - Table name, for SQL INSERT statements
+ * @param {string|null} [params.schema] - Schema name
+ * @param {Object} params.selection - { columns, column_types, rows } with rows keyed by column
+ * @param {string} [params.format] - tsv, csv, json, sql_insert, markdown or where_in
+ * @param {Object} [params.options] - Export options such as header, null_value and date_format
+ * @returns {Promise<void>}
+ */
+export async function copyData({
+  dbType,
+  table,
+  schema = null,
+  selection,
+  format = "tsv",
+  options,
+}) {
+  try {
+    return await invoke("copy_data", {
+      dbType,
+      table,
+      schema,
+      selection,
+      format,
+      options,
+    });
+  } catch (error) {
+    console.error("❌ Error copying data:", error);
+    throw error;
+  }
+}
+
+/**
+ * Dump the schema and data of a database to a SQL file
+ * @param {Object} params
+ * @param {string} params.connectionId - Connection ID
+ * @param {string} params.database - Database name
+ * @param {string} params.path - Destination file path
+ * @param {Object} [params.options] - schema, include_data, include_views,
+ *   include_routines, include_triggers and drop_existing
+ * @param {Function} [params.onEvent] - Receives progress events: `object` with kind,
+ *   name, index and total, and `rows` with table and rows
+ * @returns {Promise<Object>} Summary with tables, rows, views, routines, triggers,
+ *   skipped and cyclic_tables
+ */
+export async function dumpDatabase({
+  connectionId,
+  database,
+  path,
+  options,
+  onEvent = () => {},
+}) {
+  const channel = new Channel();
+  channel.onmessage = onEvent;
+
+  try {
+    return await invoke("dump_database", {
+      connectionId,
+      database,
+      path,
+      options,
+      onEvent: channel,
+    });
+  } catch (error) {
+    console.error("❌ Error dumping database:", error);
+    throw error;
+  }
+}
+
+/**
+ * Run the statements of a SQL file, such as a database dump
+ * @param {Object} params
+ * @param {string} params.connectionId - Connection ID
+ * @param {string|null} [params.database] - Database to restore into (MySQL, MSSQL)
+ * @param {string} params.path - SQL file path
+ * @param {boolean} [params.continueOnError] - Skip failing statements instead of stopping
+ * @param {Function} [params.onEvent] - Receives `progress` events with executed, failed
+ *   and total, and `failed` events with index, statement and error
+ * @returns {Promise<Object>} Summary with total, executed, failed, errors and aborted
+ */
+export async function restoreSqlFile({
+  connectionId,
+  database = null,
+  path,
+  continueOnError = false,
+  onEvent = () => {},
+}) {
+  const channel = new Channel();
+  channel.onmessage = onEvent;
+
+  try {
+    return await invoke("restore_sql_file", {
+      connectionId,
+      database,
+      path,
+      continueOnError,
+      onEvent: channel,
+    });
+  } catch (error) {
+    console.error("❌ Error restoring SQL file:", error);
+    throw error;
+  }
+}
+
+/**
+ * Columns, mapped types and CREATE TABLE DDL a table transfer would use
+ * @param {Object} request - { source, target, options } where source and target are
+ *   { connection_id, database, schema, table }
+ * @returns {Promise<Object>} Plan with columns, skipped_columns and ddl
+ */
+export async function previewTransfer(request) {
+  try {
+    return await invoke("preview_transfer", { request });
+  } catch (error) {
+    console.error("❌ Error planning table transfer:", error);
+    throw error;
+  }
+}
+
+/**
+ * Copy a table from one connection to another, creating it with mapped types
+ * @param {Object} params
+ * @param {Object} params.request - { source, target, options } where source and
+ *   target are { connection_id, database, schema, table } and options holds
+ *   create_table, drop_existing, truncate and batch_size
+ * @param {Function} [params.onEvent] - Receives `progress` events with rows_read,
+ *   rows_written, rows_failed and total, and `failed` events with row, column and error
+ * @returns {Promise<Object>} Summary with plan, rows_read, rows_written, rows_failed,
+ *   failures and warnings
+ */
+export async function transferTable({ request, onEvent = () => {} }) {
+  const channel = new Channel();
+  channel.onmessage = onEvent;
+
+  try {
+    return await invoke("transfer_table", { request, onEvent: channel });
+  } catch (error) {
+    console.error("❌ Error transferring table:", error);
+    throw error;
+  }
+}
+
 export async function getFilterValues(
   connectionId,
   query,
@@ -154,6 +545,213 @@ export async function getDistinctValues(
   return await invoke("get_distinct_values", { request });
 }
 
+/**
+ * Execute a multi-statement SQL script, one statement at a time
+ * @param {string} connectionId - Connection ID
+ * @param {string} script - Raw script text from the editor
+ * @param {object} options - Optional parameters
+ * @param {boolean} options.stopOnError - Stop at the first failing statement (default true)
+ * @param {string|number} options.tabId - Editor tab; runs on the tab's dedicated session so SET/USE/temp tables persist
+ * @returns {Promise<object>} Response with per-statement results, executed_count, failed_count, skipped_count, execution_time, in_transaction
+ */
+export async function executeScript(
+  connectionId,
+  script,
+  { stopOnError = true, tabId = null } = {}
+) {
+  const request = {
+    connection_id: connectionId,
+    tab_id: tabId == null ? null : String(tabId),
+    script,
+    stop_on_error: stopOnError,
+  };
+
+  return await invoke("execute_script", { request });
+}
+
+/**
+ * Execute a query and receive its rows in batches as they are fetched
+ * @param {string} connectionId - Connection ID
+ * @param {string} query - SQL (or MongoDB JSON) query
+ * @param {function} onEvent - Called with {event: "columns" | "rows" | "finished", data}
+ * @param {object} options - Optional parameters
+ * @param {number} options.batchSize - Rows per batch (default 500)
+ * @param {string|number} options.tabId - Editor tab; runs on the tab's dedicated session
+ * @returns {Promise<number>} Total number of rows streamed
+ */
+export async function executeQueryStream(
+  connectionId,
+  query,
+  onEvent,
+  { batchSize = null, tabId = null } = {}
+) {
+  const channel = new Channel();
+  channel.onmessage = onEvent;
+
+  return await invoke("execute_query_stream", {
+    connectionId,
+    tabId: tabId == null ? null : String(tabId),
+    query,
+    batchSize,
+    onEvent: channel,
+  });
+}
+
+/**
+ * Execute a query and return every result set it produces plus informational
+ * server messages (SQL Server PRINT/RAISERROR, PostgreSQL NOTICE)
+ * @param {string} connectionId - Connection ID
+ * @param {string} query - SQL query or batch, e.g. EXEC / CALL
+ * @param {object} options - Optional parameters
+ * @param {string|number} options.tabId - Editor tab; runs on the tab's dedicated session
+ * @returns {Promise<{result_sets: Array<Object>, messages: Array<{level: string, message: string}>, execution_time: number}>}
+ */
+export async function executeQueryMulti(connectionId, query, { tabId = null } = {}) {
+  return await invoke("execute_query_multi", {
+    connectionId,
+    tabId: tabId == null ? null : String(tabId),
+    query,
+  });
+}
+
+/**
+ * Get the execution plan of a query as a normalized tree
+ * @param {string} connectionId - Connection ID
+ * @param {string} query - SQL query to explain
+ * @param {object} options - Optional parameters
+ * @param {boolean} options.analyze - Run the query to collect actual rows and timings; its changes are rolled back
+ * @param {string|number} options.tabId - Editor tab; runs on the tab's dedicated session
+ * @returns {Promise<{root: Object, analyzed: boolean, planning_time_ms?: number, execution_time_ms?: number, format: string, raw: string}>}
+ */
+export async function explainQuery(connectionId, query, { analyze = false, tabId = null } = {}) {
+  return await invoke("explain_query", {
+    connectionId,
+    tabId: tabId == null ? null : String(tabId),
+    query,
+    analyze,
+  });
+}
+
+/**
+ * Cancel the query currently running on a connection
+ * @param {string} connectionId - Connection ID
+ * @param {string|number|null} tabId - Editor tab whose session runs the query
+ * @returns {Promise<void>}
+ */
+export async function cancelQuery(connectionId, tabId = null) {
+  return await invoke("cancel_query", {
+    connectionId,
+    tabId: tabId == null ? null : String(tabId),
+  });
+}
+
+/**
+ * Close the dedicated database sessions of an editor tab
+ * @param {string|number} tabId - Editor tab ID
+ * @returns {Promise<void>}
+ */
+export async function closeTabSession(tabId) {
+  return await invoke("close_tab_session", { tabId: String(tabId) });
+}
+
+/**
+ * Open a manual transaction. Until commit or rollback, every statement on the
+ * connection runs on the same server session.
+ * @param {string} connectionId - Connection ID
+ * @param {string|number|null} tabId - Editor tab whose dedicated session holds the transaction
+ * @returns {Promise<{connection_id: string, active: boolean, savepoints: string[], started_at: number|null}>}
+ */
+export async function beginTransaction(connectionId, tabId = null) {
+  return await invoke("begin_transaction", {
+    connectionId,
+    tabId: tabId == null ? null : String(tabId),
+  });
+}
+
+/**
+ * Commit the open manual transaction
+ * @param {string} connectionId - Connection ID
+ * @param {string|number|null} tabId - Editor tab whose dedicated session holds the transaction
+ * @returns {Promise<Object>} Updated transaction state
+ */
+export async function commitTransaction(connectionId, tabId = null) {
+  return await invoke("commit_transaction", {
+    connectionId,
+    tabId: tabId == null ? null : String(tabId),
+  });
+}
+
+/**
+ * Roll back the open manual transaction
+ * @param {string} connectionId - Connection ID
+ * @param {string|number|null} tabId - Editor tab whose dedicated session holds the transaction
+ * @returns {Promise<Object>} Updated transaction state
+ */
+export async function rollbackTransaction(connectionId, tabId = null) {
+  return await invoke("rollback_transaction", {
+    connectionId,
+    tabId: tabId == null ? null : String(tabId),
+  });
+}
+
+/**
+ * Create a savepoint in the open manual transaction
+ * @param {string} connectionId - Connection ID
+ * @param {string} name - Savepoint name (plain identifier)
+ * @param {string|number|null} tabId - Editor tab whose dedicated session holds the transaction
+ * @returns {Promise<Object>} Updated transaction state
+ */
+export async function createSavepoint(connectionId, name, tabId = null) {
+  return await invoke("create_savepoint", {
+    connectionId,
+    tabId: tabId == null ? null : String(tabId),
+    name,
+  });
+}
+
+/**
+ * Roll back to a savepoint, keeping the transaction open
+ * @param {string} connectionId - Connection ID
+ * @param {string} name - Savepoint name
+ * @param {string|number|null} tabId - Editor tab whose dedicated session holds the transaction
+ * @returns {Promise<Object>} Updated transaction state
+ */
+export async function rollbackToSavepoint(connectionId, name, tabId = null) {
+  return await invoke("rollback_to_savepoint", {
+    connectionId,
+    tabId: tabId == null ? null : String(tabId),
+    name,
+  });
+}
+
+/**
+ * Release a savepoint (a no-op on SQL Server)
+ * @param {string} connectionId - Connection ID
+ * @param {string} name - Savepoint name
+ * @param {string|number|null} tabId - Editor tab whose dedicated session holds the transaction
+ * @returns {Promise<Object>} Updated transaction state
+ */
+export async function releaseSavepoint(connectionId, name, tabId = null) {
+  return await invoke("release_savepoint", {
+    connectionId,
+    tabId: tabId == null ? null : String(tabId),
+    name,
+  });
+}
+
+/**
+ * Get the manual transaction state of a connection
+ * @param {string} connectionId - Connection ID
+ * @param {string|number|null} tabId - Editor tab whose dedicated session holds the transaction
+ * @returns {Promise<Object>} Transaction state
+ */
+export async function getTransactionState(connectionId, tabId = null) {
+  return await invoke("get_transaction_state", {
+    connectionId,
+    tabId: tabId == null ? null : String(tabId),
+  });
+}
+
 /**
  * Universal function to get database objects
  * @param {string} connectionId - Connection ID
@@ -295,7 +893,7 @@ export async function getNextQueryNumber() {
  * @param {string} params.database - Database name
  * @param {string} params.table - Table name
  * @param {string} [params.schema] - Schema name (optional)
- * @param {Object} params.saveRequest - Save request object
+ * @param {Object} params.saveRequest - Save request object; set `best_effort` to apply rows one by one instead of in one transaction
  * @returns {Promise<string>} Generated SQL query
  */
 export async function generateSql({
@@ -328,7 +926,10 @@ export async function generateSql({
  * @param {string} params.table - Table name
  * @param {string} [params.schema] - Schema name (optional)
  * @param {Object} params.saveRequest - Save request object
- * @returns {Promise<Object>} Save response with status, message, and affected rows
+ * @returns {Promise<Object>} Save response with status, message, affected rows and,
+ *   in `materialized_rows`, the inserted rows as stored with generated values. Status
+ *   `unsupported` means the database cannot save in one transaction and nothing was
+ *   changed; retry with `best_effort` to apply the rows one at a time
  */
 export async function saveData({
   connectionId,
@@ -351,3 +952,102 @@ export async function saveData({
     throw error;
   }
 }
+
+/**
+ * Dry-run a save: execute its statements inside a transaction that is always rolled back
+ * @param {Object} params
+ * @param {string} params.connectionId - Connection ID
+ * @param {string} params.database - Database name
+ * @param {string} params.table - Table name
+ * @param {string} [params.schema] - Schema name (optional)
+ * @param {Object} params.saveRequest - Save request object
+ * @returns {Promise<Object>} Preview with per-statement `affected_rows` or `error`/`constraint`,
+ *   and `would_succeed`
+ */
+export async function previewSave({
+  connectionId,
+  database,
+  table,
+  schema,
+  saveRequest,
+}) {
+  try {
+    const response = await invoke("preview_save", {
+      connectionId: connectionId,
+      database,
+      table,
+      schema,
+      saveRequest: saveRequest,
+    });
+    return response;
+  } catch (error) {
+    console.error("❌ Error previewing save:", error);
+    throw error;
+  }
+}
+
+/**
+ * Count rows in other tables that deleting the save request's deleted rows would reach
+ * through foreign keys, following ON DELETE CASCADE chains
+ * @param {Object} params
+ * @param {string} params.connectionId - Connection ID
+ * @param {string} params.database - Database name
+ * @param {string} params.table - Table name
+ * @param {string} [params.schema] - Schema name (optional)
+ * @param {Object} params.saveRequest - Save request object
+ * @returns {Promise<Object>} Impact with `dependents` (table, constraint_name, on_delete,
+ *   row_count, path, blocks_delete), `can_delete` and `truncated`
+ */
+export async function previewDeleteImpact({
+  connectionId,
+  database,
+  table,
+  schema,
+  saveRequest,
+}) {
+  try {
+    const response = await invoke("preview_delete_impact", {
+      connectionId: connectionId,
+      database,
+      table,
+      schema,
+      saveRequest: saveRequest,
+    });
+    return response;
+  } catch (error) {
+    console.error("❌ Error previewing delete impact:", error);
+    throw error;
+  }
+}
+
+/**
+ * List saved grid edits recorded in the local journal, newest first
+ * @param {Object} [params]
+ * @param {string} [params.connectionId] - Only edits of this connection
+ * @param {string} [params.table] - Only edits of this table
+ * @returns {Promise<Array>} Entries with id, table, saved_at, row counts, inverse,
+ *   not_revertible (why the edit cannot be undone) and reverted_at
+ */
+export async function listEditHistory({ connectionId, table } = {}) {
+  try {
+    return await invoke("list_edit_history", { connectionId, table });
+  } catch (error) {
+    console.error("❌ Error listing edit history:", error);
+    throw error;
+  }
+}
+
+/**
+ * Undo a recorded edit by applying its inverse change set
+ * @param {string} id - Edit history entry ID
+ * @returns {Promise<Object>} Save response of the revert; rows changed again since the
+ *   edit come back as `conflicts`
+ */
+export async function revertEdit(id) {
+  try {
+    return await invoke("revert_edit", { id });
+  } catch (error) {
+    console.error("❌ Error reverting edit:", error);
+    throw error;
+  }
+}
@@ -1,8 +1,40 @@
 /**
  * Grid Row Service
- * Handles row operations: add, delete, duplicate
+ * Handles row operations: add, delete, duplicate, apply saved rows
  */
 
+/**
+ * Replace saved new-row placeholders with the rows the server stored
+ * @param {Array} displayData - Current display data
+ * @param {Array<string>} newRowIds - _rowId of each saved new row, in save order
+ * @param {Array} materializedRows - Stored rows from the save response ({ row_index, values })
+ * @param {Array} columns - Column definitions
+ * @returns {Array|null} Updated display data, or null when some new row was not read back
+ */
+export function applyMaterializedRows(
+  displayData,
+  newRowIds,
+  materializedRows,
+  columns
+) {
+  if (!materializedRows || materializedRows.length !== newRowIds.length) {
+    return null;
+  }
+
+  const storedById = new Map(
+    materializedRows.map((row) => [newRowIds[row.row_index], row.values])
+  );
+
+  return displayData.map((row) => {
+    const stored = row?._isNewRow ? storedById.get(row._rowId) : undefined;
+    if (!stored) return row;
+    return columns.map((col) => {
+      const colName = typeof col === "object" ? col.name : col;
+      return stored[colName] ?? null;
+    });
+  });
+}
+
 /**
  * Add a new empty row to displayData
  * @param {Array} displayData - Current display data
@@ -1,6 +1,42 @@
 import { message, open, save } from "@tauri-apps/plugin-dialog";
 import { invoke } from "@tauri-apps/api/core";
 import { readTextFile, writeTextFile } from "@tauri-apps/plugin-fs";
+import {
+  dumpDatabase,
+  exportData,
+  exportQuery,
+  exportQueryXlsx,
+  restoreSqlFile,
+} from "../../../core/integrations/tauri.js";
+
+// Save dialog filters of data exports, and the export format of each file extension
+const EXPORT_FILTERS = [
+  { name: "CSV Files", extensions: ["csv"] },
+  { name: "TSV Files", extensions: ["tsv"] },
+  { name: "JSON Files", extensions: ["json"] },
+  { name: "NDJSON Files", extensions: ["ndjson", "jsonl"] },
+  { name: "SQL Insert", extensions: ["sql"] },
+  { name: "Markdown", extensions: ["md"] },
+  { name: "HTML", extensions: ["html", "htm"] },
+  { name: "Excel Workbook", extensions: ["xlsx"] },
+  { name: "Apache Parquet", extensions: ["parquet"] },
+  { name: "Apache Arrow", extensions: ["arrow", "feather"] },
+];
+const EXPORT_FORMATS = {
+  csv: "csv",
+  tsv: "tsv",
+  json: "json",
+  ndjson: "ndjson",
+  jsonl: "ndjson",
+  sql: "sql_insert",
+  md: "markdown",
+  html: "html",
+  htm: "html",
+  xlsx: "xlsx",
+  parquet: "parquet",
+  arrow: "arrow",
+  feather: "arrow",
+};
 
 // Helper function for showing messages
 export async function showMessage(msg, title = "RustDBGrid") {
@@ -85,30 +121,164 @@ export const fileService = {
   },
 
   /**
-   * Export data to file
+   * Export all rows of a table query to a file chosen by the user; the format follows
+   * the file extension
+   * @param {string} connectionId - Connection ID
+   * @param {Object} query - Query request as sent to load_table_data
+   * @param {Object} [options] - Export options
+   * @returns {Promise<Object|null>} Export summary, or null when cancelled
    */
-  async exportData(data, format = null) {
-    const filters = [
-      { name: "CSV Files", extensions: ["csv"] },
-      { name: "JSON Files", extensions: ["json"] },
-      { name: "SQL Insert", extensions: ["sql"] },
-    ];
-
+  async exportData(connectionId, query, options = undefined) {
     const filePath = await save({
       title: "Export Data",
-      filters,
+      defaultPath: `${query.table}.csv`,
+      filters: EXPORT_FILTERS,
     });
 
     if (filePath) {
-      const ext = format || filePath.split(".").pop().toLowerCase();
-      await invoke("export_data", {
-        data,
-        filePath,
-        format: ext,
+      const ext = filePath.split(".").pop().toLowerCase();
+      return await exportData({
+        connectionId,
+        query: { ...query, limit: 0, offset: 0 },
+        path: filePath,
+        format: EXPORT_FORMATS[ext] || "csv",
+        options,
+      });
+    }
+    return null;
+  },
+
+  /**
+   * Export the result of a query to a file chosen by the user; the format follows the
+   * file extension
+   * @param {string} connectionId - Connection ID
+   * @param {string|null} tabId - Editor tab whose session runs the query
+   * @param {string} query - SQL to run
+   * @param {Object} [options] - Export options
+   * @returns {Promise<Object|null>} Export summary, or null when cancelled
+   */
+  async exportQuery(connectionId, tabId, query, options = undefined) {
+    const filePath = await save({
+      title: "Export Results",
+      defaultPath: "results.parquet",
+      filters: EXPORT_FILTERS,
+    });
+
+    if (filePath) {
+      const ext = filePath.split(".").pop().toLowerCase();
+      return await exportQuery({
+        connectionId,
+        tabId,
+        query,
+        path: filePath,
+        format: EXPORT_FORMATS[ext] || "csv",
+        options,
       });
-      return true;
     }
-    return false;
+    return null;
+  },
+
+  /**
+   * Export the result sets of a query to an Excel workbook chosen by the user
+   * @param {string} connectionId - Connection ID
+   * @param {string|null} tabId - Editor tab whose session runs the query
+   * @param {string} query - SQL to run
+   * @returns {Promise<Object|null>} Export summary, or null when cancelled
+   */
+  async exportQueryXlsx(connectionId, tabId, query) {
+    const filePath = await save({
+      title: "Export Results",
+      defaultPath: "results.xlsx",
+      filters: [{ name: "Excel Workbook", extensions: ["xlsx"] }],
+    });
+
+    if (filePath) {
+      return await exportQueryXlsx({ connectionId, tabId, query, path: filePath });
+    }
+    return null;
+  },
+
+  /**
+   * Ask the user for a workbook, CSV or NDJSON file to import
+   * @returns {Promise<string|null>} File path, or null when cancelled
+   */
+  async selectImportFile() {
+    const filePath = await open({
+      title: "Import Data",
+      filters: [
+        {
+          name: "Data Files",
+          extensions: [
+            "xlsx",
+            "xlsm",
+            "xls",
+            "ods",
+            "csv",
+            "tsv",
+            "txt",
+            "ndjson",
+            "jsonl",
+          ],
+        },
+        { name: "Spreadsheets", extensions: ["xlsx", "xlsm", "xls", "ods"] },
+        { name: "CSV Files", extensions: ["csv", "tsv", "txt"] },
+        { name: "NDJSON Files", extensions: ["ndjson", "jsonl"] },
+      ],
+    });
+    return filePath || null;
+  },
+
+  /**
+   * Dump a database to a SQL file chosen by the user
+   * @param {string} connectionId - Connection ID
+   * @param {string} database - Database name
+   * @param {Object} [options] - Dump options
+   * @param {Function} [onEvent] - Progress callback
+   * @returns {Promise<Object|null>} Dump summary, or null when cancelled
+   */
+  async dumpDatabase(connectionId, database, options = undefined, onEvent) {
+    const filePath = await save({
+      title: "Dump Database",
+      defaultPath: `${database}.sql`,
+      filters: [{ name: "SQL Files", extensions: ["sql"] }],
+    });
+
+    if (filePath) {
+      return await dumpDatabase({
+        connectionId,
+        database,
+        path: filePath,
+        options,
+        onEvent,
+      });
+    }
+    return null;
+  },
+
+  /**
+   * Restore a SQL file chosen by the user into a database
+   * @param {string} connectionId - Connection ID
+   * @param {string} database - Database name
+   * @param {boolean} continueOnError - Skip failing statements instead of stopping
+   * @param {Function} [onEvent] - Progress callback
+   * @returns {Promise<Object|null>} Restore summary, or null when cancelled
+   */
+  async restoreSqlFile(connectionId, database, continueOnError, onEvent) {
+    const filePath = await open({
+      title: "Restore from SQL File",
+      filters: [{ name: "SQL Files", extensions: ["sql", "txt"] }],
+    });
+
+    if (filePath) {
+      return await restoreSqlFile({
+        connectionId,
+        database,
+        path: filePath,
+        continueOnError,
+        onEvent,
+      });
+    }
+    return null;
   },
 
   /**
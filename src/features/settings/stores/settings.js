@@ -17,6 +17,7 @@ const defaultSettings = {
   confirm_delete: true,
   auto_connect: false,
   last_connection_id: null,
+  query_timeout_secs: 0, // 0 = no timeout
 };
 
 // Create the settings store
@@ -3,6 +3,7 @@ import {
   saveConnection,
   deleteConnection,
   getDatabaseObject,
+  copySchema,
 } from "../../../core/integrations/tauri";
 import { sidebarStore } from "../stores/sidebar";
 import {
@@ -11,6 +12,11 @@ import {
   refreshDatabaseObject,
 } from "../services/sidebarDataService";
 import { copyToClipboard } from "../../../shared/utils/ui/clipboard";
+import {
+  fileService,
+  showError,
+  showMessage,
+} from "../../settings/services/fileService";
 import {
   confirmDelete,
   showNotImplemented,
@@ -122,6 +128,58 @@ export const databaseHandlers = {
       console.error("Failed to refresh database:", error);
     }
   },
+
+  async dump(database, connection) {
+    try {
+      const summary = await fileService.dumpDatabase(
+        connection.id,
+        database.name
+      );
+      if (!summary) return;
+
+      let msg = `Dumped ${summary.tables} tables (${summary.rows} rows), ${summary.views} views, ${summary.routines} routines and ${summary.triggers} triggers to ${summary.path}`;
+      if (summary.cyclic_tables.length > 0) {
+        msg += `\n\nTables with circular foreign keys, written last: ${summary.cyclic_tables.join(", ")}`;
+      }
+      if (summary.skipped.length > 0) {
+        msg += `\n\nSkipped (no source available): ${summary.skipped.join(", ")}`;
+      }
+      await showMessage(msg, "Dump Database");
+    } catch (error) {
+      console.error("Failed to dump database:", error);
+      await showError(`Failed to dump database: ${error}`);
+    }
+  },
+
+  async restore(database, connection) {
+    const continueOnError = confirm(
+      "Continue when a statement fails?\n\nOK skips failing statements, Cancel stops at the first error."
+    );
+    try {
+      const summary = await fileService.restoreSqlFile(
+        connection.id,
+        database.name,
+        continueOnError
+      );
+      if (!summary) return;
+
+      let msg = `Executed ${summary.executed} of ${summary.total} statements`;
+      if (summary.failed > 0) {
+        const first = summary.errors[0];
+        msg += `, ${summary.failed} failed${summary.aborted ? " (restore stopped)" : ""}`;
+        if (first) {
+          msg += `\n\nStatement ${first.index + 1}: ${first.error}`;
+        }
+        await showError(msg, "Restore from SQL File");
+      } else {
+        await showMessage(msg, "Restore from SQL File");
+      }
+      await refreshDatabase(connection.id, database.name, connection.db_type);
+    } catch (error) {
+      console.error("Failed to restore SQL file:", error);
+      await showError(`Failed to restore SQL file: ${error}`);
+    }
+  },
 };
 
 /**
@@ -206,14 +264,31 @@ export const tableHandlers = {
     // TODO: Implement table diagram
   },
 
-  exportData(table, database, connection) {
-    console.log("Export data from table:", table.name);
-    // TODO: Implement data export
+  async exportData(table, database, connection) {
+    try {
+      const summary = await fileService.exportData(connection.id, {
+        db_type: connection.db_type,
+        database: database.name,
+        schema: table.schema || null,
+        table: table.name,
+        filters: [],
+        order_by: [],
+      });
+      if (summary) {
+        await showMessage(
+          `Exported ${summary.rows_written} rows to ${summary.path}`
+        );
+      }
+    } catch (error) {
+      await showError(`Failed to export ${table.name}: ${error}`);
+    }
   },
 
-  importData(table, database, connection) {
-    console.log("Import data to table:", table.name);
-    // TODO: Implement data import
+  async importData(table, database, connection) {
+    const path = await fileService.selectImportFile();
+    if (path) {
+      sidebarStore.openImportModal(path, table, database, connection);
+    }
   },
 
   readInConsole(table, database, connection, dispatch) {
@@ -241,6 +316,19 @@ export const tableHandlers = {
     copyToClipboard(advancedInfo, "Advanced table info copied to clipboard");
   },
 
+  async copyDdl(table, database, connection) {
+    try {
+      await copySchema({
+        connectionId: connection.id,
+        database: database.name,
+        table: table.name,
+        schema: table.schema || null,
+      });
+    } catch (error) {
+      await showError(`Failed to copy DDL of ${table.name}: ${error}`);
+    }
+  },
+
   delete(table, database, connection) {
     if (confirmDelete("table", table.name, { isDestructive: true })) {
       console.log("Delete table:", table.name);
@@ -284,9 +372,9 @@ export const viewHandlers = {
     // TODO: Implement view definition modal
   },
 
-  exportData(view, database, connection) {
-    console.log("Export data from view:", view.name);
-    // TODO: Implement data export
+  async exportData(view, database, connection) {
+    // Views are queried like tables
+    await tableHandlers.exportData(view, database, connection);
   },
 
   importData(view, database, connection) {
@@ -5,6 +5,7 @@ import {
   disconnectFromDatabase,
 } from "../../../core/integrations/tauri";
 import { sidebarStore } from "../stores/sidebar";
+import { confirmTransactionAction } from "../../../shared/utils/ui/confirmDialog";
 import { DatabaseType } from "../../../core/config/databaseTypes";
 
 // Active abort controllers for cancellable operations
@@ -225,7 +226,7 @@ export async function disconnectDatabase(connId, preserveExpandState = false) {
   sidebarStore.setConnectionLoading(connId, true);
 
   try {
-    await disconnectFromDatabase(connId);
+    await disconnectWithTransactionPrompt(connId);
 
     if (controller.signal.aborted) return;
 
@@ -245,6 +246,20 @@ export async function disconnectDatabase(connId, preserveExpandState = false) {
   }
 }
 
+/**
+ * Disconnect, asking the user to commit or roll back open manual transactions first.
+ * The backend refuses to disconnect while one is open on the connection or any of
+ * its tab sessions.
+ */
+export async function disconnectWithTransactionPrompt(connId, connectionName = connId) {
+  try {
+    await disconnectFromDatabase(connId);
+  } catch (error) {
+    if (!String(error).includes("open transaction")) throw error;
+    await disconnectFromDatabase(connId, confirmTransactionAction(connectionName));
+  }
+}
+
 /**
  * Refresh/reconnect to a database
  */
@@ -267,7 +282,7 @@ export async function refreshConnection(connId) {
 
   try {
     // Disconnect from backend but don't collapse UI
-    await disconnectFromDatabase(connId);
+    await disconnectWithTransactionPrompt(connId);
 
     if (controller.signal.aborted) return;
 
@@ -77,6 +77,8 @@ function createSidebarStore() {
     editingConnection: null,
     showRenameModal: false,
     renameModalData: null,
+    importModalData: null,
+    transferModalData: null,
   });
 
   return {
@@ -213,6 +215,24 @@ function createSidebarStore() {
         renameModalData: null,
       })),
 
+    openImportModal: (path, table, database, connection) =>
+      update((state) => ({
+        ...state,
+        importModalData: { path, table, database, connection },
+      })),
+
+    closeImportModal: () =>
+      update((state) => ({ ...state, importModalData: null })),
+
+    openTransferModal: (table, database, connection) =>
+      update((state) => ({
+        ...state,
+        transferModalData: { table, database, connection },
+      })),
+
+    closeTransferModal: () =>
+      update((state) => ({ ...state, transferModalData: null })),
+
     // Reset all state
     reset: () =>
       set({
@@ -233,6 +253,8 @@ function createSidebarStore() {
         editingConnection: null,
         showRenameModal: false,
         renameModalData: null,
+        importModalData: null,
+        transferModalData: null,
       }),
   };
 }
@@ -4,10 +4,8 @@ import {
   showError,
 } from "../../features/settings/services/fileService";
 import { get } from "svelte/store";
-import {
-  getNextQueryNumber,
-  disconnectFromDatabase,
-} from "../../core/integrations/tauri";
+import { getNextQueryNumber } from "../../core/integrations/tauri";
+import { disconnectWithTransactionPrompt } from "../../features/sidebar/services/sidebarDataService";
 import { recentFilesStore } from "../../features/settings/stores/recentFiles";
 import { invoke } from "@tauri-apps/api/core";
 import { readTextFile } from "@tauri-apps/plugin-fs";
@@ -330,16 +328,43 @@ export function createMenuHandlers(context) {
         return;
       }
 
-      const tabData = tabDataStore.get(currentTab.id);
-      if (!tabData || !tabData.queryResult) {
-        await showError("No data to export");
-        return;
+      // Table tabs export their table; query tabs re-run their last query
+      let connectionId;
+      let query;
+      if (currentTab.type === "table" && currentTab.tableInfo) {
+        const { name, schema, database, connection } = currentTab.tableInfo;
+        connectionId = connection.id;
+        query = {
+          db_type: connection.db_type,
+          database,
+          schema: schema || null,
+          table: name,
+          filters: [],
+          order_by: [],
+        };
+      } else {
+        const tabData = tabDataStore.get(currentTab.id);
+        if (!tabData || !tabData.executedQuery || !currentTab.connection) {
+          await showError("No data to export");
+          return;
+        }
+        connectionId = currentTab.connection.id;
+        query = {
+          db_type: currentTab.connection.db_type,
+          database: null,
+          schema: null,
+          table: `RustDBGridQuery(${tabData.executedQuery})`,
+          filters: [],
+          order_by: [],
+        };
       }
 
       try {
-        const success = await fileService.exportData(tabData.queryResult);
-        if (success) {
-          await showMessage("Data exported successfully");
+        const summary = await fileService.exportData(connectionId, query);
+        if (summary) {
+          await showMessage(
+            `Exported ${summary.rows_written} rows to ${summary.path}`
+          );
         }
       } catch (error) {
         console.error("Failed to export data:", error);
@@ -450,7 +475,10 @@ export function createMenuHandlers(context) {
 
       try {
         const connectionId = activeConnection.id;
-        await disconnectFromDatabase(connectionId);
+        await disconnectWithTransactionPrompt(
+          connectionId,
+          activeConnection.name
+        );
 
         // Close all tabs for this connection and get closed tab IDs
         const closedTabIds =
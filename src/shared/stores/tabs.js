@@ -1,6 +1,7 @@
 import { writable, get } from "svelte/store";
 import { tabDataStore } from "./tabData";
 import { DatabaseType } from "../../core/config/databaseTypes";
+import { closeTabSession } from "../../core/integrations/tauri";
 
 /**
  * Generate unique ID for tabs
@@ -249,6 +250,13 @@ function createTabStore() {
     closeTab: (tabToClose) => {
       let tabIndex = -1;
 
+      // Release the tab's dedicated database session, if it opened one
+      if (tabToClose.type === "query") {
+        closeTabSession(tabToClose.id).catch((error) =>
+          console.error("Failed to close tab session:", error)
+        );
+      }
+
       tabs.update((currentTabs) => {
         tabIndex = currentTabs.findIndex((t) => t.id === tabToClose.id);
         const newTabs = currentTabs.filter((t) => t.id !== tabToClose.id);
@@ -26,6 +26,29 @@ export function confirmDelete(itemType, itemName, options = {}) {
   return confirm(message);
 }
 
+/**
+ * Asks what to do with an open manual transaction before its connection is closed
+ * @param {string} connectionName - Name of the connection being closed
+ * @returns {"commit"|"rollback"} - The chosen action
+ */
+export function confirmTransactionAction(connectionName) {
+  const commit = confirm(
+    `Connection "${connectionName}" has an open transaction.\n\nPress OK to commit it, or Cancel to roll it back.`
+  );
+  return commit ? "commit" : "rollback";
+}
+
+/**
+ * Asks whether to save edits row by row on a database that cannot apply them in
+ * one transaction
+ * @returns {boolean} - Whether the user accepted a possibly partial save
+ */
+export function confirmBestEffortSave() {
+  return confirm(
+    "This database does not support transactions, so some rows may be saved while others fail.\n\nSave anyway?"
+  );
+}
+
 /**
  * Shows a "not implemented" alert for features that are still TODO
  * @param {string} actionName - Name of the action (e.g., "Database deletion", "Schema rename")